@@ -0,0 +1,95 @@
+//! Indexer benchmarks
+//!
+//! Fixture documents are generated into a temp directory on startup:
+//! - a 10k-block note (headings, paragraphs, lists, code fences)
+//! - a large markdown table
+//! - CJK text (multi-byte, long lines)
+//! - a large JS module and HTML page
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use std::fs;
+use std::path::PathBuf;
+
+use unstablon_pkm_lib::bench::{parse_file, IndexSettings};
+
+/// Write a fixture file and return its path as a string
+fn write_fixture(name: &str, content: &str) -> String {
+    let dir = std::env::temp_dir().join("unstablon-bench");
+    fs::create_dir_all(&dir).expect("Failed to create fixture dir");
+    let path: PathBuf = dir.join(name);
+    fs::write(&path, content).expect("Failed to write fixture");
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// ~10k blocks of mixed markdown
+fn large_note() -> String {
+    let mut out = String::from("---\ntitle: Large Note\ntags: [bench]\n---\n\n");
+    for i in 0..2500 {
+        out.push_str(&format!("## Section {}\n\n", i));
+        out.push_str("Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. See [[Other Note]].\n\n");
+        out.push_str("- item one\n- item two\n- item three\n\n");
+        out.push_str("```rust\nfn main() {\n    println!(\"hello\");\n}\n```\n\n");
+    }
+    out
+}
+
+/// Markdown table with many rows
+fn large_table() -> String {
+    let mut out = String::from("# Table\n\n| id | name | value | notes |\n|---:|:-----|:-----:|-------|\n");
+    for i in 0..20_000 {
+        out.push_str(&format!("| {} | row-{} | {:.3} | some longer note text |\n", i, i, i as f64 * 0.5));
+    }
+    out
+}
+
+/// Long CJK paragraphs
+fn cjk_text() -> String {
+    let mut out = String::from("# 日本語のノート\n\n");
+    for _ in 0..5_000 {
+        out.push_str("吾輩は猫である。名前はまだ無い。どこで生れたかとんと見当がつかぬ。何でも薄暗いじめじめした所でニャーニャー泣いていた事だけは記憶している。\n\n");
+    }
+    out
+}
+
+/// Large JavaScript module (comments + string literals)
+fn large_module() -> String {
+    let mut out = String::from("export const moduleInfo = { displayName: 'Bench Module' };\n");
+    for i in 0..10_000 {
+        out.push_str(&format!("// helper {} computes things\nconst s{} = \"string literal number {}\";\n", i, i, i));
+    }
+    out
+}
+
+/// Large HTML page
+fn large_page() -> String {
+    let mut out = String::from("<html><head><title>Bench Page</title></head><body>\n");
+    for i in 0..10_000 {
+        out.push_str(&format!("<div class=\"row\"><p>Paragraph {} with <b>bold</b> text</p></div>\n", i));
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+fn bench_parse_file(c: &mut Criterion) {
+    let settings = IndexSettings::default();
+    let fixtures = [
+        ("large_note.md", large_note()),
+        ("large_table.md", large_table()),
+        ("cjk_text.md", cjk_text()),
+        ("large_module.js", large_module()),
+        ("large_page.html", large_page()),
+    ];
+
+    let mut group = c.benchmark_group("parse_file");
+    for (name, content) in &fixtures {
+        let path = write_fixture(name, content);
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| parse_file(black_box(&path), &settings).expect("parse_file failed"))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_file);
+criterion_main!(benches);
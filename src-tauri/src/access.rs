@@ -0,0 +1,77 @@
+//! Note Access - Opt-in open tracking and frecency
+//!
+//! When tracking is enabled, each note open is logged (vault-relative path
+//! and time). Frecency weighs recent opens more than old ones, so a note
+//! opened daily outranks one opened often a year ago; search and the quick
+//! switcher multiply relevance by a boost that grows with it. Nothing is
+//! recorded while tracking is off, and existing opens are kept (but still
+//! used) until `clear_note_access`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Opens are recorded only when enabled
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Opens older than this don't count toward frecency (seconds)
+pub const FRECENCY_WINDOW_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// Opens older than this are pruned from the log (seconds)
+pub const RETENTION_SECS: u64 = 400 * 24 * 60 * 60;
+
+/// Weight of an open by age: (max age in days, points)
+const AGE_WEIGHTS: [(u64, f64); 4] = [(4, 100.0), (14, 70.0), (31, 50.0), (90, 30.0)];
+
+/// How strongly frecency lifts a search score
+const BOOST_WEIGHT: f64 = 0.25;
+
+pub fn is_tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+pub fn set_tracking(enabled: bool) {
+    TRACKING.store(enabled, Ordering::Relaxed);
+}
+
+/// Frecency per path from `(path, opened_at)` rows
+pub fn frecency(opens: &[(String, u64)], now: u64) -> HashMap<String, f64> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for (path, opened_at) in opens {
+        let age_days = now.saturating_sub(*opened_at) / (24 * 60 * 60);
+        if let Some((_, points)) = AGE_WEIGHTS.iter().find(|(days, _)| age_days < *days) {
+            *scores.entry(path.clone()).or_default() += points;
+        }
+    }
+    scores
+}
+
+/// Multiplier for a relevance score: 1.0 without opens, growing
+/// logarithmically so a few opens matter and hundreds don't swamp relevance
+pub fn boost(frecency: f64) -> f64 {
+    1.0 + BOOST_WEIGHT * (1.0 + frecency / 100.0).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frecency() {
+        let day = 24 * 60 * 60;
+        let now = 1000 * day;
+        let opens = vec![
+            ("Daily.md".to_string(), now - 60),
+            ("Daily.md".to_string(), now - day),
+            ("Old.md".to_string(), now - 20 * day),
+            ("Old.md".to_string(), now - 20 * day),
+            ("Ancient.md".to_string(), now - 200 * day),
+        ];
+        let scores = frecency(&opens, now);
+        assert_eq!(scores["Daily.md"], 200.0);
+        assert_eq!(scores["Old.md"], 100.0);
+        assert!(!scores.contains_key("Ancient.md"));
+
+        assert_eq!(boost(0.0), 1.0);
+        assert!(boost(200.0) > boost(100.0));
+    }
+}
@@ -0,0 +1,393 @@
+//! Attachments - Non-note files in the vault (images, PDFs, archives, ...)
+//!
+//! Duplicate detection groups files by size first and hashes only the size
+//! collisions. Link rewriting understands markdown links/embeds
+//! (`[x](path)`, `![x](path)`, relative to the note or `/`-rooted at the
+//! vault) and wikilinks (`[[path]]`, `![[path|alias]]`).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::models::{ChangedLine, DuplicateGroup, ImportOptions};
+use crate::tables;
+use crate::thumbnails;
+use crate::utils;
+
+/// Check if path is an attachment (any file the indexer doesn't treat as content)
+pub fn is_attachment_file(path: &str) -> bool {
+    !utils::is_content_file(path)
+}
+
+/// Vault-relative paths of all files under `root`, skipping hidden entries
+pub fn walk_files(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    let root = utils::normalize_path(&root.to_string_lossy());
+
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("[WARN] [attachments] Failed to read directory {:?}: {}", dir, e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                files.push(utils::strip_root(&utils::normalize_path(&path.to_string_lossy()), &root));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Groups of byte-identical attachments under `root`
+/// Within a group the first path is the one to keep: shallowest, then
+/// alphabetical.
+pub fn find_duplicates(root: &Path) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for path in walk_files(root).into_iter().filter(|p| is_attachment_file(p)) {
+        if let Ok(metadata) = fs::metadata(root.join(&path)) {
+            if metadata.len() > 0 {
+                by_size.entry(metadata.len()).or_default().push(path);
+            }
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (size, paths) in by_size.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+        for path in paths {
+            match fs::read(root.join(&path)) {
+                Ok(bytes) => by_hash.entry(utils::content_hash(&bytes)).or_default().push(path),
+                Err(e) => warn!("[WARN] [attachments] Failed to read {}: {}", path, e),
+            }
+        }
+
+        for (hash, mut paths) in by_hash.into_iter().filter(|(_, paths)| paths.len() > 1) {
+            paths.sort_by(|a, b| a.matches('/').count().cmp(&b.matches('/').count()).then_with(|| a.cmp(b)));
+            groups.push(DuplicateGroup { hash, size, paths });
+        }
+    }
+
+    groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+    groups
+}
+
+/// Copy (or move) an external file into `target_dir`, converting it to
+/// markdown when requested and supported (.txt, .csv)
+/// Returns the created file's path, renamed if the name was taken.
+pub fn import_file(source: &Path, target_dir: &Path, options: &ImportOptions) -> Result<(PathBuf, bool), AppError> {
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::Path(format!("Not a file: {:?}", source)))?;
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem.to_string(), extension.to_ascii_lowercase()),
+        _ => (file_name.clone(), String::new()),
+    };
+    fs::create_dir_all(target_dir)?;
+
+    let converted = if options.convert {
+        match extension.as_str() {
+            "txt" => Some(fs::read_to_string(source)?),
+            "csv" => tables::from_csv(&fs::read_to_string(source)?).map(|table| table + "\n"),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let target = match converted {
+        Some(markdown) => {
+            let target = unique_path(target_dir, &format!("{}.md", stem));
+            fs::write(&target, markdown)?;
+            if options.move_file {
+                fs::remove_file(source)?;
+            }
+            return Ok((target, true));
+        }
+        None => unique_path(target_dir, &file_name),
+    };
+
+    if options.move_file {
+        // rename fails across filesystems; fall back to copy + delete
+        if let Err(e) = fs::rename(source, &target) {
+            warn!("[WARN] [attachments] Rename failed ({}), copying instead", e);
+            fs::copy(source, &target)?;
+            fs::remove_file(source)?;
+        }
+    } else {
+        fs::copy(source, &target)?;
+    }
+    Ok((target, false))
+}
+
+/// `dir/name`, or `dir/name 1.ext`, `dir/name 2.ext`, ... if taken
+pub fn unique_path(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (file_name, String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{} {}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .expect("unbounded counter")
+}
+
+/// Markdown to insert for a vault file: an embed for images, a wikilink for
+/// notes, a plain link otherwise (relative to `note_dir` when given)
+pub fn insert_text(path: &str, note_dir: Option<&str>) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if utils::is_document_file(path) && path.ends_with(".md") {
+        return format!("[[{}]]", path.trim_end_matches(".md"));
+    }
+
+    let target = match note_dir {
+        Some(dir) => relative_path(dir, path),
+        None => format!("/{}", path),
+    };
+    let target = if target.contains([' ', '(', ')']) { format!("<{}>", target) } else { target };
+
+    if thumbnails::is_image_file(path) {
+        format!("![{}]({})", name, target)
+    } else {
+        format!("[{}]({})", name, target)
+    }
+}
+
+/// Rewrite links in a note (at vault-relative `note_path`) that point at
+/// any key of `replacements` (vault-relative) to its value
+/// Returns None if nothing changed.
+pub fn rewrite_links(content: &str, note_path: &str, replacements: &HashMap<String, String>) -> Option<String> {
+    let note_dir = note_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let mut out = String::with_capacity(content.len());
+    let mut changed = false;
+    let mut rest = content;
+
+    while let Some(pos) = rest.find(['[', ']']) {
+        let (before, tail) = rest.split_at(pos);
+        out.push_str(before);
+
+        // Wikilink: [[target]] / [[target|alias]] / [[target#heading]]
+        if let Some(inner_end) = tail.strip_prefix("[[").and_then(|t| t.find("]]")) {
+            let inner = &tail[2..2 + inner_end];
+            let split = inner.find(['|', '#']).unwrap_or(inner.len());
+            let (target, suffix) = inner.split_at(split);
+            let resolved = utils::normalize_path(target.trim());
+            let resolved = resolved.trim_start_matches('/');
+            // A bare file name links to the file wherever it is; left alone
+            // when copies with that name are replaced by different files
+            let replacement = replacements.get(resolved).or_else(|| {
+                if resolved.contains('/') {
+                    return None;
+                }
+                let mut targets = replacements
+                    .iter()
+                    .filter(|(from, _)| from.rsplit('/').next() == Some(resolved))
+                    .map(|(_, to)| to);
+                let first = targets.next()?;
+                targets.all(|to| to == first).then_some(first)
+            });
+            match replacement {
+                Some(to) => {
+                    out.push_str(&format!("[[{}{}]]", to, suffix));
+                    changed = true;
+                }
+                None => out.push_str(&tail[..inner_end + 4]),
+            }
+            rest = &tail[inner_end + 4..];
+            continue;
+        }
+
+        // Markdown link target: ](target) or ](<target> "title")
+        if let Some(target_end) = tail.strip_prefix("](").and_then(|t| t.find(')')) {
+            let raw = &tail[2..2 + target_end];
+            let (target, title) = match raw.strip_prefix('<').and_then(|r| r.split_once('>')) {
+                Some((target, title)) => (target, title),
+                None => raw.split_at(raw.find(' ').unwrap_or(raw.len())),
+            };
+            let decoded = target.replace("%20", " ");
+            let resolved = if let Some(absolute) = decoded.strip_prefix('/') {
+                Some(absolute.to_string())
+            } else if decoded.contains("://") || decoded.starts_with('#') {
+                None
+            } else {
+                resolve_relative(note_dir, &decoded)
+            };
+
+            match resolved.as_ref().and_then(|r| replacements.get(r)) {
+                Some(to) => {
+                    let new_target = if decoded.starts_with('/') {
+                        format!("/{}", to)
+                    } else {
+                        relative_path(note_dir, to)
+                    };
+                    let new_target = if raw.starts_with('<') || new_target.contains(' ') {
+                        format!("<{}>", new_target)
+                    } else {
+                        new_target
+                    };
+                    out.push_str(&format!("]({}{})", new_target, title));
+                    changed = true;
+                }
+                None => out.push_str(&tail[..target_end + 3]),
+            }
+            rest = &tail[target_end + 3..];
+            continue;
+        }
+
+        out.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+    out.push_str(rest);
+
+    changed.then_some(out)
+}
+
+/// Link replacements for moving `from` to `to` (both vault-relative), a
+/// file or every file under a folder. Notes also map without `.md`, the way
+/// wikilinks name them.
+pub fn rename_replacements(files: &[String], from: &str, to: &str) -> HashMap<String, String> {
+    let (from, to) = (from.trim_matches('/'), to.trim_matches('/'));
+    let mut replacements = HashMap::new();
+    for file in files {
+        let moved = if file == from {
+            to.to_string()
+        } else if let Some(rest) = file.strip_prefix(from).and_then(|r| r.strip_prefix('/')) {
+            format!("{}/{}", to, rest)
+        } else {
+            continue;
+        };
+        if let (Some(old), Some(new)) = (file.strip_suffix(".md"), moved.strip_suffix(".md")) {
+            replacements.insert(old.to_string(), new.to_string());
+        }
+        replacements.insert(file.clone(), moved);
+    }
+    replacements
+}
+
+/// Lines that differ between `before` and a rewrite of it that kept its
+/// line breaks (as `rewrite_links` does)
+pub fn changed_lines(before: &str, after: &str) -> Vec<ChangedLine> {
+    before
+        .lines()
+        .zip(after.lines())
+        .enumerate()
+        .filter(|(_, (old, new))| old != new)
+        .map(|(index, (old, new))| ChangedLine { line: index + 1, before: old.to_string(), after: new.to_string() })
+        .collect()
+}
+
+/// Join a relative link onto a vault-relative directory (None if it escapes)
+pub fn resolve_relative(dir: &str, link: &str) -> Option<String> {
+    let link = utils::normalize_path(link);
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for component in link.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            c => parts.push(c),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Relative link from vault-relative directory `from_dir` to `to`
+pub fn relative_path(from_dir: &str, to: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|p| !p.is_empty()).collect();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from.iter().zip(&to_parts).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&to_parts[common..]);
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_links() {
+        let replacements = HashMap::from([
+            ("Projects/img/copy.png".to_string(), "assets/logo.png".to_string()),
+        ]);
+        let content = "![a](img/copy.png) [b](<img/copy.png> \"t\") ![[Projects/img/copy.png|200]] [c](/Projects/img/copy.png) [d](other.png) [[Note]] ![[copy.png]]";
+
+        let updated = rewrite_links(content, "Projects/plan.md", &replacements).unwrap();
+        assert_eq!(
+            updated,
+            "![a](../assets/logo.png) [b](<../assets/logo.png> \"t\") ![[assets/logo.png|200]] [c](/assets/logo.png) [d](other.png) [[Note]] ![[assets/logo.png]]"
+        );
+        assert!(rewrite_links("[d](other.png)", "plan.md", &replacements).is_none());
+
+        let ambiguous = HashMap::from([
+            ("a/copy.png".to_string(), "assets/logo.png".to_string()),
+            ("b/copy.png".to_string(), "assets/icon.png".to_string()),
+        ]);
+        assert!(rewrite_links("![[copy.png]]", "plan.md", &ambiguous).is_none());
+    }
+
+    #[test]
+    fn test_rename_preview() {
+        let files = vec!["Projects/Plan.md".to_string(), "Projects/img/a.png".to_string(), "Projects.md".to_string()];
+        let replacements = rename_replacements(&files, "Projects", "Archive/Projects");
+        assert_eq!(replacements.len(), 3);
+        assert_eq!(replacements["Projects/Plan"], "Archive/Projects/Plan");
+
+        let content = "# Index\nSee [[Plan|the plan]] and [[Projects]].\n![x](Projects/img/a.png)\n";
+        let updated = rewrite_links(content, "Index.md", &replacements).unwrap();
+        assert_eq!(
+            changed_lines(content, &updated),
+            vec![
+                ChangedLine { line: 2, before: "See [[Plan|the plan]] and [[Projects]].".to_string(), after: "See [[Archive/Projects/Plan|the plan]] and [[Projects]].".to_string() },
+                ChangedLine { line: 3, before: "![x](Projects/img/a.png)".to_string(), after: "![x](Archive/Projects/img/a.png)".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_text() {
+        assert_eq!(insert_text("assets/a b.png", Some("Notes")), "![a b.png](<../assets/a b.png>)");
+        assert_eq!(insert_text("assets/report.pdf", None), "[report.pdf](/assets/report.pdf)");
+        assert_eq!(insert_text("Inbox/data.md", Some("Notes")), "[[Inbox/data]]");
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let root = std::env::temp_dir().join(format!("unstablon-dupes-{}", std::process::id()));
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("logo.png"), b"same").unwrap();
+        fs::write(root.join("a/b/logo copy.png"), b"same").unwrap();
+        fs::write(root.join("a/other.png"), b"diff").unwrap();
+        fs::write(root.join("a/note.md"), b"same").unwrap();
+
+        let groups = find_duplicates(&root);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].paths, vec!["logo.png", "a/b/logo copy.png"]);
+
+        assert_eq!(unique_path(&root, "logo.png"), root.join("logo 1.png"));
+        assert_eq!(unique_path(&root, "new.png"), root.join("new.png"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,233 @@
+//! Autocomplete - In-memory prefix store of titles, aliases, tags, and headings
+//!
+//! Per-keystroke completion can't afford an FTS round-trip, so every indexed
+//! note's terms are kept in sorted maps (one per kind) and answered with a
+//! range scan. The store is loaded from the index at startup and updated
+//! incrementally whenever the indexer writes entries.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{OnceLock, RwLock};
+
+use crate::db::Database;
+use crate::models::{AutocompleteItem, ContentIndexEntry};
+use crate::utils;
+
+/// Term kinds served by `complete`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Title,
+    Alias,
+    Tag,
+    Heading,
+}
+
+impl Kind {
+    pub fn parse(kind: &str) -> Result<Self, String> {
+        match kind {
+            "title" => Ok(Kind::Title),
+            "alias" => Ok(Kind::Alias),
+            "tag" => Ok(Kind::Tag),
+            "heading" => Ok(Kind::Heading),
+            other => Err(format!("Unknown autocomplete kind: {}", other)),
+        }
+    }
+}
+
+/// A term and the notes it occurs in
+struct Term {
+    /// Original casing (first seen)
+    display: String,
+    notes: HashSet<String>,
+}
+
+/// Terms contributed by one note (kept for incremental removal)
+struct NoteTerms {
+    path: String,
+    terms: Vec<(Kind, String)>,
+}
+
+#[derive(Default)]
+struct PrefixStore {
+    /// Per kind: lowercase term -> term
+    terms: HashMap<Kind, BTreeMap<String, Term>>,
+    /// Note id -> its terms
+    notes: HashMap<String, NoteTerms>,
+    /// Path -> note id (a re-indexed path may get a new id)
+    paths: HashMap<String, String>,
+}
+
+impl PrefixStore {
+    fn insert(&mut self, entry: &ContentIndexEntry) {
+        self.remove_note(&entry.id);
+        if let Some(old_id) = self.paths.get(&entry.path).cloned() {
+            self.remove_note(&old_id);
+        }
+
+        let terms = note_terms(entry);
+        for (kind, value) in &terms {
+            let term = self
+                .terms
+                .entry(*kind)
+                .or_default()
+                .entry(value.to_lowercase())
+                .or_insert_with(|| Term {
+                    display: value.clone(),
+                    notes: HashSet::new(),
+                });
+            term.notes.insert(entry.id.clone());
+        }
+
+        self.paths.insert(entry.path.clone(), entry.id.clone());
+        self.notes.insert(entry.id.clone(), NoteTerms {
+            path: entry.path.clone(),
+            terms,
+        });
+    }
+
+    fn remove_note(&mut self, id: &str) {
+        let note = match self.notes.remove(id) {
+            Some(note) => note,
+            None => return,
+        };
+        self.paths.remove(&note.path);
+
+        for (kind, value) in note.terms {
+            if let Some(map) = self.terms.get_mut(&kind) {
+                let key = value.to_lowercase();
+                if let Some(term) = map.get_mut(&key) {
+                    term.notes.remove(id);
+                    if term.notes.is_empty() {
+                        map.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn complete(&self, kind: Kind, prefix: &str, limit: usize) -> Vec<AutocompleteItem> {
+        let map = match self.terms.get(&kind) {
+            Some(map) => map,
+            None => return Vec::new(),
+        };
+        let prefix = prefix.to_lowercase();
+
+        let mut items: Vec<AutocompleteItem> = map
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, term)| AutocompleteItem {
+                value: term.display.clone(),
+                count: term.notes.len() as u32,
+                note_id: if term.notes.len() == 1 {
+                    term.notes.iter().next().cloned()
+                } else {
+                    None
+                },
+            })
+            .collect();
+
+        // Most used first; the range scan already yields alphabetical order
+        items.sort_by_key(|item| std::cmp::Reverse(item.count));
+        items.truncate(limit);
+        items
+    }
+}
+
+/// Deduplicated (kind, term) pairs for a note
+fn note_terms(entry: &ContentIndexEntry) -> Vec<(Kind, String)> {
+    let mut terms = vec![(Kind::Title, entry.title.clone())];
+    terms.extend(entry.aliases.iter().map(|a| (Kind::Alias, a.clone())));
+
+    terms.extend(entry.tags.iter().map(|t| (Kind::Tag, t.clone())));
+
+    if entry.content_type == "document" {
+        if let Some(body) = &entry.body {
+            terms.extend(utils::extract_headings(body).into_iter().map(|h| (Kind::Heading, h)));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    terms.retain(|(kind, value)| !value.trim().is_empty() && seen.insert((*kind, value.to_lowercase())));
+    terms
+}
+
+/// Global prefix store
+static STORE: OnceLock<RwLock<PrefixStore>> = OnceLock::new();
+
+fn get_store() -> &'static RwLock<PrefixStore> {
+    STORE.get_or_init(|| RwLock::new(PrefixStore::default()))
+}
+
+/// Add or replace the terms of indexed entries
+pub fn update(entries: &[ContentIndexEntry]) {
+    let mut store = get_store().write().unwrap();
+    for entry in entries {
+        store.insert(entry);
+    }
+}
+
+/// Drop everything (the index was cleared)
+pub fn clear() {
+    *get_store().write().unwrap() = PrefixStore::default();
+}
+
+/// Load the store from the existing index (startup)
+pub fn load(db: &Database) -> Result<usize, String> {
+    let mut store = PrefixStore::default();
+    let mut count = 0;
+    db.for_each_entry(|entry| {
+        store.insert(&entry);
+        count += 1;
+    })?;
+
+    *get_store().write().unwrap() = store;
+    Ok(count)
+}
+
+/// Terms of `kind` starting with `prefix` (case-insensitive), most used first
+pub fn complete(kind: Kind, prefix: &str, limit: usize) -> Vec<AutocompleteItem> {
+    get_store().read().unwrap().complete(kind, prefix, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, path: &str, title: &str, body: &str) -> ContentIndexEntry {
+        ContentIndexEntry {
+            id: id.to_string(),
+            path: path.to_string(),
+            title: title.to_string(),
+            content_type: "document".to_string(),
+            body: Some(body.to_string()),
+            modified_at: 0,
+            indexed_at: 0,
+            created_at: None,
+            truncated: false,
+            aliases: vec!["Rusty".to_string()],
+            tags: utils::extract_tags(body),
+            properties: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_prefix_store() {
+        let mut store = PrefixStore::default();
+        store.insert(&entry("a", "/v/a.md", "Rust Notes", "# Ownership\n#rust #rustlang"));
+        store.insert(&entry("b", "/v/b.md", "Ruby", "#rust"));
+
+        let titles = store.complete(Kind::Title, "ru", 10);
+        assert_eq!(titles.iter().map(|i| i.value.as_str()).collect::<Vec<_>>(), vec!["Ruby", "Rust Notes"]);
+
+        let tags = store.complete(Kind::Tag, "RUST", 10);
+        assert_eq!((tags[0].value.as_str(), tags[0].count), ("rust", 2));
+        assert_eq!((tags[1].value.as_str(), tags[1].note_id.as_deref()), ("rustlang", Some("a")));
+
+        assert_eq!(store.complete(Kind::Heading, "own", 10)[0].value, "Ownership");
+
+        // Re-indexing a note replaces its terms
+        store.insert(&entry("a", "/v/a.md", "Rust Notes", "no tags"));
+        assert_eq!(store.complete(Kind::Tag, "rust", 10).len(), 1);
+        assert!(store.complete(Kind::Heading, "own", 10).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! Backup - Whole-vault zip archives
+//!
+//! Archives hold every file under the vault root at its vault-relative
+//! path, so unzipping one recreates the vault. Hidden entries (`.git`,
+//! `.obsidian`, ...) and editor temp files are left out, and attachments can
+//! be too. The zip is written under a `.partial` name and renamed once
+//! complete, so an interrupted backup never looks like a finished one.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::attachments;
+
+/// Editor temp and swap files, which `walk_files` doesn't skip
+fn is_ignored(rel: &str) -> bool {
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    name.starts_with('~') || name.ends_with(".tmp") || name.ends_with(".swp")
+}
+
+/// Vault-relative paths of the files a backup of `root` holds
+pub fn backup_files(root: &Path, include_attachments: bool) -> Vec<String> {
+    attachments::walk_files(root)
+        .into_iter()
+        .filter(|rel| !is_ignored(rel))
+        .filter(|rel| include_attachments || !attachments::is_attachment_file(rel))
+        .collect()
+}
+
+/// Canonical path of an archive about to be written to `target`, refused
+/// if it lies inside `root`
+/// The parent folder must exist; resolving it first means `..` components
+/// and symlinks can't hide a path into the vault.
+pub fn outside_path(root: &Path, target: &Path) -> Result<PathBuf, String> {
+    let name = target.file_name().ok_or_else(|| format!("Not a file path: {:?}", target))?;
+    let parent = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let parent = fs::canonicalize(parent).map_err(|e| format!("Failed to resolve {:?}: {}", parent, e))?;
+    let root = fs::canonicalize(root).map_err(|e| format!("Failed to resolve {:?}: {}", root, e))?;
+    if parent.starts_with(&root) {
+        return Err("The archive can't be saved inside the vault".to_string());
+    }
+    Ok(parent.join(name))
+}
+
+/// Zip `files` (vault-relative, under `root`) into `target`
+/// `on_progress(archived, total)` is called after each file. Files that
+/// can't be read are skipped with a warning. An existing `target` is only
+/// replaced with `overwrite`. Returns the number archived.
+pub fn write_zip(
+    root: &Path,
+    files: &[String],
+    target: &Path,
+    overwrite: bool,
+    on_progress: &dyn Fn(usize, usize),
+) -> Result<usize, String> {
+    // symlink_metadata: a link at `target` counts as a file there
+    let exists = || fs::symlink_metadata(target).is_ok();
+    if !overwrite && exists() {
+        return Err(format!("File already exists: {:?}", target));
+    }
+
+    let partial = PathBuf::from(format!("{}.partial", target.to_string_lossy()));
+    let result = write_entries(root, files, &partial, on_progress).and_then(|count| {
+        // Checked again: the archive may have taken a while
+        if !overwrite && exists() {
+            return Err(format!("File already exists: {:?}", target));
+        }
+        fs::rename(&partial, target)
+            .map(|()| count)
+            .map_err(|e| format!("Failed to finish {:?}: {}", target, e))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result
+}
+
+fn write_entries(root: &Path, files: &[String], target: &Path, on_progress: &dyn Fn(usize, usize)) -> Result<usize, String> {
+    let file = File::create(target).map_err(|e| format!("Failed to create {:?}: {}", target, e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().large_file(true);
+    let mut count = 0;
+
+    for (done, rel) in files.iter().enumerate() {
+        match File::open(root.join(rel)) {
+            Ok(mut source) => {
+                zip.start_file(rel.as_str(), options)
+                    .map_err(|e| format!("Failed to add {} to backup: {}", rel, e))?;
+                io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to add {} to backup: {}", rel, e))?;
+                count += 1;
+            }
+            Err(e) => warn!("[WARN] [backup] Skipping {}: {}", rel, e),
+        }
+        on_progress(done + 1, files.len());
+    }
+
+    zip.finish().map_err(|e| format!("Failed to write backup: {}", e))?;
+    Ok(count)
+}
+
+/// Delete all but the newest `keep` zips in `dir` named `<prefix>-<timestamp>.zip`
+/// Returns the paths removed.
+pub fn prune(dir: &Path, prefix: &str, keep: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('-')) && name.ends_with(".zip")
+        })
+        .collect();
+    // Timestamps sort chronologically
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    backups
+        .into_iter()
+        .take(excess)
+        .filter(|path| match fs::remove_file(path) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("[WARN] [backup] Failed to remove old backup {:?}: {}", path, e);
+                false
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_zip_and_prune() {
+        let dir = std::env::temp_dir().join(format!("unstablon-backup-{}", std::process::id()));
+        let vault = dir.join("Vault");
+        let out = dir.join("backups");
+        fs::create_dir_all(vault.join("Journal")).unwrap();
+        fs::create_dir_all(vault.join(".git")).unwrap();
+        fs::create_dir_all(&out).unwrap();
+        fs::write(vault.join("Ideas.md"), "# Ideas\n").unwrap();
+        fs::write(vault.join("Journal/today.md"), "# Today\n").unwrap();
+        fs::write(vault.join(".git/HEAD"), "ref").unwrap();
+        fs::write(vault.join("Journal/cat.png"), "png").unwrap();
+        fs::write(vault.join("Ideas.md.swp"), "").unwrap();
+
+        assert_eq!(backup_files(&vault, false), vec!["Ideas.md", "Journal/today.md"]);
+        let files = backup_files(&vault, true);
+        assert_eq!(files, vec!["Ideas.md", "Journal/cat.png", "Journal/today.md"]);
+
+        let target = out.join("Vault-20260101-000000.zip");
+        let last = std::cell::Cell::new((0, 0));
+        assert_eq!(write_zip(&vault, &files, &target, false, &|done, total| last.set((done, total))).unwrap(), 3);
+        assert!(write_zip(&vault, &files, &target, false, &|_, _| {}).is_err());
+        assert!(outside_path(&vault, &vault.join("Journal/../x.zip")).is_err());
+        assert!(outside_path(&vault, &out.join("../Vault/x.zip")).is_err());
+        assert!(outside_path(&vault, &out.join("x.zip")).is_ok());
+        assert_eq!(last.get(), (3, 3));
+        let archive = zip::ZipArchive::new(File::open(&target).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, files);
+
+        fs::write(out.join("Vault-20260102-000000.zip"), "").unwrap();
+        fs::write(out.join("Vault-20260103-000000.zip"), "").unwrap();
+        fs::write(out.join("Vault2-20250101-000000.zip"), "").unwrap();
+        assert_eq!(prune(&out, "Vault", 2), vec![target]);
+        assert!(out.join("Vault2-20250101-000000.zip").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
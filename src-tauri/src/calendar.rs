@@ -0,0 +1,360 @@
+//! Calendar - Meeting notes from iCalendar (.ics) events
+//!
+//! Each VEVENT in the import window gets a dated note in the meetings
+//! folder, named `<date> <summary>.md`, with the time, attendees and
+//! location as frontmatter properties. Notes are matched to events by their
+//! `calendar-uid` and `date` properties, so a re-import updates those
+//! properties (leaving the note body alone) instead of creating a
+//! duplicate. Recurring events are imported at their first occurrence and
+//! any overridden occurrences; rules aren't expanded. Times with a TZID are
+//! taken as local time.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::attachments;
+use crate::frontmatter;
+use crate::utils;
+use crate::webpage;
+
+/// Folder (vault-relative) meeting notes go in when none is given
+pub const DEFAULT_FOLDER: &str = "Meetings";
+
+/// Days ahead (from the start of today) imported when not given
+pub const DEFAULT_DAYS: u32 = 7;
+
+/// Largest calendar downloaded
+const MAX_CALENDAR_BYTES: usize = 16 * 1024 * 1024;
+
+/// A calendar event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    /// Local time (midnight for all-day events)
+    pub start: NaiveDateTime,
+    pub end: Option<NaiveDateTime>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    /// Display names, else email addresses
+    pub attendees: Vec<String>,
+}
+
+/// A meeting note to write
+pub struct NoteWrite {
+    pub path: PathBuf,
+    pub content: String,
+    /// False if it updates an existing note
+    pub created: bool,
+}
+
+/// Calendar text from an http(s)/webcal URL or a file path
+pub async fn load(source: &str) -> Result<String, String> {
+    let source = source.trim();
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => Some(format!("https://{}", rest)),
+        None if source.starts_with("http://") || source.starts_with("https://") => Some(source.to_string()),
+        None => None,
+    };
+    match url {
+        Some(url) => webpage::fetch_text(&webpage::parse_url(&url)?, MAX_CALENDAR_BYTES).await,
+        None => fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e)),
+    }
+}
+
+/// Events of an iCalendar file (cancelled ones left out)
+pub fn parse(ics: &str) -> Result<Vec<Event>, String> {
+    if !ics.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err("Not an iCalendar file".to_string());
+    }
+
+    let mut events = Vec::new();
+    // Properties of the VEVENT being read; None outside one
+    let mut current: Option<Vec<Property>> = None;
+    // Components nested inside the VEVENT (VALARM, ...)
+    let mut nested = 0;
+
+    for line in unfold(ics) {
+        let Some(property) = Property::parse(&line) else { continue };
+        match (property.name.as_str(), property.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take().and_then(|properties| to_event(&properties)) {
+                    events.push(event);
+                }
+                nested = 0;
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {
+                if let Some(properties) = current.as_mut().filter(|_| nested == 0) {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Join folded continuation lines (starting with a space or tab)
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// A content line: `NAME;PARAM=value:VALUE`
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            (c == ':' && !in_quotes).then_some(i)
+        })?;
+        let mut head = line[..colon].split(';');
+        let name = head.next()?.trim().to_ascii_uppercase();
+        let params = head
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+            .collect();
+        Some(Self { name, params, value: line[colon + 1..].to_string() })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+fn to_event(properties: &[Property]) -> Option<Event> {
+    let get = |name: &str| properties.iter().find(|p| p.name == name);
+    let text = |name: &str| get(name).map(|p| unescape(&p.value)).filter(|v| !v.trim().is_empty());
+
+    if get("STATUS").is_some_and(|p| p.value.eq_ignore_ascii_case("CANCELLED")) {
+        return None;
+    }
+    let (start, all_day) = get("DTSTART").and_then(parse_time)?;
+    let end = get("DTEND").and_then(parse_time).map(|(end, _)| end);
+    let attendees = properties
+        .iter()
+        .filter(|p| p.name == "ATTENDEE")
+        .filter_map(|p| {
+            let name = p.param("CN").map(str::to_string);
+            let email = p.value.strip_prefix("mailto:").or_else(|| p.value.strip_prefix("MAILTO:")).unwrap_or(&p.value);
+            name.or_else(|| (!email.is_empty()).then(|| email.to_string()))
+        })
+        .collect();
+
+    Some(Event {
+        uid: text("UID").unwrap_or_else(|| start.to_string()),
+        summary: text("SUMMARY").unwrap_or_else(|| "Untitled event".to_string()),
+        start,
+        end,
+        all_day,
+        location: text("LOCATION"),
+        description: text("DESCRIPTION"),
+        attendees,
+    })
+}
+
+/// A DATE or DATE-TIME value in local time, and whether it's a date only
+fn parse_time(property: &Property) -> Option<(NaiveDateTime, bool)> {
+    let value = property.value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?, true));
+    }
+    match value.strip_suffix('Z') {
+        Some(utc) => {
+            let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            Some((Utc.from_utc_datetime(&time).with_timezone(&Local).naive_local(), false))
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok().map(|time| (time, false)),
+    }
+}
+
+/// Undo TEXT value escaping
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Frontmatter properties of an event's note, in the order they're written
+fn properties(event: &Event) -> Vec<(&'static str, Option<JsonValue>)> {
+    let time = |time: &NaiveDateTime| {
+        let format = if event.all_day { "%Y-%m-%d" } else { "%Y-%m-%dT%H:%M" };
+        JsonValue::String(time.format(format).to_string())
+    };
+    vec![
+        ("title", Some(JsonValue::String(event.summary.clone()))),
+        ("date", Some(JsonValue::String(event.start.format("%Y-%m-%d").to_string()))),
+        ("start", Some(time(&event.start))),
+        ("end", event.end.as_ref().map(time)),
+        (
+            "attendees",
+            (!event.attendees.is_empty())
+                .then(|| JsonValue::Array(event.attendees.iter().cloned().map(JsonValue::String).collect())),
+        ),
+        ("location", event.location.clone().map(JsonValue::String)),
+        ("calendar-uid", Some(JsonValue::String(event.uid.clone()))),
+    ]
+}
+
+/// `content` with the event's properties set (and stale optional ones removed)
+fn apply_properties(content: &str, event: &Event) -> Result<String, String> {
+    let mut content = content.to_string();
+    for (key, value) in properties(event) {
+        content = match value {
+            Some(value) => frontmatter::set_property(&content, key, &value)?,
+            None => frontmatter::remove_property(&content, key).unwrap_or(content),
+        };
+    }
+    Ok(content)
+}
+
+/// Whether an event starts within `days` days from the start of `today`
+/// (a window reaching past chrono's last date has no upper bound). Only the
+/// event's own DTSTART counts: later occurrences of an RRULE aren't expanded,
+/// so a series that began before `today` is skipped.
+pub fn in_window(event: &Event, today: NaiveDate, days: u32) -> bool {
+    let date = event.start.date();
+    date >= today && today.checked_add_days(chrono::Days::new(u64::from(days))).map_or(true, |end| date < end)
+}
+
+/// Notes to create or update in `folder` for the events `in_window`
+/// (unchanged notes are left out)
+pub fn plan(folder: &Path, events: &[Event], today: NaiveDate, days: u32) -> Result<Vec<NoteWrite>, String> {
+
+    // (uid, date) -> path and content of the note already made for it
+    let mut existing = HashMap::new();
+    for rel in attachments::walk_files(folder).into_iter().filter(|rel| rel.ends_with(".md")) {
+        let path = folder.join(&rel);
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        let metadata = frontmatter::parse(&content);
+        let property = |key: &str| metadata.properties.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        if let (Some(uid), Some(date)) = (property("calendar-uid"), property("date")) {
+            existing.insert((uid, date), (path, content));
+        }
+    }
+
+    let mut writes = Vec::new();
+    let mut planned = HashSet::new();
+    for event in events.iter().filter(|event| in_window(event, today, days)) {
+        let date = event.start.format("%Y-%m-%d").to_string();
+        if let Some((path, content)) = existing.get(&(event.uid.clone(), date.clone())) {
+            let updated = apply_properties(content, event)?;
+            if updated != *content {
+                writes.push(NoteWrite { path: path.clone(), content: updated, created: false });
+            }
+            continue;
+        }
+
+        let stem = format!("{} {}", date, utils::safe_file_name(&event.summary));
+        let path = (0..)
+            .map(|n| match n {
+                0 => folder.join(format!("{}.md", stem)),
+                n => folder.join(format!("{} {}.md", stem, n)),
+            })
+            .find(|path| !path.exists() && !planned.contains(path))
+            .expect("unbounded counter");
+        planned.insert(path.clone());
+
+        let mut body = format!("# {}\n\n", event.summary);
+        if let Some(description) = &event.description {
+            body.push_str(&format!("{}\n\n", description));
+        }
+        body.push_str("## Notes\n\n");
+        writes.push(NoteWrite { path, content: apply_properties(&body, event)?, created: true });
+    }
+    Ok(writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VEVENT\r\nUID:sync-1\r\nSUMMARY:Weekly sync\\, team\r\nDTSTART:20260105T100000\r\nDTEND:20260105T110000\r\n\
+ATTENDEE;CN=\"Doe, Jane\":mailto:jane@example.com\r\nATTENDEE:mailto:bob@exam\r\n ple.com\r\n\
+BEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\nDESCRIPTION:Agenda:\\nStatus\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:off\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20260106\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nUID:gone\r\nSUMMARY:Cancelled\r\nSTATUS:CANCELLED\r\nDTSTART:20260105T120000\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse() {
+        let events = parse(ICS).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].summary, "Weekly sync, team");
+        assert_eq!(events[0].attendees, vec!["Doe, Jane", "bob@example.com"]);
+        assert_eq!(events[0].description.as_deref(), Some("Agenda:\nStatus"));
+        assert_eq!(events[0].end.unwrap().format("%H:%M").to_string(), "11:00");
+        assert!(events[1].all_day);
+        assert!(parse("<html>").is_err());
+    }
+
+    #[test]
+    fn test_in_window() {
+        let events = parse(ICS).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        assert!(in_window(&events[0], today, 1));
+        assert!(!in_window(&events[1], today, 1));
+        assert!(in_window(&events[1], today, u32::MAX));
+        assert!(!in_window(&events[0], today.succ_opt().unwrap(), u32::MAX));
+    }
+
+    #[test]
+    fn test_plan() {
+        let dir = std::env::temp_dir().join(format!("unstablon-calendar-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut events = parse(ICS).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+
+        let writes = plan(&dir, &events, today, 1).unwrap();
+        assert_eq!(writes.len(), 1);
+        assert!(writes[0].created);
+        assert!(writes[0].path.ends_with("2026-01-05 Weekly sync, team.md"));
+        assert!(writes[0].content.contains("calendar-uid: sync-1\n"));
+        fs::write(&writes[0].path, writes[0].content.replace("## Notes\n", "## Notes\n\nDecided things\n")).unwrap();
+
+        assert!(plan(&dir, &events, today, 1).unwrap().is_empty());
+        events[0].location = Some("Room 4".to_string());
+        let writes = plan(&dir, &events, today, 2).unwrap();
+        assert_eq!(writes.len(), 2);
+        assert!(!writes[0].created);
+        assert!(writes[0].content.contains("location: Room 4\n"));
+        assert!(writes[0].content.contains("Decided things"));
+        assert!(writes[1].path.ends_with("2026-01-06 Holiday.md"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
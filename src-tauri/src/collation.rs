@@ -0,0 +1,152 @@
+//! Collation - Locale-aware name ordering for file listings and the nav tree
+//!
+//! Sorting by `to_lowercase()` compares code points, which puts accented
+//! names after `z` and orders non-Latin scripts arbitrarily. Names are
+//! compared with an ICU collator for the user's system locale instead.
+//!
+//! With natural sorting enabled, runs of ASCII digits compare by numeric
+//! value, so "note2.md" sorts before "note10.md".
+
+use icu_collator::{Collator, CollatorOptions, Strength};
+use icu_locid::Locale;
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use crate::models::SortSettings;
+
+/// Natural (numeric-aware) sorting enabled
+static NATURAL: AtomicBool = AtomicBool::new(false);
+
+/// Collator for the system locale (created on first use)
+static COLLATOR: OnceLock<Option<Collator>> = OnceLock::new();
+
+fn get_collator() -> Option<&'static Collator> {
+    COLLATOR
+        .get_or_init(|| {
+            let tag = sys_locale::get_locale().unwrap_or_else(|| "und".to_string());
+            // POSIX-style tags ("en_US.UTF-8") aren't BCP-47
+            let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+            let locale: Locale = tag.parse().unwrap_or(Locale::UND);
+
+            // Case-insensitive like the old `to_lowercase()` sort; accents still count
+            let mut options = CollatorOptions::new();
+            options.strength = Some(Strength::Secondary);
+
+            match Collator::try_new(&(&locale).into(), options) {
+                Ok(collator) => {
+                    info!("[INFO] [collation] Using collation for locale {}", locale);
+                    Some(collator)
+                }
+                Err(e) => {
+                    warn!("[WARN] [collation] No collator for {}: {}", locale, e);
+                    Collator::try_new(&Default::default(), options).ok()
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Current sort settings
+pub fn settings() -> SortSettings {
+    SortSettings {
+        natural: NATURAL.load(AtomicOrdering::Relaxed),
+    }
+}
+
+/// Replace the sort settings (applies to subsequent listings)
+pub fn set_settings(settings: &SortSettings) {
+    NATURAL.store(settings.natural, AtomicOrdering::Relaxed);
+}
+
+/// Compare two names using the current sort settings
+/// Names equal under collation fall back to code point order so sorting
+/// is deterministic
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let ordering = if NATURAL.load(AtomicOrdering::Relaxed) {
+        natural_compare(a, b)
+    } else {
+        collate(a, b)
+    };
+    ordering.then_with(|| a.cmp(b))
+}
+
+/// Compare two strings in the user's locale
+fn collate(a: &str, b: &str) -> Ordering {
+    match get_collator() {
+        Some(collator) => collator.compare(a, b),
+        None => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Compare chunk by chunk: digit runs numerically, text by collation
+fn natural_compare(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+
+    loop {
+        let ordering = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if is_digits(x) && is_digits(y) => compare_numbers(x, y),
+            (Some(x), Some(y)) => collate(x, y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Split into alternating runs of ASCII digits and other characters
+fn chunks(s: &str) -> impl Iterator<Item = &str> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let digits = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+fn is_digits(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Compare digit runs by value without parsing (no overflow on long runs);
+/// equal values with more leading zeros sort later
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a_trimmed = a.trim_start_matches('0');
+    let b_trimmed = b.trim_start_matches('0');
+    a_trimmed
+        .len()
+        .cmp(&b_trimmed.len())
+        .then_with(|| a_trimmed.cmp(b_trimmed))
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let mut names = vec!["zebra", "Éclair", "apple", "eclair", "Banana"];
+        names.sort_by(|a, b| compare(a, b));
+        assert_eq!(names, vec!["apple", "Banana", "eclair", "Éclair", "zebra"]);
+    }
+
+    #[test]
+    fn test_natural_compare() {
+        let mut names = vec!["note10.md", "note2.md", "Note1.md", "note02.md", "notes.md"];
+        names.sort_by(|a, b| natural_compare(a, b).then_with(|| a.cmp(b)));
+        assert_eq!(names, vec!["Note1.md", "note2.md", "note02.md", "note10.md", "notes.md"]);
+
+        assert_eq!(natural_compare("a99999999999999999999", "a100000000000000000000"), Ordering::Less);
+    }
+}
@@ -0,0 +1,71 @@
+//! Note access IPC commands
+//!
+//! Open tracking is opt-in; `record_note_open` is a no-op until the user
+//! enables it. Opens are stored by vault-relative path, like pins.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::access;
+use crate::db::{Database, DbState};
+use crate::metrics;
+use crate::vault;
+
+/// Settings key holding whether opens are tracked
+const TRACKING_SETTING: &str = "access_tracking";
+
+/// Load the tracking choice saved in an earlier session
+pub(crate) fn restore_settings(db: &Database) {
+    match db.setting_json::<bool>(TRACKING_SETTING) {
+        Ok(Some(enabled)) => access::set_tracking(enabled),
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [access] Failed to load open tracking setting: {}", e),
+    }
+}
+
+/// Whether note opens are being recorded
+#[tauri::command]
+pub async fn get_access_tracking() -> Result<bool, String> {
+    metrics::measure("get_access_tracking", 0, async move {
+        Ok(access::is_tracking())
+    }).await
+}
+
+/// Turn recording of note opens on or off (kept across restarts)
+#[tauri::command]
+pub async fn set_access_tracking(enabled: bool, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_access_tracking", 0, async move {
+        info!("[INFO] [access] Open tracking {}", if enabled { "enabled" } else { "disabled" });
+        db.0.set_setting_json(TRACKING_SETTING, &enabled)?;
+        access::set_tracking(enabled);
+        Ok(())
+    }).await
+}
+
+/// Record that a note was opened (ignored while tracking is off)
+#[tauri::command]
+pub async fn record_note_open(path: String, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("record_note_open", path.len(), async move {
+        if !access::is_tracking() {
+            return Ok(());
+        }
+        let path = vault::resolve(&path).map_err(|e| e.to_string())?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        db.0.record_open(&vault::relative_path(&path), now, now.saturating_sub(access::RETENTION_SECS))
+    }).await
+}
+
+/// Forget all recorded opens; returns how many were removed
+#[tauri::command]
+pub async fn clear_note_access(db: State<'_, DbState>) -> Result<usize, String> {
+    metrics::measure("clear_note_access", 0, async move {
+        info!("[INFO] [access] Clearing recorded note opens");
+        db.0.clear_opens()
+    }).await
+}
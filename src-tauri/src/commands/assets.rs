@@ -0,0 +1,305 @@
+//! Asset (image and attachment) IPC commands
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::attachments;
+use crate::commands::fileops::write_tracked;
+use crate::db::DbState;
+use crate::email;
+use crate::error::AppError;
+use crate::indexer::{self, IndexSettingsState};
+use crate::metrics;
+use crate::models::{DedupReport, DuplicateGroup, EmailConversion, FileAnnotation, ImportOptions, ImportResult, Thumbnail};
+use crate::sidecar;
+use crate::thumbnails;
+use crate::undo;
+use crate::utils;
+use crate::vault;
+
+/// Folder (vault-relative) imports go to when none is given
+const DEFAULT_IMPORT_FOLDER: &str = "attachments";
+
+/// Thumbnail of a vault image no larger than `max_px` on either side
+/// Generated once per image content and size, then served from the cache
+/// in app data. Returns the path of the image to display.
+#[tauri::command]
+pub async fn get_thumbnail(path: String, max_px: u32, app: AppHandle) -> Result<Thumbnail, AppError> {
+    metrics::measure("get_thumbnail", path.len(), async move {
+        if !thumbnails::is_image_file(&path) {
+            return Err(AppError::InvalidOperation(format!("Not a supported image: {}", path)));
+        }
+        let source = vault::resolve(&path)?;
+        info!("[INFO] [assets] Thumbnail {}px for: {}", max_px, source);
+
+        let cache_dir = app.path()
+            .app_data_dir()
+            .map_err(|e| AppError::Path(format!("Failed to get app data dir: {}", e)))?
+            .join("thumbnails");
+
+        tauri::async_runtime::spawn_blocking(move || {
+            thumbnails::thumbnail(Path::new(&source), max_px, &cache_dir)
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Thumbnail task failed: {}", e)))?
+    }).await
+}
+
+/// Groups of byte-identical attachment files in the open vault
+#[tauri::command]
+pub async fn find_duplicate_attachments() -> Result<Vec<DuplicateGroup>, AppError> {
+    metrics::measure("find_duplicate_attachments", 0, async move {
+        let root = vault::resolve(".")?;
+        info!("[INFO] [assets] Scanning for duplicate attachments in: {}", root);
+
+        tauri::async_runtime::spawn_blocking(move || attachments::find_duplicates(Path::new(&root)))
+            .await
+            .map_err(|e| AppError::InvalidOperation(format!("Duplicate scan failed: {}", e)))
+    }).await
+}
+
+/// Keep one copy of each duplicate group, pointing every note link at it,
+/// and delete the other copies
+/// Notes are rewritten (and re-indexed) before any file is deleted, so a
+/// failure part-way never leaves links to a removed copy.
+#[tauri::command]
+pub async fn deduplicate_attachments(
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<DedupReport, AppError> {
+    metrics::measure("deduplicate_attachments", 0, async move {
+        let root = vault::resolve(".")?;
+        info!("[INFO] [assets] Deduplicating attachments in: {}", root);
+
+        let scan_root = root.clone();
+        let (groups, notes) = tauri::async_runtime::spawn_blocking(move || {
+            let root = Path::new(&scan_root);
+            let notes: Vec<String> = attachments::walk_files(root)
+                .into_iter()
+                .filter(|path| utils::is_document_file(path))
+                .collect();
+            (attachments::find_duplicates(root), notes)
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Duplicate scan failed: {}", e)))?;
+
+        let mut report = DedupReport::default();
+        let mut replacements = HashMap::new();
+        for group in &groups {
+            for copy in &group.paths[1..] {
+                replacements.insert(copy.clone(), group.paths[0].clone());
+            }
+        }
+        if replacements.is_empty() {
+            return Ok(report);
+        }
+
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        let mut previous = Vec::new();
+        // Whatever was changed before a failure still gets an undo entry
+        let outcome: Result<(), AppError> = async {
+            for note in notes {
+                let path = Path::new(&root).join(&note).to_string_lossy().to_string();
+                let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+                if let Some(updated) = attachments::rewrite_links(&content, &note, &replacements) {
+                    write_tracked(&path, &updated).await?;
+                    previous.push((path.clone(), Some(content.into_bytes())));
+                    if let Err(e) = indexer::index_file(&db.0, &path, &settings).await {
+                        warn!("[WARN] [assets] Failed to re-index {}: {}", path, e);
+                    }
+                    report.updated_notes.push(note);
+                }
+            }
+
+            for group in groups {
+                for copy in group.paths.into_iter().skip(1) {
+                    let path = Path::new(&root).join(&copy);
+                    let bytes = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+                    fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
+                    previous.push((path.to_string_lossy().to_string(), Some(bytes)));
+                    report.bytes_freed += group.size;
+                    report.removed.push(copy);
+                }
+            }
+            Ok(())
+        }
+        .await;
+
+        info!(
+            "[INFO] [assets] Removed {} duplicate(s), rewrote links in {} note(s)",
+            report.removed.len(),
+            report.updated_notes.len()
+        );
+        undo::record(format!("Remove {} duplicate attachments", report.removed.len()), previous);
+        outcome?;
+        Ok(report)
+    }).await
+}
+
+/// Note and tags of a non-markdown file (None if it has no sidecar)
+#[tauri::command]
+pub async fn get_file_annotation(path: String) -> Result<Option<FileAnnotation>, AppError> {
+    metrics::measure("get_file_annotation", path.len(), async move {
+        let path = vault::resolve(&path)?;
+        let sidecar_path = sidecar::path_for(&path);
+        match fs::read_to_string(&sidecar_path) {
+            Ok(content) => sidecar::parse(&content).map(Some).map_err(AppError::InvalidOperation),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::io(&sidecar_path, e)),
+        }
+    }).await
+}
+
+/// Annotate a non-markdown file, writing its sidecar and indexing the
+/// annotation under the file's path. An empty annotation removes both.
+#[tauri::command]
+pub async fn set_file_annotation(
+    path: String,
+    annotation: FileAnnotation,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<(), AppError> {
+    metrics::measure("set_file_annotation", path.len(), async move {
+        let path = utils::normalize_path(&vault::resolve(&path)?);
+        if utils::is_content_file(&path) {
+            return Err(AppError::InvalidOperation(format!("Content files carry their own metadata: {}", path)));
+        }
+        if !Path::new(&path).is_file() {
+            return Err(AppError::NotFound(format!("File not found: {}", path)));
+        }
+        let sidecar_path = sidecar::path_for(&path);
+
+        if annotation.is_empty() {
+            info!("[INFO] [assets] Removing annotation of: {}", path);
+            if Path::new(&sidecar_path).exists() {
+                crate::write_tracker::record_write(&sidecar_path);
+                fs::remove_file(&sidecar_path).map_err(|e| AppError::io(&sidecar_path, e))?;
+            }
+            db.0.remove_content(&path).map_err(AppError::InvalidOperation)?;
+            return Ok(());
+        }
+
+        info!("[INFO] [assets] Annotating: {}", path);
+        let content = sidecar::render(&annotation).map_err(AppError::InvalidOperation)?;
+        write_tracked(&sidecar_path, &content).await?;
+
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        if let Err(e) = indexer::index_file(&db.0, &sidecar_path, &settings).await {
+            warn!("[WARN] [assets] Failed to index annotation of {}: {}", path, e);
+        }
+        Ok(())
+    }).await
+}
+
+/// Import an external file (e.g. drag-and-drop) into a vault folder
+/// `src_path` is outside the vault; `target_folder` is vault-relative
+/// ("attachments" if omitted). Name collisions get a numeric suffix, and
+/// imported content files are indexed right away.
+#[tauri::command]
+pub async fn import_external_file(
+    src_path: String,
+    target_folder: Option<String>,
+    options: Option<ImportOptions>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<ImportResult, AppError> {
+    metrics::measure("import_external_file", src_path.len(), async move {
+        let options = options.unwrap_or_default();
+        let source = Path::new(&src_path);
+        if !source.is_file() {
+            return Err(AppError::NotFound(format!("File not found: {}", src_path)));
+        }
+        let target_dir = vault::resolve(target_folder.as_deref().unwrap_or(DEFAULT_IMPORT_FOLDER))?;
+        info!("[INFO] [assets] Importing {} into {} ({:?})", src_path, target_dir, options);
+
+        let (target, converted) = attachments::import_file(source, Path::new(&target_dir), &options)?;
+        let target = utils::normalize_path(&target.to_string_lossy());
+
+        if utils::is_content_file(&target) {
+            let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+            if let Err(e) = indexer::index_file(&db.0, &target, &settings).await {
+                warn!("[WARN] [assets] Failed to index imported file {}: {}", target, e);
+            }
+        }
+
+        let path = vault::relative_path(&target);
+        let note_dir = options.note_path.as_deref().map(|note| {
+            let note = vault::relative_path(note);
+            note.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default()
+        });
+
+        Ok(ImportResult {
+            insert_text: attachments::insert_text(&path, note_dir.as_deref()),
+            path,
+            converted,
+        })
+    }).await
+}
+
+/// Convert an .eml message in the vault to a markdown note beside it
+/// Headers become frontmatter properties and attachments are extracted to
+/// `attachments_folder` (the import folder by default) and linked from the
+/// note. With `remove_original` the .eml file is deleted. Undoable.
+#[tauri::command]
+pub async fn convert_email_to_note(
+    path: String,
+    attachments_folder: Option<String>,
+    remove_original: Option<bool>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<EmailConversion, AppError> {
+    metrics::measure("convert_email_to_note", path.len(), async move {
+        if !email::is_email_file(&path) {
+            return Err(AppError::InvalidOperation(format!("Not an email: {}", path)));
+        }
+        let source = vault::resolve(&path)?;
+        let raw = fs::read(&source).map_err(|e| AppError::io(&source, e))?;
+        let (message, data) = email::parse(&raw).map_err(AppError::InvalidOperation)?;
+        let folder = vault::resolve(attachments_folder.as_deref().unwrap_or(DEFAULT_IMPORT_FOLDER))?;
+        info!("[INFO] [assets] Converting {} to a note ({} attachments)", path, data.len());
+
+        let note_dir = Path::new(&source).parent().unwrap_or(Path::new(&source)).to_path_buf();
+        let note_path = attachments::unique_path(&note_dir, &format!("{}.md", utils::safe_file_name(&email::title(&source, &message))));
+        let note_path = utils::normalize_path(&note_path.to_string_lossy());
+        let note_rel = vault::relative_path(&note_path);
+        let note_rel_dir = note_rel.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default();
+
+        let mut previous = Vec::new();
+        let mut extracted = Vec::new();
+        let mut links = Vec::new();
+        if !data.is_empty() {
+            fs::create_dir_all(&folder).map_err(|e| AppError::io(&folder, e))?;
+        }
+        for (attachment, bytes) in message.attachments.iter().zip(&data) {
+            let target = attachments::unique_path(Path::new(&folder), &utils::safe_file_name(&attachment.name));
+            fs::write(&target, bytes).map_err(|e| AppError::io(&target, e))?;
+            let target = utils::normalize_path(&target.to_string_lossy());
+            let rel = vault::relative_path(&target);
+            links.push(attachments::insert_text(&rel, Some(&note_rel_dir)));
+            previous.push((target, None));
+            extracted.push(rel);
+        }
+
+        let content = email::to_markdown(&message, &links).map_err(AppError::InvalidOperation)?;
+        write_tracked(&note_path, &content).await?;
+        previous.push((note_path.clone(), None));
+
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        if let Err(e) = indexer::index_file(&db.0, &note_path, &settings).await {
+            warn!("[WARN] [assets] Failed to index {}: {}", note_path, e);
+        }
+        if remove_original.unwrap_or(false) {
+            fs::remove_file(&source).map_err(|e| AppError::io(&source, e))?;
+            previous.push((source.clone(), Some(raw)));
+            if let Err(e) = db.0.remove_content(&utils::normalize_path(&source)) {
+                warn!("[WARN] [assets] Failed to remove {} from the index: {}", source, e);
+            }
+        }
+
+        undo::record(format!("Convert {} to a note", path), previous);
+        Ok(EmailConversion { note: note_rel, attachments: extracted })
+    }).await
+}
@@ -0,0 +1,218 @@
+//! Diagnostics IPC commands
+
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::State;
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::db::DbState;
+use crate::error_log;
+use crate::indexer::IndexSettingsState;
+use crate::lint;
+use crate::logging;
+use crate::metrics;
+use crate::models::{ErrorLogEntry, LintOptions, LintReport, PerformanceMetrics, TitleGroup};
+use crate::platform;
+use crate::utils;
+use crate::vault;
+use crate::watcher;
+
+/// Default number of entries returned by get_recent_errors
+const DEFAULT_ERROR_LIMIT: usize = 100;
+
+/// Error log entries included in a diagnostics bundle
+const EXPORT_ERROR_LIMIT: usize = 1000;
+
+/// Default and maximum entries returned by get_recent_logs
+const DEFAULT_LOG_LINES: usize = 200;
+const MAX_LOG_LINES: usize = 5000;
+
+/// Get per-command timing and payload size metrics
+#[tauri::command]
+pub async fn get_performance_metrics() -> Result<PerformanceMetrics, String> {
+    metrics::measure("get_performance_metrics", 0, async move {
+        Ok(metrics::snapshot())
+    }).await
+}
+
+/// Clear recorded command metrics (e.g. before reproducing an issue)
+#[tauri::command]
+pub async fn reset_performance_metrics() -> Result<(), String> {
+    metrics::measure("reset_performance_metrics", 0, async move {
+        info!("[INFO] [diagnostics] Resetting performance metrics");
+        metrics::reset();
+        Ok(())
+    }).await
+}
+
+/// Get recent backend warnings and errors, newest first
+#[tauri::command]
+pub async fn get_recent_errors(limit: Option<usize>) -> Result<Vec<ErrorLogEntry>, String> {
+    metrics::measure("get_recent_errors", 0, async move {
+        Ok(error_log::recent(limit.unwrap_or(DEFAULT_ERROR_LIMIT)))
+    }).await
+}
+
+/// Clear the recent error buffer
+#[tauri::command]
+pub async fn clear_recent_errors() -> Result<(), String> {
+    metrics::measure("clear_recent_errors", 0, async move {
+        info!("[INFO] [diagnostics] Clearing recent errors");
+        error_log::clear();
+        Ok(())
+    }).await
+}
+
+/// Get the last `lines` entries from the log files, oldest first
+/// With `level`, only entries at that level or more severe are returned.
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>, level: Option<String>) -> Result<Vec<String>, String> {
+    metrics::measure("get_recent_logs", 0, async move {
+        let limit = lines.unwrap_or(DEFAULT_LOG_LINES).min(MAX_LOG_LINES);
+        let min_level = level.as_deref().map(logging::parse_level).transpose()?;
+
+        tauri::async_runtime::spawn_blocking(move || logging::recent(limit, min_level))
+            .await
+            .map_err(|e| format!("Log read failed: {}", e))?
+    }).await
+}
+
+/// Open the log directory in the OS file manager
+#[tauri::command]
+pub async fn open_log_folder() -> Result<(), String> {
+    metrics::measure("open_log_folder", 0, async move {
+        let dir = logging::log_dir().ok_or("File logging is not initialized")?;
+        info!("[INFO] [diagnostics] Opening log folder {:?}", dir);
+        platform::open_path(dir)
+    }).await
+}
+
+/// Get the active log filter directives
+#[tauri::command]
+pub async fn get_log_level() -> Result<String, String> {
+    metrics::measure("get_log_level", 0, async move {
+        logging::filter()
+    }).await
+}
+
+/// Change the log filter without restarting (EnvFilter syntax)
+/// e.g. `"unstablon_pkm=info,unstablon_pkm::watcher=debug"`; an empty
+/// filter restores the default. Returns the filter now in effect.
+#[tauri::command]
+pub async fn set_log_level(filter: String) -> Result<String, String> {
+    metrics::measure("set_log_level", filter.len(), async move {
+        logging::set_filter(&filter)?;
+        let active = logging::filter()?;
+        info!("[INFO] [diagnostics] Log filter set to {}", active);
+        Ok(active)
+    }).await
+}
+
+/// Write a diagnostics bundle (zip) for bug reports
+/// Contains recent logs and errors, performance metrics, database stats, watcher
+/// status and platform info. Note contents are never included; the only
+/// vault data is the watched path and whatever paths appear in log messages.
+/// `dest` is a new zip file, or a directory to create a timestamped one in;
+/// an existing file is never replaced. Returns the path written.
+#[tauri::command]
+pub async fn export_diagnostics(dest: String, db: State<'_, DbState>) -> Result<String, String> {
+    metrics::measure("export_diagnostics", dest.len(), async move {
+        let dest = PathBuf::from(&dest);
+        let target = if dest.is_dir() {
+            let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+            dest.join(format!("unstablon-diagnostics-{}.zip", stamp))
+        } else {
+            dest
+        };
+
+        let watcher_status = watcher::vault_watcher_status().await;
+        let db = db.0.clone();
+
+        tauri::async_runtime::spawn_blocking(move || {
+            // A failed stats query still yields a bundle, with the error in its place
+            let db_stats = db.stats().map_err(|e| serde_json::json!({ "error": e }));
+            let manifest = serde_json::json!({
+                "app": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "createdAt": chrono::Local::now().to_rfc3339(),
+            });
+
+            // create_new: refuse to truncate whatever file `dest` names
+            let file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&target)
+                .map_err(|e| format!("Failed to create {:?}: {}", target, e))?;
+            let mut zip = ZipWriter::new(file);
+            add_json(&mut zip, "manifest.json", &manifest)?;
+            add_json(&mut zip, "platform.json", &platform::current())?;
+            add_json(&mut zip, "watcher.json", &watcher_status)?;
+            add_json(&mut zip, "database.json", &db_stats)?;
+            add_json(&mut zip, "metrics.json", &metrics::snapshot())?;
+            add_json(&mut zip, "logs/recent-errors.json", &error_log::recent(EXPORT_ERROR_LIMIT))?;
+            let log_tail = logging::recent(MAX_LOG_LINES, None).unwrap_or_else(|e| vec![e]);
+            add_file(&mut zip, "logs/unstablon.log", log_tail.join("\n").as_bytes())?;
+            zip.finish().map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+
+            info!("[INFO] [diagnostics] Exported diagnostics to {:?}", target);
+            Ok(utils::normalize_path(&target.to_string_lossy()))
+        })
+        .await
+        .map_err(|e| format!("Diagnostics export failed: {}", e))?
+    }).await
+}
+
+fn add_json<W, T>(zip: &mut ZipWriter<W>, name: &str, value: &T) -> Result<(), String>
+where
+    W: Write + std::io::Seek,
+    T: Serialize + ?Sized,
+{
+    let json = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    add_file(zip, name, &json)
+}
+
+fn add_file<W: Write + std::io::Seek>(zip: &mut ZipWriter<W>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .and_then(|()| zip.write_all(bytes).map_err(Into::into))
+        .map_err(|e| format!("Failed to write {} to diagnostics bundle: {}", name, e))
+}
+
+/// Check the open vault for broken links, missing frontmatter, duplicate
+/// titles, empty notes, oversized files and non-UTF-8 files
+#[tauri::command]
+pub async fn lint_vault(
+    options: Option<LintOptions>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<LintReport, String> {
+    metrics::measure("lint_vault", 0, async move {
+        let root = vault::resolve(".").map_err(|e| e.to_string())?;
+        let options = options.unwrap_or_default();
+        let max_file_bytes = settings.snapshot()?.max_file_bytes;
+        info!("[INFO] [diagnostics] Linting vault: {}", root);
+
+        let mut report = tauri::async_runtime::spawn_blocking(move || {
+            lint::lint(std::path::Path::new(&root), &options, max_file_bytes)
+        })
+        .await
+        .map_err(|e| format!("Vault lint failed: {}", e))?;
+
+        report.duplicate_titles = db.0.duplicate_titles()?
+            .into_iter()
+            .map(|(title, paths)| TitleGroup {
+                title,
+                paths: paths.iter().map(|p| vault::relative_path(p)).collect(),
+            })
+            .collect();
+
+        info!(
+            "[INFO] [diagnostics] Lint found {} broken links, {} duplicate titles",
+            report.broken_links.len(), report.duplicate_titles.len()
+        );
+        Ok(report)
+    }).await
+}
@@ -1,202 +1,825 @@
 //! File operation IPC commands
 
-use crate::error::AppError;
-use crate::models::{FileEntry, FolderNode, ModuleNode, NavigationNode, PageNode, DocumentNode};
+use crate::attachments;
+use crate::collation;
+use crate::db::{Database, DbState};
+use crate::email;
+use crate::error::{self, AppError};
+use crate::headings;
+use crate::indexer::{self, IndexSettingsState};
+use crate::metrics;
+use crate::notebook;
+use crate::sidecar;
+use crate::structured;
+use crate::textdiff;
+use crate::undo;
+use crate::models::{AttachmentNode, DiffAlgorithm, DiffGranularity, DiffResult, FileEntry, FileMtime, UndoResult, FileSnapshot, FileVersion, FolderNode, ModuleNode, NavigationNode, OpenedVault, PageNode, DocumentNode, DataNode, EmailMessage, NavigationFilter, IndexSettings, Notebook, RenameFileChange, RenamePreview, SortSettings, StructuredData};
 use crate::utils;
+use crate::vault;
+use crate::vault_key;
 use crate::watcher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::Path;
-use std::time::SystemTime;
-use tauri::AppHandle;
-use tracing::info;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::PoisonError;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+use tracing::{info, warn};
+
+/// Largest file the read commands return without `force` (bytes)
+static READ_LIMIT: AtomicU64 = AtomicU64::new(64 * 1024 * 1024);
+
+/// Settings keys for the read limit and sort settings
+const READ_LIMIT_SETTING: &str = "read_limit";
+const SORT_SETTING: &str = "sort_settings";
+
+/// Restore the read limit and sort settings saved in earlier sessions
+pub(crate) fn restore_settings(db: &Database) {
+    match db.setting_json::<u64>(READ_LIMIT_SETTING) {
+        Ok(Some(bytes)) => READ_LIMIT.store(bytes, Ordering::Relaxed),
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [fileops] Failed to load read limit: {}", e),
+    }
+    match db.setting_json::<SortSettings>(SORT_SETTING) {
+        Ok(Some(settings)) => collation::set_settings(&settings),
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [fileops] Failed to load sort settings: {}", e),
+    }
+}
+
+/// Get the read size limit in bytes
+#[tauri::command]
+pub async fn get_read_limit() -> Result<u64, String> {
+    metrics::measure("get_read_limit", 0, async move {
+        Ok(READ_LIMIT.load(Ordering::Relaxed))
+    }).await
+}
+
+/// Set the read size limit in bytes (applies to subsequent reads, and is
+/// kept across restarts)
+#[tauri::command]
+pub async fn set_read_limit(bytes: u64, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_read_limit", 0, async move {
+        info!("[INFO] [fileops] Setting read limit: {} bytes", bytes);
+        db.0.set_setting_json(READ_LIMIT_SETTING, &bytes)?;
+        READ_LIMIT.store(bytes, Ordering::Relaxed);
+        Ok(())
+    }).await
+}
+
+/// Fail with TooLarge if a file exceeds the read limit (unless forced)
+fn check_read_size(metadata: &fs::Metadata, force: bool) -> Result<(), AppError> {
+    let limit = READ_LIMIT.load(Ordering::Relaxed);
+    if !force && metadata.len() > limit {
+        return Err(AppError::TooLarge { size: metadata.len(), limit });
+    }
+    Ok(())
+}
 
 /// Read file contents
+/// Files over the read limit fail with TooLarge unless `force` is set.
 #[tauri::command]
-pub async fn read_file(path: String) -> Result<String, String> {
-    info!("[INFO] [fileops] Reading file: {}", path);
+pub async fn read_file(path: String, force: Option<bool>) -> Result<String, AppError> {
+    metrics::measure("read_file", path.len(), async move {
+        info!("[INFO] [fileops] Reading file: {}", path);
+        let path = vault::resolve(&path)?;
+
+        check_read_size(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?, force.unwrap_or(false))?;
+        fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))
+    }).await
+}
 
-    fs::read_to_string(&path)
-        .map_err(|e| AppError::Io(e).to_string())
+/// Read a JSON/YAML/TOML file as parsed data
+#[tauri::command]
+pub async fn read_structured(path: String) -> Result<StructuredData, AppError> {
+    metrics::measure("read_structured", path.len(), async move {
+        info!("[INFO] [fileops] Reading structured data: {}", path);
+        let format = structured::Format::from_path(&path)
+            .ok_or_else(|| AppError::InvalidOperation(format!("Not a data file: {}", path)))?;
+        let path = vault::resolve(&path)?;
+
+        check_read_size(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?, false)?;
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let data = structured::parse(format, &content)
+            .map_err(|e| AppError::InvalidOperation(format!("Failed to parse {}: {}", path, e)))?;
+
+        Ok(StructuredData {
+            format: format.as_str().to_string(),
+            data,
+        })
+    }).await
+}
+
+/// Read a Jupyter notebook as structured cells (read-only rendering)
+#[tauri::command]
+pub async fn read_notebook(path: String) -> Result<Notebook, AppError> {
+    metrics::measure("read_notebook", path.len(), async move {
+        info!("[INFO] [fileops] Reading notebook: {}", path);
+        if !notebook::is_notebook_file(&path) {
+            return Err(AppError::InvalidOperation(format!("Not a notebook: {}", path)));
+        }
+        let path = vault::resolve(&path)?;
+
+        check_read_size(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?, false)?;
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        notebook::parse(&content).map_err(AppError::InvalidOperation)
+    }).await
+}
+
+/// Read an .eml message as headers, body and attachment list (read-only rendering)
+#[tauri::command]
+pub async fn read_email(path: String) -> Result<EmailMessage, AppError> {
+    metrics::measure("read_email", path.len(), async move {
+        info!("[INFO] [fileops] Reading email: {}", path);
+        if !email::is_email_file(&path) {
+            return Err(AppError::InvalidOperation(format!("Not an email: {}", path)));
+        }
+        let path = vault::resolve(&path)?;
+
+        check_read_size(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?, false)?;
+        let raw = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+        email::parse(&raw).map(|(message, _)| message).map_err(AppError::InvalidOperation)
+    }).await
+}
+
+/// Read file contents along with mtime, hash, and size in one call
+/// Establishes the editor's conflict baseline without a read/stat race.
+#[tauri::command]
+pub async fn read_file_ex(path: String, force: Option<bool>) -> Result<FileSnapshot, AppError> {
+    metrics::measure("read_file_ex", path.len(), async move {
+        info!("[INFO] [fileops] Reading file with metadata: {}", path);
+        let path = vault::resolve(&path)?;
+
+        let mut file = fs::File::open(&path).map_err(|e| AppError::io(&path, e))?;
+        let metadata = file.metadata().map_err(|e| AppError::io(&path, e))?;
+        check_read_size(&metadata, force.unwrap_or(false))?;
+        let mut content = String::with_capacity(metadata.len() as usize);
+        file.read_to_string(&mut content).map_err(|e| AppError::io(&path, e))?;
+
+        Ok(FileSnapshot {
+            hash: utils::content_hash(content.as_bytes()),
+            size: content.len() as u64,
+            mtime: mtime_millis(&metadata)?,
+            content,
+        })
+    }).await
 }
 
 /// Write content to file
+/// Markdown notes with TOC markers have their TOC refreshed on the way.
 #[tauri::command]
-pub async fn write_file(path: String, content: String) -> Result<(), String> {
-    info!("[INFO] [fileops] Writing file: {}", path);
+pub async fn write_file(path: String, content: String, db: State<'_, DbState>) -> Result<(), AppError> {
+    metrics::measure("write_file", path.len() + content.len(), async move {
+        info!("[INFO] [fileops] Writing file: {}", path);
+        let path = vault::resolve(&path)?;
+
+        let previous = fs::read_to_string(&path).ok();
+        let content = with_fresh_toc(&path, content);
+        write_tracked(&path, &content).await?;
+        indexer::record_writing(&db.0, &path, previous.as_deref(), &content);
+        Ok(())
+    }).await
+}
+
+/// Write content only if the file still matches the caller's baseline
+/// (mtime and/or hash from read_file_ex); fails with a Conflict error if it
+/// changed or was deleted since. Returns the new baseline.
+#[tauri::command]
+pub async fn write_file_checked(
+    path: String,
+    content: String,
+    expected_mtime: Option<u64>,
+    expected_hash: Option<String>,
+    db: State<'_, DbState>,
+) -> Result<FileVersion, AppError> {
+    metrics::measure("write_file_checked", path.len() + content.len(), async move {
+        info!("[INFO] [fileops] Writing file (checked): {}", path);
+        let path = vault::resolve(&path)?;
+
+        if expected_mtime.is_none() && expected_hash.is_none() {
+            return Err(AppError::InvalidOperation(
+                "write_file_checked needs an expected mtime or hash".to_string(),
+            ));
+        }
+
+        let current = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(AppError::Conflict(format!("File was deleted: {}", path)));
+            }
+            Err(e) => return Err(AppError::io(&path, e)),
+        };
+
+        if let Some(expected) = expected_mtime {
+            let mtime = mtime_millis(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?)?;
+            if mtime != expected {
+                return Err(AppError::Conflict(format!(
+                    "File was modified externally: {} (mtime {} != {})",
+                    path, mtime, expected
+                )));
+            }
+        }
+
+        if let Some(expected) = &expected_hash {
+            if utils::content_hash(&current) != *expected {
+                return Err(AppError::Conflict(format!("File content changed: {}", path)));
+            }
+        }
+
+        let content = with_fresh_toc(&path, content);
+        write_tracked(&path, &content).await?;
+        indexer::record_writing(&db.0, &path, Some(&String::from_utf8_lossy(&current)), &content);
+
+        Ok(FileVersion {
+            mtime: mtime_millis(&fs::metadata(&path).map_err(|e| AppError::io(&path, e))?)?,
+            hash: utils::content_hash(content.as_bytes()),
+            size: content.len() as u64,
+        })
+    }).await
+}
+
+/// Notes saved with TOC markers get their TOC regenerated
+fn with_fresh_toc(path: &str, content: String) -> String {
+    if !path.ends_with(".md") {
+        return content;
+    }
+    headings::refresh_toc(&content).unwrap_or(content)
+}
+
+/// Waits between save attempts while another process holds the file
+/// (about 1.5s in all before giving up with a Busy error)
+const BUSY_RETRY_DELAYS_MS: [u64; 5] = [50, 100, 200, 400, 800];
 
+/// Write a file the way the app's own saves do: recorded in the write
+/// tracker (so the watcher ignores the change) with parent dirs created.
+/// Sync clients like OneDrive and Dropbox briefly lock files they upload,
+/// so sharing violations are retried with backoff (without blocking the
+/// async runtime while waiting).
+pub(crate) async fn write_tracked(path: &str, content: &str) -> Result<(), AppError> {
     // Record write BEFORE writing (so watcher knows to ignore the event)
-    crate::write_tracker::record_write(&path);
+    crate::write_tracker::record_write(path);
 
     // Ensure parent directory exists
-    if let Some(parent) = Path::new(&path).parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| AppError::Io(e).to_string())?;
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
     }
 
-    fs::write(&path, content)
-        .map_err(|e| AppError::Io(e).to_string())
+    let mut delays = BUSY_RETRY_DELAYS_MS.iter();
+    loop {
+        match fs::write(path, content) {
+            Ok(()) => return Ok(()),
+            Err(e) if error::is_busy(&e) => match delays.next() {
+                Some(delay) => {
+                    warn!("[WARN] [fileops] {} is in use, retrying in {}ms: {}", path, delay, e);
+                    tokio::time::sleep(Duration::from_millis(*delay)).await;
+                }
+                None => return Err(AppError::Busy(format!("{} is in use by another program: {}", path, e))),
+            },
+            Err(e) => return Err(AppError::io(path, e)),
+        }
+    }
 }
 
-/// List directory contents
+/// Every note line a rename (or move) of `path` to `new_path` would rewrite
+/// to keep links pointing at it; nothing is changed. `path` may be a folder.
 #[tauri::command]
-pub async fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
-    info!("[INFO] [fileops] Listing directory: {}", path);
+pub async fn preview_rename(path: String, new_path: String) -> Result<RenamePreview, AppError> {
+    metrics::measure("preview_rename", path.len() + new_path.len(), async move {
+        let from = vault::relative_path(&vault::resolve(&path)?);
+        let to = vault::relative_path(&vault::resolve(&new_path)?);
+        if !Path::new(&vault::resolve(&from)?).exists() {
+            return Err(AppError::NotFound(format!("Not found: {}", from)));
+        }
+        if Path::new(&vault::resolve(&to)?).exists() {
+            return Err(AppError::Conflict(format!("Already exists: {}", to)));
+        }
+        info!("[INFO] [fileops] Previewing rename: {} -> {}", from, to);
+
+        let root = vault::resolve(".")?;
+        tauri::async_runtime::spawn_blocking(move || {
+            let root = Path::new(&root);
+            let files = attachments::walk_files(root);
+            let replacements = attachments::rename_replacements(&files, &from, &to);
+
+            let mut preview = RenamePreview::default();
+            for note in files.iter().filter(|f| utils::is_document_file(f)) {
+                let Ok(content) = fs::read_to_string(root.join(note)) else {
+                    continue;
+                };
+                if let Some(updated) = attachments::rewrite_links(&content, note, &replacements) {
+                    let lines = attachments::changed_lines(&content, &updated);
+                    preview.total_lines += lines.len();
+                    preview.files.push(RenameFileChange { path: note.clone(), lines });
+                }
+            }
 
-    let entries = fs::read_dir(&path)
-        .map_err(|e| AppError::Io(e).to_string())?;
+            let mut moved: Vec<(String, String)> = replacements.into_iter().filter(|(old, _)| files.binary_search(old).is_ok()).collect();
+            moved.sort();
+            preview.moved = moved;
+            preview
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Rename preview failed: {}", e)))
+    }).await
+}
 
-    let mut result = Vec::new();
+/// Diff two texts by line (default) or word, as hunks for the version
+/// history and external-conflict views
+#[tauri::command]
+pub async fn diff_texts(
+    old: String,
+    new: String,
+    granularity: Option<DiffGranularity>,
+    algorithm: Option<DiffAlgorithm>,
+) -> Result<DiffResult, String> {
+    metrics::measure("diff_texts", old.len() + new.len(), async move {
+        let granularity = granularity.unwrap_or_default();
+        let algorithm = algorithm.unwrap_or_default();
+        tauri::async_runtime::spawn_blocking(move || textdiff::diff(&old, &new, granularity, algorithm))
+            .await
+            .map_err(|e| format!("Diff failed: {}", e))
+    }).await
+}
 
-    for entry in entries.flatten() {
-        let metadata = entry.metadata().ok();
-        let modified_at = metadata
-            .as_ref()
-            .and_then(|m| m.modified().ok())
-            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
-
-        let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
-        let is_file = metadata.as_ref().map(|m| m.is_file()).unwrap_or(false);
-
-        result.push(FileEntry {
-            name: entry.file_name().to_string_lossy().to_string(),
-            path: utils::normalize_path(&entry.path().to_string_lossy()),
-            is_directory: is_dir,
-            is_file,
-            modified_at,
+/// Label of the operation `undo_last_operation` would revert, if any
+#[tauri::command]
+pub async fn get_undo_operation() -> Result<Option<String>, String> {
+    metrics::measure("get_undo_operation", 0, async move {
+        Ok(undo::peek_label())
+    }).await
+}
+
+/// Revert the most recent vault-wide operation (bulk edit, deduplication,
+/// ...) still within the undo window, re-indexing the files it touched.
+/// None if there is nothing to undo.
+#[tauri::command]
+pub async fn undo_last_operation(
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<Option<UndoResult>, AppError> {
+    metrics::measure("undo_last_operation", 0, async move {
+        let Some(operation) = undo::take_last() else {
+            return Ok(None);
+        };
+        info!("[INFO] [fileops] Undoing: {} ({} files)", operation.label, operation.files.len());
+
+        // Undoing over a later edit would silently lose it
+        if let Some(changed) = operation.files.iter().find(|file| !file.is_unchanged()) {
+            return Err(AppError::Conflict(format!(
+                "Can't undo {}: {} changed since",
+                operation.label,
+                vault::relative_path(&changed.path)
+            )));
+        }
+
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        let mut result = UndoResult { label: operation.label, ..Default::default() };
+        for undo::UndoFile { path, before, .. } in operation.files {
+            crate::write_tracker::record_write(&path);
+            match before {
+                Some(bytes) => {
+                    if let Some(parent) = Path::new(&path).parent() {
+                        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+                    }
+                    fs::write(&path, bytes).map_err(|e| AppError::io(&path, e))?;
+                    if utils::is_content_file(&path) {
+                        if let Err(e) = indexer::index_file(&db.0, &path, &settings).await {
+                            warn!("[WARN] [fileops] Failed to re-index {}: {}", path, e);
+                        }
+                    }
+                    result.restored.push(vault::relative_path(&path));
+                }
+                None => {
+                    if Path::new(&path).exists() {
+                        fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
+                    }
+                    db.0.remove_content(&utils::normalize_path(&path)).map_err(AppError::InvalidOperation)?;
+                    result.removed.push(vault::relative_path(&path));
+                }
+            }
+        }
+        Ok(Some(result))
+    }).await
+}
+
+/// List directory contents
+#[tauri::command]
+pub async fn list_directory(path: String) -> Result<Vec<FileEntry>, AppError> {
+    metrics::measure("list_directory", path.len(), async move {
+        info!("[INFO] [fileops] Listing directory: {}", path);
+        let path = vault::resolve(&path)?;
+
+        let entries = fs::read_dir(&path).map_err(|e| AppError::io(&path, e))?;
+
+        let mut result = Vec::new();
+
+        for entry in entries.flatten() {
+            let metadata = entry.metadata().ok();
+            let modified_at = metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+
+            let is_dir = metadata.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+            let is_file = metadata.as_ref().map(|m| m.is_file()).unwrap_or(false);
+
+            result.push(FileEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: utils::normalize_path(&entry.path().to_string_lossy()),
+                is_directory: is_dir,
+                is_file,
+                modified_at,
+            });
+        }
+
+        // Sort: directories first, then by name
+        result.sort_by(|a, b| {
+            match (a.is_directory, b.is_directory) {
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                _ => collation::compare(&a.name, &b.name),
+            }
         });
-    }
 
-    // Sort: directories first, then by name
-    result.sort_by(|a, b| {
-        match (a.is_directory, b.is_directory) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        Ok(result)
+    }).await
+}
+
+/// Get the current sort settings
+#[tauri::command]
+pub async fn get_sort_settings() -> Result<SortSettings, String> {
+    metrics::measure("get_sort_settings", 0, async move {
+        Ok(collation::settings())
+    }).await
+}
+
+/// Replace the sort settings used by `list_directory` and the navigation tree
+/// (kept across restarts)
+#[tauri::command]
+pub async fn set_sort_settings(new_settings: SortSettings, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_sort_settings", 0, async move {
+        info!("[INFO] [fileops] Updating sort settings: {:?}", new_settings);
+        db.0.set_setting_json(SORT_SETTING, &new_settings)?;
+        collation::set_settings(&new_settings);
+        Ok(())
+    }).await
+}
+
+/// Settings key holding the root of the last opened vault
+pub(crate) const VAULT_ROOT_SETTING: &str = "vault_root";
+
+/// Pick the vault folder in a native dialog and make it the open vault
+/// This is the only command that changes the vault root. Returns None if
+/// the dialog is cancelled.
+#[tauri::command]
+pub async fn open_vault(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<Option<OpenedVault>, AppError> {
+    metrics::measure("open_vault", 0, async move {
+        info!("[INFO] [fileops] Opening vault folder picker");
+        let picked = tauri::async_runtime::spawn_blocking(move || {
+            app.dialog().file().set_title("Select Vault Folder").blocking_pick_folder()
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Folder picker failed: {}", e)))?;
+
+        let Some(picked) = picked else {
+            info!("[INFO] [fileops] Vault selection cancelled");
+            return Ok(None);
+        };
+        let path = picked.into_path().map_err(|e| AppError::Path(e.to_string()))?;
+        let path = path.to_string_lossy().to_string();
+
+        // The key in memory belongs to the vault opened before
+        if vault_key::lock() {
+            info!("[INFO] [fileops] Locked the previous vault");
         }
-    });
+        vault::set_root(&path);
+        db.0.set_setting(VAULT_ROOT_SETTING, &path).map_err(AppError::InvalidOperation)?;
+        let title_sources = indexer::saved_title_sources(&db.0, &path);
+        settings.0.lock().unwrap_or_else(PoisonError::into_inner).title_sources = title_sources;
+        info!("[INFO] [fileops] Opened vault: {}", path);
+        Ok(vault::root().map(|root| opened_vault(&root)))
+    }).await
+}
 
-    Ok(result)
+/// The open vault, if any (restored from the last session at startup)
+#[tauri::command]
+pub async fn get_vault() -> Result<Option<OpenedVault>, AppError> {
+    metrics::measure("get_vault", 0, async move {
+        Ok(vault::root().map(|root| opened_vault(&root)))
+    }).await
 }
 
-/// Get navigation tree from Home directory
+fn opened_vault(root: &str) -> OpenedVault {
+    let name = Path::new(root)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Vault".to_string());
+    OpenedVault { path: root.to_string(), name }
+}
+
+/// Get navigation tree of the open vault
+#[tauri::command]
+pub async fn get_navigation_tree(
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<FolderNode, AppError> {
+    metrics::measure("get_navigation_tree", 0, async move {
+        let home_path = vault::require_root()?;
+        info!("[INFO] [fileops] Building navigation tree from: {}", home_path);
+        // Titles follow the same rules as the index
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        navigation_tree(&home_path, &db.0, &settings)
+    }).await
+}
+
+/// The navigation tree pruned to the nodes matching `query` and the folders
+/// leading to them. A folder whose own name matches (when only a name is
+/// given) is kept whole.
 #[tauri::command]
-pub async fn get_navigation_tree(home_path: String) -> Result<FolderNode, String> {
-    info!("[INFO] [fileops] Building navigation tree from: {}", home_path);
+pub async fn filter_navigation_tree(
+    query: NavigationFilter,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<FolderNode, AppError> {
+    metrics::measure("filter_navigation_tree", 0, async move {
+        let home_path = vault::require_root()?;
+        info!("[INFO] [fileops] Filtering navigation tree: {:?}", query);
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        let mut tree = navigation_tree(&home_path, &db.0, &settings)?;
+
+        let tagged = match query.tag.as_deref().map(|tag| tag.trim().trim_start_matches('#')).filter(|tag| !tag.is_empty()) {
+            Some(tag) => Some(
+                db.0.paths_with_tag(tag)
+                    .map_err(AppError::InvalidOperation)?
+                    .iter()
+                    .map(|path| vault::relative_path(path))
+                    .collect::<HashSet<_>>(),
+            ),
+            None => None,
+        };
+        let filter = NodeFilter {
+            name: query.name.map(|name| utils::nfc(name.trim()).to_lowercase()).filter(|name| !name.is_empty()),
+            tagged,
+            types: query.types,
+        };
 
-    let normalized_root = utils::normalize_path(&home_path);
-    build_folder_node(&home_path, "Home", &normalized_root)
-        .map_err(|e| e.to_string())
+        tree.children = prune_nodes(tree.children, &filter);
+        Ok(tree)
+    }).await
+}
+
+/// Build the navigation tree of the vault at `home_path`
+fn navigation_tree(home_path: &str, db: &Database, settings: &IndexSettings) -> Result<FolderNode, AppError> {
+    let normalized_root = utils::normalize_path(home_path);
+
+    // Pins only decorate the tree; don't fail it over them
+    let pinned: HashSet<String> = match db.list_pinned() {
+        Ok(pins) => pins.into_iter().map(|pin| pin.path).collect(),
+        Err(e) => {
+            warn!("[WARN] [fileops] Failed to load pinned notes: {}", e);
+            HashSet::new()
+        }
+    };
+
+    let orders = match db.folder_orders() {
+        Ok(orders) => orders,
+        Err(e) => {
+            warn!("[WARN] [fileops] Failed to load folder orders: {}", e);
+            HashMap::new()
+        }
+    };
+
+    build_folder_node(home_path, "Home", &normalized_root, &pinned, &orders, settings)
+}
+
+/// A `NavigationFilter` ready to test nodes against
+struct NodeFilter {
+    /// Lowercase, NFC
+    name: Option<String>,
+    /// Vault-relative paths carrying the tag
+    tagged: Option<HashSet<String>>,
+    types: Vec<String>,
+}
+
+impl NodeFilter {
+    /// A folder named like the filter, when the name is all there is to match
+    fn keeps_folder(&self, name: &str) -> bool {
+        self.tagged.is_none() && self.types.is_empty() && self.name.is_some() && self.matches_name(name, "")
+    }
+
+    fn matches(&self, node: &NavigationNode) -> bool {
+        let (kind, title, path) = match node {
+            NavigationNode::Folder(f) => ("folder", &f.name, &f.path),
+            NavigationNode::Module(m) => ("module", &m.title, &m.path),
+            NavigationNode::Page(p) => ("page", &p.title, &p.path),
+            NavigationNode::Document(d) => ("document", &d.title, &d.path),
+            NavigationNode::Data(d) => ("data", &d.title, &d.path),
+            NavigationNode::Attachment(a) => ("attachment", &a.title, &a.path),
+        };
+        self.matches_name(node_name(node), title)
+            && self.tagged.as_ref().map_or(true, |tagged| tagged.contains(path.as_str()))
+            && (self.types.is_empty() || self.types.iter().any(|t| t == kind))
+    }
+
+    /// A folder's note (taken out of its children) by its vault-relative path
+    fn matches_folder_note(&self, path: &str) -> bool {
+        self.matches_name(path.rsplit('/').next().unwrap_or(path), "")
+            && self.tagged.as_ref().map_or(true, |tagged| tagged.contains(path))
+            && (self.types.is_empty() || self.types.iter().any(|t| t == "document"))
+    }
+
+    fn matches_name(&self, name: &str, title: &str) -> bool {
+        self.name.as_ref().map_or(true, |text| {
+            [name, title].iter().any(|s| utils::nfc(s).to_lowercase().contains(text.as_str()))
+        })
+    }
+}
+
+/// Nodes matching `filter`, with folders pruned to their matching contents
+/// (a folder whose folder note matches is kept even if nothing inside does)
+fn prune_nodes(nodes: Vec<NavigationNode>, filter: &NodeFilter) -> Vec<NavigationNode> {
+    nodes
+        .into_iter()
+        .filter_map(|node| match node {
+            NavigationNode::Folder(folder) if filter.keeps_folder(&folder.name) => Some(NavigationNode::Folder(folder)),
+            NavigationNode::Folder(mut folder) => {
+                folder.children = prune_nodes(folder.children, filter);
+                let note_matches = folder.folder_note.as_deref().is_some_and(|note| filter.matches_folder_note(note));
+                (note_matches || !folder.children.is_empty()).then_some(NavigationNode::Folder(folder))
+            }
+            node => filter.matches(&node).then_some(node),
+        })
+        .collect()
+}
+
+/// Persist a manual ordering of a folder's children in the navigation tree
+/// `path` is the folder's nav tree path ("Home" for the vault root). Listed
+/// names come first in the given order; unlisted children follow in the
+/// default order. An empty list restores the default order.
+#[tauri::command]
+pub async fn set_folder_order(
+    path: String,
+    ordered_names: Vec<String>,
+    db: State<'_, DbState>,
+) -> Result<(), AppError> {
+    metrics::measure("set_folder_order", ordered_names.len(), async move {
+        let folder = vault::resolve(if path == "Home" { "." } else { &path })?;
+        if !Path::new(&folder).is_dir() {
+            return Err(AppError::NotFound(format!("Folder not found: {}", path)));
+        }
+        let folder = vault::relative_path(&folder);
+        info!("[INFO] [fileops] Setting order of {} children in '{}'", ordered_names.len(), folder);
+
+        db.0.set_folder_order(&folder, &ordered_names)
+            .map_err(AppError::InvalidOperation)
+    }).await
+}
+
+/// Content of a file for its tree title, unless it's larger than
+/// `max_file_bytes` (the index skips reading those too)
+fn read_for_title(path: &Path, max_file_bytes: u64) -> Option<String> {
+    let size = fs::metadata(path).ok()?.len();
+    if size > max_file_bytes {
+        return None;
+    }
+    fs::read_to_string(path).ok()
 }
 
 /// Recursively build a folder node from filesystem
-/// vault_root is used to compute relative paths
-fn build_folder_node(path: &str, name: &str, vault_root: &str) -> Result<FolderNode, AppError> {
-    let entries = fs::read_dir(path)?;
+/// vault_root is used to compute relative paths; `pinned` holds vault-relative paths
+/// and `orders` manual child orderings keyed by vault-relative folder
+fn build_folder_node(
+    path: &str,
+    name: &str,
+    vault_root: &str,
+    pinned: &HashSet<String>,
+    orders: &HashMap<String, HashMap<String, usize>>,
+    settings: &IndexSettings,
+) -> Result<FolderNode, AppError> {
+    let entries = fs::read_dir(path).map_err(|e| AppError::io(path, e))?;
     let mut children = Vec::new();
 
     for entry in entries.flatten() {
         let entry_path = entry.path();
         let entry_name = entry.file_name().to_string_lossy().to_string();
 
-        // Skip hidden files and special directories
-        if entry_name.starts_with('.') || entry_name.starts_with('_') {
+        // Skip hidden files and special directories, and sidecars (their
+        // annotations show on the annotated file)
+        if entry_name.starts_with('.') || entry_name.starts_with('_') || sidecar::is_sidecar(&entry_name) {
             continue;
         }
 
         let normalized_path = utils::normalize_path(&entry_path.to_string_lossy());
 
         // Compute relative path from vault root
-        let relative_path = if normalized_path.starts_with(vault_root) {
-            normalized_path[vault_root.len()..].trim_start_matches('/').to_string()
-        } else {
-            normalized_path.clone()
-        };
+        let relative_path = utils::strip_root(&normalized_path, vault_root);
 
         if entry_path.is_dir() {
             // Recurse into subdirectory
-            match build_folder_node(&entry_path.to_string_lossy(), &entry_name, vault_root) {
+            match build_folder_node(&entry_path.to_string_lossy(), &entry_name, vault_root, pinned, orders, settings) {
                 Ok(folder) => children.push(NavigationNode::Folder(folder)),
-                Err(e) => tracing::warn!("[WARN] [fileops] Skipping directory {}: {}", entry_name, e),
+                Err(e) => warn!("[WARN] [fileops] Skipping directory {}: {}", entry_name, e),
             }
         } else if entry_path.is_file() {
             let file_path_str = normalized_path.clone();
 
             if utils::is_module_file(&file_path_str) {
-                let title = if let Ok(content) = fs::read_to_string(&entry_path) {
-                    utils::extract_title_from_content(&content)
-                        .unwrap_or_else(|| utils::path_to_title(&file_path_str))
-                } else {
-                    utils::path_to_title(&file_path_str)
-                };
+                let content = read_for_title(&entry_path, settings.max_file_bytes);
+                let title = utils::note_title(&file_path_str, content.as_deref().unwrap_or_default(), &settings.title_sources);
+                let uid = utils::note_uid(&relative_path, content.as_deref());
 
                 children.push(NavigationNode::Module(ModuleNode {
                     id: utils::path_to_id(&file_path_str),
+                    uid,
                     name: entry_name.clone(),
                     path: relative_path.clone(),
                     title,
+                    pinned: pinned.contains(&relative_path),
                     file: relative_path,
                     tags: Vec::new(),
                 }));
             } else if utils::is_page_file(&file_path_str) {
-                let title = if let Ok(content) = fs::read_to_string(&entry_path) {
-                    utils::extract_title_from_content(&content)
-                        .unwrap_or_else(|| utils::path_to_title(&file_path_str))
-                } else {
-                    utils::path_to_title(&file_path_str)
-                };
+                let content = read_for_title(&entry_path, settings.max_file_bytes);
+                let title = utils::note_title(&file_path_str, content.as_deref().unwrap_or_default(), &settings.title_sources);
+                let uid = utils::note_uid(&relative_path, content.as_deref());
 
                 children.push(NavigationNode::Page(PageNode {
                     id: utils::path_to_id(&file_path_str),
+                    uid,
                     name: entry_name.clone(),
                     path: relative_path.clone(),
                     title,
+                    pinned: pinned.contains(&relative_path),
                     file: relative_path,
                 }));
             } else if utils::is_document_file(&file_path_str) {
-                let title = if let Ok(content) = fs::read_to_string(&entry_path) {
-                    utils::extract_title_from_content(&content)
-                        .unwrap_or_else(|| utils::path_to_title(&file_path_str))
-                } else {
-                    utils::path_to_title(&file_path_str)
-                };
+                let content = read_for_title(&entry_path, settings.max_file_bytes);
+                let title = utils::note_title(&file_path_str, content.as_deref().unwrap_or_default(), &settings.title_sources);
+                let uid = utils::note_uid(&relative_path, content.as_deref());
 
                 children.push(NavigationNode::Document(DocumentNode {
                     id: utils::path_to_id(&file_path_str),
+                    uid,
                     name: entry_name.clone(),
                     path: relative_path.clone(),
                     title,
+                    pinned: pinned.contains(&relative_path),
                     file: relative_path,
                 }));
-            }
-        }
-    }
+            } else if notebook::is_notebook_file(&file_path_str) {
+                let title = read_for_title(&entry_path, settings.max_file_bytes)
+                    .and_then(|content| notebook::parse(&content).ok())
+                    .map(|parsed| notebook::title(&file_path_str, &parsed))
+                    .unwrap_or_else(|| utils::path_to_title(&file_path_str));
 
-    // Sort children: folders first, then by name
-    children.sort_by(|a, b| {
-        let a_is_folder = matches!(a, NavigationNode::Folder(_));
-        let b_is_folder = matches!(b, NavigationNode::Folder(_));
+                children.push(NavigationNode::Document(DocumentNode {
+                    id: utils::path_to_id(&file_path_str),
+                    uid: utils::note_uid(&relative_path, None),
+                    name: entry_name.clone(),
+                    path: relative_path.clone(),
+                    title,
+                    pinned: pinned.contains(&relative_path),
+                    file: relative_path,
+                }));
+            } else if let Some(format) = structured::Format::from_path(&file_path_str) {
+                let title = read_for_title(&entry_path, settings.max_file_bytes)
+                    .and_then(|content| structured::parse(format, &content).ok())
+                    .map(|data| structured::title(&file_path_str, &data))
+                    .unwrap_or_else(|| utils::path_to_title(&file_path_str));
 
-        match (a_is_folder, b_is_folder) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => {
-                let a_name = match a {
-                    NavigationNode::Folder(f) => &f.name,
-                    NavigationNode::Module(m) => &m.name,
-                    NavigationNode::Page(p) => &p.name,
-                    NavigationNode::Document(d) => &d.name,
-                };
-                let b_name = match b {
-                    NavigationNode::Folder(f) => &f.name,
-                    NavigationNode::Module(m) => &m.name,
-                    NavigationNode::Page(p) => &p.name,
-                    NavigationNode::Document(d) => &d.name,
-                };
-                a_name.to_lowercase().cmp(&b_name.to_lowercase())
+                children.push(NavigationNode::Data(DataNode {
+                    id: utils::path_to_id(&file_path_str),
+                    uid: utils::note_uid(&relative_path, None),
+                    name: entry_name.clone(),
+                    path: relative_path.clone(),
+                    title,
+                    pinned: pinned.contains(&relative_path),
+                    file: relative_path,
+                    format: format.as_str().to_string(),
+                }));
+            } else if let Some(annotation) = sidecar::read(&file_path_str) {
+                children.push(NavigationNode::Attachment(AttachmentNode {
+                    id: utils::path_to_id(&file_path_str),
+                    uid: utils::note_uid(&relative_path, None),
+                    name: entry_name.clone(),
+                    path: relative_path.clone(),
+                    title: entry_name.clone(),
+                    pinned: pinned.contains(&relative_path),
+                    file: relative_path,
+                    note: annotation.note,
+                    tags: annotation.tags,
+                }));
             }
         }
-    });
+    }
 
     // Compute relative path for this folder
     let normalized_path = utils::normalize_path(path);
@@ -206,35 +829,103 @@ fn build_folder_node(path: &str, name: &str, vault_root: &str) -> Result<FolderN
         normalized_path.clone()
     };
 
+    // A folder's note opens with the folder rather than listing inside it
+    let folder_note = utils::folder_note_paths(&relative_path).into_iter().find_map(|note_path| {
+        let index = children
+            .iter()
+            .position(|child| matches!(child, NavigationNode::Document(d) if d.path == note_path))?;
+        children.remove(index);
+        Some(note_path)
+    });
+
+    // Sort children: manually ordered names first, then folders, then by name
+    let order = orders.get(&relative_path);
+    let position = |node: &NavigationNode| order.and_then(|order| order.get(node_name(node)).copied());
+
+    children.sort_by(|a, b| {
+        let a_is_folder = matches!(a, NavigationNode::Folder(_));
+        let b_is_folder = matches!(b, NavigationNode::Folder(_));
+
+        match (position(a), position(b)) {
+            (Some(a_pos), Some(b_pos)) => a_pos.cmp(&b_pos),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => match (a_is_folder, b_is_folder) {
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                _ => collation::compare(node_name(a), node_name(b)),
+            },
+        }
+    });
+
     Ok(FolderNode {
         node_type: "folder".to_string(),
         name: name.to_string(),
         path: if relative_path.is_empty() { "Home".to_string() } else { relative_path },
         children,
+        folder_note,
     })
 }
 
 /// Get file modification time in milliseconds since UNIX epoch
 #[tauri::command]
-pub async fn get_file_mtime(path: String) -> Result<u64, String> {
-    let metadata = fs::metadata(&path)
-        .map_err(|e| AppError::Io(e).to_string())?;
+pub async fn get_file_mtime(path: String) -> Result<u64, AppError> {
+    metrics::measure("get_file_mtime", path.len(), async move {
+        let path = vault::resolve(&path)?;
+        let metadata = fs::metadata(&path).map_err(|e| AppError::io(&path, e))?;
+        mtime_millis(&metadata)
+    }).await
+}
 
-    let modified = metadata
-        .modified()
-        .map_err(|e| AppError::Io(e).to_string())?;
+/// Modification times of many files in one call (e.g. every open tab)
+/// Keyed by the paths as given; a failure marks its own entry only.
+#[tauri::command]
+pub async fn get_file_mtimes(paths: Vec<String>) -> Result<HashMap<String, FileMtime>, AppError> {
+    metrics::measure("get_file_mtimes", paths.iter().map(String::len).sum(), async move {
+        let mut mtimes = HashMap::with_capacity(paths.len());
+        for path in paths {
+            let result = vault::resolve(&path).and_then(|resolved| mtime_millis(&fs::metadata(&resolved).map_err(|e| AppError::io(&resolved, e))?));
+            let entry = match result {
+                Ok(mtime) => FileMtime { mtime: Some(mtime), ..Default::default() },
+                Err(AppError::NotFound(_)) => FileMtime { missing: true, ..Default::default() },
+                Err(e) => FileMtime { error: Some(e.to_string()), ..Default::default() },
+            };
+            mtimes.insert(path, entry);
+        }
+        Ok(mtimes)
+    }).await
+}
 
-    let duration = modified
+/// Display name of a navigation node
+fn node_name(node: &NavigationNode) -> &str {
+    match node {
+        NavigationNode::Folder(f) => &f.name,
+        NavigationNode::Module(m) => &m.name,
+        NavigationNode::Page(p) => &p.name,
+        NavigationNode::Document(d) => &d.name,
+        NavigationNode::Data(d) => &d.name,
+        NavigationNode::Attachment(a) => &a.name,
+    }
+}
+
+/// Modification time in milliseconds since UNIX epoch
+fn mtime_millis(metadata: &fs::Metadata) -> Result<u64, AppError> {
+    let duration = metadata
+        .modified()?
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default();
 
     Ok(duration.as_millis() as u64)
 }
 
-/// Start watching the vault directory for file changes
+/// Start watching the open vault for file changes
 /// Emits 'vault:changed' event when files are created/deleted/renamed
 #[tauri::command]
-pub fn start_watching_vault(app: AppHandle, vault_path: String) {
-    info!("[INFO] [fileops] Starting vault watcher for: {}", vault_path);
-    watcher::start_vault_watcher(app, vault_path);
+pub fn start_watching_vault(app: AppHandle) -> Result<(), AppError> {
+    metrics::measure_sync("start_watching_vault", 0, || {
+        let vault_path = vault::require_root()?;
+        info!("[INFO] [fileops] Starting vault watcher for: {}", vault_path);
+        watcher::start_vault_watcher(app, vault_path);
+        Ok(())
+    })
 }
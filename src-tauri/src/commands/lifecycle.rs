@@ -0,0 +1,61 @@
+//! App lifecycle IPC commands
+
+use std::time::{Duration, Instant};
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::db::DbState;
+use crate::indexer::jobs;
+use crate::metrics;
+use crate::models::ShutdownReport;
+use crate::watcher;
+
+/// How long to wait for a cancelled rebuild to stop
+const REBUILD_STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Wind the backend down before the window closes
+/// Called from the frontend's close-requested handler once its own unsaved
+/// changes are dealt with: stops the vault watcher, cancels a running index
+/// rebuild (checkpointed, so it resumes next launch), and checkpoints the
+/// database WAL. With `close`, the window is then closed unless work is
+/// still pending.
+#[tauri::command]
+pub async fn prepare_shutdown(
+    close: Option<bool>,
+    window: tauri::WebviewWindow,
+    db: State<'_, DbState>,
+) -> Result<ShutdownReport, String> {
+    metrics::measure("prepare_shutdown", 0, async move {
+        info!("[INFO] [lifecycle] Preparing shutdown");
+        let mut report = ShutdownReport::default();
+
+        watcher::stop_vault_watcher().await;
+
+        if jobs::cancel() {
+            report.rebuild_cancelled = true;
+            let started = Instant::now();
+            while jobs::is_running() && started.elapsed() < REBUILD_STOP_TIMEOUT {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            if jobs::is_running() {
+                report.pending.push("Index rebuild is still stopping".to_string());
+            }
+        }
+
+        match db.0.checkpoint() {
+            Ok(()) => report.wal_checkpointed = true,
+            Err(e) => {
+                warn!("[WARN] [lifecycle] WAL checkpoint failed: {}", e);
+                report.errors.push(format!("WAL checkpoint failed: {}", e));
+            }
+        }
+
+        if close.unwrap_or(false) && report.pending.is_empty() {
+            info!("[INFO] [lifecycle] Closing window");
+            window.destroy().map_err(|e| e.to_string())?;
+            report.closed = true;
+        }
+
+        Ok(report)
+    }).await
+}
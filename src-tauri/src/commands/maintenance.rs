@@ -0,0 +1,142 @@
+//! Maintenance and backup IPC commands
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
+
+use crate::backup;
+use crate::db::DbState;
+use crate::metrics;
+use crate::models::{ScheduledTask, ScheduledTaskRun, SnapshotProgress, VaultSnapshot};
+use crate::scheduler;
+use crate::utils;
+use crate::vault;
+
+/// Files archived between `snapshot:progress` events
+const SNAPSHOT_PROGRESS_EVERY: usize = 100;
+
+/// Scheduled tasks with their settings and last run status
+#[tauri::command]
+pub async fn list_scheduled_tasks(db: State<'_, DbState>) -> Result<Vec<ScheduledTask>, String> {
+    metrics::measure("list_scheduled_tasks", 0, async move {
+        scheduler::list(&db.0)
+    }).await
+}
+
+/// Enable/disable a scheduled task or change its interval (seconds)
+#[tauri::command]
+pub async fn configure_scheduled_task(
+    id: String,
+    enabled: Option<bool>,
+    interval_secs: Option<u64>,
+    db: State<'_, DbState>,
+) -> Result<(), String> {
+    metrics::measure("configure_scheduled_task", 0, async move {
+        info!("[INFO] [maintenance] Configuring {}: enabled={:?} interval={:?}", id, enabled, interval_secs);
+        scheduler::configure(&db.0, &id, enabled, interval_secs)
+    }).await
+}
+
+/// Run a scheduled task now, regardless of when it last ran
+#[tauri::command]
+pub async fn run_scheduled_task(id: String, db: State<'_, DbState>) -> Result<ScheduledTaskRun, String> {
+    metrics::measure("run_scheduled_task", 0, async move {
+        scheduler::run(db.0.clone(), &id).await
+    }).await
+}
+
+/// Folder scheduled vault backups are written to
+#[tauri::command]
+pub async fn get_backup_folder() -> Result<Option<String>, String> {
+    metrics::measure("get_backup_folder", 0, async move {
+        Ok(scheduler::backup_dir().map(|dir| utils::normalize_path(&dir.to_string_lossy())))
+    }).await
+}
+
+/// Set (or clear) the folder for scheduled vault backups
+/// Must be an absolute path; backups fail while it's inside the vault.
+#[tauri::command]
+pub async fn set_backup_folder(path: Option<String>, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_backup_folder", 0, async move {
+        let dir = path.map(PathBuf::from);
+        if dir.as_ref().is_some_and(|dir| !dir.is_absolute()) {
+            return Err("Backup folder must be an absolute path".to_string());
+        }
+        info!("[INFO] [maintenance] Backup folder set to {:?}", dir);
+        scheduler::set_backup_dir(&db.0, dir)
+    }).await
+}
+
+/// Calendar (URL or file path) the scheduled calendar import reads
+#[tauri::command]
+pub async fn get_calendar_source() -> Result<Option<String>, String> {
+    metrics::measure("get_calendar_source", 0, async move {
+        Ok(scheduler::calendar_source())
+    }).await
+}
+
+/// Set (or clear) the calendar for the scheduled calendar import
+/// An http(s) or webcal URL, or an absolute path to an .ics file.
+#[tauri::command]
+pub async fn set_calendar_source(source: Option<String>, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_calendar_source", 0, async move {
+        let source = source.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        if let Some(source) = &source {
+            let is_url = ["http://", "https://", "webcal://"].iter().any(|scheme| source.starts_with(scheme));
+            if !is_url && !Path::new(source).is_absolute() {
+                return Err("Calendar must be a URL or an absolute path".to_string());
+            }
+        }
+        info!("[INFO] [maintenance] Calendar source set to {:?}", source);
+        scheduler::set_calendar_source(&db.0, source)
+    }).await
+}
+
+/// Write a zip of the whole open vault
+/// `dest` is the zip file, or a directory to create a timestamped one in;
+/// it must be outside the vault, and an existing file is only replaced with
+/// `overwrite`. Hidden and temp files are skipped, and attachments too with
+/// `exclude_attachments`. Emits `snapshot:progress` as files are archived.
+#[tauri::command]
+pub async fn snapshot_vault(
+    dest: String,
+    exclude_attachments: Option<bool>,
+    overwrite: Option<bool>,
+    app: AppHandle,
+) -> Result<VaultSnapshot, String> {
+    metrics::measure("snapshot_vault", dest.len(), async move {
+        let root = PathBuf::from(vault::root().ok_or("No vault is open")?);
+        let dest = PathBuf::from(&dest);
+        let target = if dest.is_dir() {
+            let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "vault".to_string());
+            let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+            dest.join(format!("{}-{}.zip", name, stamp))
+        } else {
+            dest
+        };
+        let target = backup::outside_path(&root, &target)?;
+        info!("[INFO] [maintenance] Snapshotting vault to {:?}", target);
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let files = backup::backup_files(&root, !exclude_attachments.unwrap_or(false));
+            let on_progress = |archived: usize, total: usize| {
+                if archived % SNAPSHOT_PROGRESS_EVERY == 0 || archived == total {
+                    if let Err(e) = app.emit("snapshot:progress", SnapshotProgress { archived, total }) {
+                        warn!("[WARN] [maintenance] Failed to emit snapshot:progress: {}", e);
+                    }
+                }
+            };
+            let count = backup::write_zip(&root, &files, &target, overwrite.unwrap_or(false), &on_progress)?;
+
+            let bytes = std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+            info!("[INFO] [maintenance] Snapshot of {} files written to {:?}", count, target);
+            Ok(VaultSnapshot {
+                path: utils::normalize_path(&target.to_string_lossy()),
+                files: count,
+                bytes,
+            })
+        })
+        .await
+        .map_err(|e| format!("Vault snapshot failed: {}", e))?
+    }).await
+}
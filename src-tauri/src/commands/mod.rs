@@ -1,4 +1,15 @@
 //! IPC command handlers for Unstablon PKM
 
+pub mod access;
+pub mod assets;
+pub mod diagnostics;
 pub mod fileops;
+pub mod lifecycle;
+pub mod maintenance;
+pub mod notes;
+pub mod pins;
+pub mod platform;
 pub mod search;
+pub mod security;
+pub mod stats;
+pub mod web;
@@ -0,0 +1,972 @@
+//! Note metadata IPC commands
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use tauri::State;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::attachments;
+use crate::calendar;
+use crate::commands::fileops::write_tracked;
+use crate::commands::search;
+use crate::db::{Database, DbState};
+use crate::docx;
+use crate::embeds::{self, NoteResolver};
+use crate::error::AppError;
+use crate::footnotes;
+use crate::formatter;
+use crate::frontmatter;
+use crate::headings;
+use crate::indexer::{self, IndexSettingsState};
+use crate::latex;
+use crate::merge;
+use crate::metrics;
+use crate::opml;
+use crate::stats;
+use crate::tables;
+use crate::undo;
+use crate::db::SearchFilter;
+use crate::models::{BulkFileResult, BulkPropertyResult, CalendarImport, DocxExport, EmbedContent, FootnoteReport, FrontmatterBlock, FormatOptions, FormatResult, HeadingNumberOptions, TocOptions, LatexExport, MergeOrder, MergedExport, Mention, NoteMetadata, NoteOverview, OutgoingLink, TableData, TableOp};
+use crate::utils;
+use crate::vault;
+
+/// Folder notes created from broken links go in (None = beside the linking note)
+static NEW_NOTE_FOLDER: Mutex<Option<String>> = Mutex::new(None);
+
+/// Settings key for the new note folder
+const NEW_NOTE_FOLDER_SETTING: &str = "new_note_folder";
+
+/// Restore the new note folder saved in an earlier session
+pub(crate) fn restore_settings(db: &Database) {
+    match db.setting_json::<Option<String>>(NEW_NOTE_FOLDER_SETTING) {
+        Ok(Some(folder)) => *NEW_NOTE_FOLDER.lock().unwrap_or_else(PoisonError::into_inner) = folder,
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [notes] Failed to load new note folder: {}", e),
+    }
+}
+
+/// Parse a note's YAML frontmatter into typed metadata
+#[tauri::command]
+pub async fn get_note_metadata(path: String) -> Result<NoteMetadata, AppError> {
+    metrics::measure("get_note_metadata", path.len(), async move {
+        let path = vault::resolve(&path)?;
+        info!("[INFO] [notes] Reading metadata: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+
+        Ok(frontmatter::parse(&content))
+    }).await
+}
+
+/// Set a frontmatter property, rewriting only the frontmatter block
+/// (created if missing). Re-indexes the note and returns the new metadata.
+#[tauri::command]
+pub async fn set_note_property(
+    path: String,
+    key: String,
+    value: serde_json::Value,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<NoteMetadata, AppError> {
+    metrics::measure("set_note_property", path.len() + key.len(), async move {
+        let path = vault::resolve(&path)?;
+        info!("[INFO] [notes] Setting property {} on: {}", key, path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let updated = frontmatter::set_property(&content, &key, &value)
+            .map_err(AppError::InvalidOperation)?;
+
+        save_and_reindex(&path, &updated, &db, &settings).await?;
+        Ok(frontmatter::parse(&updated))
+    }).await
+}
+
+/// A note's whole frontmatter block as YAML, for editing it as text
+#[tauri::command]
+pub async fn get_frontmatter_block(doc_id: String) -> Result<FrontmatterBlock, AppError> {
+    metrics::measure("get_frontmatter_block", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Reading frontmatter block: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        Ok(match frontmatter::split(&content) {
+            Some((yaml, _)) => FrontmatterBlock { yaml: yaml.to_string(), present: true },
+            None => FrontmatterBlock { yaml: String::new(), present: false },
+        })
+    }).await
+}
+
+/// Replace a note's whole frontmatter block with `yaml` (created if
+/// missing; blank `yaml` removes it). Re-indexes the note and returns the
+/// new metadata.
+#[tauri::command]
+pub async fn set_frontmatter(
+    doc_id: String,
+    yaml: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<NoteMetadata, AppError> {
+    metrics::measure("set_frontmatter", doc_id.len() + yaml.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Replacing frontmatter of: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let updated = frontmatter::set_block(&content, &yaml).map_err(AppError::InvalidOperation)?;
+
+        if updated != content {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(frontmatter::parse(&updated))
+    }).await
+}
+
+/// Notes matched by a query that bulk_set_property will update
+const MAX_BULK_NOTES: usize = 1000;
+
+/// Set a frontmatter property on many notes: the vault-relative `paths`,
+/// else the notes matching `query`, else every note under `folder` (with a
+/// query, `folder` narrows its matches). With `dry_run` nothing is written.
+/// A note that can't be read or updated is reported and skipped.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn bulk_set_property(
+    paths: Option<Vec<String>>,
+    query: Option<String>,
+    folder: Option<String>,
+    key: String,
+    value: serde_json::Value,
+    dry_run: Option<bool>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<BulkPropertyResult, AppError> {
+    metrics::measure("bulk_set_property", key.len(), async move {
+        let dry_run = dry_run.unwrap_or(false);
+        let folder = match folder.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+            Some(folder) => Some(vault::relative_path(&vault::resolve(folder)?)),
+            None => None,
+        };
+
+        let notes: Vec<String> = match (paths, query) {
+            (Some(paths), _) => {
+                for path in &paths {
+                    vault::resolve(path)?;
+                }
+                paths.iter().map(|p| p.trim_start_matches('/').to_string()).collect()
+            }
+            (None, Some(query)) if !query.trim().is_empty() => {
+                let filter = match &folder {
+                    Some(folder) => Some(SearchFilter::Folder(utils::normalize_path(&vault::resolve(folder)?))),
+                    None => None,
+                };
+                db.0
+                    .search_in(&search::fts_query(&query), MAX_BULK_NOTES, filter.as_ref())
+                    .map_err(AppError::InvalidOperation)?
+                    .into_iter()
+                    .map(|result| vault::relative_path(&result.path))
+                    .filter(|path| path.ends_with(".md"))
+                    .collect()
+            }
+            _ => match folder {
+                Some(folder) => {
+                    let root = vault::resolve(".")?;
+                    let prefix = format!("{}/", folder);
+                    tauri::async_runtime::spawn_blocking(move || attachments::walk_files(Path::new(&root)))
+                        .await
+                        .map_err(|e| AppError::InvalidOperation(format!("Vault scan failed: {}", e)))?
+                        .into_iter()
+                        .filter(|path| path.ends_with(".md") && (folder.is_empty() || path.starts_with(&prefix)))
+                        .collect()
+                }
+                None => return Err(AppError::InvalidOperation("No notes selected".to_string())),
+            },
+        };
+        info!(
+            "[INFO] [notes] Setting property {} on {} notes{}",
+            key,
+            notes.len(),
+            if dry_run { " (dry run)" } else { "" }
+        );
+
+        let mut result = BulkPropertyResult { dry_run, ..Default::default() };
+        let mut previous = Vec::new();
+        for note in notes {
+            let outcome = async {
+                let path = vault::resolve(&note)?;
+                let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+                let updated = frontmatter::set_property(&content, &key, &value).map_err(AppError::InvalidOperation)?;
+                if updated == content {
+                    return Ok(false);
+                }
+                if !dry_run {
+                    save_and_reindex(&path, &updated, &db, &settings).await?;
+                    previous.push((path, Some(content.into_bytes())));
+                }
+                Ok::<bool, AppError>(true)
+            }
+            .await;
+
+            let (changed, error) = match outcome {
+                Ok(changed) => (changed, None),
+                Err(e) => {
+                    warn!("[WARN] [notes] Skipping {}: {}", note, e);
+                    (false, Some(e.to_string()))
+                }
+            };
+            result.changed += changed as usize;
+            result.files.push(BulkFileResult { path: note, changed, error });
+        }
+        undo::record(format!("Set {} on {} notes", key, previous.len()), previous);
+        Ok(result)
+    }).await
+}
+
+/// Remove a frontmatter property (no-op if absent)
+/// Re-indexes the note and returns the new metadata.
+#[tauri::command]
+pub async fn remove_note_property(
+    path: String,
+    key: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<NoteMetadata, AppError> {
+    metrics::measure("remove_note_property", path.len() + key.len(), async move {
+        let path = vault::resolve(&path)?;
+        info!("[INFO] [notes] Removing property {} from: {}", key, path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+
+        match frontmatter::remove_property(&content, &key) {
+            Some(updated) => {
+                save_and_reindex(&path, &updated, &db, &settings).await?;
+                Ok(frontmatter::parse(&updated))
+            }
+            None => Ok(frontmatter::parse(&content)),
+        }
+    }).await
+}
+
+/// Pin a note's identity by writing a random UUID as its frontmatter `id`
+/// Returns the existing ID if one is already set, so the call is idempotent.
+#[tauri::command]
+pub async fn assign_note_id(
+    path: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<String, AppError> {
+    metrics::measure("assign_note_id", path.len(), async move {
+        let path = vault::resolve(&path)?;
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+
+        if let Some(id) = frontmatter::parse(&content).id {
+            return Ok(id);
+        }
+
+        let id = Uuid::new_v4().to_string();
+        info!("[INFO] [notes] Assigning id {} to: {}", id, path);
+
+        let updated = frontmatter::set_property(&content, "id", &serde_json::Value::from(id.as_str()))
+            .map_err(AppError::InvalidOperation)?;
+        save_and_reindex(&path, &updated, &db, &settings).await?;
+        Ok(id)
+    }).await
+}
+
+/// Parse a markdown table of a note into a cell matrix
+/// `block_id` is the table's zero-based position among the note's tables.
+#[tauri::command]
+pub async fn get_table(doc_id: String, block_id: usize) -> Result<TableData, AppError> {
+    metrics::measure("get_table", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Reading table {} of: {}", block_id, path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        tables::get_table(&content, block_id)
+            .ok_or_else(|| AppError::NotFound(format!("No table {} in {}", block_id, doc_id)))
+    }).await
+}
+
+/// Apply cell/row/column edits to a note's table and rewrite it aligned
+/// Ops apply in order; if any fails, nothing is written.
+#[tauri::command]
+pub async fn apply_table_edit(
+    doc_id: String,
+    block_id: usize,
+    ops: Vec<TableOp>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<TableData, AppError> {
+    metrics::measure("apply_table_edit", ops.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Applying {} edits to table {} of: {}", ops.len(), block_id, path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, table) = tables::apply_edits(&content, block_id, &ops)
+            .map_err(AppError::InvalidOperation)?;
+
+        save_and_reindex(&path, &updated, &db, &settings).await?;
+        Ok(table)
+    }).await
+}
+
+/// Realign one of a note's tables (pipes, padding, delimiter row)
+/// Returns the reformatted table markdown.
+#[tauri::command]
+pub async fn format_table(
+    doc_id: String,
+    block_id: usize,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<String, AppError> {
+    metrics::measure("format_table", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Formatting table {} of: {}", block_id, path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, table) = tables::format_table(&content, block_id)
+            .ok_or_else(|| AppError::NotFound(format!("No table {} in {}", block_id, doc_id)))?;
+
+        if updated != content {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(table)
+    }).await
+}
+
+/// Realign every table in a note; returns how many changed
+#[tauri::command]
+pub async fn format_tables(
+    doc_id: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<usize, AppError> {
+    metrics::measure("format_tables", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Formatting tables of: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, changed) = tables::format_all(&content);
+
+        if changed > 0 {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(changed)
+    }).await
+}
+
+/// Renumber a note's footnotes in order of first reference, gather their
+/// definitions at the end, and drop definitions nothing refers to
+/// The note is only rewritten if something changed.
+#[tauri::command]
+pub async fn normalize_footnotes(
+    doc_id: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<FootnoteReport, AppError> {
+    metrics::measure("normalize_footnotes", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Normalizing footnotes of: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, report) = footnotes::normalize(&content);
+        if let Some(updated) = updated {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(report)
+    }).await
+}
+
+/// Normalize a note's formatting (heading spacing, list markers, trailing
+/// whitespace, fence style) per `options`
+/// With `preview`, only the diff is returned and the note is left as-is.
+#[tauri::command]
+pub async fn format_document(
+    doc_id: String,
+    options: Option<FormatOptions>,
+    preview: Option<bool>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<FormatResult, AppError> {
+    metrics::measure("format_document", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        let preview = preview.unwrap_or(false);
+        info!("[INFO] [notes] Formatting: {} (preview: {})", path, preview);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let formatted = formatter::format(&content, &options.unwrap_or_default());
+        let changed = formatted != content;
+
+        if changed && !preview {
+            save_and_reindex(&path, &formatted, &db, &settings).await?;
+        }
+        Ok(FormatResult {
+            changed,
+            diff: formatter::diff(&vault::relative_path(&path), &content, &formatted),
+            written: changed && !preview,
+        })
+    }).await
+}
+
+/// Number a note's headings hierarchically (`1.`, `1.1`, `1.1.1`)
+/// Existing numbers are replaced, so this also renumbers after sections
+/// move. Returns how many headings are numbered.
+#[tauri::command]
+pub async fn apply_heading_numbers(
+    doc_id: String,
+    options: Option<HeadingNumberOptions>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<usize, AppError> {
+    metrics::measure("apply_heading_numbers", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Numbering headings of: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, numbered) = headings::apply_numbers(&content, &options.unwrap_or_default());
+        if updated != content {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(numbered)
+    }).await
+}
+
+/// Strip heading numbers written by `apply_heading_numbers`
+/// Returns how many numbers were removed.
+#[tauri::command]
+pub async fn remove_heading_numbers(
+    doc_id: String,
+    options: Option<HeadingNumberOptions>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<usize, AppError> {
+    metrics::measure("remove_heading_numbers", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Removing heading numbers from: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (updated, removed) = headings::remove_numbers(&content, &options.unwrap_or_default());
+        if removed > 0 {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(removed)
+    }).await
+}
+
+/// Insert a linked table of contents into a note, or regenerate the one
+/// between its `<!-- toc -->` / `<!-- tocstop -->` markers
+/// Saving a note with markers refreshes the TOC (see `write_file`).
+#[tauri::command]
+pub async fn insert_toc(
+    doc_id: String,
+    options: Option<TocOptions>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<(), AppError> {
+    metrics::measure("insert_toc", doc_id.len(), async move {
+        let path = vault::resolve(&doc_id)?;
+        info!("[INFO] [notes] Inserting TOC into: {}", path);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let updated = headings::insert_toc(&content, &options.unwrap_or_default());
+        if updated != content {
+            save_and_reindex(&path, &updated, &db, &settings).await?;
+        }
+        Ok(())
+    }).await
+}
+
+/// Title, outline, counts and links of a note in one call (for the info panel)
+#[tauri::command]
+pub async fn get_note_overview(path: String, db: State<'_, DbState>) -> Result<NoteOverview, AppError> {
+    metrics::measure("get_note_overview", path.len(), async move {
+        let path = vault::resolve(&path)?;
+        let relative = vault::relative_path(&path);
+        info!("[INFO] [notes] Building overview of: {}", relative);
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let modified_at = fs::metadata(&path).map_err(|e| AppError::io(&path, e))?
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        let own_id = db.0.note_by_path(&utils::normalize_path(&path)).map_err(AppError::InvalidOperation)?.map(|(id, _)| id);
+        let backlink_count = db.0
+            .linking_note_ids(&utils::note_link_keys(&relative, vault::exists))
+            .map_err(AppError::InvalidOperation)?
+            .into_iter()
+            .filter(|id| Some(id) != own_id.as_ref())
+            .count();
+
+        Ok(NoteOverview {
+            title: utils::extract_title_from_content(&content).unwrap_or_else(|| utils::path_to_title(&path)),
+            headings: utils::outline(&content),
+            word_count: stats::word_count(frontmatter::body(&content)),
+            tags: utils::extract_tags(&content),
+            backlink_count,
+            outgoing_link_count: utils::extract_links(&content).len(),
+            modified_at,
+            path: relative,
+        })
+    }).await
+}
+
+/// Every `@Name` mention of a person, given by their note (an ID, path,
+/// title or alias) in the people folder; most recently modified notes first,
+/// in line order within a note
+#[tauri::command]
+pub async fn get_mentions(
+    person_note: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<Vec<Mention>, AppError> {
+    metrics::measure("get_mentions", person_note.len(), async move {
+        let (_, path) = search::find_note(&db.0, &person_note).map_err(AppError::NotFound)?;
+        let keys = utils::note_link_keys(&vault::relative_path(&path), vault::exists);
+        let people_folder = settings.snapshot().map_err(AppError::InvalidOperation)?.people_folder;
+        info!("[INFO] [notes] Listing mentions of: {}", path);
+
+        let mut mentions = Vec::new();
+        for (source, title, modified_at) in db.0.mentioning_notes(&keys).map_err(AppError::InvalidOperation)? {
+            // The index may be behind the file; a vanished note has nothing to show
+            let Ok(content) = fs::read_to_string(&source) else { continue };
+            let lines: Vec<&str> = content.lines().collect();
+            let mut last_line = 0;
+            for mention in utils::extract_mentions(&content) {
+                if mention.line == last_line || !keys.contains(&utils::mention_key(&people_folder, &mention.name)) {
+                    continue;
+                }
+                last_line = mention.line;
+                mentions.push(Mention {
+                    path: vault::relative_path(&source),
+                    title: title.clone(),
+                    line: mention.line,
+                    text: lines.get(mention.line - 1).map(|line| line.trim().to_string()).unwrap_or_default(),
+                    modified_at,
+                });
+            }
+        }
+        Ok(mentions)
+    }).await
+}
+
+/// Links from a note (an ID, path, title or alias) in document order, each
+/// with the vault-relative path it resolves to (None if it's broken)
+#[tauri::command]
+pub async fn get_outgoing_links(note_id: String, db: State<'_, DbState>) -> Result<Vec<OutgoingLink>, AppError> {
+    metrics::measure("get_outgoing_links", note_id.len(), async move {
+        let (_, path) = search::find_note(&db.0, &note_id).map_err(AppError::NotFound)?;
+        info!("[INFO] [notes] Listing outgoing links of: {}", path);
+
+        let root = vault::resolve(".")?;
+        let note_path = vault::relative_path(&path);
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let files = attachments::walk_files(Path::new(&root));
+            let resolver = NoteResolver::new(&files);
+            utils::extract_links(&content)
+                .into_iter()
+                .map(|link| {
+                    let resolved = if link.wiki {
+                        resolver.wikilink(&link.target)
+                    } else {
+                        resolver.markdown_link(&note_path, &link.target)
+                    };
+                    let link_type = match (link.embed, link.wiki) {
+                        (true, _) => "embed",
+                        (false, true) => "wikilink",
+                        (false, false) => "markdown",
+                    };
+                    OutgoingLink {
+                        target: link.target,
+                        resolved_path: resolved.map(str::to_string),
+                        link_type: link_type.to_string(),
+                        line: link.line,
+                    }
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Link listing failed: {}", e)))
+    }).await
+}
+
+/// Get the folder `create_note_for_link` puts new notes in (None = the
+/// linking note's folder)
+#[tauri::command]
+pub async fn get_new_note_folder() -> Result<Option<String>, String> {
+    metrics::measure("get_new_note_folder", 0, async move {
+        Ok(NEW_NOTE_FOLDER.lock().unwrap_or_else(PoisonError::into_inner).clone())
+    }).await
+}
+
+/// Set the vault-relative folder for notes created from links (kept
+/// across restarts)
+#[tauri::command]
+pub async fn set_new_note_folder(folder: Option<String>, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_new_note_folder", 0, async move {
+        let folder = folder
+            .map(|f| utils::normalize_path(&f).trim_matches('/').to_string())
+            .filter(|f| !f.is_empty());
+        info!("[INFO] [notes] Setting new note folder: {:?}", folder);
+        db.0.set_setting_json(NEW_NOTE_FOLDER_SETTING, &folder)?;
+        *NEW_NOTE_FOLDER.lock().unwrap_or_else(PoisonError::into_inner) = folder;
+        Ok(())
+    }).await
+}
+
+/// Create the missing note a wikilink (`[[Target]]`, `Target|alias`, ...)
+/// in `source_path` points to, titled after the target. The new note and
+/// the source are indexed so the link resolves right away. Returns the
+/// new note's vault-relative path.
+#[tauri::command]
+pub async fn create_note_for_link(
+    source_path: String,
+    link_text: String,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<String, AppError> {
+    metrics::measure("create_note_for_link", source_path.len() + link_text.len(), async move {
+        let source = vault::resolve(&source_path)?;
+        let source_rel = vault::relative_path(&source);
+        let (target, _) = embeds::parse(&link_text)
+            .ok_or_else(|| AppError::InvalidOperation(format!("Not a note link: {}", link_text)))?;
+        let target = target.to_string();
+
+        let root = vault::resolve(".")?;
+        let files = tauri::async_runtime::spawn_blocking(move || attachments::walk_files(Path::new(&root)))
+            .await
+            .map_err(|e| AppError::InvalidOperation(format!("Vault scan failed: {}", e)))?;
+        if let Some(existing) = NoteResolver::new(&files).wikilink(&target) {
+            return Err(AppError::Conflict(format!("[[{}]] already links to {}", target, existing)));
+        }
+
+        let folder = NEW_NOTE_FOLDER.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let new_rel = embeds::new_note_path(&target, &source_rel, folder.as_deref());
+        let path = vault::resolve(&new_rel)?;
+        if Path::new(&path).exists() {
+            return Err(AppError::Conflict(format!("File already exists: {}", new_rel)));
+        }
+        info!("[INFO] [notes] Creating {} for link [[{}]] in {}", new_rel, target, source_rel);
+
+        let title = Path::new(&new_rel).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or(target);
+        save_and_reindex(&path, &format!("# {}\n", title), &db, &settings).await?;
+
+        // The link may not have been indexed yet if the source was just edited
+        let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+        if let Err(e) = indexer::index_file(&db.0, &source, &settings).await {
+            warn!("[WARN] [notes] Failed to re-index {}: {}", source, e);
+        }
+        Ok(new_rel)
+    }).await
+}
+
+/// Write a note through the write tracker and refresh its index entry
+async fn save_and_reindex(
+    path: &str,
+    content: &str,
+    db: &DbState,
+    settings: &IndexSettingsState,
+) -> Result<(), AppError> {
+    write_tracked(path, content).await?;
+
+    // The file is saved; a stale index entry shouldn't fail the edit
+    let settings = settings.snapshot().map_err(AppError::InvalidOperation)?;
+    if let Err(e) = indexer::index_file(&db.0, path, &settings).await {
+        warn!("[WARN] [notes] Failed to re-index {}: {}", path, e);
+    }
+    Ok(())
+}
+
+/// Resolve an embed (`![[note]]`, `![[note#Heading]]`, `![[note#^block]]`)
+/// written in the note at `source_path` to the content it shows
+#[tauri::command]
+pub async fn resolve_embed(source_path: String, embed_text: String) -> Result<EmbedContent, AppError> {
+    metrics::measure("resolve_embed", embed_text.len(), async move {
+        let root = vault::resolve(".")?;
+        let source_path = vault::relative_path(&vault::resolve(&source_path)?);
+
+        tauri::async_runtime::spawn_blocking(move || embeds::resolve(Path::new(&root), &source_path, &embed_text))
+            .await
+            .map_err(|e| AppError::InvalidOperation(format!("Embed resolution failed: {}", e)))?
+    }).await
+}
+
+/// Notes matched by a query that export_merged will take
+const MAX_MERGE_NOTES: usize = 500;
+
+/// Write selected notes to `dest` (in the vault) as one markdown document
+/// Notes are the vault-relative `paths`, else the markdown notes matching
+/// `query`. Embeds are inlined, headings demoted under a per-note heading,
+/// and links between the selected notes become in-document anchors. An
+/// existing `dest` is only replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_merged(
+    paths: Option<Vec<String>>,
+    query: Option<String>,
+    dest: String,
+    order: Option<MergeOrder>,
+    overwrite: Option<bool>,
+    db: State<'_, DbState>,
+) -> Result<MergedExport, AppError> {
+    metrics::measure("export_merged", dest.len(), async move {
+        let root = vault::resolve(".")?;
+        let dest = vault::resolve_output(&dest, overwrite.unwrap_or(false))?;
+
+        let mut notes = match (paths, query) {
+            (Some(paths), _) => {
+                // Validates each path stays in the vault
+                for path in &paths {
+                    vault::resolve(path)?;
+                }
+                paths.iter().map(|p| p.trim_start_matches('/').to_string()).collect::<Vec<_>>()
+            }
+            (None, Some(query)) if !query.trim().is_empty() => db.0
+                .search(&search::fts_query(&query), MAX_MERGE_NOTES)
+                .map_err(AppError::InvalidOperation)?
+                .into_iter()
+                .map(|result| vault::relative_path(&result.path))
+                .filter(|path| path.ends_with(".md"))
+                .collect(),
+            _ => return Err(AppError::InvalidOperation("No notes selected".to_string())),
+        };
+
+        match order.unwrap_or_default() {
+            MergeOrder::Given => {}
+            MergeOrder::Path => notes.sort(),
+            MergeOrder::Modified => notes.sort_by_key(|path| {
+                fs::metadata(Path::new(&root).join(path)).and_then(|m| m.modified()).ok()
+            }),
+        }
+        info!("[INFO] [notes] Merging {} notes into {}", notes.len(), dest);
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let merged = merge::merge(Path::new(&root), &notes)?;
+            fs::write(&dest, merged.markdown).map_err(|e| AppError::io(&dest, e))?;
+            Ok(MergedExport {
+                path: utils::normalize_path(&dest),
+                notes,
+                unresolved_embeds: merged.unresolved_embeds,
+            })
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Merged export failed: {}", e)))?
+    }).await
+}
+
+/// Export a note as a Word document
+/// `dest` (in the vault) defaults to the note's path with a `.docx`
+/// extension; an existing file is only replaced with `overwrite`. Embedded
+/// notes are inlined; images that couldn't be embedded are returned.
+#[tauri::command]
+pub async fn export_note_docx(path: String, dest: Option<String>, overwrite: Option<bool>) -> Result<DocxExport, AppError> {
+    metrics::measure("export_note_docx", path.len(), async move {
+        let root = vault::resolve(".")?;
+        let note_path = vault::relative_path(&vault::resolve(&path)?);
+        let dest = match dest {
+            Some(dest) => dest,
+            None => format!("{}.docx", path.strip_suffix(".md").unwrap_or(&path)),
+        };
+        let dest = vault::resolve_output(&dest, overwrite.unwrap_or(false))?;
+        info!("[INFO] [notes] Exporting {} to {}", note_path, dest);
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let exported = docx::export(Path::new(&root), &note_path)?;
+            fs::write(&dest, docx::pack(exported.docx).map_err(AppError::InvalidOperation)?).map_err(|e| AppError::io(&dest, e))?;
+            Ok(DocxExport {
+                path: utils::normalize_path(&dest),
+                skipped_images: exported.skipped_images,
+            })
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("DOCX export failed: {}", e)))?
+    }).await
+}
+
+/// Export a note as a LaTeX source
+/// `template` is a vault-relative .tex file with a `$body$` placeholder
+/// (and optionally `$title$`, `$author$`, `$date$`, `$bibliography$`); the
+/// built-in article template is used without one. `dest` (in the vault)
+/// defaults to the note's path with a `.tex` extension; an existing file is
+/// only replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_note_latex(
+    path: String,
+    template: Option<String>,
+    dest: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<LatexExport, AppError> {
+    metrics::measure("export_note_latex", path.len(), async move {
+        let root = vault::resolve(".")?;
+        let full_path = vault::resolve(&path)?;
+        let note_path = vault::relative_path(&full_path);
+        let template_path = template.as_deref().map(vault::resolve).transpose()?;
+        let dest = match dest {
+            Some(dest) => dest,
+            None => format!("{}.tex", path.strip_suffix(".md").unwrap_or(&path)),
+        };
+        let dest = vault::resolve_output(&dest, overwrite.unwrap_or(false))?;
+        info!("[INFO] [notes] Exporting {} to {}", note_path, dest);
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let template = match template_path {
+                Some(template_path) => fs::read_to_string(&template_path).map_err(|e| AppError::io(&template_path, e))?,
+                None => latex::DEFAULT_TEMPLATE.to_string(),
+            };
+            let content = fs::read_to_string(&full_path).map_err(|e| AppError::io(&full_path, e))?;
+            let files = attachments::walk_files(Path::new(&root));
+            let exported = latex::export(&content, &note_path, &files, &template).map_err(AppError::InvalidOperation)?;
+            fs::write(&dest, exported.tex).map_err(|e| AppError::io(&dest, e))?;
+            Ok(LatexExport {
+                path: utils::normalize_path(&dest),
+                citations: exported.citations,
+            })
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("LaTeX export failed: {}", e)))?
+    }).await
+}
+
+/// Import an OPML outline file (from a mind-mapping or outliner tool)
+/// `file` is a path in the vault. The outline becomes a note of nested list items named after its title,
+/// in `target_folder` (the vault root by default). With `split`, each
+/// top-level outline becomes its own note instead, in a new folder named
+/// after the title. Returns the vault-relative paths of the notes created.
+#[tauri::command]
+pub async fn import_opml(
+    file: String,
+    target_folder: Option<String>,
+    split: Option<bool>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<Vec<String>, AppError> {
+    metrics::measure("import_opml", file.len(), async move {
+        let source = vault::resolve(&file)?;
+        let xml = fs::read_to_string(&source)
+            .map_err(|e| AppError::NotFound(format!("Failed to read {}: {}", file, e)))?;
+        let doc = opml::parse(&xml).map_err(AppError::InvalidOperation)?;
+        let title = doc.title.clone().unwrap_or_else(|| utils::path_to_title(&file));
+        let target_dir = PathBuf::from(vault::resolve(target_folder.as_deref().unwrap_or("."))?);
+        info!("[INFO] [notes] Importing OPML {} into {:?}", file, target_dir);
+
+        // (folder, file stem, content) per note
+        let notes: Vec<(PathBuf, String, String)> = if split.unwrap_or(false) {
+            let dir = attachments::unique_path(&target_dir, &utils::safe_file_name(&title));
+            doc.outlines
+                .iter()
+                .map(|outline| {
+                    let content = opml::to_markdown(&outline.text, outline.note.as_deref(), &outline.children);
+                    (dir.clone(), utils::safe_file_name(&outline.text), content)
+                })
+                .collect()
+        } else {
+            vec![(target_dir, utils::safe_file_name(&title), opml::to_markdown(&title, None, &doc.outlines))]
+        };
+
+        let mut created = Vec::new();
+        for (dir, stem, content) in notes {
+            // Checked one at a time so notes from the same import don't collide
+            let path = attachments::unique_path(&dir, &format!("{}.md", stem));
+            let path = utils::normalize_path(&path.to_string_lossy());
+            save_and_reindex(&path, &content, &db, &settings).await?;
+            created.push(vault::relative_path(&path));
+        }
+        info!("[INFO] [notes] Imported {} notes from {}", created.len(), file);
+        Ok(created)
+    }).await
+}
+
+/// Export a note or folder as an OPML outline
+/// A note's headings and list items become nested outlines, with paragraph
+/// text as their notes; a folder gives one outline per note, nested by
+/// subfolder. `dest` (in the vault) defaults to the note's path with an
+/// `.opml` extension, or `<folder name>.opml` inside a folder; an existing
+/// file is only replaced with `overwrite`. Returns the path written.
+#[tauri::command]
+pub async fn export_opml(path: String, dest: Option<String>, overwrite: Option<bool>) -> Result<String, AppError> {
+    metrics::measure("export_opml", path.len(), async move {
+        let full_path = vault::resolve(&path)?;
+        let is_folder = Path::new(&full_path).is_dir();
+        let name = match Path::new(&full_path).file_name() {
+            Some(name) if name != "." => utils::path_to_title(&name.to_string_lossy()),
+            _ => "vault".to_string(),
+        };
+        let dest = match dest {
+            Some(dest) => dest,
+            None if is_folder => utils::normalize_path(&Path::new(&full_path).join(format!("{}.opml", utils::safe_file_name(&name))).to_string_lossy()),
+            None => format!("{}.opml", path.strip_suffix(".md").unwrap_or(&path)),
+        };
+        let dest = vault::resolve_output(&dest, overwrite.unwrap_or(false))?;
+        info!("[INFO] [notes] Exporting {} to {}", vault::relative_path(&full_path), dest);
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let (title, outlines) = if is_folder {
+                let mut notes: Vec<String> = attachments::walk_files(Path::new(&full_path))
+                    .into_iter()
+                    .filter(|rel| rel.ends_with(".md"))
+                    .collect();
+                notes.sort();
+                let mut outlines = Vec::new();
+                for rel in notes {
+                    let file = Path::new(&full_path).join(&rel);
+                    let content = fs::read_to_string(&file).map_err(|e| AppError::io(&file, e))?;
+                    let title = opml::title(&content, &utils::path_to_title(&rel));
+                    opml::insert_at_path(&mut outlines, &rel, opml::note_outline(&title, &content));
+                }
+                (name, outlines)
+            } else {
+                let content = fs::read_to_string(&full_path).map_err(|e| AppError::io(&full_path, e))?;
+                (opml::title(&content, &name), opml::from_markdown(&content))
+            };
+            fs::write(&dest, opml::render(&title, &outlines)).map_err(|e| AppError::io(&dest, e))?;
+            Ok(utils::normalize_path(&dest))
+        })
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("OPML export failed: {}", e)))?
+    }).await
+}
+
+/// Create or update dated meeting notes from the events of an iCalendar
+/// file or feed (`source`: a path, or an http(s)/webcal URL)
+/// Events starting within `days` days from today (7 by default) get a note
+/// in `folder` ("Meetings" by default) with their time, attendees and
+/// location as properties. Re-importing updates those properties on notes
+/// made earlier, keeping what was written in them.
+#[tauri::command]
+pub async fn import_ics(
+    source: String,
+    folder: Option<String>,
+    days: Option<u32>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<CalendarImport, AppError> {
+    metrics::measure("import_ics", source.len(), async move {
+        let folder = PathBuf::from(vault::resolve(folder.as_deref().unwrap_or(calendar::DEFAULT_FOLDER))?);
+        let days = days.unwrap_or(calendar::DEFAULT_DAYS);
+        info!("[INFO] [notes] Importing calendar {} into {:?} ({} days)", source, folder, days);
+
+        let ics = calendar::load(&source).await.map_err(AppError::InvalidOperation)?;
+        let events = calendar::parse(&ics).map_err(AppError::InvalidOperation)?;
+        let today = chrono::Local::now().date_naive();
+        let writes = calendar::plan(&folder, &events, today, days).map_err(AppError::InvalidOperation)?;
+
+        let mut result = CalendarImport {
+            events: events.iter().filter(|event| calendar::in_window(event, today, days)).count(),
+            created: Vec::new(),
+            updated: Vec::new(),
+        };
+        for write in writes {
+            let path = utils::normalize_path(&write.path.to_string_lossy());
+            save_and_reindex(&path, &write.content, &db, &settings).await?;
+            if write.created {
+                result.created.push(vault::relative_path(&path));
+            } else {
+                result.updated.push(vault::relative_path(&path));
+            }
+        }
+        info!("[INFO] [notes] Calendar import: {} created, {} updated", result.created.len(), result.updated.len());
+        Ok(result)
+    }).await
+}
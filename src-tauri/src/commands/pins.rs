@@ -0,0 +1,57 @@
+//! Pinned note IPC commands
+//!
+//! Pins are stored by vault-relative path so they survive when the
+//! database is synced between devices with different vault locations.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+use tracing::info;
+
+use crate::db::DbState;
+use crate::metrics;
+use crate::models::PinnedNote;
+use crate::vault;
+
+/// Pin a note, appending it (or inserting at `position`)
+/// Pinning an already pinned note moves it.
+#[tauri::command]
+pub async fn pin_note(
+    path: String,
+    position: Option<u32>,
+    db: State<'_, DbState>,
+) -> Result<Vec<PinnedNote>, String> {
+    metrics::measure("pin_note", path.len(), async move {
+        let path = vault::resolve(&path).map_err(|e| e.to_string())?;
+        let relative_path = vault::relative_path(&path);
+        info!("[INFO] [pins] Pinning: {}", relative_path);
+
+        let pinned_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        db.0.pin_note(&relative_path, position, pinned_at)?;
+        db.0.list_pinned()
+    }).await
+}
+
+/// Unpin a note (no-op if it isn't pinned)
+#[tauri::command]
+pub async fn unpin_note(path: String, db: State<'_, DbState>) -> Result<Vec<PinnedNote>, String> {
+    metrics::measure("unpin_note", path.len(), async move {
+        let path = vault::resolve(&path).map_err(|e| e.to_string())?;
+        let relative_path = vault::relative_path(&path);
+        info!("[INFO] [pins] Unpinning: {}", relative_path);
+
+        db.0.unpin_note(&relative_path)?;
+        db.0.list_pinned()
+    }).await
+}
+
+/// Pinned notes in display order (vault-relative paths)
+#[tauri::command]
+pub async fn list_pinned(db: State<'_, DbState>) -> Result<Vec<PinnedNote>, String> {
+    metrics::measure("list_pinned", 0, async move {
+        db.0.list_pinned()
+    }).await
+}
@@ -0,0 +1,34 @@
+//! Platform IPC commands
+
+use crate::deeplink;
+use crate::metrics;
+use crate::models::{NavigatePayload, PlatformInfo, SystemFont};
+use crate::platform;
+
+/// Get OS and display information
+#[tauri::command]
+pub async fn get_platform_info() -> Result<PlatformInfo, String> {
+    metrics::measure("get_platform_info", 0, async move {
+        Ok(platform::current())
+    }).await
+}
+
+/// List installed font families and whether they're monospace
+#[tauri::command]
+pub async fn list_system_fonts() -> Result<Vec<SystemFont>, String> {
+    metrics::measure("list_system_fonts", 0, async move {
+        tauri::async_runtime::spawn_blocking(|| platform::system_fonts().to_vec())
+            .await
+            .map_err(|e| format!("Font enumeration failed: {}", e))
+    }).await
+}
+
+/// Take the navigation requested by the latest `unstablon://` link
+/// Lets the frontend catch a link that launched the app before it was
+/// listening for `navigate`.
+#[tauri::command]
+pub async fn take_pending_navigation() -> Result<Option<NavigatePayload>, String> {
+    metrics::measure("take_pending_navigation", 0, async move {
+        Ok(deeplink::take_pending())
+    }).await
+}
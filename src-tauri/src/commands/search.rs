@@ -1,28 +1,332 @@
 //! Search IPC commands
 
-use tauri::State;
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
 use tracing::info;
 
-use crate::db::DbState;
-use crate::models::SearchResult;
-use crate::indexer;
+use crate::access;
+use crate::db::{Database, DbState, SearchFilter};
+use crate::autocomplete;
+use crate::models::{AutocompleteItem, IndexSettings, LinkSuggestion, QueryResult, SearchRanking, SearchResult};
+use crate::indexer::{self, jobs, IndexSettingsState};
+use crate::metrics;
+use crate::query;
+use crate::suggest;
+use crate::synonyms;
+use crate::utils;
+use crate::vault;
+
+/// Title/alias rows considered per suggestion request
+const MAX_TITLE_CANDIDATES: usize = 200;
+
+/// Autocomplete results when no limit is given
+const DEFAULT_AUTOCOMPLETE_LIMIT: usize = 20;
+
+/// Results returned by `search_content`
+const SEARCH_LIMIT: usize = 20;
+
+/// FTS matches re-ranked by frecency before `SEARCH_LIMIT` is applied
+const SEARCH_CANDIDATES: usize = 50;
+
+/// Quick switcher results when no limit is given
+const DEFAULT_QUICK_SWITCH_LIMIT: usize = 20;
 
 /// Search content using FTS5
+/// With recorded note opens, relevance is boosted by frecency. `scope`
+/// limits results to `folder:<path>`, `linked-from:<note>` (notes the given
+/// note links to) or `links-to:<note>` (notes linking to it); a note is a
+/// path, ID, title or alias.
 #[tauri::command]
 pub async fn search_content(
     query: String,
+    scope: Option<String>,
     db: State<'_, DbState>,
 ) -> Result<Vec<SearchResult>, String> {
-    info!("[INFO] [search] Searching for: {}", query);
+    metrics::measure("search_content", query.len(), async move {
+        info!("[INFO] [search] Searching for: {} (scope: {:?})", query, scope);
+
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let filter = match scope.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            Some(scope) => Some(resolve_scope(&db.0, scope)?),
+            None => None,
+        };
+        let mut results = db.0.search_in(&fts_query(&query), SEARCH_CANDIDATES, filter.as_ref())?;
+        let frecency = frecency_scores(&db.0)?;
+        if !frecency.is_empty() {
+            for result in &mut results {
+                if let Some(score) = frecency.get(&vault::relative_path(&result.path)) {
+                    result.score *= access::boost(*score);
+                }
+            }
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        }
+        results.truncate(SEARCH_LIMIT);
+        Ok(results)
+    }).await
+}
+
+/// Evaluate the contents of a fenced `pkm-query` block against the index
+/// See `query` for the clauses; the frontend renders the returned rows.
+#[tauri::command]
+pub async fn run_query_block(source: String, db: State<'_, DbState>) -> Result<QueryResult, String> {
+    metrics::measure("run_query_block", source.len(), async move {
+        let query = query::parse(&source)?;
+        info!("[INFO] [search] Running query block: {:?}", query);
+
+        let folder = match &query.folder {
+            Some(folder) => Some(utils::normalize_path(&vault::resolve(folder).map_err(|e| e.to_string())?)),
+            None => None,
+        };
+        let fts = query.search.as_deref().map(fts_query);
+        let mut rows = db.0.query_notes(&query, folder.as_deref(), fts.as_deref())?;
+        for row in &mut rows {
+            row.path = vault::relative_path(&row.path);
+        }
+        Ok(QueryResult { fields: query.fields, rows })
+    }).await
+}
+
+/// Jump to a note by title or alias, frequently and recently opened notes
+/// first; with an empty query, the most frecent notes
+#[tauri::command]
+pub async fn quick_switch(
+    query: String,
+    limit: Option<usize>,
+    db: State<'_, DbState>,
+) -> Result<Vec<LinkSuggestion>, String> {
+    metrics::measure("quick_switch", query.len(), async move {
+        let limit = limit.unwrap_or(DEFAULT_QUICK_SWITCH_LIMIT);
+        let frecency = frecency_scores(&db.0)?;
+        let query = query.trim();
+
+        if query.is_empty() {
+            let mut recent: Vec<(&String, &f64)> = frecency.iter().collect();
+            recent.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+            let mut results = Vec::new();
+            for (path, score) in recent {
+                if results.len() >= limit {
+                    break;
+                }
+                // Opens of deleted or unindexed notes are skipped
+                let Ok(full_path) = vault::resolve(path) else { continue };
+                let full_path = utils::normalize_path(&full_path);
+                if let Some((id, title)) = db.0.note_by_path(&full_path)? {
+                    results.push(LinkSuggestion {
+                        id,
+                        path: full_path,
+                        title,
+                        alias: None,
+                        source: "recent".to_string(),
+                        score: *score,
+                    });
+                }
+            }
+            return Ok(results);
+        }
+
+        let mut results: Vec<LinkSuggestion> = title_matches(&db.0, query, |_| false)?.into_values().collect();
+        for result in &mut results {
+            if let Some(score) = frecency.get(&vault::relative_path(&result.path)) {
+                result.score *= access::boost(*score);
+            }
+        }
+        results.sort_by(|a, b| {
+            b.score.total_cmp(&a.score).then_with(|| a.title.len().cmp(&b.title.len()))
+        });
+        results.truncate(limit);
+        Ok(results)
+    }).await
+}
 
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
+/// Turn a `search_content` scope into a search filter
+fn resolve_scope(db: &Database, scope: &str) -> Result<SearchFilter, String> {
+    let (kind, value) = scope
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid search scope: {:?}", scope))?;
+    let value = value.trim();
+
+    match kind.trim() {
+        "folder" => {
+            let folder = vault::resolve(value).map_err(|e| e.to_string())?;
+            Ok(SearchFilter::Folder(utils::normalize_path(&folder)))
+        }
+        "linked-from" => {
+            let (id, _) = find_note(db, value)?;
+            let keys: HashSet<String> = db.outgoing_link_keys(&id)?.into_iter().collect();
+            let ids = db
+                .indexed_paths()?
+                .into_iter()
+                .filter(|(_, path)| utils::note_link_keys(&vault::relative_path(path), vault::exists).iter().any(|k| keys.contains(k)))
+                .map(|(id, _)| id)
+                .collect();
+            Ok(SearchFilter::Notes(ids))
+        }
+        "links-to" => {
+            let (_, path) = find_note(db, value)?;
+            let keys = utils::note_link_keys(&vault::relative_path(&path), vault::exists);
+            Ok(SearchFilter::Notes(db.linking_note_ids(&keys)?))
+        }
+        other => Err(format!("Unknown search scope: {}", other)),
+    }
+}
+
+/// (id, path) of the note `note` names: a path (or a folder's path, for its
+/// folder note), ID, title, then alias
+pub(crate) fn find_note(db: &Database, note: &str) -> Result<(String, String), String> {
+    let paths: Vec<String> = [note.to_string(), format!("{}.md", note)]
+        .into_iter()
+        .chain(utils::folder_note_paths(note))
+        .filter_map(|candidate| vault::resolve(&candidate).ok())
+        .collect();
+    db.find_note(&paths, note)?
+        .ok_or_else(|| format!("No note matches {:?}", note))
+}
+
+/// Frecency per vault-relative path from recorded opens
+fn frecency_scores(db: &Database) -> Result<HashMap<String, f64>, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let opens = db.opens_since(now.saturating_sub(access::FRECENCY_WINDOW_SECS))?;
+    Ok(access::frecency(&opens, now))
+}
+
+/// Best title/alias match per note for typed `prefix`, keyed by note ID
+fn title_matches(
+    db: &Database,
+    prefix: &str,
+    exclude: impl Fn(&str) -> bool,
+) -> Result<HashMap<String, LinkSuggestion>, String> {
+    let mut matches: HashMap<String, LinkSuggestion> = HashMap::new();
+    for m in db.match_titles(prefix, MAX_TITLE_CANDIDATES)? {
+        if exclude(&m.path) {
+            continue;
+        }
+        let (candidate, source) = match &m.alias {
+            Some(alias) => (alias.as_str(), "alias"),
+            None => (m.title.as_str(), "title"),
+        };
+        let score = match suggest::match_score(prefix, candidate) {
+            Some(score) => score,
+            None => continue,
+        };
+        if matches.get(&m.id).is_some_and(|best| best.score >= score) {
+            continue;
+        }
+        matches.insert(m.id.clone(), LinkSuggestion {
+            source: source.to_string(),
+            score,
+            id: m.id,
+            path: m.path,
+            title: m.title,
+            alias: m.alias,
+        });
     }
+    Ok(matches)
+}
+
+/// FTS5 query for user search text: escaped, prefix-matched, and expanded
+/// with per-vault synonyms (e.g. js -> javascript)
+pub(crate) fn fts_query(query: &str) -> String {
+    let fts_query = format!("{}*", escape_fts_query(query));
+    let synonyms = synonyms::for_vault();
+    if synonyms.is_empty() { fts_query } else { synonyms.expand(&fts_query) }
+}
+
+/// Propose notes to link from the text before the cursor
+/// Title/alias matches on the text after an unclosed `[[` rank first,
+/// followed by notes relevant to the current sentence (FTS)
+#[tauri::command]
+pub async fn suggest_links(
+    context_text: String,
+    limit: usize,
+    current_path: Option<String>,
+    db: State<'_, DbState>,
+) -> Result<Vec<LinkSuggestion>, String> {
+    metrics::measure("suggest_links", context_text.len(), async move {
+        let context = suggest::link_context(&context_text);
+        let current_path = current_path.map(|p| utils::normalize_path(&p));
+        let is_current = |path: &str| current_path.as_deref() == Some(path);
+
+        // Best title/alias match per note
+        let matches = match context.prefix.filter(|p| !p.is_empty()) {
+            Some(prefix) => title_matches(&db.0, prefix, is_current)?,
+            None => HashMap::new(),
+        };
+
+        let mut suggestions: Vec<LinkSuggestion> = matches.into_values().collect();
+        suggestions.sort_by(|a, b| {
+            b.score.total_cmp(&a.score).then_with(|| a.title.len().cmp(&b.title.len()))
+        });
+        suggestions.truncate(limit);
+
+        // Fill the rest with notes about the same topic
+        let topic = format!("{} {}", context.sentence, context.prefix.unwrap_or(""));
+        if suggestions.len() < limit {
+            if let Some(fts_query) = suggest::fts_query(&topic) {
+                for result in db.0.search(&fts_query, limit * 2)? {
+                    if suggestions.len() >= limit {
+                        break;
+                    }
+                    if is_current(&result.path) || suggestions.iter().any(|s| s.id == result.id) {
+                        continue;
+                    }
+                    suggestions.push(LinkSuggestion {
+                        id: result.id,
+                        path: result.path,
+                        title: result.title,
+                        alias: None,
+                        source: "content".to_string(),
+                        score: result.score,
+                    });
+                }
+            }
+        }
+
+        Ok(suggestions)
+    }).await
+}
 
-    // Escape special FTS5 characters and create query
-    let fts_query = format!("{}*", escape_fts_query(&query));
+/// Complete a title, alias, tag, or heading from the in-memory prefix store
+/// `kind` is one of "title", "alias", "tag", "heading"
+#[tauri::command]
+pub async fn autocomplete(kind: String, prefix: String, limit: Option<usize>) -> Result<Vec<AutocompleteItem>, String> {
+    metrics::measure("autocomplete", prefix.len(), async move {
+        let kind = autocomplete::Kind::parse(&kind)?;
+        Ok(autocomplete::complete(kind, &prefix, limit.unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT)))
+    }).await
+}
+
+/// Tags starting with `prefix` (a leading '#' is ignored), most used first
+#[tauri::command]
+pub async fn autocomplete_tags(
+    prefix: String,
+    limit: Option<usize>,
+    db: State<'_, DbState>,
+) -> Result<Vec<AutocompleteItem>, String> {
+    metrics::measure("autocomplete_tags", prefix.len(), async move {
+        db.0.autocomplete_tags(&prefix, limit.unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT))
+    }).await
+}
 
-    db.0.search(&fts_query, 20)
+/// Existing values of frontmatter property `key` starting with `prefix`,
+/// most used first
+#[tauri::command]
+pub async fn autocomplete_property_values(
+    key: String,
+    prefix: String,
+    limit: Option<usize>,
+    db: State<'_, DbState>,
+) -> Result<Vec<AutocompleteItem>, String> {
+    metrics::measure("autocomplete_property_values", key.len() + prefix.len(), async move {
+        db.0.autocomplete_property_values(&key, &prefix, limit.unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT))
+    }).await
 }
 
 /// Index a single content file
@@ -30,21 +334,135 @@ pub async fn search_content(
 pub async fn index_content(
     path: String,
     db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
 ) -> Result<(), String> {
-    info!("[INFO] [search] Indexing: {}", path);
+    metrics::measure("index_content", path.len(), async move {
+        info!("[INFO] [search] Indexing: {}", path);
+        let path = vault::resolve(&path).map_err(|e| e.to_string())?;
+
+        let settings = settings.snapshot()?;
+        indexer::index_file(&db.0, &path, &settings).await
+    }).await
+}
 
-    indexer::index_file(&db.0, &path).await
+/// Index several content files in batched transactions
+/// Returns the number of files indexed
+#[tauri::command]
+pub async fn index_content_batch(
+    paths: Vec<String>,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<u32, String> {
+    let request_bytes = paths.iter().map(|p| p.len()).sum();
+    metrics::measure("index_content_batch", request_bytes, async move {
+        info!("[INFO] [search] Batch indexing {} files", paths.len());
+        let paths = paths
+            .iter()
+            .map(|path| vault::resolve(path))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let settings = settings.snapshot()?;
+        indexer::index_files(&db.0, &paths, &settings).await
+    }).await
 }
 
-/// Rebuild the entire search index
+/// Rebuild the search index of the open vault
+/// Resumes an interrupted rebuild of the same vault; emits `index:progress`
 #[tauri::command]
 pub async fn rebuild_index(
-    home_path: String,
+    app: AppHandle,
     db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
 ) -> Result<u32, String> {
-    info!("[INFO] [search] Rebuilding index from: {}", home_path);
+    metrics::measure("rebuild_index", 0, async move {
+        let home_path = vault::require_root().map_err(|e| e.to_string())?;
+        info!("[INFO] [search] Rebuilding index from: {}", home_path);
+
+        let settings = settings.snapshot()?;
+        let guard = jobs::begin()?;
+        let on_progress = |indexed: u32| jobs::emit_progress(&app, &home_path, indexed);
 
-    indexer::rebuild_index(&db.0, &home_path).await
+        indexer::rebuild_index(&db.0, &home_path, &settings, guard.token(), &on_progress)
+            .await
+            .map(|outcome| outcome.indexed)
+    }).await
+}
+
+/// Start a cancellable index rebuild of the open vault in the background
+/// Progress arrives as `index:progress`, completion as `index:finished`
+#[tauri::command]
+pub async fn start_index_rebuild(
+    app: AppHandle,
+    db: State<'_, DbState>,
+    settings: State<'_, IndexSettingsState>,
+) -> Result<(), String> {
+    metrics::measure("start_index_rebuild", 0, async move {
+        let home_path = vault::require_root().map_err(|e| e.to_string())?;
+        info!("[INFO] [search] Starting background rebuild from: {}", home_path);
+
+        let settings = settings.snapshot()?;
+        jobs::spawn_rebuild(app, db.0.clone(), home_path, settings)
+    }).await
+}
+
+/// Cancel the running index rebuild (progress is kept for resuming)
+/// Returns whether a rebuild was running
+#[tauri::command]
+pub async fn cancel_index_rebuild() -> Result<bool, String> {
+    metrics::measure("cancel_index_rebuild", 0, async move {
+        info!("[INFO] [search] Cancelling index rebuild");
+        Ok(jobs::cancel())
+    }).await
+}
+
+/// Get the current index size limits
+#[tauri::command]
+pub async fn get_index_settings(
+    settings: State<'_, IndexSettingsState>,
+) -> Result<IndexSettings, String> {
+    metrics::measure("get_index_settings", 0, async move {
+        settings.snapshot()
+    }).await
+}
+
+/// Replace the index size limits (applies to subsequent indexing, and is
+/// kept across restarts; title sources are kept for the open vault only)
+#[tauri::command]
+pub async fn set_index_settings(
+    new_settings: IndexSettings,
+    settings: State<'_, IndexSettingsState>,
+    db: State<'_, DbState>,
+) -> Result<(), String> {
+    metrics::measure("set_index_settings", 0, async move {
+        info!("[INFO] [search] Updating index settings: {:?}", new_settings);
+        indexer::save_settings(&db.0, vault::root().as_deref(), &new_settings)?;
+
+        let mut guard = settings.0.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        *guard = new_settings;
+        Ok(())
+    }).await
+}
+
+/// Get the search ranking settings
+#[tauri::command]
+pub async fn get_search_ranking(db: State<'_, DbState>) -> Result<SearchRanking, String> {
+    metrics::measure("get_search_ranking", 0, async move {
+        Ok(db.0.ranking())
+    }).await
+}
+
+/// Replace the search ranking settings (column weights and boosts)
+#[tauri::command]
+pub async fn set_search_ranking(ranking: SearchRanking, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_search_ranking", 0, async move {
+        info!("[INFO] [search] Updating search ranking: {:?}", ranking);
+        if ranking.title_weight < 0.0 || ranking.body_weight < 0.0 {
+            return Err("Column weights can't be negative".to_string());
+        }
+        db.0.set_ranking(ranking)
+    }).await
 }
 
 /// Escape special FTS5 query characters
@@ -0,0 +1,262 @@
+//! Vault key and secret IPC commands
+//!
+//! Each vault root can have a passphrase protecting its vault key (see
+//! `vault_key`). Key derivation is deliberately slow and keychain access
+//! may block on the OS, so both run off the async runtime. When the vault
+//! locks itself a `vault:locked` event is emitted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::db::{Database, DbState};
+use crate::error::AppError;
+use crate::metrics;
+use crate::models::{VaultLockStatus, WrappedVaultKey};
+use crate::secrets;
+use crate::vault;
+use crate::vault_key;
+
+/// How often an unlocked vault checks whether it should lock itself
+const AUTO_LOCK_CHECK: Duration = Duration::from_secs(15);
+
+/// Bumped by every unlock; an auto-lock poller from an older unlock stops
+static WATCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Settings key holding the auto-lock timeout
+const AUTO_LOCK_SETTING: &str = "auto_lock_secs";
+
+/// Load the auto-lock timeout saved in an earlier session
+pub(crate) fn restore_settings(db: &Database) {
+    match db.setting_json::<u64>(AUTO_LOCK_SETTING) {
+        Ok(Some(secs)) => vault_key::set_auto_lock_secs(secs),
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [security] Failed to load auto-lock timeout: {}", e),
+    }
+}
+
+/// Whether the open vault has a passphrase and is unlocked
+#[tauri::command]
+pub async fn get_vault_lock_status(db: State<'_, DbState>) -> Result<VaultLockStatus, AppError> {
+    metrics::measure("get_vault_lock_status", 0, async move {
+        Ok(VaultLockStatus {
+            has_passphrase: db.0.vault_key(&vault_root()?).map_err(AppError::InvalidOperation)?.is_some(),
+            unlocked: vault_key::is_unlocked(),
+            auto_lock_secs: vault_key::auto_lock_secs(),
+        })
+    }).await
+}
+
+/// Set the first passphrase of the open vault, creating its key
+/// The vault is left unlocked. Fails if a passphrase is already set.
+#[tauri::command]
+pub async fn set_vault_passphrase(passphrase: String, db: State<'_, DbState>, app: AppHandle) -> Result<(), AppError> {
+    metrics::measure("set_vault_passphrase", 0, async move {
+        let root = vault_root()?;
+        if db.0.vault_key(&root).map_err(AppError::InvalidOperation)?.is_some() {
+            return Err(AppError::Conflict("The vault already has a passphrase".to_string()));
+        }
+        info!("[INFO] [security] Setting passphrase for: {}", root);
+
+        let (wrapped, key) = blocking(move || vault_key::create(&passphrase)).await?;
+        db.0.save_vault_key(&root, &wrapped, now_secs()).map_err(AppError::InvalidOperation)?;
+        vault_key::unlock(key);
+        watch_auto_lock(app);
+        Ok(())
+    }).await
+}
+
+/// Replace the passphrase; the vault key itself is unchanged
+#[tauri::command]
+pub async fn change_passphrase(
+    old_passphrase: String,
+    new_passphrase: String,
+    db: State<'_, DbState>,
+) -> Result<(), AppError> {
+    metrics::measure("change_passphrase", 0, async move {
+        let root = vault_root()?;
+        let wrapped = stored_key(&db.0, &root)?;
+        info!("[INFO] [security] Changing passphrase for: {}", root);
+
+        let rewrapped = blocking(move || {
+            let key = vault_key::unwrap(&wrapped, &old_passphrase)?;
+            vault_key::wrap(&key, &new_passphrase)
+        })
+        .await?;
+        db.0.save_vault_key(&root, &rewrapped, now_secs()).map_err(AppError::InvalidOperation)
+    }).await
+}
+
+/// Unlock the open vault with its passphrase
+/// With `remember`, the vault key is also saved in the OS keychain so
+/// `unlock_vault_from_keychain` can unlock without the passphrase.
+#[tauri::command]
+pub async fn unlock_vault(
+    passphrase: String,
+    remember: Option<bool>,
+    db: State<'_, DbState>,
+    app: AppHandle,
+) -> Result<(), AppError> {
+    metrics::measure("unlock_vault", 0, async move {
+        let root = vault_root()?;
+        let wrapped = stored_key(&db.0, &root)?;
+
+        let name = secrets::vault_key_name(&root);
+        let key = blocking(move || {
+            let key = vault_key::unwrap(&wrapped, &passphrase)?;
+            if remember.unwrap_or(false) {
+                secrets::store(&name, key.as_ref())?;
+            }
+            Ok(key)
+        })
+        .await?;
+        vault_key::unlock(key);
+        info!("[INFO] [security] Vault unlocked: {}", root);
+        watch_auto_lock(app);
+        Ok(())
+    }).await
+}
+
+/// Unlock the open vault with the key remembered in the OS keychain
+/// Returns false if none is remembered.
+#[tauri::command]
+pub async fn unlock_vault_from_keychain(db: State<'_, DbState>, app: AppHandle) -> Result<bool, AppError> {
+    metrics::measure("unlock_vault_from_keychain", 0, async move {
+        let root = vault_root()?;
+        stored_key(&db.0, &root)?;
+
+        let name = secrets::vault_key_name(&root);
+        let Some(stored) = blocking(move || secrets::get(&name)).await? else {
+            return Ok(false);
+        };
+        let stored = Zeroizing::new(stored);
+        if stored.len() != vault_key::KEY_LEN {
+            return Err(AppError::InvalidOperation("Remembered vault key is corrupt".to_string()));
+        }
+        let mut key = Zeroizing::new([0u8; vault_key::KEY_LEN]);
+        key.copy_from_slice(&stored);
+
+        vault_key::unlock(key);
+        info!("[INFO] [security] Vault unlocked from keychain: {}", root);
+        watch_auto_lock(app);
+        Ok(true)
+    }).await
+}
+
+/// Remove the open vault's remembered key from the OS keychain
+/// Returns false if none was remembered.
+#[tauri::command]
+pub async fn forget_vault_key() -> Result<bool, AppError> {
+    metrics::measure("forget_vault_key", 0, async move {
+        let name = secrets::vault_key_name(&vault_root()?);
+        blocking(move || secrets::delete(&name)).await
+    }).await
+}
+
+/// Store a credential (API token, ...) in the OS keychain
+#[tauri::command]
+pub async fn store_secret(name: String, value: String) -> Result<(), AppError> {
+    metrics::measure("store_secret", name.len(), async move {
+        secrets::validate_name(&name).map_err(AppError::InvalidOperation)?;
+        info!("[INFO] [security] Storing secret: {}", name);
+        blocking(move || secrets::store(&name, value.as_bytes())).await
+    }).await
+}
+
+/// A credential from the OS keychain (None if not stored)
+#[tauri::command]
+pub async fn get_secret(name: String) -> Result<Option<String>, AppError> {
+    metrics::measure("get_secret", name.len(), async move {
+        secrets::validate_name(&name).map_err(AppError::InvalidOperation)?;
+        blocking(move || {
+            secrets::get(&name)?
+                .map(|value| String::from_utf8(value).map_err(|_| format!("Secret {} is not text", name)))
+                .transpose()
+        })
+        .await
+    }).await
+}
+
+/// Delete a credential from the OS keychain; false if it wasn't stored
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<bool, AppError> {
+    metrics::measure("delete_secret", name.len(), async move {
+        secrets::validate_name(&name).map_err(AppError::InvalidOperation)?;
+        info!("[INFO] [security] Deleting secret: {}", name);
+        blocking(move || secrets::delete(&name)).await
+    }).await
+}
+
+/// Lock the vault now, dropping its key from memory
+#[tauri::command]
+pub async fn lock_vault() -> Result<(), String> {
+    metrics::measure("lock_vault", 0, async move {
+        if vault_key::lock() {
+            info!("[INFO] [security] Vault locked");
+        }
+        Ok(())
+    }).await
+}
+
+/// Seconds after unlocking before the vault locks itself (0 = never; kept
+/// across restarts)
+#[tauri::command]
+pub async fn set_auto_lock_timeout(secs: u64, db: State<'_, DbState>) -> Result<(), String> {
+    metrics::measure("set_auto_lock_timeout", 0, async move {
+        info!("[INFO] [security] Auto-lock after {}s", secs);
+        db.0.set_setting_json(AUTO_LOCK_SETTING, &secs)?;
+        vault_key::set_auto_lock_secs(secs);
+        Ok(())
+    }).await
+}
+
+fn vault_root() -> Result<String, AppError> {
+    vault::root().ok_or_else(|| AppError::Path("No vault is open".to_string()))
+}
+
+fn stored_key(db: &Database, root: &str) -> Result<WrappedVaultKey, AppError> {
+    db.vault_key(root)
+        .map_err(AppError::InvalidOperation)?
+        .ok_or_else(|| AppError::NotFound("The vault has no passphrase".to_string()))
+}
+
+/// Run key derivation or keychain access off the async runtime
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, AppError> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::InvalidOperation(format!("Key task failed: {}", e)))?
+        .map_err(AppError::InvalidOperation)
+}
+
+/// Poll until the vault locks (by timeout or `lock_vault`), emitting
+/// `vault:locked` if it timed out. Replaces the poller of an earlier unlock.
+fn watch_auto_lock(app: AppHandle) {
+    let generation = WATCH_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(AUTO_LOCK_CHECK).await;
+            if WATCH_GENERATION.load(Ordering::Relaxed) != generation {
+                break;
+            }
+            if vault_key::expire() {
+                info!("[INFO] [security] Vault auto-locked");
+                if let Err(e) = app.emit("vault:locked", ()) {
+                    warn!("[WARN] [security] Failed to emit vault:locked: {}", e);
+                }
+                break;
+            }
+            if !vault_key::is_unlocked() {
+                break;
+            }
+        }
+    });
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,80 @@
+//! Writing statistics IPC commands
+
+use tauri::State;
+use tracing::info;
+
+use crate::access;
+use crate::db::DbState;
+use crate::metrics;
+use crate::models::{ActivityHeatmap, StatsRange, WritingStats};
+use crate::stats::{self, TermCounter};
+
+/// Default range when `from` is omitted
+const DEFAULT_RANGE_DAYS: u32 = 365;
+
+/// Number of top terms returned
+const TOP_TERMS: usize = 50;
+
+/// Words written per day, top terms, and average note length over a date
+/// range (defaults to the last year)
+#[tauri::command]
+pub async fn get_writing_stats(
+    range: Option<StatsRange>,
+    db: State<'_, DbState>,
+) -> Result<WritingStats, String> {
+    metrics::measure("get_writing_stats", 0, async move {
+        let range = range.unwrap_or_default();
+        let from = range.from.unwrap_or_else(|| stats::days_ago(DEFAULT_RANGE_DAYS));
+        let to = range.to.unwrap_or_else(stats::today);
+        info!("[INFO] [stats] Computing writing stats: {} to {}", from, to);
+
+        let from_secs = stats::day_start_secs(&from)?;
+        let to_secs = stats::day_start_secs(&to)? + 24 * 60 * 60;
+
+        let db = db.0.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let daily = db.writing_daily(&from, &to)?;
+
+            let mut terms = TermCounter::default();
+            let mut note_count = 0u32;
+            let mut total_words = 0u64;
+            db.for_each_body(from_secs, to_secs, |body| {
+                terms.add(body);
+                note_count += 1;
+                total_words += stats::word_count(body) as u64;
+            })?;
+
+            Ok(WritingStats {
+                total_words_added: daily.iter().map(|d| d.words_added).sum(),
+                total_words_removed: daily.iter().map(|d| d.words_removed).sum(),
+                daily,
+                top_terms: terms.top(TOP_TERMS),
+                average_note_words: if note_count > 0 {
+                    total_words as f64 / note_count as f64
+                } else {
+                    0.0
+                },
+                note_count,
+                from,
+                to,
+            })
+        })
+        .await
+        .map_err(|e| format!("Stats computation failed: {}", e))?
+    }).await
+}
+
+/// Notes created, modified, and (with open tracking on) opened per day of
+/// `year` (defaults to this year), as compact per-day arrays for a heatmap
+#[tauri::command]
+pub async fn get_activity_heatmap(year: Option<i32>, db: State<'_, DbState>) -> Result<ActivityHeatmap, String> {
+    metrics::measure("get_activity_heatmap", 0, async move {
+        let year = year.unwrap_or_else(|| stats::today()[..4].parse().unwrap_or(1970));
+        info!("[INFO] [stats] Computing activity heatmap: {}", year);
+
+        let (from, to) = stats::year_bounds(year)?;
+        let (created, modified) = db.0.activity_times(from, to)?;
+        let opens = if access::is_tracking() { Some(db.0.open_times(from, to)?) } else { None };
+        stats::heatmap(year, &created, &modified, opens.as_deref())
+    }).await
+}
@@ -0,0 +1,56 @@
+//! External web page IPC commands
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::db::DbState;
+use crate::metrics;
+use crate::models::LinkPreview;
+use crate::webpage;
+
+/// Title of an external page (`og:title`, else `<title>`), so a pasted URL
+/// can become `[Title](url)`; None if the page has no title
+#[tauri::command]
+pub async fn resolve_url_title(url: String) -> Result<Option<String>, String> {
+    metrics::measure("resolve_url_title", url.len(), async move {
+        let url = webpage::parse_url(&url)?;
+        info!("[INFO] [web] Resolving title: {}", url);
+
+        webpage::title(&url).await
+    }).await
+}
+
+/// Preview of an external link (title, description, image, icon)
+/// Served from the database cache within `PREVIEW_TTL_SECS`. If the page
+/// can't be fetched, an expired preview is returned marked `stale`, or None
+/// if it was never cached.
+#[tauri::command]
+pub async fn get_link_preview(url: String, db: State<'_, DbState>) -> Result<Option<LinkPreview>, String> {
+    metrics::measure("get_link_preview", url.len(), async move {
+        let url = webpage::parse_url(&url)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let cached = db.0.link_preview(url.as_str())?;
+        if let Some(preview) = &cached {
+            if now.saturating_sub(preview.fetched_at) < webpage::PREVIEW_TTL_SECS {
+                return Ok(cached);
+            }
+        }
+
+        info!("[INFO] [web] Fetching link preview: {}", url);
+        match webpage::preview(&url, now).await {
+            Ok(preview) => {
+                db.0.save_link_preview(&preview)?;
+                Ok(Some(preview))
+            }
+            Err(e) => {
+                warn!("[WARN] [web] {}; using cached preview", e);
+                Ok(cached.map(|preview| LinkPreview { stale: true, ..preview }))
+            }
+        }
+    }).await
+}
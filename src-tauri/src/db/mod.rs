@@ -1,16 +1,37 @@
 //! Database module for SQLite operations
 
-use rusqlite::{Connection, Result as SqliteResult, params};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager};
-use tracing::{info, error};
+use std::time::{SystemTime, UNIX_EPOCH};
+use unicode_normalization::UnicodeNormalization;
+use tracing::error;
 
-use crate::models::{ContentIndexEntry, SearchResult};
+use crate::models::{AutocompleteItem, ContentIndexEntry, DailyWords, DbStats, LinkPreview, PinnedNote, QueryRow, ScheduledTaskRun, SearchRanking, SearchResult, TableStats, WrappedVaultKey};
+use crate::query::{Condition, Query, SortKey};
+use crate::utils;
+use crate::vault;
+
+/// Settings key holding the search ranking
+const RANKING_SETTING: &str = "search_ranking";
+
+/// Matches fetched per requested result when boosts may reorder them
+const RANKING_CANDIDATE_FACTOR: usize = 3;
+
+/// Restricts `Database::search_in` to part of the vault
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+    /// Notes under a folder (normalized absolute path)
+    Folder(String),
+    /// Notes with these IDs
+    Notes(Vec<String>),
+}
 
 /// Database connection wrapper
 pub struct Database {
     conn: Mutex<Connection>,
+    ranking: Mutex<SearchRanking>,
 }
 
 impl Database {
@@ -21,6 +42,7 @@ impl Database {
 
         Ok(Self {
             conn: Mutex::new(conn),
+            ranking: Mutex::new(SearchRanking::default()),
         })
     }
 
@@ -28,6 +50,7 @@ impl Database {
     pub fn from_connection(conn: Connection) -> Self {
         Self {
             conn: Mutex::new(conn),
+            ranking: Mutex::new(SearchRanking::default()),
         }
     }
 
@@ -41,61 +64,381 @@ impl Database {
         f(&conn).map_err(|e| format!("Database error: {}", e))
     }
 
+    /// Flush the write-ahead log into the database file (no-op outside WAL mode)
+    pub fn checkpoint(&self) -> Result<(), String> {
+        self.execute(|conn| conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())))
+    }
+
+    /// Table row counts and file size, for diagnostics
+    pub fn stats(&self) -> Result<DbStats, String> {
+        self.execute(|conn| {
+            let names: Vec<String> = conn
+                .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")?
+                .query_map([], |row| row.get(0))?
+                .collect::<SqliteResult<_>>()?;
+
+            let mut tables = Vec::with_capacity(names.len());
+            for name in names {
+                let rows: u64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")),
+                    [],
+                    |row| row.get(0),
+                )?;
+                tables.push(TableStats { name, rows });
+            }
+
+            let page_count: u64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+            let page_size: u64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+
+            Ok(DbStats {
+                schema_version: conn.query_row("PRAGMA user_version", [], |row| row.get(0))?,
+                sqlite_version: rusqlite::version().to_string(),
+                size_bytes: page_count * page_size,
+                tables,
+            })
+        })
+    }
+
     /// Index a content entry
     pub fn index_content(&self, entry: &ContentIndexEntry) -> Result<(), String> {
-        self.execute(|conn| {
-            // Insert or replace content
-            conn.execute(
-                "INSERT OR REPLACE INTO content (id, path, title, type, body, modified_at, indexed_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![
-                    entry.id,
-                    entry.path,
-                    entry.title,
-                    entry.content_type,
-                    entry.body,
-                    entry.modified_at,
-                    entry.indexed_at,
-                ],
-            )?;
-
-            // Update FTS index
-            conn.execute(
-                "INSERT OR REPLACE INTO content_fts (rowid, title, body)
-                 SELECT rowid, title, body FROM content WHERE id = ?1",
-                params![entry.id],
-            )?;
+        self.execute(|conn| write_content_entry(conn, entry))
+    }
 
-            Ok(())
+    /// Index many entries in a single transaction (one fsync per batch)
+    /// With `checkpoint`, also records each path as processed by the running rebuild
+    pub fn index_content_batch(&self, entries: &[ContentIndexEntry], checkpoint: bool) -> Result<(), String> {
+        self.execute(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            for entry in entries {
+                write_content_entry(&tx, entry)?;
+                if checkpoint {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO rebuild_checkpoint (path, modified_at) VALUES (?1, ?2)",
+                        params![entry.path, entry.modified_at],
+                    )?;
+                }
+            }
+            tx.commit()
         })
     }
 
-    /// Search content using FTS5
+    /// Current search ranking settings
+    pub fn ranking(&self) -> SearchRanking {
+        self.ranking.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Replace the search ranking settings (saved for later sessions)
+    pub fn set_ranking(&self, ranking: SearchRanking) -> Result<(), String> {
+        self.set_setting_json(RANKING_SETTING, &ranking)?;
+        if let Ok(mut current) = self.ranking.lock() {
+            *current = ranking;
+        }
+        Ok(())
+    }
+
+    /// Load the search ranking saved by `set_ranking`, if any
+    pub fn load_ranking(&self) -> Result<(), String> {
+        if let Some(ranking) = self.setting_json(RANKING_SETTING)? {
+            if let Ok(mut current) = self.ranking.lock() {
+                *current = ranking;
+            }
+        }
+        Ok(())
+    }
+
+    /// Search content using FTS5, ranked per `SearchRanking`
     pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, String> {
+        self.search_in(query, limit, None)
+    }
+
+    /// `search`, limited to notes matching `filter`
+    pub fn search_in(&self, query: &str, limit: usize, filter: Option<&SearchFilter>) -> Result<Vec<SearchResult>, String> {
+        let ranking = self.ranking();
+        let boosted = ranking.tag_boost > 0.0 || ranking.recency_boost > 0.0 || ranking.link_boost > 0.0;
+        let candidates = if boosted { limit * RANKING_CANDIDATE_FACTOR } else { limit };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        // ?5 is a LIKE pattern or a JSON array of IDs, depending on the filter
+        let (filter_sql, filter_param) = match filter {
+            None => ("", String::new()),
+            Some(SearchFilter::Folder(folder)) => (
+                "AND c.path LIKE ?5 ESCAPE '\\'",
+                format!("{}/%", escape_like(folder.trim_end_matches('/'))),
+            ),
+            Some(SearchFilter::Notes(ids)) => (
+                "AND c.id IN (SELECT value FROM json_each(?5))",
+                serde_json::to_string(ids).map_err(|e| e.to_string())?,
+            ),
+        };
+
         self.execute(|conn| {
-            let mut stmt = conn.prepare(
+            let mut stmt = conn.prepare(&format!(
                 "SELECT c.id, c.title, c.path, c.type,
-                        bm25(content_fts) as score,
-                        snippet(content_fts, 1, '<mark>', '</mark>', '...', 32) as snippet
+                        bm25(content_fts, ?3, ?4) as score,
+                        snippet(content_fts, 1, '<mark>', '</mark>', '...', 32) as snippet,
+                        c.truncated, c.modified_at,
+                        (SELECT group_concat(lower(tag), char(31)) FROM tags WHERE content_id = c.id)
                  FROM content_fts
                  JOIN content c ON content_fts.rowid = c.rowid
-                 WHERE content_fts MATCH ?1
-                 ORDER BY bm25(content_fts)
-                 LIMIT ?2"
+                 WHERE content_fts MATCH ?1 {}
+                 ORDER BY score
+                 LIMIT ?2",
+                filter_sql
+            ))?;
+
+            let candidates = candidates as i64;
+            let mut args = params![query, candidates, ranking.title_weight, ranking.body_weight].to_vec();
+            if filter.is_some() {
+                args.push(&filter_param);
+            }
+            let rows = stmt.query_map(
+                args.as_slice(),
+                |row| {
+                    let result = SearchResult {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        path: row.get(2)?,
+                        content_type: row.get(3)?,
+                        score: row.get::<_, f64>(4)?.abs(), // bm25 returns negative scores
+                        snippet: row.get(5)?,
+                        truncated: row.get(6)?,
+                    };
+                    let modified_at: Option<u64> = row.get(7)?;
+                    let tags: Option<String> = row.get(8)?;
+                    Ok((result, modified_at.unwrap_or(0), tags.unwrap_or_default()))
+                },
+            )?;
+            let rows = rows.collect::<Result<Vec<_>, _>>()?;
+            if !boosted {
+                return Ok(rows.into_iter().map(|(result, _, _)| result).collect());
+            }
+
+            let terms = query_terms(query);
+            let inbound = if ranking.link_boost > 0.0 { inbound_link_counts(conn)? } else { HashMap::new() };
+            let half_life = ranking.recency_half_life_days.max(0.1) * 24.0 * 60.0 * 60.0;
+
+            let mut results: Vec<SearchResult> = rows
+                .into_iter()
+                .map(|(mut result, modified_at, tags)| {
+                    let tag_hits = tags.split('\u{1f}').filter(|tag| terms.contains(*tag)).count() as f64;
+                    let age = now.saturating_sub(modified_at) as f64;
+                    let links: u32 = utils::note_link_keys(&vault::relative_path(&result.path), vault::exists)
+                        .iter()
+                        .filter_map(|key| inbound.get(key))
+                        .sum();
+
+                    result.score *= 1.0
+                        + ranking.tag_boost * tag_hits
+                        + ranking.recency_boost * 0.5f64.powf(age / half_life)
+                        + ranking.link_boost * (1.0 + links as f64).ln();
+                    result
+                })
+                .collect();
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+
+    /// Notes matching a query block, with the values of its `fields`
+    /// `folder` is a normalized absolute path and `fts` an FTS5 expression;
+    /// they stand in for the query's own `folder` and `search`.
+    pub fn query_notes(&self, query: &Query, folder: Option<&str>, fts: Option<&str>) -> Result<Vec<QueryRow>, String> {
+        let mut sql = String::from(
+            "SELECT c.id, c.path, COALESCE(c.title, ''), c.modified_at, c.created_at FROM content c WHERE c.type = ?",
+        );
+        let mut args = vec![query.content_type.clone()];
+
+        if let Some(folder) = folder {
+            sql.push_str(" AND c.path LIKE ? ESCAPE '\\'");
+            args.push(format!("{}/%", escape_like(folder.trim_end_matches('/'))));
+        }
+        for tag in &query.tags {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM tags t WHERE t.content_id = c.id
+                   AND (lower(t.tag) = ? OR lower(t.tag) LIKE ? ESCAPE '\\'))",
+            );
+            args.push(tag.clone());
+            args.push(format!("{}/%", escape_like(tag)));
+        }
+        for condition in &query.conditions {
+            let (negate, key, value) = match condition {
+                Condition::Has(key) => (false, key, None),
+                Condition::Missing(key) => (true, key, None),
+                Condition::Equals(key, value) => (false, key, Some(value)),
+                Condition::NotEquals(key, value) => (true, key, Some(value)),
+            };
+            sql.push_str(if negate { " AND NOT EXISTS" } else { " AND EXISTS" });
+            sql.push_str(" (SELECT 1 FROM note_properties p WHERE p.note_id = c.id AND p.key = ?");
+            args.push(key.clone());
+            if let Some(value) = value {
+                sql.push_str(" AND p.value = ? COLLATE NOCASE");
+                args.push(value.clone());
+            }
+            sql.push(')');
+        }
+        if let Some(fts) = fts {
+            sql.push_str(" AND c.rowid IN (SELECT rowid FROM content_fts WHERE content_fts MATCH ?)");
+            args.push(fts.to_string());
+        }
+
+        let order = if query.descending { "DESC" } else { "ASC" };
+        let sort = match &query.sort {
+            SortKey::Modified => "c.modified_at".to_string(),
+            SortKey::Created => "c.created_at".to_string(),
+            SortKey::Title => "c.title COLLATE NOCASE".to_string(),
+            SortKey::Path => "c.path".to_string(),
+            SortKey::Property(key) => {
+                // Bound once per use of `sort` below
+                args.extend([key.clone(), key.clone()]);
+                "(SELECT MIN(value) FROM note_properties WHERE note_id = c.id AND key = ?)".to_string()
+            }
+        };
+        // Notes without the sort value go last either way
+        sql.push_str(&format!(" ORDER BY {sort} IS NULL, {sort} {order}, c.path LIMIT {}", query.limit));
+
+        let fields = serde_json::to_string(&query.fields).map_err(|e| e.to_string())?;
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(&args), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    QueryRow {
+                        path: row.get(1)?,
+                        title: row.get(2)?,
+                        modified_at: row.get(3)?,
+                        created_at: row.get(4)?,
+                        values: HashMap::new(),
+                    },
+                ))
+            })?;
+            let mut rows = rows.collect::<SqliteResult<Vec<_>>>()?;
+            if query.fields.is_empty() {
+                return Ok(rows.into_iter().map(|(_, row)| row).collect());
+            }
+
+            let mut stmt = conn.prepare(
+                "SELECT key, value FROM note_properties
+                 WHERE note_id = ?1 AND key IN (SELECT value FROM json_each(?2)) ORDER BY rowid",
+            )?;
+            for (id, row) in &mut rows {
+                let values = stmt.query_map(params![&*id, fields], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))?;
+                for value in values {
+                    let (key, value) = value?;
+                    row.values.entry(key).or_default().push(value);
+                }
+            }
+            Ok(rows.into_iter().map(|(_, row)| row).collect())
+        })
+    }
+
+    /// Paths of notes tagged `tag` or a tag nested under it (case-insensitive)
+    pub fn paths_with_tag(&self, tag: &str) -> Result<Vec<String>, String> {
+        let tag = tag.to_lowercase();
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT c.path FROM content c JOIN tags t ON t.content_id = c.id
+                 WHERE lower(t.tag) = ?1 OR lower(t.tag) LIKE ?2 ESCAPE '\\'",
             )?;
+            let paths = stmt.query_map(params![tag, format!("{}/%", escape_like(&tag))], |row| row.get(0))?;
+            paths.collect()
+        })
+    }
 
-            let results = stmt.query_map(params![query, limit as i64], |row| {
-                Ok(SearchResult {
+    /// Notes whose title or an alias contains `text` (case-insensitive)
+    /// Ranking is up to the caller.
+    pub fn match_titles(&self, text: &str, limit: usize) -> Result<Vec<TitleMatch>, String> {
+        let pattern = format!("%{}%", escape_like(text));
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, path, title, NULL FROM content WHERE title LIKE ?1 ESCAPE '\\'
+                 UNION ALL
+                 SELECT c.id, c.path, c.title, a.alias FROM note_aliases a
+                 JOIN content c ON c.id = a.note_id
+                 WHERE a.alias LIKE ?1 ESCAPE '\\'
+                 LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![pattern, limit as i64], |row| {
+                Ok(TitleMatch {
                     id: row.get(0)?,
-                    title: row.get(1)?,
-                    path: row.get(2)?,
-                    content_type: row.get(3)?,
-                    score: row.get::<_, f64>(4)?.abs(), // bm25 returns negative scores
-                    snippet: row.get(5)?,
+                    path: row.get(1)?,
+                    title: row.get(2)?,
+                    alias: row.get(3)?,
                 })
             })?;
+            rows.collect()
+        })
+    }
+
+    /// Tags starting with `prefix` (case-insensitive) with the number of notes using them
+    pub fn autocomplete_tags(&self, prefix: &str, limit: usize) -> Result<Vec<AutocompleteItem>, String> {
+        let pattern = format!("{}%", escape_like(prefix.trim_start_matches('#')));
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT MIN(tag), COUNT(DISTINCT content_id), MIN(content_id)
+                 FROM tags WHERE tag LIKE ?1 ESCAPE '\\'
+                 GROUP BY lower(tag)
+                 ORDER BY COUNT(DISTINCT content_id) DESC, lower(tag)
+                 LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![pattern, limit as i64], usage_row)?;
+            rows.collect()
+        })
+    }
+
+    /// Values of property `key` starting with `prefix` (case-insensitive)
+    /// with the number of notes using them
+    pub fn autocomplete_property_values(&self, key: &str, prefix: &str, limit: usize) -> Result<Vec<AutocompleteItem>, String> {
+        let pattern = format!("{}%", escape_like(prefix));
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT MIN(value), COUNT(DISTINCT note_id), MIN(note_id)
+                 FROM note_properties WHERE key = ?1 AND value LIKE ?2 ESCAPE '\\'
+                 GROUP BY lower(value)
+                 ORDER BY COUNT(DISTINCT note_id) DESC, lower(value)
+                 LIMIT ?3",
+            )?;
+            let rows = stmt.query_map(params![key, pattern, limit as i64], usage_row)?;
+            rows.collect()
+        })
+    }
+
+    /// Remove the index entry at `path`, under either Unicode form (false if
+    /// there was none)
+    pub fn remove_content(&self, path: &str) -> Result<bool, String> {
+        self.execute(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let mut removed = 0;
+            for form in path_forms(path) {
+                removed += delete_content(&tx, &form)?;
+            }
+            tx.commit()?;
+            Ok(removed > 0)
+        })
+    }
+
+    /// Remove index entries that duplicate another under a different Unicode
+    /// form of the same path (indexed before note IDs were derived from NFC
+    /// paths), keeping the most recently indexed. Returns how many went.
+    pub fn merge_unicode_duplicates(&self) -> Result<usize, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT path FROM content ORDER BY indexed_at DESC, rowid DESC")?;
+            let paths = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<SqliteResult<Vec<_>>>()?;
+            let mut seen = HashSet::new();
+            let duplicates: Vec<String> = paths.into_iter().filter(|path| !seen.insert(utils::path_key(path))).collect();
+            if duplicates.is_empty() {
+                return Ok(0);
+            }
 
-            results.collect::<Result<Vec<_>, _>>()
+            let tx = conn.unchecked_transaction()?;
+            for path in &duplicates {
+                delete_content(&tx, path)?;
+            }
+            tx.commit()?;
+            Ok(duplicates.len())
         })
     }
 
@@ -105,129 +448,682 @@ impl Database {
             conn.execute("DELETE FROM content_fts", [])?;
             conn.execute("DELETE FROM tags", [])?;
             conn.execute("DELETE FROM links", [])?;
+            conn.execute("DELETE FROM note_aliases", [])?;
+            conn.execute("DELETE FROM note_properties", [])?;
             conn.execute("DELETE FROM content", [])?;
             Ok(())
         })
     }
 
-    /// Get all indexed content IDs
-    pub fn get_indexed_ids(&self) -> Result<Vec<String>, String> {
+    /// Record the start of a fresh rebuild, discarding any previous checkpoint
+    pub fn begin_rebuild(&self, home_path: &str, started_at: u64) -> Result<(), String> {
         self.execute(|conn| {
-            let mut stmt = conn.prepare("SELECT id FROM content")?;
-            let ids = stmt.query_map([], |row| row.get(0))?;
-            ids.collect::<Result<Vec<_>, _>>()
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM rebuild_checkpoint", [])?;
+            tx.execute(
+                "INSERT OR REPLACE INTO rebuild_state (id, home_path, started_at) VALUES (1, ?1, ?2)",
+                params![home_path, started_at],
+            )?;
+            tx.commit()
         })
     }
-}
 
-/// Global database state
-pub struct DbState(pub Arc<Database>);
+    /// Home path of an interrupted rebuild, if any
+    pub fn pending_rebuild(&self) -> Result<Option<String>, String> {
+        self.execute(|conn| {
+            conn.query_row("SELECT home_path FROM rebuild_state WHERE id = 1", [], |row| row.get(0))
+                .optional()
+        })
+    }
+
+    /// Paths (with mtimes) already processed by an interrupted rebuild of `home_path`
+    pub fn rebuild_checkpoint(&self, home_path: &str) -> Result<Option<HashMap<String, u64>>, String> {
+        if self.pending_rebuild()?.as_deref() != Some(home_path) {
+            return Ok(None);
+        }
+
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT path, modified_at FROM rebuild_checkpoint")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<HashMap<_, _>, _>>().map(Some)
+        })
+    }
+
+    /// Mark the running rebuild as complete
+    pub fn finish_rebuild(&self) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute("DELETE FROM rebuild_checkpoint", [])?;
+            conn.execute("DELETE FROM rebuild_state", [])?;
+            Ok(())
+        })
+    }
+
+    /// Pin a note (vault-relative path) at `position`, or at the end
+    /// Re-pinning an already pinned note moves it.
+    pub fn pin_note(&self, path: &str, position: Option<u32>, pinned_at: u64) -> Result<(), String> {
+        self.execute(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM pinned_notes WHERE path = ?1", params![path])?;
+
+            let position = match position {
+                Some(position) => {
+                    tx.execute(
+                        "UPDATE pinned_notes SET position = position + 1 WHERE position >= ?1",
+                        params![position],
+                    )?;
+                    position
+                }
+                None => tx.query_row(
+                    "SELECT COALESCE(MAX(position) + 1, 0) FROM pinned_notes",
+                    [],
+                    |row| row.get(0),
+                )?,
+            };
+
+            tx.execute(
+                "INSERT INTO pinned_notes (path, position, pinned_at) VALUES (?1, ?2, ?3)",
+                params![path, position, pinned_at],
+            )?;
+            tx.commit()
+        })
+    }
+
+    /// Unpin a note; returns false if it wasn't pinned
+    pub fn unpin_note(&self, path: &str) -> Result<bool, String> {
+        self.execute(|conn| {
+            conn.execute("DELETE FROM pinned_notes WHERE path = ?1", params![path])
+                .map(|removed| removed > 0)
+        })
+    }
+
+    /// Pinned notes in display order
+    pub fn list_pinned(&self) -> Result<Vec<PinnedNote>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT path, position, pinned_at FROM pinned_notes ORDER BY position, pinned_at",
+            )?;
+            let pins = stmt.query_map([], |row| {
+                Ok(PinnedNote {
+                    path: row.get(0)?,
+                    position: row.get(1)?,
+                    pinned_at: row.get(2)?,
+                })
+            })?;
+            pins.collect()
+        })
+    }
+
+    /// Replace a folder's manual child ordering (empty `names` clears it)
+    pub fn set_folder_order(&self, folder: &str, names: &[String]) -> Result<(), String> {
+        self.execute(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM folder_order WHERE folder = ?1", params![folder])?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR IGNORE INTO folder_order (folder, name, position) VALUES (?1, ?2, ?3)",
+                )?;
+                for (position, name) in names.iter().enumerate() {
+                    stmt.execute(params![folder, name, position as i64])?;
+                }
+            }
+            tx.commit()
+        })
+    }
 
-/// Initialize the SQLite database
-pub async fn init_database(app: &AppHandle) -> Result<Arc<Database>, String> {
-    info!("[INFO] [db] Initializing database");
+    /// Manual orderings of all folders: folder -> (child name -> position)
+    pub fn folder_orders(&self) -> Result<HashMap<String, HashMap<String, usize>>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT folder, name, position FROM folder_order")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })?;
 
-    // Get app data directory
-    let app_data_dir = app.path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            let mut orders: HashMap<String, HashMap<String, usize>> = HashMap::new();
+            for row in rows {
+                let (folder, name, position) = row?;
+                orders.entry(folder).or_default().insert(name, position as usize);
+            }
+            Ok(orders)
+        })
+    }
 
-    // Create directory if it doesn't exist
-    std::fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    /// Add a save's word delta to a day's writing totals
+    pub fn record_writing(&self, day: &str, words_added: u64, words_removed: u64) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT INTO writing_daily (day, words_added, words_removed) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(day) DO UPDATE SET
+                    words_added = words_added + excluded.words_added,
+                    words_removed = words_removed + excluded.words_removed",
+                params![day, words_added, words_removed],
+            )?;
+            Ok(())
+        })
+    }
 
-    let db_path = app_data_dir.join("unstablon.db");
-    info!("[INFO] [db] Database path: {:?}", db_path);
+    /// Daily writing totals between two days (inclusive), oldest first
+    pub fn writing_daily(&self, from: &str, to: &str) -> Result<Vec<DailyWords>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT day, words_added, words_removed FROM writing_daily
+                 WHERE day >= ?1 AND day <= ?2 ORDER BY day",
+            )?;
+            let days = stmt.query_map(params![from, to], |row| {
+                Ok(DailyWords {
+                    day: row.get(0)?,
+                    words_added: row.get(1)?,
+                    words_removed: row.get(2)?,
+                })
+            })?;
+            days.collect()
+        })
+    }
 
-    // Open connection
-    let conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
+    /// Log a note open (vault-relative path), pruning opens before `prune_before`
+    pub fn record_open(&self, path: &str, opened_at: u64, prune_before: u64) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT INTO note_opens (path, opened_at) VALUES (?1, ?2)",
+                params![path, opened_at],
+            )?;
+            conn.execute("DELETE FROM note_opens WHERE opened_at < ?1", params![prune_before])?;
+            Ok(())
+        })
+    }
 
-    // Create schema
-    create_schema(&conn)
-        .map_err(|e| format!("Failed to create schema: {}", e))?;
+    /// Opens since `since` (unix seconds) as (vault-relative path, time)
+    pub fn opens_since(&self, since: u64) -> Result<Vec<(String, u64)>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT path, opened_at FROM note_opens WHERE opened_at >= ?1")?;
+            let rows = stmt.query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        })
+    }
 
-    // Create database wrapper
-    let db = Arc::new(Database {
-        conn: Mutex::new(conn),
-    });
+    /// Times of note opens in `[from, to)` (unix seconds)
+    pub fn open_times(&self, from: u64, to: u64) -> Result<Vec<u64>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT opened_at FROM note_opens WHERE opened_at >= ?1 AND opened_at < ?2")?;
+            let rows = stmt.query_map(params![from, to], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
 
-    info!("[INFO] [db] Database initialized successfully");
-    Ok(db)
+    /// Forget all recorded opens; returns how many were removed
+    pub fn clear_opens(&self) -> Result<usize, String> {
+        self.execute(|conn| conn.execute("DELETE FROM note_opens", []))
+    }
+
+    /// Link keys of a note's outgoing links
+    pub fn outgoing_link_keys(&self, note_id: &str) -> Result<Vec<String>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT DISTINCT target_path FROM links WHERE source_id = ?1")?;
+            let rows = stmt.query_map(params![note_id], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    /// IDs of notes with a link to any of `keys`
+    pub fn linking_note_ids(&self, keys: &[String]) -> Result<Vec<String>, String> {
+        let keys = serde_json::to_string(keys).map_err(|e| e.to_string())?;
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT source_id FROM links WHERE target_path IN (SELECT value FROM json_each(?1))",
+            )?;
+            let rows = stmt.query_map(params![keys], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    /// (path, title, modified_at) of notes with an `@Name` mention indexed
+    /// under any of `keys`, most recently modified first
+    pub fn mentioning_notes(&self, keys: &[String]) -> Result<Vec<(String, String, u64)>, String> {
+        let keys = serde_json::to_string(keys).map_err(|e| e.to_string())?;
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT c.path, COALESCE(c.title, ''), COALESCE(c.modified_at, 0)
+                 FROM links l JOIN content c ON c.id = l.source_id
+                 WHERE l.link_type = 'mention' AND l.target_path IN (SELECT value FROM json_each(?1))
+                 ORDER BY c.modified_at DESC, c.path",
+            )?;
+            let rows = stmt.query_map(params![keys], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect()
+        })
+    }
+
+    /// (id, path) of every indexed file
+    pub fn indexed_paths(&self) -> Result<Vec<(String, String)>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT id, path FROM content")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        })
+    }
+
+    /// ID and title of the indexed note at a (normalized) path
+    pub fn note_by_path(&self, path: &str) -> Result<Option<(String, String)>, String> {
+        self.execute(|conn| {
+            conn.query_row(
+                "SELECT id, COALESCE(title, '') FROM content WHERE path = ?1",
+                params![path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+        })
+    }
+
+    /// Cached preview of an external link (regardless of age)
+    pub fn link_preview(&self, url: &str) -> Result<Option<LinkPreview>, String> {
+        self.execute(|conn| {
+            conn.query_row(
+                "SELECT url, title, description, image, favicon, fetched_at FROM link_previews WHERE url = ?1",
+                params![url],
+                |row| {
+                    Ok(LinkPreview {
+                        url: row.get(0)?,
+                        title: row.get(1)?,
+                        description: row.get(2)?,
+                        image: row.get(3)?,
+                        favicon: row.get(4)?,
+                        fetched_at: row.get(5)?,
+                        stale: false,
+                    })
+                },
+            )
+            .optional()
+        })
+    }
+
+    /// Cache a link preview, replacing an older one
+    pub fn save_link_preview(&self, preview: &LinkPreview) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO link_previews (url, title, description, image, favicon, fetched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![preview.url, preview.title, preview.description, preview.image, preview.favicon, preview.fetched_at],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Wrapped key of the vault at `home_path`, if a passphrase is set
+    pub fn vault_key(&self, home_path: &str) -> Result<Option<WrappedVaultKey>, String> {
+        self.execute(|conn| {
+            conn.query_row(
+                "SELECT salt, nonce, wrapped_key, m_cost, t_cost, p_cost FROM vault_keys WHERE home_path = ?1",
+                params![home_path],
+                |row| {
+                    Ok(WrappedVaultKey {
+                        salt: row.get(0)?,
+                        nonce: row.get(1)?,
+                        wrapped_key: row.get(2)?,
+                        m_cost: row.get(3)?,
+                        t_cost: row.get(4)?,
+                        p_cost: row.get(5)?,
+                    })
+                },
+            )
+            .optional()
+        })
+    }
+
+    /// Store the wrapped key of the vault at `home_path`, replacing any other
+    pub fn save_vault_key(&self, home_path: &str, key: &WrappedVaultKey, updated_at: u64) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO vault_keys (home_path, salt, nonce, wrapped_key, m_cost, t_cost, p_cost, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![home_path, key.salt, key.nonce, key.wrapped_key, key.m_cost, key.t_cost, key.p_cost, updated_at],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Last recorded run of each scheduled task
+    pub fn scheduled_task_runs(&self) -> Result<Vec<ScheduledTaskRun>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT task_id, ran_at, ok, message FROM scheduled_task_runs")?;
+            let rows = stmt.query_map([], |row| {
+                Ok(ScheduledTaskRun {
+                    task_id: row.get(0)?,
+                    ran_at: row.get(1)?,
+                    ok: row.get(2)?,
+                    message: row.get(3)?,
+                })
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Record a scheduled task run, replacing its previous one
+    pub fn save_scheduled_task_run(&self, run: &ScheduledTaskRun) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO scheduled_task_runs (task_id, ran_at, ok, message) VALUES (?1, ?2, ?3, ?4)",
+                params![run.task_id, run.ran_at, run.ok, run.message],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Stored value of an app setting
+    pub fn setting(&self, key: &str) -> Result<Option<String>, String> {
+        self.execute(|conn| {
+            conn.query_row("SELECT value FROM app_settings WHERE key = ?1", params![key], |row| row.get(0))
+                .optional()
+        })
+    }
+
+    /// Store an app setting, replacing its previous value
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Stored app setting decoded from JSON
+    /// A value that no longer decodes (e.g. after a type change) reads as None.
+    pub fn setting_json<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        Ok(self.setting(key)?.and_then(|value| match serde_json::from_str(&value) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                error!("[ERROR] [db] Ignoring unreadable setting {}: {}", key, e);
+                None
+            }
+        }))
+    }
+
+    /// Store an app setting as JSON
+    pub fn set_setting_json<T: serde::Serialize>(&self, key: &str, value: &T) -> Result<(), String> {
+        let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+        self.set_setting(key, &json)
+    }
+
+    /// Let SQLite refresh its query planner statistics and merge FTS segments
+    pub fn optimize(&self) -> Result<(), String> {
+        self.execute(|conn| {
+            conn.execute_batch("PRAGMA optimize; INSERT INTO content_fts(content_fts) VALUES('optimize');")
+        })
+    }
+
+    /// Creation and modification times (unix seconds) falling in `[from, to)`
+    pub fn activity_times(&self, from: u64, to: u64) -> Result<(Vec<u64>, Vec<u64>), String> {
+        self.execute(|conn| {
+            let times = |column: &str| -> rusqlite::Result<Vec<u64>> {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {0} FROM content WHERE {0} >= ?1 AND {0} < ?2",
+                    column
+                ))?;
+                let rows = stmt.query_map(params![from, to], |row| row.get(0))?;
+                rows.collect()
+            };
+            Ok((times("created_at")?, times("modified_at")?))
+        })
+    }
+
+    /// Visit the body of every note modified in `[from, to)` (unix seconds)
+    pub fn for_each_body<F>(&self, from: u64, to: u64, mut f: F) -> Result<(), String>
+    where
+        F: FnMut(&str),
+    {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT body FROM content WHERE modified_at >= ?1 AND modified_at < ?2 AND body IS NOT NULL",
+            )?;
+            let mut rows = stmt.query(params![from, to])?;
+            while let Some(row) = rows.next()? {
+                let body: String = row.get(0)?;
+                f(&body);
+            }
+            Ok(())
+        })
+    }
+
+    /// Visit every indexed entry (with aliases and tags; properties and links are not loaded)
+    pub fn for_each_entry<F>(&self, mut f: F) -> Result<(), String>
+    where
+        F: FnMut(ContentIndexEntry),
+    {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT c.id, c.path, c.title, c.type, c.body, c.modified_at, c.indexed_at, c.truncated,
+                        c.created_at,
+                        (SELECT group_concat(alias, char(31)) FROM note_aliases WHERE note_id = c.id),
+                        (SELECT group_concat(tag, char(31)) FROM tags WHERE content_id = c.id)
+                 FROM content c",
+            )?;
+            let mut rows = stmt.query([])?;
+            let split = |joined: Option<String>| -> Vec<String> {
+                joined
+                    .map(|s| s.split('\u{1f}').map(str::to_string).collect())
+                    .unwrap_or_default()
+            };
+            while let Some(row) = rows.next()? {
+                f(ContentIndexEntry {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    title: row.get(2)?,
+                    content_type: row.get(3)?,
+                    body: row.get(4)?,
+                    modified_at: row.get::<_, Option<u64>>(5)?.unwrap_or(0),
+                    indexed_at: row.get::<_, Option<u64>>(6)?.unwrap_or(0),
+                    created_at: row.get(8)?,
+                    truncated: row.get(7)?,
+                    aliases: split(row.get(9)?),
+                    tags: split(row.get(10)?),
+                    properties: Vec::new(),
+                    links: Vec::new(),
+                });
+            }
+            Ok(())
+        })
+    }
+
+    /// Titles shared by more than one indexed file, with their paths
+    pub fn duplicate_titles(&self) -> Result<Vec<(String, Vec<String>)>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT MIN(title), group_concat(path, char(31)) FROM content
+                 WHERE title IS NOT NULL AND title != ''
+                 GROUP BY title COLLATE NOCASE
+                 HAVING COUNT(*) > 1
+                 ORDER BY MIN(title) COLLATE NOCASE",
+            )?;
+            let groups = stmt.query_map([], |row| {
+                let paths: String = row.get(1)?;
+                Ok((row.get(0)?, paths.split('\u{1f}').map(str::to_string).collect()))
+            })?;
+            groups.collect()
+        })
+    }
+
+    /// (id, path) of the note matching one of `paths`, else the ID, title or
+    /// alias `key` (case-insensitive), in that order of preference
+    pub fn find_note(&self, paths: &[String], key: &str) -> Result<Option<(String, String)>, String> {
+        self.execute(|conn| {
+            let row = |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?));
+            for path in paths {
+                let path = crate::utils::normalize_path(path);
+                if let Some(found) = conn.query_row("SELECT id, path FROM content WHERE path = ?1", params![path], row).optional()? {
+                    return Ok(Some(found));
+                }
+            }
+            for sql in [
+                "SELECT id, path FROM content WHERE id = ?1 ORDER BY path LIMIT 1",
+                "SELECT id, path FROM content WHERE title = ?1 COLLATE NOCASE ORDER BY path LIMIT 1",
+                "SELECT c.id, c.path FROM note_aliases a JOIN content c ON c.id = a.note_id
+                 WHERE a.alias = ?1 COLLATE NOCASE ORDER BY c.path LIMIT 1",
+            ] {
+                if let Some(found) = conn.query_row(sql, params![key], row).optional()? {
+                    return Ok(Some(found));
+                }
+            }
+            Ok(None)
+        })
+    }
+
+    /// Indexed note ID for a (normalized) path
+    pub fn id_for_path(&self, path: &str) -> Result<Option<String>, String> {
+        let [path, nfc, nfd] = path_forms(path);
+        self.execute(|conn| {
+            conn.query_row("SELECT id FROM content WHERE path IN (?1, ?2, ?3)", params![path, nfc, nfd], |row| row.get(0))
+                .optional()
+        })
+    }
+
+    /// Get all indexed content IDs
+    pub fn get_indexed_ids(&self) -> Result<Vec<String>, String> {
+        self.execute(|conn| {
+            let mut stmt = conn.prepare("SELECT id FROM content")?;
+            let ids = stmt.query_map([], |row| row.get(0))?;
+            ids.collect::<Result<Vec<_>, _>>()
+        })
+    }
 }
 
-/// Create database schema
-fn create_schema(conn: &Connection) -> SqliteResult<()> {
-    // Content table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS content (
-            id TEXT PRIMARY KEY,
-            path TEXT NOT NULL UNIQUE,
-            title TEXT,
-            type TEXT NOT NULL,
-            body TEXT,
-            modified_at INTEGER,
-            indexed_at INTEGER
-        )",
-        [],
-    )?;
+/// Lowercase words of an FTS query, without operators and syntax
+fn query_terms(query: &str) -> HashSet<String> {
+    query
+        .split_whitespace()
+        .map(|token| token.trim_matches(|c: char| matches!(c, '(' | ')' | '"' | '*' | '#')).to_lowercase())
+        .filter(|term| !term.is_empty() && !matches!(term.as_str(), "and" | "or" | "not" | "near"))
+        .collect()
+}
 
-    // Links table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS links (
-            id INTEGER PRIMARY KEY,
-            source_id TEXT NOT NULL,
-            target_id TEXT,
-            target_path TEXT NOT NULL,
-            link_type TEXT,
-            FOREIGN KEY (source_id) REFERENCES content(id)
-        )",
-        [],
-    )?;
+/// Number of links per link key (see `utils::link_key`)
+fn inbound_link_counts(conn: &Connection) -> SqliteResult<HashMap<String, u32>> {
+    let mut stmt = conn.prepare("SELECT target_path, COUNT(DISTINCT source_id) FROM links GROUP BY target_path")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
 
-    // Tags table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tags (
-            id INTEGER PRIMARY KEY,
-            content_id TEXT NOT NULL,
-            tag TEXT NOT NULL,
-            FOREIGN KEY (content_id) REFERENCES content(id)
-        )",
-        [],
-    )?;
+/// `path` as given and in NFC and NFD form; a file synced from macOS may
+/// have been indexed under either
+fn path_forms(path: &str) -> [String; 3] {
+    [path.to_string(), utils::nfc(path), path.nfd().collect()]
+}
 
-    // Create indexes
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_content_path ON content(path)",
-        [],
-    )?;
+/// Delete the content row at exactly `path` with its FTS and derived rows
+fn delete_content(conn: &Connection, path: &str) -> SqliteResult<usize> {
+    for (table, column) in [
+        ("note_aliases", "note_id"),
+        ("tags", "content_id"),
+        ("note_properties", "note_id"),
+        ("links", "source_id"),
+    ] {
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE {column} IN (SELECT id FROM content WHERE path = ?1)"),
+            params![path],
+        )?;
+    }
+    conn.execute("DELETE FROM content_fts WHERE rowid IN (SELECT rowid FROM content WHERE path = ?1)", params![path])?;
+    conn.execute("DELETE FROM content WHERE path = ?1", params![path])
+}
 
+/// Insert or replace a content row and its FTS entry
+fn write_content_entry(conn: &Connection, entry: &ContentIndexEntry) -> SqliteResult<()> {
+    // Drop derived rows of this note and of any row it replaces by path
+    for (table, column) in [
+        ("note_aliases", "note_id"),
+        ("tags", "content_id"),
+        ("note_properties", "note_id"),
+        ("links", "source_id"),
+    ] {
+        conn.execute(
+            &format!(
+                "DELETE FROM {table} WHERE {column} = ?1 OR {column} IN (SELECT id FROM content WHERE path = ?2)"
+            ),
+            params![entry.id, entry.path],
+        )?;
+    }
+    // ... and their FTS rows, as REPLACE gives the new row a new rowid
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_id)",
-        [],
+        "DELETE FROM content_fts WHERE rowid IN (SELECT rowid FROM content WHERE id = ?1 OR path = ?2)",
+        params![entry.id, entry.path],
     )?;
 
+    // Insert or replace content
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_path)",
-        [],
+        "INSERT OR REPLACE INTO content (id, path, title, type, body, modified_at, indexed_at, truncated, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            entry.id,
+            entry.path,
+            entry.title,
+            entry.content_type,
+            entry.body,
+            entry.modified_at,
+            entry.indexed_at,
+            entry.truncated,
+            entry.created_at,
+        ],
     )?;
 
+    // Update FTS index
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_tags_content ON tags(content_id)",
-        [],
+        "INSERT OR REPLACE INTO content_fts (rowid, title, body)
+         SELECT rowid, title, body FROM content WHERE id = ?1",
+        params![entry.id],
     )?;
 
-    // Full-text search virtual table
-    conn.execute(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS content_fts USING fts5(
-            title,
-            body,
-            content='content',
-            content_rowid='rowid'
-        )",
-        [],
-    )?;
+    for alias in &entry.aliases {
+        conn.execute(
+            "INSERT OR IGNORE INTO note_aliases (note_id, alias) VALUES (?1, ?2)",
+            params![entry.id, alias],
+        )?;
+    }
+
+    for tag in &entry.tags {
+        conn.execute(
+            "INSERT INTO tags (content_id, tag) VALUES (?1, ?2)",
+            params![entry.id, tag],
+        )?;
+    }
+
+    for (target, link_type) in &entry.links {
+        conn.execute(
+            "INSERT INTO links (source_id, target_path, link_type) VALUES (?1, ?2, ?3)",
+            params![entry.id, target, link_type],
+        )?;
+    }
+
+    for (key, value) in &entry.properties {
+        conn.execute(
+            "INSERT INTO note_properties (note_id, key, value) VALUES (?1, ?2, ?3)",
+            params![entry.id, key, value],
+        )?;
+    }
 
-    info!("[INFO] [db] Schema created successfully");
     Ok(())
 }
+
+/// (value, note count, any note id) row as an autocomplete item
+fn usage_row(row: &rusqlite::Row) -> SqliteResult<AutocompleteItem> {
+    let count: u32 = row.get(1)?;
+    Ok(AutocompleteItem {
+        value: row.get(0)?,
+        count,
+        note_id: if count == 1 { row.get(2)? } else { None },
+    })
+}
+
+/// A note whose title or alias matched `match_titles`
+pub struct TitleMatch {
+    pub id: String,
+    pub path: String,
+    pub title: String,
+    /// The alias that matched (None for a title match)
+    pub alias: Option<String>,
+}
+
+/// Escape LIKE wildcards (with a backslash escape character)
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Global database state
+pub struct DbState(pub Arc<Database>);
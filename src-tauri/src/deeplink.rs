@@ -0,0 +1,110 @@
+//! Deep Links - `unstablon://` URLs opened from other apps
+//!
+//! `unstablon://open?note=<note>&heading=<heading>` resolves `note` through
+//! the index (vault-relative path, note ID, title, then alias) and emits
+//! `navigate` to the frontend. A link that launches the app arrives before
+//! the frontend is listening, so the latest navigation is also kept until
+//! `take_pending_navigation` collects it.
+
+use std::sync::{Mutex, PoisonError};
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tracing::{error, info, warn};
+
+use crate::db::{DbState, Database};
+use crate::models::NavigatePayload;
+use crate::vault;
+
+pub const SCHEME: &str = "unstablon";
+
+/// Navigation not yet collected by the frontend
+static PENDING: Mutex<Option<NavigatePayload>> = Mutex::new(None);
+
+/// A parsed `unstablon://open` link
+#[derive(Debug, PartialEq)]
+pub struct OpenLink {
+    pub note: String,
+    pub heading: Option<String>,
+}
+
+/// Parse an `unstablon://open?note=...` URL
+pub fn parse(url: &Url) -> Result<OpenLink, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+    // `unstablon://open?..` has "open" as host; `unstablon:open?..` as path
+    let action = url.host_str().unwrap_or_else(|| url.path()).trim_matches('/');
+    if action != "open" {
+        return Err(format!("Unsupported deep link action: {}", action));
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    Ok(OpenLink {
+        note: param("note").ok_or("Deep link is missing the note parameter")?,
+        heading: param("heading"),
+    })
+}
+
+/// Find the linked note in the index
+pub fn resolve(db: &Database, link: &OpenLink) -> Result<NavigatePayload, String> {
+    let paths: Vec<String> = [link.note.clone(), format!("{}.md", link.note)]
+        .iter()
+        .filter_map(|candidate| vault::resolve(candidate).ok())
+        .collect();
+
+    let (note_id, path) = db.find_note(&paths, &link.note)?
+        .ok_or_else(|| format!("No note matches {:?}", link.note))?;
+
+    Ok(NavigatePayload {
+        path: vault::relative_path(&path),
+        note_id,
+        heading: link.heading.clone(),
+    })
+}
+
+/// Resolve opened URLs and emit `navigate` for each
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    let db = app.state::<DbState>().0.clone();
+    for url in urls {
+        info!("[INFO] [deeplink] Opening {}", url);
+        let payload = match parse(&url).and_then(|link| resolve(&db, &link)) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("[WARN] [deeplink] Ignoring {}: {}", url, e);
+                continue;
+            }
+        };
+
+        *PENDING.lock().unwrap_or_else(PoisonError::into_inner) = Some(payload.clone());
+        if let Err(e) = app.emit("navigate", payload) {
+            error!("[ERROR] [deeplink] Failed to emit navigate: {}", e);
+        }
+    }
+}
+
+/// Take the navigation from the most recent deep link, if not yet taken
+pub fn take_pending() -> Option<NavigatePayload> {
+    PENDING.lock().unwrap_or_else(PoisonError::into_inner).take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let url = Url::parse("unstablon://open?note=Projects%2FPlan&heading=Next%20steps").unwrap();
+        assert_eq!(
+            parse(&url),
+            Ok(OpenLink { note: "Projects/Plan".to_string(), heading: Some("Next steps".to_string()) })
+        );
+
+        assert!(parse(&Url::parse("unstablon://open?heading=x").unwrap()).is_err());
+        assert!(parse(&Url::parse("unstablon://delete?note=x").unwrap()).is_err());
+        assert!(parse(&Url::parse("https://open?note=x").unwrap()).is_err());
+    }
+}
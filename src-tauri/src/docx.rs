@@ -0,0 +1,502 @@
+//! DOCX Export - Markdown notes as Word documents
+//!
+//! Embeds of other notes are inlined first (see `embeds`), and wikilinks
+//! become their alias or target text. The markdown is then walked with
+//! pulldown-cmark and rebuilt from Word's built-in paragraph styles
+//! (`Heading1`..`Heading6`, `Quote`, ...) so the result restyles with the
+//! reader's template. Local images are embedded, scaled down to the page
+//! width; remote and missing ones are left as their alt text.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use docx_rs::{
+    AbstractNumbering, AlignmentType, BreakType, Docx, Hyperlink, HyperlinkType, IndentLevel, Level, LevelJc,
+    LevelOverride, LevelText, NumberFormat, Numbering, NumberingId, Paragraph, Pic, Run, RunFonts, SpecialIndentType,
+    Start, Style, StyleType, Table, TableCell, TableRow,
+};
+use pulldown_cmark::{Alignment, Event, Options, Parser, Tag};
+
+use crate::attachments;
+use crate::embeds::{self, NoteResolver};
+use crate::error::AppError;
+use crate::frontmatter;
+use crate::utils;
+
+/// Widest an embedded image is shown (pixels at 96 dpi, about the text width)
+const MAX_IMAGE_PX: u32 = 600;
+
+const CODE_FONT: &str = "Consolas";
+const LINK_COLOR: &str = "0563C1";
+
+/// Abstract numbering definitions (id 1 is docx-rs' built-in default)
+const BULLET_ABSTRACT: usize = 2;
+const DECIMAL_ABSTRACT: usize = 3;
+
+/// Numbering instance shared by all bullet lists
+const BULLET_NUMBERING: usize = 2;
+
+/// List levels defined (deeper items use the last)
+const LIST_LEVELS: usize = 9;
+
+/// A rendered document and the images that couldn't be embedded
+pub struct Exported {
+    pub docx: Docx,
+    pub skipped_images: Vec<String>,
+}
+
+/// Render the note at vault-relative `note_path` under `root`
+pub fn export(root: &Path, note_path: &str) -> Result<Exported, AppError> {
+    let content = fs::read_to_string(root.join(note_path))?;
+    let files = attachments::walk_files(root);
+    let resolver = NoteResolver::new(&files);
+
+    let mut stack = vec![note_path.to_string()];
+    let mut unresolved = Vec::new();
+    let body = embeds::expand(root, &resolver, frontmatter::body(&content), &mut stack, &mut unresolved);
+    let markdown = wikilinks_to_markdown(&body, &resolver);
+
+    let mut builder = Builder::new(root, note_path, &resolver);
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(&markdown, options) {
+        builder.event(event);
+    }
+    Ok(builder.finish())
+}
+
+/// Pack a rendered document into .docx bytes
+pub fn pack(docx: Docx) -> Result<Vec<u8>, String> {
+    let mut bytes = Cursor::new(Vec::new());
+    docx.build().pack(&mut bytes).map_err(|e| format!("Failed to write document: {}", e))?;
+    Ok(bytes.into_inner())
+}
+
+/// Rewrite `![[image]]` as a markdown image (vault-rooted) and `[[note|alias]]`
+/// as its alias, or the target without section, outside code fences
+fn wikilinks_to_markdown(body: &str, resolver: &NoteResolver) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find("[[") {
+            let Some(end) = rest[pos + 2..].find("]]") else { break };
+            let embed = pos > 0 && rest.as_bytes()[pos - 1] == b'!';
+            out.push_str(&rest[..if embed { pos - 1 } else { pos }]);
+            let inner = &rest[pos + 2..pos + 2 + end];
+            rest = &rest[pos + 2 + end + 2..];
+
+            let target = embeds::parse(inner).map(|(target, _)| target).unwrap_or(inner);
+            let path = resolver.wikilink(target);
+            if embed && !path.is_some_and(|p| p.ends_with(".md")) {
+                let src = path.map(|p| format!("/{}", p)).unwrap_or_else(|| target.to_string());
+                out.push_str(&format!("![{}](<{}>)", target, src));
+            } else {
+                let text = inner.split_once('|').map(|(_, alias)| alias).unwrap_or(target);
+                out.push_str(text.trim());
+            }
+        }
+        out.push_str(rest);
+    }
+
+    out
+}
+
+struct List {
+    numbering: usize,
+}
+
+struct TableState {
+    alignments: Vec<Alignment>,
+    rows: Vec<TableRow>,
+    cells: Vec<TableCell>,
+    in_head: bool,
+}
+
+/// Builds the document from parser events
+struct Builder<'a> {
+    root: &'a Path,
+    note_path: &'a str,
+    resolver: &'a NoteResolver<'a>,
+    docx: Docx,
+    paragraph: Option<Paragraph>,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    quote_depth: usize,
+    lists: Vec<List>,
+    next_numbering: usize,
+    code_block: Option<String>,
+    table: Option<TableState>,
+    /// Link target and the runs inside it
+    link: Option<(String, Vec<Run>)>,
+    /// Image source and its alt text
+    image: Option<(String, String)>,
+    skipped_images: Vec<String>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(root: &'a Path, note_path: &'a str, resolver: &'a NoteResolver<'a>) -> Self {
+        Self {
+            root,
+            note_path,
+            resolver,
+            docx: document(),
+            paragraph: None,
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            quote_depth: 0,
+            lists: Vec::new(),
+            next_numbering: BULLET_NUMBERING + 1,
+            code_block: None,
+            table: None,
+            link: None,
+            image: None,
+            skipped_images: Vec::new(),
+        }
+    }
+
+    fn finish(mut self) -> Exported {
+        self.flush();
+        Exported { docx: self.docx, skipped_images: self.skipped_images }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some(code) = self.code_block.as_mut() {
+                    code.push_str(&text);
+                } else if let Some((_, alt)) = self.image.as_mut() {
+                    alt.push_str(&text);
+                } else {
+                    let run = self.run(&text);
+                    self.push_run(run);
+                }
+            }
+            Event::Code(text) => {
+                let run = self.run(&text).fonts(RunFonts::new().ascii(CODE_FONT).hi_ansi(CODE_FONT));
+                self.push_run(run);
+            }
+            Event::SoftBreak => self.push_run(self.run(" ")),
+            Event::HardBreak => self.push_run(Run::new().add_break(BreakType::TextWrapping)),
+            Event::Rule => {
+                self.flush();
+                self.docx = std::mem::take(&mut self.docx)
+                    .add_paragraph(Paragraph::new().align(AlignmentType::Center).add_run(Run::new().add_text("* * *")));
+            }
+            Event::TaskListMarker(checked) => self.push_run(Run::new().add_text(if checked { "\u{2612} " } else { "\u{2610} " })),
+            Event::FootnoteReference(label) => self.push_run(self.run(&format!("[^{}]", label))),
+            Event::Html(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                if self.paragraph.is_none() {
+                    self.open(Paragraph::new());
+                }
+            }
+            Tag::Heading(level, _, _) => {
+                let level = level as usize;
+                self.open(Paragraph::new().style(&format!("Heading{}", level)).outline_lvl(level - 1));
+            }
+            Tag::BlockQuote => {
+                self.flush();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.flush();
+                self.code_block = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush();
+                let numbering = match start {
+                    Some(start) => {
+                        let id = self.next_numbering;
+                        self.next_numbering += 1;
+                        let level = self.lists.len().min(LIST_LEVELS - 1);
+                        self.docx = std::mem::take(&mut self.docx).add_numbering(
+                            Numbering::new(id, DECIMAL_ABSTRACT)
+                                .add_override(LevelOverride::new(level).start(start as usize)),
+                        );
+                        id
+                    }
+                    None => BULLET_NUMBERING,
+                };
+                self.lists.push(List { numbering });
+            }
+            Tag::Item => {
+                self.flush();
+                let level = self.lists.len().saturating_sub(1).min(LIST_LEVELS - 1);
+                let numbering = self.lists.last().map_or(BULLET_NUMBERING, |list| list.numbering);
+                self.open(Paragraph::new().numbering(NumberingId::new(numbering), IndentLevel::new(level)));
+            }
+            Tag::Table(alignments) => {
+                self.flush();
+                self.table = Some(TableState { alignments, rows: Vec::new(), cells: Vec::new(), in_head: false });
+            }
+            Tag::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.in_head = true;
+                }
+                self.bold += 1;
+            }
+            Tag::TableRow => {}
+            Tag::TableCell => {
+                let column = self.table.as_ref().map_or(0, |table| table.cells.len());
+                let align = match self.table.as_ref().and_then(|table| table.alignments.get(column)) {
+                    Some(Alignment::Center) => AlignmentType::Center,
+                    Some(Alignment::Right) => AlignmentType::Right,
+                    _ => AlignmentType::Left,
+                };
+                self.paragraph = Some(Paragraph::new().align(align));
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Strikethrough => self.strike += 1,
+            Tag::Link(_, url, _) => self.link = Some((url.to_string(), Vec::new())),
+            Tag::Image(_, url, _) => self.image = Some((url.to_string(), String::new())),
+            Tag::FootnoteDefinition(_) => self.flush(),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::FootnoteDefinition(_) => self.flush(),
+            Tag::BlockQuote => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            Tag::CodeBlock(_) => {
+                let code = self.code_block.take().unwrap_or_default();
+                let mut run = Run::new();
+                for (i, line) in code.trim_end_matches('\n').split('\n').enumerate() {
+                    if i > 0 {
+                        run = run.add_break(BreakType::TextWrapping);
+                    }
+                    run = run.add_text(line);
+                }
+                self.docx = std::mem::take(&mut self.docx).add_paragraph(Paragraph::new().style("Code").add_run(run));
+            }
+            Tag::List(_) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Tag::Table(_) => {
+                if let Some(table) = self.table.take() {
+                    self.docx = std::mem::take(&mut self.docx).add_table(Table::new(table.rows));
+                }
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    let cells = std::mem::take(&mut table.cells);
+                    table.rows.push(TableRow::new(cells));
+                    if table.in_head {
+                        table.in_head = false;
+                        self.bold = self.bold.saturating_sub(1);
+                    }
+                }
+            }
+            Tag::TableCell => {
+                let paragraph = self.paragraph.take().unwrap_or_default();
+                if let Some(table) = self.table.as_mut() {
+                    table.cells.push(TableCell::new().add_paragraph(paragraph));
+                }
+            }
+            Tag::Emphasis => self.italic = self.italic.saturating_sub(1),
+            Tag::Strong => self.bold = self.bold.saturating_sub(1),
+            Tag::Strikethrough => self.strike = self.strike.saturating_sub(1),
+            Tag::Link(..) => {
+                let Some((url, runs)) = self.link.take() else { return };
+                if url.contains("://") || url.starts_with("mailto:") {
+                    let link = runs.into_iter().fold(Hyperlink::new(url, HyperlinkType::External), |link, run| {
+                        link.add_run(run.color(LINK_COLOR).underline("single"))
+                    });
+                    let paragraph = self.paragraph.take().unwrap_or_default();
+                    self.paragraph = Some(paragraph.add_hyperlink(link));
+                } else {
+                    // Links to notes and files only mean something in the vault
+                    for run in runs {
+                        self.push_run(run);
+                    }
+                }
+            }
+            Tag::Image(..) => {
+                let Some((src, alt)) = self.image.take() else { return };
+                match self.picture(&src) {
+                    Some(pic) => self.push_run(Run::new().add_image(pic)),
+                    None => {
+                        self.skipped_images.push(src);
+                        let run = self.run(&format!("[{}]", alt));
+                        self.push_run(run);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start a paragraph, ending any open one
+    fn open(&mut self, paragraph: Paragraph) {
+        self.flush();
+        let paragraph = if self.quote_depth > 0 { paragraph.style("Quote") } else { paragraph };
+        self.paragraph = Some(paragraph);
+    }
+
+    /// Add the open paragraph to the document (table cells keep theirs)
+    fn flush(&mut self) {
+        if self.table.is_some() {
+            return;
+        }
+        if let Some(paragraph) = self.paragraph.take() {
+            self.docx = std::mem::take(&mut self.docx).add_paragraph(paragraph);
+        }
+    }
+
+    /// Text run in the current bold/italic/strikethrough state
+    fn run(&self, text: &str) -> Run {
+        let mut run = Run::new().add_text(text);
+        if self.bold > 0 {
+            run = run.bold();
+        }
+        if self.italic > 0 {
+            run = run.italic();
+        }
+        if self.strike > 0 {
+            run = run.strike();
+        }
+        run
+    }
+
+    fn push_run(&mut self, run: Run) {
+        if let Some((_, runs)) = self.link.as_mut() {
+            runs.push(run);
+            return;
+        }
+        if self.paragraph.is_none() {
+            self.open(Paragraph::new());
+        }
+        self.paragraph = self.paragraph.take().map(|paragraph| paragraph.add_run(run));
+    }
+
+    /// A local image as PNG, scaled to fit `MAX_IMAGE_PX`
+    fn picture(&self, src: &str) -> Option<Pic> {
+        if src.contains("://") {
+            return None;
+        }
+        let path = self.resolver.markdown_link(self.note_path, src)?;
+        let bytes = fs::read(self.root.join(path)).ok()?;
+        let image = image::load_from_memory(&bytes).ok()?;
+        let (width, height) = (image.width().max(1), image.height().max(1));
+
+        let mut png = Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).ok()?;
+        let (shown_width, shown_height) = if width > MAX_IMAGE_PX {
+            (MAX_IMAGE_PX, (height as u64 * MAX_IMAGE_PX as u64 / width as u64).max(1) as u32)
+        } else {
+            (width, height)
+        };
+        Some(Pic::new_with_dimensions(png.into_inner(), shown_width, shown_height))
+    }
+}
+
+/// Empty document with the styles and list numbering the export uses
+fn document() -> Docx {
+    let mut docx = Docx::new();
+    for (level, size) in [(1, 32), (2, 28), (3, 26), (4, 24), (5, 22), (6, 22)] {
+        docx = docx.add_style(
+            Style::new(format!("Heading{}", level), StyleType::Paragraph)
+                .name(format!("heading {}", level))
+                .based_on("Normal")
+                .next("Normal")
+                .size(size)
+                .bold(),
+        );
+    }
+    docx = docx
+        .add_style(
+            Style::new("Code", StyleType::Paragraph)
+                .name("Code")
+                .based_on("Normal")
+                .fonts(RunFonts::new().ascii(CODE_FONT).hi_ansi(CODE_FONT))
+                .size(20),
+        )
+        .add_style(Style::new("Quote", StyleType::Paragraph).name("Quote").based_on("Normal").italic().color("595959"));
+
+    let mut bullets = AbstractNumbering::new(BULLET_ABSTRACT);
+    let mut decimals = AbstractNumbering::new(DECIMAL_ABSTRACT);
+    for level in 0..LIST_LEVELS {
+        let indent = |l: Level| l.indent(Some(720 * (level as i32 + 1)), Some(SpecialIndentType::Hanging(360)), None, None);
+        let bullet = ["\u{2022}", "\u{25e6}", "\u{25aa}"][level % 3];
+        bullets = bullets.add_level(indent(Level::new(
+            level,
+            Start::new(1),
+            NumberFormat::new("bullet"),
+            LevelText::new(bullet),
+            LevelJc::new("left"),
+        )));
+        decimals = decimals.add_level(indent(Level::new(
+            level,
+            Start::new(1),
+            NumberFormat::new("decimal"),
+            LevelText::new(format!("%{}.", level + 1)),
+            LevelJc::new("left"),
+        )));
+    }
+    docx.add_abstract_numbering(bullets)
+        .add_abstract_numbering(decimals)
+        .add_numbering(Numbering::new(BULLET_NUMBERING, BULLET_ABSTRACT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir().join(format!("unstablon-docx-{}", std::process::id()));
+        fs::create_dir_all(dir.join("img")).unwrap();
+        let mut png = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(1200, 300).write_to(&mut png, image::ImageFormat::Png).unwrap();
+        fs::write(dir.join("img/wide.png"), png.into_inner()).unwrap();
+        fs::write(dir.join("Other.md"), "# Other\n").unwrap();
+        fs::write(
+            dir.join("Note.md"),
+            "---\ntags: [a]\n---\n# Title\n\nSee [[Other|the other note]] and **bold**.\n\n\
+             1. one\n2. two\n   - nested\n\n| A | B |\n|---|--:|\n| x | `y` |\n\n\
+             ```rust\nfn main() {}\n```\n\n![[wide.png]]\n![gone](missing.png)\n",
+        )
+        .unwrap();
+
+        let exported = export(&dir, "Note.md").unwrap();
+        assert_eq!(exported.skipped_images, vec!["missing.png"]);
+
+        let bytes = pack(exported.docx).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut xml = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut xml).unwrap();
+        assert!(xml.contains("w:val=\"Heading1\""));
+        assert!(xml.contains("the other note"));
+        assert!(!xml.contains("[["));
+        assert!(xml.contains("w:tbl>"));
+        assert!(xml.contains("fn main() {}"));
+        assert!(xml.contains("<w:numId w:val=\"3\" />"));
+        assert!(xml.contains("<w:drawing>"));
+        assert!(!xml.contains("tags: [a]"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
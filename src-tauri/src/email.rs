@@ -0,0 +1,260 @@
+//! Email - .eml message parsing
+//!
+//! Messages are MIME; each part declares its own transfer encoding and
+//! charset, so they're parsed from bytes. The first text/plain part is the
+//! body, falling back to the first text/html part with its tags stripped.
+//! Every other leaf part counts as an attachment.
+
+use mailparse::{DispositionType, MailHeaderMap, ParsedMail};
+use serde_json::Value as JsonValue;
+
+use crate::frontmatter;
+use crate::models::{EmailAttachment, EmailMessage};
+use crate::utils;
+use crate::webpage;
+
+/// Check if path is an email message
+pub fn is_email_file(path: &str) -> bool {
+    path.ends_with(".eml")
+}
+
+/// Parse a message, returning it with the decoded data of each of its
+/// attachments (in the order of `message.attachments`)
+pub fn parse(raw: &[u8]) -> Result<(EmailMessage, Vec<Vec<u8>>), String> {
+    let mail = mailparse::parse_mail(raw).map_err(|e| format!("Invalid email: {}", e))?;
+    let header = |key: &str| {
+        mail.headers
+            .get_first_value(key)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let date = header("Date").and_then(|date| mailparse::dateparse(&date).ok()).and_then(|secs| {
+        chrono::DateTime::from_timestamp(secs, 0).map(|date| date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    });
+
+    let mut parts = Parts::default();
+    collect_parts(&mail, &mut parts)?;
+    let text = match (parts.text, &parts.html) {
+        (Some(text), _) => text.trim().to_string(),
+        (None, Some(html)) => html_to_text(html),
+        (None, None) => String::new(),
+    };
+
+    let message = EmailMessage {
+        from: header("From"),
+        to: addresses(&mail, "To"),
+        cc: addresses(&mail, "Cc"),
+        subject: header("Subject"),
+        date,
+        text,
+        html: parts.html,
+        attachments: parts.attachments,
+    };
+    Ok((message, parts.data))
+}
+
+#[derive(Default)]
+struct Parts {
+    text: Option<String>,
+    html: Option<String>,
+    attachments: Vec<EmailAttachment>,
+    data: Vec<Vec<u8>>,
+}
+
+fn collect_parts(part: &ParsedMail, parts: &mut Parts) -> Result<(), String> {
+    if !part.subparts.is_empty() {
+        return part.subparts.iter().try_for_each(|sub| collect_parts(sub, parts));
+    }
+
+    let disposition = part.get_content_disposition();
+    let name = disposition
+        .params
+        .get("filename")
+        .or_else(|| part.ctype.params.get("name"))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    let mimetype = part.ctype.mimetype.to_ascii_lowercase();
+    let is_attachment = disposition.disposition == DispositionType::Attachment || name.is_some();
+
+    if !is_attachment && mimetype == "text/plain" && parts.text.is_none() {
+        parts.text = Some(part.get_body().map_err(|e| format!("Invalid email body: {}", e))?);
+    } else if !is_attachment && mimetype == "text/html" && parts.html.is_none() {
+        parts.html = Some(part.get_body().map_err(|e| format!("Invalid email body: {}", e))?);
+    } else {
+        let data = part.get_body_raw().map_err(|e| format!("Invalid email attachment: {}", e))?;
+        parts.attachments.push(EmailAttachment {
+            name: name.unwrap_or_else(|| format!("attachment-{}", parts.attachments.len() + 1)),
+            content_type: mimetype,
+            size: data.len(),
+        });
+        parts.data.push(data);
+    }
+    Ok(())
+}
+
+/// Addresses in an address-list header, as `Name <addr>` or `addr`
+fn addresses(mail: &ParsedMail, key: &str) -> Vec<String> {
+    let Some(header) = mail.headers.get_first_header(key) else {
+        return Vec::new();
+    };
+    let Ok(list) = mailparse::addrparse_header(header) else {
+        // Keep a malformed header as written rather than drop it
+        let value = header.get_value();
+        return vec![value.trim().to_string()].into_iter().filter(|v| !v.is_empty()).collect();
+    };
+
+    let format = |single: &mailparse::SingleInfo| match &single.display_name {
+        Some(name) => format!("{} <{}>", name, single.addr),
+        None => single.addr.clone(),
+    };
+    list.iter()
+        .flat_map(|addr| match addr {
+            mailparse::MailAddr::Single(single) => vec![format(single)],
+            mailparse::MailAddr::Group(group) => group.addrs.iter().map(format).collect(),
+        })
+        .collect()
+}
+
+/// Visible text of an HTML body, one paragraph per block element
+fn html_to_text(html: &str) -> String {
+    const BLOCKS: &[&str] = &["p", "br", "div", "tr", "li", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "table", "hr"];
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[1..close]
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .find(|s| !s.is_empty())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let closing = rest[1..].starts_with('/');
+        rest = &rest[close + 1..];
+
+        if !closing && matches!(tag.as_str(), "head" | "style" | "script" | "title") {
+            // Skip to the closing tag, which the next pass consumes
+            let end = format!("</{}", tag);
+            rest = rest.to_ascii_lowercase().find(&end).map_or("", |at| &rest[at..]);
+        } else if BLOCKS.contains(&tag.as_str()) {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    text.lines().filter_map(webpage::clean_text).collect::<Vec<_>>().join("\n\n")
+}
+
+/// Title from the subject, else the file name
+pub fn title(path: &str, message: &EmailMessage) -> String {
+    message.subject.clone().unwrap_or_else(|| utils::path_to_title(path))
+}
+
+/// Headers, body text and attachment names as searchable text
+pub fn searchable_text(message: &EmailMessage) -> String {
+    let mut lines: Vec<&str> = message.from.iter().map(String::as_str).collect();
+    lines.extend(message.to.iter().chain(&message.cc).map(String::as_str));
+    lines.extend(message.subject.as_deref());
+    lines.push(&message.text);
+    lines.extend(message.attachments.iter().map(|attachment| attachment.name.as_str()));
+    lines.join("\n")
+}
+
+/// Sender, recipients, subject and date as (key, value) index properties
+pub fn properties(message: &EmailMessage) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    pairs.extend(message.from.iter().map(|from| ("from".to_string(), from.clone())));
+    pairs.extend(message.to.iter().map(|to| ("to".to_string(), to.clone())));
+    pairs.extend(message.cc.iter().map(|cc| ("cc".to_string(), cc.clone())));
+    pairs.extend(message.subject.iter().map(|subject| ("subject".to_string(), subject.clone())));
+    pairs.extend(message.date.iter().map(|date| ("date".to_string(), date.clone())));
+    pairs
+}
+
+/// A note for `message`: headers as frontmatter (the same keys as
+/// `properties`), the body under the subject heading, and `attachment_links`
+/// listed at the end
+pub fn to_markdown(message: &EmailMessage, attachment_links: &[String]) -> Result<String, String> {
+    let mut note = format!("# {}\n\n", message.subject.as_deref().unwrap_or("(no subject)"));
+    if !message.text.is_empty() {
+        note.push_str(&message.text);
+        note.push_str("\n\n");
+    }
+    if !attachment_links.is_empty() {
+        note.push_str("## Attachments\n\n");
+        for link in attachment_links {
+            note.push_str(&format!("- {}\n", link));
+        }
+    }
+    let mut note = note.trim_end().to_string();
+    note.push('\n');
+
+    let list = |values: &[String]| JsonValue::Array(values.iter().cloned().map(JsonValue::String).collect());
+    let fields = [
+        ("from", message.from.clone().map(JsonValue::String)),
+        ("to", (!message.to.is_empty()).then(|| list(&message.to))),
+        ("cc", (!message.cc.is_empty()).then(|| list(&message.cc))),
+        ("subject", message.subject.clone().map(JsonValue::String)),
+        ("date", message.date.clone().map(JsonValue::String)),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            note = frontmatter::set_property(&note, key, &value)?;
+        }
+    }
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart() {
+        let raw = concat!(
+            "From: Alice Example <alice@example.com>\r\n",
+            "To: bob@example.com, \"Carol\" <carol@example.com>\r\n",
+            "Subject: =?utf-8?q?Caf=C3=A9_plans?=\r\n",
+            "Date: Mon, 5 Jan 2026 10:00:00 +0100\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b1\"\r\n",
+            "\r\n",
+            "--b1\r\n",
+            "Content-Type: text/html; charset=iso-8859-1\r\n",
+            "Content-Transfer-Encoding: quoted-printable\r\n",
+            "\r\n",
+            "<html><head><style>p { color: red }</style></head>",
+            "<body><p>Caf=E9 at <b>noon</b>?</p><p>Fish &amp; chips</p></body></html>\r\n",
+            "--b1\r\n",
+            "Content-Type: application/pdf; name=\"menu.pdf\"\r\n",
+            "Content-Disposition: attachment; filename=\"menu.pdf\"\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "\r\n",
+            "JVBERi0=\r\n",
+            "--b1--\r\n",
+        );
+        let (message, data) = parse(raw.as_bytes()).unwrap();
+
+        assert_eq!(message.from.as_deref(), Some("Alice Example <alice@example.com>"));
+        assert_eq!(message.to, vec!["bob@example.com", "Carol <carol@example.com>"]);
+        assert_eq!(message.subject.as_deref(), Some("Café plans"));
+        assert_eq!(message.date.as_deref(), Some("2026-01-05T09:00:00Z"));
+        assert_eq!(message.text, "Café at noon?\n\nFish & chips");
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].name, "menu.pdf");
+        assert_eq!(data, vec![b"%PDF-".to_vec()]);
+
+        let note = to_markdown(&message, &["[menu.pdf](attachments/menu.pdf)".to_string()]).unwrap();
+        assert!(note.starts_with("---\nfrom: Alice Example <alice@example.com>\nto:\n- bob@example.com\n"));
+        assert!(note.ends_with("# Café plans\n\nCafé at noon?\n\nFish & chips\n\n## Attachments\n\n- [menu.pdf](attachments/menu.pdf)\n"));
+        assert!(properties(&message).contains(&("subject".to_string(), "Café plans".to_string())));
+    }
+}
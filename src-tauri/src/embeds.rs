@@ -0,0 +1,325 @@
+//! Embeds - `![[note]]` transclusion
+//!
+//! An embed names a note (path or file name, `.md` optional) and optionally
+//! a section: `![[note#Heading]]` takes the heading and everything under it,
+//! `![[note#^block]]` the paragraph or list item tagged `^block`. Embedded
+//! content is expanded recursively up to `MAX_DEPTH`; an embed that would
+//! recurse into a note already being expanded is left as embed syntax.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::attachments;
+use crate::error::AppError;
+use crate::frontmatter;
+use crate::models::EmbedContent;
+use crate::utils;
+
+/// Nested embeds deeper than this are left as embed syntax
+pub const MAX_DEPTH: usize = 4;
+
+/// Part of a note an embed selects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Section {
+    Heading(String),
+    Block(String),
+}
+
+/// Parse `![[target#section|alias]]` (brackets optional) into target and section
+pub fn parse(embed: &str) -> Option<(&str, Option<Section>)> {
+    let inner = embed.trim();
+    let inner = inner.strip_prefix('!').unwrap_or(inner);
+    let inner = inner.strip_prefix("[[").and_then(|i| i.strip_suffix("]]")).unwrap_or(inner);
+    let inner = inner.split('|').next().unwrap_or(inner);
+
+    let (target, section) = match inner.split_once('#') {
+        Some((target, section)) => {
+            let section = section.trim();
+            let section = match section.strip_prefix('^') {
+                Some(block) => Section::Block(block.to_string()),
+                None => Section::Heading(section.to_string()),
+            };
+            (target.trim(), Some(section))
+        }
+        None => (inner.trim(), None),
+    };
+    (!target.is_empty()).then_some((target, section))
+}
+
+/// Resolve `embed` written in the note at vault-relative `source_path`
+/// Nested embeds are expanded; the source note counts as already open, so
+/// a note embedding itself (directly or through others) stops there.
+pub fn resolve(root: &Path, source_path: &str, embed: &str) -> Result<EmbedContent, AppError> {
+    let (target, section) = parse(embed)
+        .ok_or_else(|| AppError::InvalidOperation(format!("Not an embed: {}", embed)))?;
+
+    let files = attachments::walk_files(root);
+    let resolver = NoteResolver::new(&files);
+    let path = resolver
+        .wikilink(target)
+        .filter(|path| path.ends_with(".md"))
+        .ok_or_else(|| AppError::NotFound(format!("No note matches {:?}", target)))?;
+    if path == source_path && section.is_none() {
+        return Err(AppError::InvalidOperation("A note cannot embed itself".to_string()));
+    }
+
+    let content = fs::read_to_string(root.join(path))?;
+    let body = frontmatter::body(&content);
+    let selected = select(body, section.as_ref())
+        .ok_or_else(|| AppError::NotFound(format!("No section {:?} in {}", section, path)))?;
+
+    let mut stack = vec![source_path.to_string(), path.to_string()];
+    let mut unresolved = Vec::new();
+    let content = expand(root, &resolver, selected, &mut stack, &mut unresolved);
+
+    Ok(EmbedContent {
+        path: path.to_string(),
+        content: content.trim().to_string(),
+        unresolved,
+    })
+}
+
+/// The part of `body` an embed selects (all of it without a section)
+pub fn select<'a>(body: &'a str, section: Option<&Section>) -> Option<&'a str> {
+    match section {
+        None => Some(body),
+        Some(Section::Heading(text)) => heading_section(body, text),
+        Some(Section::Block(id)) => block(body, id),
+    }
+}
+
+/// Inline `![[...]]` embeds of markdown notes in `body`
+/// `stack` holds the notes being expanded (innermost last); embeds of those,
+/// or beyond `MAX_DEPTH`, are kept as-is. Embeds whose target or section is
+/// missing are kept and recorded in `unresolved`.
+pub fn expand(root: &Path, resolver: &NoteResolver, body: &str, stack: &mut Vec<String>, unresolved: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find("![[") {
+            let Some(end) = rest[pos + 3..].find("]]") else { break };
+            out.push_str(&rest[..pos]);
+            let embed = &rest[pos..pos + 3 + end + 2];
+            rest = &rest[pos + 3 + end + 2..];
+
+            let Some((target, section)) = parse(embed) else {
+                out.push_str(embed);
+                continue;
+            };
+            let Some(path) = resolver.wikilink(target) else {
+                unresolved.push(embed.to_string());
+                out.push_str(embed);
+                continue;
+            };
+            // Images and other attachments stay embeds
+            if !path.ends_with(".md") || stack.len() > MAX_DEPTH || stack.iter().any(|p| p == path) {
+                out.push_str(embed);
+                continue;
+            }
+
+            let content = fs::read_to_string(root.join(path)).unwrap_or_default();
+            let Some(selected) = select(frontmatter::body(&content), section.as_ref()) else {
+                unresolved.push(embed.to_string());
+                out.push_str(embed);
+                continue;
+            };
+
+            stack.push(path.to_string());
+            out.push_str(expand(root, resolver, selected, stack, unresolved).trim());
+            stack.pop();
+        }
+        out.push_str(rest);
+    }
+
+    out
+}
+
+/// Resolves link targets to vault-relative paths
+pub struct NoteResolver<'a> {
+    paths: HashSet<&'a str>,
+    by_name: HashMap<&'a str, &'a str>,
+    /// Folder notes by folder path and by folder name
+    folder_notes: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> NoteResolver<'a> {
+    /// Resolver over vault-relative `files` (as from `attachments::walk_files`)
+    pub fn new(files: &'a [String]) -> Self {
+        let paths: HashSet<&str> = files.iter().map(String::as_str).collect();
+        let mut by_name = HashMap::new();
+        let mut folder_notes = HashMap::new();
+        // Shallowest, then alphabetical (files are sorted), wins a shared name
+        let mut by_depth: Vec<&String> = files.iter().collect();
+        by_depth.sort_by_key(|f| f.matches('/').count());
+        for file in by_depth {
+            by_name.entry(file.rsplit('/').next().unwrap_or(file)).or_insert(file.as_str());
+            if let Some((folder, _)) = file.rsplit_once('/').filter(|_| utils::is_folder_note(file, |p| paths.contains(p))) {
+                folder_notes.entry(folder).or_insert(file.as_str());
+                folder_notes.entry(folder.rsplit('/').next().unwrap_or(folder)).or_insert(file.as_str());
+            }
+        }
+        Self { paths, by_name, folder_notes }
+    }
+
+    /// Wikilink target: a path or file name, `.md` optional, or a folder
+    /// with a folder note
+    pub fn wikilink(&self, target: &str) -> Option<&'a str> {
+        let target = target.trim().trim_start_matches('/');
+        [target.to_string(), format!("{}.md", target)]
+            .iter()
+            .find_map(|candidate| {
+                self.paths.get(candidate.as_str()).copied().or_else(|| {
+                    if candidate.contains('/') { None } else { self.by_name.get(candidate.as_str()).copied() }
+                })
+            })
+            .or_else(|| self.folder_notes.get(target.trim_end_matches('/')).copied())
+    }
+
+    /// Markdown link target, relative to the note or `/`-rooted
+    pub fn markdown_link(&self, note_path: &str, target: &str) -> Option<&'a str> {
+        let target = target.replace("%20", " ");
+        let resolved = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => {
+                let note_dir = note_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+                attachments::resolve_relative(note_dir, &target)?
+            }
+        };
+        self.paths.get(resolved.as_str()).copied()
+    }
+}
+
+/// Vault-relative path for a note created from a wikilink to `target`
+/// A target with a folder is taken from the vault root; a bare name goes in
+/// `folder`, or beside the linking note at `source_path` if None.
+pub fn new_note_path(target: &str, source_path: &str, folder: Option<&str>) -> String {
+    let target = target.trim().trim_start_matches('/');
+    let file = if target.ends_with(".md") { target.to_string() } else { format!("{}.md", target) };
+    if target.contains('/') {
+        return file;
+    }
+    let dir = match folder {
+        Some(folder) => folder.trim_matches('/'),
+        None => source_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or(""),
+    };
+    if dir.is_empty() { file } else { format!("{}/{}", dir, file) }
+}
+
+/// The heading `text` (case-insensitive, or its anchor as a TOC links it)
+/// and the lines under it, up to the next heading of the same or higher level
+fn heading_section<'a>(body: &'a str, text: &str) -> Option<&'a str> {
+    let mut start = None;
+    let mut offset = 0;
+    let mut in_fence = false;
+    let mut slugs = utils::HeadingSlugs::default();
+
+    for line in body.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if !in_fence {
+            if let Some((level, heading)) = utils::atx_heading(line) {
+                // Empty headings get no anchor, as in the TOC
+                let slug = (!heading.is_empty()).then(|| slugs.next(heading));
+                match start {
+                    Some((start_offset, start_level)) if level <= start_level => {
+                        return Some(&body[start_offset..offset]);
+                    }
+                    None if heading.eq_ignore_ascii_case(text) || slug.as_deref() == Some(text) => {
+                        start = Some((offset, level))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        offset += line.len();
+    }
+
+    start.map(|(start, _)| &body[start..])
+}
+
+/// The list item or paragraph ending in ` ^id` (marker excluded)
+fn block<'a>(body: &'a str, id: &str) -> Option<&'a str> {
+    let marker = format!(" ^{}", id);
+    let mut paragraph_start = 0;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let text = line.trim_end();
+        if let Some(text) = text.strip_suffix(&marker) {
+            let start = if is_list_item(text) { offset } else { paragraph_start };
+            return Some(&body[start..offset + text.len()]);
+        }
+        offset += line.len();
+        if text.trim().is_empty() || utils::atx_heading(text).is_some() {
+            paragraph_start = offset;
+        }
+    }
+
+    None
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("- ")
+        || line.starts_with("* ")
+        || line.starts_with("+ ")
+        || line.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let dir = std::env::temp_dir().join(format!("unstablon-embeds-{}", std::process::id()));
+        fs::create_dir_all(dir.join("Refs")).unwrap();
+        fs::write(dir.join("Refs/Method.md"), "# Method\n## Setup\nPipettes.\n![[Log]]\n## Other\nFirst line\nkey finding ^k1\n- item ^k2\n").unwrap();
+        fs::write(dir.join("Log.md"), "Logged. ![[Method]] ![[Missing]]\n").unwrap();
+
+        let setup = resolve(&dir, "Index.md", "![[Method#setup]]").unwrap();
+        assert_eq!(setup.path, "Refs/Method.md");
+        // Method is already being expanded, so Log's embed of it stays
+        assert_eq!(setup.content, "## Setup\nPipettes.\nLogged. ![[Method]] ![[Missing]]");
+        assert_eq!(setup.unresolved, vec!["![[Missing]]"]);
+        // Embedding back into the source note stops at the source
+        assert_eq!(resolve(&dir, "Log.md", "![[Method#setup]]").unwrap().content, "## Setup\nPipettes.\n![[Log]]");
+
+        assert_eq!(resolve(&dir, "Log.md", "Method#^k1").unwrap().content, "First line\nkey finding");
+        assert_eq!(resolve(&dir, "Log.md", "[[Method#^k2]]").unwrap().content, "- item");
+        assert!(resolve(&dir, "Log.md", "![[Method#Nope]]").is_err());
+        // A folder resolves to its folder note
+        fs::create_dir_all(dir.join("Refs/Projects")).unwrap();
+        fs::write(dir.join("Refs/Projects/index.md"), "Project list\n").unwrap();
+        let folder = resolve(&dir, "Log.md", "![[Projects]]").unwrap();
+        assert_eq!((folder.path.as_str(), folder.content.as_str()), ("Refs/Projects/index.md", "Project list"));
+        fs::write(dir.join("Refs/Projects/Projects.md"), "Projects home\n").unwrap();
+        assert_eq!(resolve(&dir, "Log.md", "![[Projects]]").unwrap().path, "Refs/Projects/Projects.md");
+        // A TOC anchor picks out a repeated heading
+        fs::write(dir.join("Faq.md"), "## What's new?\nOld\n## What's new?\nNew\n").unwrap();
+        assert_eq!(resolve(&dir, "Log.md", "![[Faq#whats-new-1]]").unwrap().content, "## What's new?\nNew");
+        assert!(resolve(&dir, "Log.md", "![[Log]]").is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_new_note_path() {
+        assert_eq!(new_note_path("Idea", "Projects/Plan.md", None), "Projects/Idea.md");
+        assert_eq!(new_note_path("Idea", "Plan.md", None), "Idea.md");
+        assert_eq!(new_note_path("Idea.md", "Projects/Plan.md", Some("Inbox/")), "Inbox/Idea.md");
+        assert_eq!(new_note_path("/Areas/Idea", "Projects/Plan.md", Some("Inbox")), "Areas/Idea.md");
+    }
+}
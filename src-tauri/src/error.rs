@@ -1,12 +1,14 @@
 //! Error types for Unstablon PKM
 
+use std::io;
+use std::path::Path;
 use thiserror::Error;
 
 /// Application error types
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(io::Error),
 
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
@@ -23,8 +25,112 @@ pub enum AppError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Disk full: {0}")]
+    DiskFull(String),
+
+    #[error("Read-only filesystem: {0}")]
+    ReadOnlyFilesystem(String),
+
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("File busy: {0}")]
+    Busy(String),
+
+    #[error("File too large: {size} bytes (limit {limit})")]
+    TooLarge { size: u64, limit: u64 },
+}
+
+impl AppError {
+    /// Stable machine-readable error kind for the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Io(_) => "io",
+            AppError::Database(_) => "database",
+            AppError::Serialization(_) => "serialization",
+            AppError::Yaml(_) => "yaml",
+            AppError::Path(_) => "path",
+            AppError::NotFound(_) => "notFound",
+            AppError::PermissionDenied(_) => "permissionDenied",
+            AppError::DiskFull(_) => "diskFull",
+            AppError::ReadOnlyFilesystem(_) => "readOnlyFilesystem",
+            AppError::InvalidOperation(_) => "invalidOperation",
+            AppError::Conflict(_) => "conflict",
+            AppError::Busy(_) => "busy",
+            AppError::TooLarge { .. } => "tooLarge",
+        }
+    }
+}
+
+impl AppError {
+    /// Classify an IO error on `path`, naming the path in the message
+    pub fn io(path: impl AsRef<Path>, error: io::Error) -> Self {
+        let message = format!("{}: {}", path.as_ref().display(), error);
+        Self::classify(io::Error::new(error.kind(), message.clone()), error.raw_os_error(), message)
+    }
+
+    /// Disk-full and read-only are detected via raw OS codes (their ErrorKinds
+    /// need a newer Rust than our MSRV)
+    fn classify(error: io::Error, raw_os_error: Option<i32>, message: String) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => return AppError::NotFound(message),
+            io::ErrorKind::PermissionDenied => return AppError::PermissionDenied(message),
+            _ => {}
+        }
+
+        match raw_os_error {
+            Some(code) if DISK_FULL_CODES.contains(&code) => AppError::DiskFull(message),
+            Some(code) if READ_ONLY_CODES.contains(&code) => AppError::ReadOnlyFilesystem(message),
+            Some(code) if BUSY_CODES.contains(&code) => AppError::Busy(message),
+            _ => AppError::Io(error),
+        }
+    }
+}
+
+/// Classify IO errors so the UI can tell "disk full" from "not found"
+/// Prefer `AppError::io` where the path is known.
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        let message = error.to_string();
+        let raw_os_error = error.raw_os_error();
+        Self::classify(error, raw_os_error, message)
+    }
+}
+
+/// ENOSPC, EDQUOT
+#[cfg(target_os = "linux")]
+const DISK_FULL_CODES: [i32; 2] = [28, 122];
+#[cfg(all(unix, not(target_os = "linux")))]
+const DISK_FULL_CODES: [i32; 2] = [28, 69];
+/// ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL
+#[cfg(windows)]
+const DISK_FULL_CODES: [i32; 2] = [39, 112];
+
+/// EROFS
+#[cfg(unix)]
+const READ_ONLY_CODES: [i32; 1] = [30];
+/// ERROR_WRITE_PROTECT
+#[cfg(windows)]
+const READ_ONLY_CODES: [i32; 1] = [19];
+
+/// EBUSY
+#[cfg(unix)]
+const BUSY_CODES: [i32; 1] = [16];
+/// ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION (a sync client or virus
+/// scanner has the file open without sharing, or holds a byte-range lock)
+#[cfg(windows)]
+const BUSY_CODES: [i32; 2] = [32, 33];
+
+/// Whether an IO error means another process holds the file, so the same
+/// operation may succeed shortly
+pub fn is_busy(error: &io::Error) -> bool {
+    error.raw_os_error().is_some_and(|code| BUSY_CODES.contains(&code))
 }
 
 /// Result type alias for AppError
@@ -37,12 +143,63 @@ impl From<AppError> for String {
     }
 }
 
-/// Serializable error for IPC responses
+/// Structured error for IPC responses: `{ kind, message }`, plus
+/// `{ size, limit }` for TooLarge
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let AppError::TooLarge { size, limit } = self {
+            state.serialize_field("size", size)?;
+            state.serialize_field("limit", limit)?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_classification() {
+        let not_found = AppError::from(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(not_found.kind(), "notFound");
+
+        let denied = AppError::from(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.kind(), "permissionDenied");
+
+        let full = AppError::from(io::Error::from_raw_os_error(DISK_FULL_CODES[0]));
+        assert_eq!(full.kind(), "diskFull");
+
+        let read_only = AppError::from(io::Error::from_raw_os_error(READ_ONLY_CODES[0]));
+        assert_eq!(read_only.kind(), "readOnlyFilesystem");
+
+        let busy = io::Error::from_raw_os_error(BUSY_CODES[0]);
+        assert!(is_busy(&busy));
+        assert_eq!(AppError::from(busy).kind(), "busy");
+
+        let other = AppError::from(io::Error::new(io::ErrorKind::Other, "boom"));
+        assert_eq!(other.kind(), "io");
+
+        let at_path = AppError::io("/vault/a.md", io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(at_path.kind(), "notFound");
+        assert!(at_path.to_string().contains("/vault/a.md"));
+        let at_path = AppError::io("/vault/a.md", io::Error::from_raw_os_error(BUSY_CODES[0]));
+        assert_eq!(at_path.kind(), "busy");
+        assert!(AppError::io("/vault/b.md", io::Error::new(io::ErrorKind::Other, "boom")).to_string().contains("/vault/b.md: boom"));
+
+        let json = serde_json::to_value(&not_found).unwrap();
+        assert_eq!(json["kind"], "notFound");
+        assert!(json["message"].as_str().unwrap().starts_with("Not found:"));
+
+        let json = serde_json::to_value(AppError::TooLarge { size: 10, limit: 5 }).unwrap();
+        assert_eq!((json["kind"].as_str(), json["size"].as_u64(), json["limit"].as_u64()), (Some("tooLarge"), Some(10), Some(5)));
     }
 }
@@ -0,0 +1,122 @@
+//! Error Log - Bounded in-memory buffer of recent warnings and errors
+//!
+//! A tracing layer captures WARN/ERROR events into a ring buffer so the
+//! frontend can inspect failures that would otherwise only reach stdout.
+//! ERROR events are also pushed to the frontend as `app:error`.
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::models::ErrorLogEntry;
+
+/// Maximum number of entries kept in memory
+const MAX_ENTRIES: usize = 500;
+
+/// Global error buffer instance
+static ERROR_LOG: OnceLock<Mutex<VecDeque<ErrorLogEntry>>> = OnceLock::new();
+
+/// App handle used to emit `app:error` (set once setup runs)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+thread_local! {
+    /// Guards against re-entry when emitting triggers further events
+    static IN_LAYER: Cell<bool> = const { Cell::new(false) };
+}
+
+fn get_log() -> &'static Mutex<VecDeque<ErrorLogEntry>> {
+    ERROR_LOG.get_or_init(|| Mutex::new(VecDeque::with_capacity(MAX_ENTRIES)))
+}
+
+/// Provide the app handle so ERROR events can be forwarded to the frontend
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Most recent entries, newest first
+pub fn recent(limit: usize) -> Vec<ErrorLogEntry> {
+    let log = get_log().lock().unwrap_or_else(PoisonError::into_inner);
+    log.iter().rev().take(limit).cloned().collect()
+}
+
+/// Drop all buffered entries
+pub fn clear() {
+    get_log().lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+fn push(entry: ErrorLogEntry) {
+    let mut log = get_log().lock().unwrap_or_else(PoisonError::into_inner);
+    if log.len() == MAX_ENTRIES {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// Collects the `message` field and any structured fields of an event
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// Tracing layer feeding the error buffer
+pub struct ErrorLogLayer;
+
+impl<S: Subscriber> Layer<S> for ErrorLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+
+        if IN_LAYER.with(|flag| flag.replace(true)) {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = ErrorLogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            level: level.to_string(),
+            target: event.metadata().target().to_string(),
+            message: format!("{}{}", visitor.message, visitor.fields),
+        };
+
+        if level == Level::ERROR {
+            if let Some(app) = APP_HANDLE.get() {
+                app.emit("app:error", entry.clone()).ok();
+            }
+        }
+
+        push(entry);
+        IN_LAYER.with(|flag| flag.set(false));
+    }
+}
@@ -0,0 +1,239 @@
+//! Footnotes - Renumbering and cleanup of markdown footnotes
+//!
+//! References (`[^label]`) are numbered 1..n in order of first appearance
+//! and their definitions (`[^label]: text`, with indented continuation
+//! lines) are collected in that order at the end of the note. Definitions
+//! nothing refers to are removed; references without a definition are left
+//! alone. Code fences and inline code are skipped.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::frontmatter;
+use crate::models::FootnoteReport;
+use crate::utils;
+
+struct Definition {
+    label: String,
+    /// First line's text after `[^label]:`, then continuation lines verbatim
+    lines: Vec<String>,
+}
+
+/// Normalize the footnotes of `content`
+/// Returns the rewritten note (None if nothing changed) and what was done.
+pub fn normalize(content: &str) -> (Option<String>, FootnoteReport) {
+    let body = frontmatter::body(content);
+    let head = &content[..content.len() - body.len()];
+
+    let (text_lines, definitions) = split_definitions(body);
+
+    // Numbers follow first reference, for labels that have a definition
+    let defined: HashMap<&str, &Definition> = definitions.iter().rev().map(|d| (d.label.as_str(), d)).collect();
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut order = Vec::new();
+    let mut undefined = Vec::new();
+    for label in references(&text_lines) {
+        if numbers.contains_key(&label) || undefined.contains(&label) {
+            continue;
+        }
+        if defined.contains_key(label.as_str()) {
+            order.push(label.clone());
+            numbers.insert(label, order.len());
+        } else {
+            undefined.push(label);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let removed: Vec<String> = definitions
+        .iter()
+        .filter(|d| !numbers.contains_key(&d.label) || !seen.insert(d.label.as_str()))
+        .map(|d| d.label.clone())
+        .collect();
+
+    let mut out = String::from(head);
+    let mut in_fence = false;
+    for line in &text_lines {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+        } else {
+            out.push_str(&rewrite_references(line, &numbers));
+        }
+        out.push('\n');
+    }
+
+    if !order.is_empty() {
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+        out.push_str("\n\n");
+        for (index, label) in order.iter().enumerate() {
+            let definition = defined[label.as_str()];
+            out.push_str(&format!("[^{}]: {}\n", index + 1, definition.lines[0]));
+            for line in &definition.lines[1..] {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    if !body.ends_with('\n') && out.ends_with('\n') && order.is_empty() {
+        out.pop();
+    }
+
+    let report = FootnoteReport {
+        renumbered: order.iter().enumerate().filter(|(i, label)| label.as_str() != (i + 1).to_string()).count(),
+        removed,
+        undefined,
+    };
+    let changed = out != content;
+    (changed.then_some(out), report)
+}
+
+/// Separate definition blocks from the rest of the body
+fn split_definitions(body: &str) -> (Vec<&str>, Vec<Definition>) {
+    let mut text = Vec::new();
+    let mut definitions: Vec<Definition> = Vec::new();
+    let mut in_fence = false;
+    let mut lines = body.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        let parsed = if in_fence { None } else { parse_definition(line) };
+        let Some((label, first)) = parsed else {
+            text.push(line);
+            continue;
+        };
+
+        let mut definition = Definition { label, lines: vec![first.to_string()] };
+        // Continuation: indented lines, possibly after blank lines
+        let mut blanks = 0;
+        while let Some(next) = lines.peek() {
+            if next.trim().is_empty() {
+                blanks += 1;
+            } else if next.starts_with("    ") || next.starts_with('\t') {
+                definition.lines.extend(std::iter::repeat(String::new()).take(blanks));
+                definition.lines.push(next.to_string());
+                blanks = 0;
+            } else {
+                break;
+            }
+            lines.next();
+        }
+        // Don't leave a double gap where the definition was
+        if blanks > 0 && !text.last().is_some_and(|l| l.trim().is_empty()) {
+            text.extend(std::iter::repeat("").take(blanks));
+        }
+        definitions.push(definition);
+    }
+
+    (text, definitions)
+}
+
+/// `[^label]: text` -> (label, text)
+fn parse_definition(line: &str) -> Option<(String, &str)> {
+    let rest = line.strip_prefix("[^")?;
+    let (label, text) = rest.split_once("]:")?;
+    if label.is_empty() || label.contains(char::is_whitespace) || label.contains(']') {
+        return None;
+    }
+    Some((label.to_string(), text.trim_start()))
+}
+
+/// Reference labels in order of appearance (outside code)
+fn references(lines: &[&str]) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut in_fence = false;
+    for line in lines {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if !in_fence {
+            for_each_reference_span(line, |_, _, label| labels.push(label.to_string()));
+        }
+    }
+    labels
+}
+
+/// Replace numbered labels in `line`
+fn rewrite_references(line: &str, numbers: &HashMap<String, usize>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    for_each_reference_span(line, |start, end, label| {
+        if let Some(number) = numbers.get(label) {
+            out.push_str(&line[last..start]);
+            out.push_str(&format!("[^{}]", number));
+            last = end;
+        }
+    });
+    out.push_str(&line[last..]);
+    out
+}
+
+/// Calls `f(start, end, label)` for each `[^label]` outside inline code
+fn for_each_reference_span<'a>(line: &'a str, mut f: impl FnMut(usize, usize, &'a str)) {
+    let mut in_code = false;
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        if rest.starts_with('`') {
+            in_code = !in_code;
+        } else if !in_code && rest.starts_with("[^") {
+            if let Some(close) = rest.find(']') {
+                let label = &rest[2..close];
+                if !label.is_empty() && !label.contains(char::is_whitespace) && !label.contains('[') {
+                    f(i, i + close + 1, label);
+                    i += close + 1;
+                    continue;
+                }
+            }
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let content = "\
+---
+title: Draft
+---
+Claim[^b] and another[^a], again[^b]. Missing[^x]. `code[^a]`
+
+[^a]: First source.
+[^old]: Nobody cites this.
+[^b]: Second source,
+    continued.
+
+More text.
+";
+        let (updated, report) = normalize(content);
+        assert_eq!(
+            updated.unwrap(),
+            "\
+---
+title: Draft
+---
+Claim[^1] and another[^2], again[^1]. Missing[^x]. `code[^a]`
+
+More text.
+
+[^1]: Second source,
+    continued.
+[^2]: First source.
+"
+        );
+        assert_eq!(report.renumbered, 2);
+        assert_eq!(report.removed, vec!["old"]);
+        assert_eq!(report.undefined, vec!["x"]);
+
+        let clean = "Text[^1].\n\n[^1]: Source.\n";
+        assert!(normalize(clean).0.is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! Formatter - Consistent markdown formatting
+//!
+//! A line-based normalizer in the spirit of prettier's markdown mode, each
+//! rule switchable through `FormatOptions`. Frontmatter is never touched,
+//! and code blocks only have their fence markers changed.
+
+use similar::TextDiff;
+
+use crate::frontmatter;
+use crate::models::FormatOptions;
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Blank,
+    Heading,
+    Code,
+    Text,
+}
+
+/// Format `content`; returns it unchanged if already formatted
+pub fn format(content: &str, options: &FormatOptions) -> String {
+    let body = frontmatter::body(content);
+    let head = &content[..content.len() - body.len()];
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut lines = classify(body, options);
+    if let Some(marker) = options.list_marker.as_deref().and_then(|m| m.chars().next()) {
+        for (line, kind) in &mut lines {
+            if *kind == Kind::Text {
+                *line = set_list_marker(line, marker);
+            }
+        }
+    }
+    if options.trim_trailing_whitespace {
+        trim_trailing(&mut lines);
+    }
+    if options.heading_spacing {
+        lines = space_headings(lines);
+    }
+
+    let mut out = String::from(head);
+    out.push_str(&lines.iter().map(|(line, _)| line.as_str()).collect::<Vec<_>>().join(newline));
+    if body.ends_with('\n') || options.trim_trailing_whitespace {
+        out.push_str(newline);
+    }
+    out
+}
+
+/// Unified diff from `before` to `after` (empty if they're equal)
+pub fn diff(path: &str, before: &str, after: &str) -> String {
+    if before == after {
+        return String::new();
+    }
+    TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(3)
+        .header(path, path)
+        .to_string()
+}
+
+/// Split into lines with their kind, rewriting fence markers on the way
+fn classify(body: &str, options: &FormatOptions) -> Vec<(String, Kind)> {
+    let source: Vec<&str> = body.lines().collect();
+    let fence_style = options.fence_style.as_deref().filter(|s| matches!(*s, "```" | "~~~"));
+    let mut lines = Vec::with_capacity(source.len());
+    let mut i = 0;
+
+    while i < source.len() {
+        let line = source[i];
+        if !utils::is_code_fence(line) {
+            let kind = if line.trim().is_empty() {
+                Kind::Blank
+            } else if utils::atx_heading(line).is_some() {
+                Kind::Heading
+            } else {
+                Kind::Text
+            };
+            lines.push((line.to_string(), kind));
+            i += 1;
+            continue;
+        }
+
+        // Fenced block: the closing fence uses the same character, at least
+        // as many times; an unclosed block runs to the end
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let opening = line.trim_start();
+        let fence_char = opening.chars().next().unwrap_or('`');
+        let fence_len = opening.chars().take_while(|c| *c == fence_char).count();
+        let close = source[i + 1..]
+            .iter()
+            .position(|l| {
+                let t = l.trim();
+                t.len() >= fence_len && t.chars().all(|c| c == fence_char)
+            })
+            .map(|offset| i + 1 + offset);
+        let end = close.unwrap_or(source.len() - 1);
+
+        let inner = &source[i + 1..close.unwrap_or(source.len())];
+        // A block containing the target marker would end early if converted
+        let convert = fence_style.filter(|style| {
+            !style.starts_with(fence_char)
+                && close.is_some()
+                && !inner.iter().any(|l| l.trim_start().starts_with(style))
+        });
+
+        match convert {
+            Some(style) => {
+                let info = &opening[fence_len..];
+                lines.push((format!("{}{}{}", indent, style, info), Kind::Code));
+                lines.extend(inner.iter().map(|l| (l.to_string(), Kind::Code)));
+                let closing = source[end];
+                let closing_indent = &closing[..closing.len() - closing.trim_start().len()];
+                lines.push((format!("{}{}", closing_indent, style), Kind::Code));
+            }
+            None => lines.extend(source[i..=end].iter().map(|l| (l.to_string(), Kind::Code))),
+        }
+        i = end + 1;
+    }
+
+    lines
+}
+
+/// Replace a bullet list marker (`-`, `*`, `+`), leaving thematic breaks alone
+fn set_list_marker(line: &str, marker: char) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let mut chars = trimmed.chars();
+    let first = chars.next();
+    let is_bullet = matches!(first, Some('-' | '*' | '+')) && chars.next() == Some(' ');
+    let is_break = trimmed.chars().filter(|c| !c.is_whitespace()).all(|c| Some(c) == first)
+        && trimmed.chars().filter(|c| !c.is_whitespace()).count() >= 3;
+    if is_bullet && !is_break {
+        format!("{}{}{}", indent, marker, &trimmed[1..])
+    } else {
+        line.to_string()
+    }
+}
+
+/// Strip trailing whitespace outside code, keeping hard breaks as two
+/// spaces, and drop trailing blank lines
+fn trim_trailing(lines: &mut Vec<(String, Kind)>) {
+    for i in 0..lines.len() {
+        if lines[i].1 == Kind::Code {
+            continue;
+        }
+        let hard_break = lines[i].0.ends_with("  ")
+            && lines[i].1 == Kind::Text
+            && lines.get(i + 1).is_some_and(|(_, kind)| *kind == Kind::Text);
+        let trimmed = lines[i].0.trim_end().to_string();
+        lines[i].0 = if hard_break { format!("{}  ", trimmed) } else { trimmed };
+    }
+    while lines.last().is_some_and(|(_, kind)| *kind == Kind::Blank) {
+        lines.pop();
+    }
+}
+
+/// Exactly one blank line before and after each heading
+fn space_headings(lines: Vec<(String, Kind)>) -> Vec<(String, Kind)> {
+    let mut out: Vec<(String, Kind)> = Vec::with_capacity(lines.len());
+    let mut after_heading = false;
+
+    for (line, kind) in lines {
+        if kind == Kind::Blank && out.last().is_some_and(|(_, k)| *k == Kind::Blank) {
+            // Collapse gaps next to headings only
+            let prev_heading = out.len() >= 2 && out[out.len() - 2].1 == Kind::Heading;
+            if prev_heading {
+                continue;
+            }
+        }
+        if kind == Kind::Heading {
+            while out.len() >= 2
+                && out[out.len() - 1].1 == Kind::Blank
+                && out[out.len() - 2].1 == Kind::Blank
+            {
+                out.pop();
+            }
+            if out.last().is_some_and(|(_, k)| *k != Kind::Blank) {
+                out.push((String::new(), Kind::Blank));
+            }
+        } else if after_heading && kind != Kind::Blank {
+            out.push((String::new(), Kind::Blank));
+        }
+        after_heading = kind == Kind::Heading;
+        out.push((line, kind));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let content = "---\ntitle: x  \n---\n# Title\nIntro \nline two  \nnext\n\n\n\n## Items\n* one\n+ two\n* * *\n~~~js\nlet a = 1;   \n~~~\n\n\n";
+        let options = FormatOptions {
+            heading_spacing: true,
+            list_marker: Some("-".to_string()),
+            trim_trailing_whitespace: true,
+            fence_style: Some("```".to_string()),
+        };
+        let formatted = format(content, &options);
+        assert_eq!(
+            formatted,
+            "---\ntitle: x  \n---\n# Title\n\nIntro\nline two  \nnext\n\n## Items\n\n- one\n- two\n* * *\n```js\nlet a = 1;   \n```\n"
+        );
+        assert_eq!(format(&formatted, &options), formatted);
+
+        let patch = diff("a.md", content, &formatted);
+        assert!(patch.starts_with("--- a.md\n+++ a.md\n"));
+        assert!(patch.contains("\n-* one\n") && patch.contains("\n+- one\n"));
+        assert!(diff("a.md", "x", "x").is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+//! Frontmatter - YAML frontmatter parsing and editing for markdown notes
+//!
+//! A note has frontmatter when its first line is `---` and a later line
+//! closes the block with `---` (or `...`). Everything after the closing
+//! line is the body.
+//!
+//! Property edits are line-based: only the lines of the edited key are
+//! rewritten, so comments, ordering, and quoting of other keys survive.
+
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+
+use crate::models::NoteMetadata;
+
+/// Keys mapped onto typed NoteMetadata fields (excluded from `properties`)
+const TYPED_KEYS: [&str; 8] = ["id", "title", "tags", "tag", "aliases", "alias", "created", "modified"];
+
+/// Split content into (frontmatter YAML, body)
+/// Returns None if the content has no frontmatter block
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\r\n")
+        .or_else(|| content.strip_prefix("---\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed == "---" || trimmed == "..." {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((yaml, body));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Body of a note with any frontmatter removed
+pub fn body(content: &str) -> &str {
+    split(content).map(|(_, body)| body).unwrap_or(content)
+}
+
+/// Parse frontmatter into typed metadata
+/// Missing or invalid frontmatter yields empty metadata (with `error` set if invalid)
+pub fn parse(content: &str) -> NoteMetadata {
+    let mut metadata = NoteMetadata::default();
+
+    let yaml = match split(content) {
+        Some((yaml, _)) => yaml,
+        None => return metadata,
+    };
+    metadata.has_frontmatter = true;
+
+    if yaml.trim().is_empty() {
+        return metadata;
+    }
+
+    let mapping = match serde_yaml::from_str::<YamlValue>(yaml) {
+        Ok(YamlValue::Mapping(mapping)) => mapping,
+        Ok(YamlValue::Null) => return metadata,
+        Ok(_) => {
+            metadata.error = Some("Frontmatter is not a key/value mapping".to_string());
+            return metadata;
+        }
+        Err(e) => {
+            metadata.error = Some(e.to_string());
+            return metadata;
+        }
+    };
+
+    for (key, value) in mapping {
+        let key = match key {
+            YamlValue::String(key) => key,
+            other => match scalar_to_string(&other) {
+                Some(key) => key,
+                None => continue,
+            },
+        };
+
+        match key.as_str() {
+            "id" => metadata.id = scalar_to_string(&value),
+            "title" => metadata.title = scalar_to_string(&value),
+            "tags" | "tag" => metadata.tags.extend(
+                string_list(&value, &[',', ' ']).into_iter().map(normalize_tag),
+            ),
+            "aliases" | "alias" => metadata.aliases.extend(string_list(&value, &[','])),
+            "created" => metadata.created = scalar_to_string(&value),
+            "modified" => metadata.modified = scalar_to_string(&value),
+            _ => {}
+        }
+
+        if !TYPED_KEYS.contains(&key.as_str()) {
+            let json = serde_json::to_value(&value).unwrap_or(JsonValue::Null);
+            metadata.properties.insert(key, json);
+        }
+    }
+
+    // Same order-preserving, case-insensitive dedup as `utils::extract_tags`
+    let mut seen = std::collections::HashSet::new();
+    metadata.tags.retain(|t| !t.is_empty() && seen.insert(t.to_lowercase()));
+    metadata
+}
+
+/// Flatten untyped properties into (key, value) pairs for indexing
+/// Scalars become strings, lists contribute one pair per scalar item, and
+/// nested mappings are skipped
+pub fn property_pairs(metadata: &NoteMetadata) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for (key, value) in &metadata.properties {
+        let items = match value {
+            JsonValue::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for item in items {
+            let text = match item {
+                JsonValue::String(s) => s.trim().to_string(),
+                JsonValue::Number(n) => n.to_string(),
+                JsonValue::Bool(b) => b.to_string(),
+                _ => continue,
+            };
+            if !text.is_empty() {
+                pairs.push((key.clone(), text));
+            }
+        }
+    }
+    pairs
+}
+
+/// Set a top-level property, replacing its existing lines in place or
+/// appending it (creating the frontmatter block if missing)
+pub fn set_property(content: &str, key: &str, value: &JsonValue) -> Result<String, String> {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let rendered = render_property(key, value)?.replace('\n', newline);
+
+    let yaml = match split(content) {
+        Some((yaml, _)) => yaml,
+        None => return Ok(format!("---{nl}{}---{nl}{}", rendered, content, nl = newline)),
+    };
+
+    let new_yaml = match property_range(yaml, key) {
+        Some((start, end)) => format!("{}{}{}", &yaml[..start], rendered, &yaml[end..]),
+        None => format!("{}{}", yaml, rendered),
+    };
+
+    Ok(replace_yaml(content, yaml, &new_yaml))
+}
+
+/// Remove a top-level property; returns None if it isn't present
+pub fn remove_property(content: &str, key: &str) -> Option<String> {
+    let (yaml, _) = split(content)?;
+    let (start, end) = property_range(yaml, key)?;
+    let new_yaml = format!("{}{}", &yaml[..start], &yaml[end..]);
+    Some(replace_yaml(content, yaml, &new_yaml))
+}
+
+/// Replace the whole frontmatter block with `yaml` (creating the block if
+/// missing); blank `yaml` removes the block. `yaml` must be a mapping.
+pub fn set_block(content: &str, yaml: &str) -> Result<String, String> {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let body = body(content);
+    if yaml.trim().is_empty() {
+        return Ok(body.to_string());
+    }
+
+    // A marker line would end the block early and spill the rest into the body
+    if yaml.lines().any(|line| matches!(line.trim_end(), "---" | "...")) {
+        return Err("Frontmatter can't contain a --- or ... line".to_string());
+    }
+    match serde_yaml::from_str::<YamlValue>(yaml) {
+        Ok(YamlValue::Mapping(_)) => {}
+        Ok(_) => return Err("Frontmatter must be a mapping of properties".to_string()),
+        Err(e) => return Err(format!("Invalid frontmatter YAML: {}", e)),
+    }
+
+    let yaml = yaml.trim_end().replace("\r\n", "\n").replace('\n', newline);
+    Ok(format!("---{nl}{}{nl}---{nl}{}", yaml, body, nl = newline))
+}
+
+/// Validate and render `key: value` as YAML (ends with a newline)
+fn render_property(key: &str, value: &JsonValue) -> Result<String, String> {
+    if key.trim().is_empty() || key.contains(['\n', '\r']) {
+        return Err(format!("Invalid property name: {:?}", key));
+    }
+
+    let value = serde_yaml::to_value(value).map_err(|e| e.to_string())?;
+    let mut mapping = serde_yaml::Mapping::new();
+    mapping.insert(YamlValue::String(key.to_string()), value);
+
+    serde_yaml::to_string(&mapping).map_err(|e| e.to_string())
+}
+
+/// Byte range of a top-level key's lines (the key line plus indented or
+/// list continuation lines) within the frontmatter YAML
+fn property_range(yaml: &str, key: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut start = None;
+
+    for line in yaml.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let is_continuation = content.starts_with([' ', '\t']) || content == "-" || content.starts_with("- ");
+
+        match start {
+            Some(start) if !is_continuation => return Some((start, offset)),
+            None if line_defines_key(content, key) => start = Some(offset),
+            _ => {}
+        }
+
+        offset += line.len();
+    }
+
+    start.map(|start| (start, yaml.len()))
+}
+
+/// Whether a top-level line is `key:` (bare or quoted)
+fn line_defines_key(line: &str, key: &str) -> bool {
+    [key.to_string(), format!("\"{}\"", key), format!("'{}'", key)]
+        .iter()
+        .any(|candidate| {
+            line.strip_prefix(candidate.as_str())
+                .map(|rest| rest.starts_with(':'))
+                .unwrap_or(false)
+        })
+}
+
+/// Swap the frontmatter YAML (a subslice of `content`) for `new_yaml`
+fn replace_yaml(content: &str, yaml: &str, new_yaml: &str) -> String {
+    let start = yaml.as_ptr() as usize - content.as_ptr() as usize;
+    let end = start + yaml.len();
+    format!("{}{}{}", &content[..start], new_yaml, &content[end..])
+}
+
+/// Scalar YAML value as a string (strings, numbers, bools)
+fn scalar_to_string(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        YamlValue::Number(n) => Some(n.to_string()),
+        YamlValue::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A YAML list of scalars, or a single string split on `separators`
+fn string_list(value: &YamlValue, separators: &[char]) -> Vec<String> {
+    match value {
+        YamlValue::Sequence(items) => items.iter().filter_map(scalar_to_string).collect(),
+        YamlValue::String(s) => s
+            .split(separators)
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        other => scalar_to_string(other).into_iter().collect(),
+    }
+}
+
+/// Tags are stored without a leading '#'
+fn normalize_tag(tag: String) -> String {
+    tag.trim_start_matches('#').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("---\ntitle: A\n---\nBody"), Some(("title: A\n", "Body")));
+        assert_eq!(split("---\r\ntitle: A\r\n---\r\nBody"), Some(("title: A\r\n", "Body")));
+        assert_eq!(split("# No frontmatter\n---\n"), None);
+        assert_eq!(split("---\nunterminated"), None);
+    }
+
+    #[test]
+    fn test_parse_typed_fields() {
+        let content = "---\ntitle: \"My Note\"\ntags: [rust, '#pkm']\naliases: Other Name, Alt\ncreated: 2024-01-02\nstatus: draft\npriority: 2\n---\n# Heading\n";
+        let meta = parse(content);
+
+        assert!(meta.has_frontmatter);
+        assert_eq!(meta.title.as_deref(), Some("My Note"));
+        assert_eq!(meta.tags, vec!["rust", "pkm"]);
+        assert_eq!(meta.aliases, vec!["Other Name", "Alt"]);
+        assert_eq!(meta.created.as_deref(), Some("2024-01-02"));
+        assert_eq!(meta.properties.get("status"), Some(&JsonValue::from("draft")));
+        assert_eq!(meta.properties.get("priority"), Some(&JsonValue::from(2)));
+        assert!(!meta.properties.contains_key("title"));
+
+        let meta = parse("---\ntags: [a, b, A]\ntag: b\n---\n");
+        assert_eq!(meta.tags, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_set_property() {
+        let content = "---\ntitle: A # keep\ntags:\n  - x\n  - y\nstatus: draft\n---\nBody\n";
+
+        let updated = set_property(content, "tags", &serde_json::json!(["z"])).unwrap();
+        assert_eq!(updated, "---\ntitle: A # keep\ntags:\n- z\nstatus: draft\n---\nBody\n");
+
+        let updated = set_property(content, "priority", &serde_json::json!(1)).unwrap();
+        assert_eq!(updated, "---\ntitle: A # keep\ntags:\n  - x\n  - y\nstatus: draft\npriority: 1\n---\nBody\n");
+
+        let created = set_property("Body\n", "status", &serde_json::json!("done")).unwrap();
+        assert_eq!(created, "---\nstatus: done\n---\nBody\n");
+
+        assert!(set_property(content, "", &serde_json::json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_remove_property() {
+        let content = "---\ntitle: A\ntags:\n- x\nstatus: draft\n---\nBody";
+        assert_eq!(remove_property(content, "tags").unwrap(), "---\ntitle: A\nstatus: draft\n---\nBody");
+        assert_eq!(remove_property(content, "status").unwrap(), "---\ntitle: A\ntags:\n- x\n---\nBody");
+        assert!(remove_property(content, "missing").is_none());
+        assert!(remove_property("Body", "title").is_none());
+    }
+
+    #[test]
+    fn test_set_block() {
+        let content = "---\ntitle: A\n---\nBody\n";
+        assert_eq!(set_block(content, "title: B\ntags: [x]\n").unwrap(), "---\ntitle: B\ntags: [x]\n---\nBody\n");
+        assert_eq!(set_block("Body\r\n", "status: done").unwrap(), "---\r\nstatus: done\r\n---\r\nBody\r\n");
+        assert_eq!(set_block(content, "  ").unwrap(), "Body\n");
+        assert!(set_block(content, "- a list").is_err());
+        assert!(set_block(content, "a: 1\n---\nb: 2").is_err());
+    }
+
+    #[test]
+    fn test_property_pairs() {
+        let meta = parse("---\nstatus: draft\nproject: [alpha, beta]\npriority: 2\nnested: {a: 1}\n---\n");
+        let mut pairs = property_pairs(&meta);
+        pairs.sort();
+        assert_eq!(pairs, vec![
+            ("priority".to_string(), "2".to_string()),
+            ("project".to_string(), "alpha".to_string()),
+            ("project".to_string(), "beta".to_string()),
+            ("status".to_string(), "draft".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_invalid_yaml() {
+        let meta = parse("---\ntitle: [unclosed\n---\n");
+        assert!(meta.has_frontmatter);
+        assert!(meta.error.is_some());
+        assert!(meta.title.is_none());
+    }
+}
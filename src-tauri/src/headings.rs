@@ -0,0 +1,272 @@
+//! Headings - Hierarchical heading numbers
+//!
+//! Numbers are derived from heading order every time they're applied, so
+//! re-applying after sections move renumbers them. Only numbers in the
+//! shape this module writes (`1.` for the top level, `1.2`/`1.2.3` below,
+//! with as many parts as the heading's depth) are recognized, so a heading
+//! like `## 2024 review` keeps its text.
+//!
+//! A table of contents lives between `<!-- toc -->` and `<!-- tocstop -->`
+//! markers; the start marker records the levels it covers so a refresh on
+//! save regenerates the same TOC.
+
+use crate::frontmatter;
+use crate::models::{HeadingNumberOptions, TocOptions};
+use crate::utils;
+
+const TOC_START: &str = "<!-- toc";
+const TOC_END: &str = "<!-- tocstop -->";
+
+/// Number the headings from `min_level` to `max_level`, replacing existing
+/// numbers. Returns the rewritten content and how many headings are numbered.
+pub fn apply_numbers(content: &str, options: &HeadingNumberOptions) -> (String, usize) {
+    let range = levels(options);
+    let min_level = *range.start();
+    let mut counters = vec![0usize; range.count()];
+    let mut numbered = 0;
+
+    let updated = rewrite(content, options, |level, text| {
+        let index = level - min_level;
+        counters[index] += 1;
+        counters[index + 1..].iter_mut().for_each(|c| *c = 0);
+        numbered += 1;
+
+        let parts: Vec<String> = counters[..=index].iter().map(|c| c.to_string()).collect();
+        let number = if index == 0 { format!("{}.", parts[0]) } else { parts.join(".") };
+        format!("{} {}", number, strip_number(text, index + 1))
+    });
+    (updated, numbered)
+}
+
+/// Strip numbers from the headings from `min_level` to `max_level`
+/// Returns the rewritten content and how many numbers were removed.
+pub fn remove_numbers(content: &str, options: &HeadingNumberOptions) -> (String, usize) {
+    let min_level = *levels(options).start();
+    let mut removed = 0;
+    let updated = rewrite(content, options, |level, text| {
+        let stripped = strip_number(text, level - min_level + 1);
+        if stripped.len() != text.len() {
+            removed += 1;
+        }
+        stripped.to_string()
+    });
+    (updated, removed)
+}
+
+/// Heading levels to number, clamped to 1..=6
+fn levels(options: &HeadingNumberOptions) -> std::ops::RangeInclusive<usize> {
+    options.min_level.max(1)..=options.max_level.min(6)
+}
+
+/// Replace the text of each heading in range with `f(level, text)`,
+/// outside frontmatter and code fences
+fn rewrite(content: &str, options: &HeadingNumberOptions, mut f: impl FnMut(usize, &str) -> String) -> String {
+    let body = frontmatter::body(content);
+    let mut out = String::from(&content[..content.len() - body.len()]);
+    let range = levels(options);
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        let heading = if in_fence { None } else { utils::atx_heading(line) };
+        match heading {
+            Some((level, _)) if range.contains(&level) => {
+                // Text starts after the indent, the hashes and their spaces
+                let trimmed = line.trim_start();
+                let after_hashes = &trimmed[level..];
+                let start = line.len() - after_hashes.trim_start().len();
+                let text = line[start..].trim_end();
+                if text.is_empty() {
+                    out.push_str(line);
+                    continue;
+                }
+                out.push_str(&line[..start]);
+                out.push_str(&f(level, text));
+                out.push_str(&line[start + text.len()..]);
+            }
+            _ => out.push_str(line),
+        }
+    }
+
+    out
+}
+
+/// `text` without a leading number of `parts` parts (`1.` or `1.2.3`)
+fn strip_number(text: &str, parts: usize) -> &str {
+    let Some((number, rest)) = text.split_once(' ') else {
+        return text;
+    };
+    let number = if parts == 1 {
+        match number.strip_suffix('.') {
+            Some(number) => number,
+            None => return text,
+        }
+    } else {
+        number
+    };
+    let components: Vec<&str> = number.split('.').collect();
+    let valid = components.len() == parts
+        && components.iter().all(|c| !c.is_empty() && c.chars().all(|ch| ch.is_ascii_digit()));
+    if valid { rest.trim_start() } else { text }
+}
+
+/// Insert a TOC, or replace the one between existing markers
+/// A new TOC goes after a leading `# Title`, else at the top of the body.
+pub fn insert_toc(content: &str, options: &TocOptions) -> String {
+    let block = toc_block(content, options);
+    if let Some((start, end)) = toc_span(content) {
+        return format!("{}{}{}", &content[..start], block, &content[end..]);
+    }
+
+    let body = frontmatter::body(content);
+    let mut at = content.len() - body.len();
+    let first = body.split_inclusive('\n').next().unwrap_or_default();
+    if utils::atx_heading(first).is_some_and(|(level, _)| level == 1) {
+        at += first.len();
+    }
+
+    let before = &content[..at];
+    let after = content[at..].trim_start_matches(['\n', '\r']);
+    let mut out = String::from(before);
+    if !before.is_empty() && !before.ends_with('\n') {
+        out.push('\n');
+    }
+    if utils::atx_heading(before.lines().last().unwrap_or_default()).is_some() {
+        out.push('\n');
+    }
+    out.push_str(&block);
+    if !after.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(after);
+    } else {
+        out.push('\n');
+    }
+    out
+}
+
+/// Regenerate the TOC between existing markers, with the levels recorded
+/// in the start marker. None if there are no markers or nothing changed.
+pub fn refresh_toc(content: &str) -> Option<String> {
+    let (start, end) = toc_span(content)?;
+    let options = parse_marker(content[start..].lines().next().unwrap_or_default());
+    let updated = format!("{}{}{}", &content[..start], toc_block(content, &options), &content[end..]);
+    (updated != content).then_some(updated)
+}
+
+/// Byte range from the start marker to the end of the `tocstop` marker
+fn toc_span(content: &str) -> Option<(usize, usize)> {
+    let mut start = None;
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        let trimmed = line.trim();
+        if !in_fence {
+            if trimmed == TOC_END {
+                if let Some(start) = start {
+                    return Some((start, offset + line.trim_end().len()));
+                }
+            } else if start.is_none() && trimmed.starts_with(TOC_START) && trimmed.ends_with("-->") {
+                start = Some(offset + line.len() - line.trim_start().len());
+            }
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Levels from `<!-- toc min=2 max=3 -->` (defaults for missing values)
+fn parse_marker(marker: &str) -> TocOptions {
+    let mut options = TocOptions::default();
+    let inner = marker.trim().trim_start_matches(TOC_START).trim_end_matches("-->");
+    for (key, value) in inner.split_whitespace().filter_map(|token| token.split_once('=')) {
+        match (key, value.parse()) {
+            ("min", Ok(level)) => options.min_level = level,
+            ("max", Ok(level)) => options.max_level = level,
+            _ => {}
+        }
+    }
+    options
+}
+
+/// Markers and a nested list linking to each heading in range
+fn toc_block(content: &str, options: &TocOptions) -> String {
+    let min_level = options.min_level.clamp(1, 6);
+    let max_level = options.max_level.clamp(min_level, 6);
+    let mut block = format!("{} min={} max={} -->\n", TOC_START, min_level, max_level);
+
+    let mut slugs = utils::HeadingSlugs::default();
+    let mut in_fence = false;
+    for line in frontmatter::body(content).lines() {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        let Some((level, text)) = utils::atx_heading(line).filter(|_| !in_fence) else {
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        let anchor = slugs.next(text);
+
+        if (min_level..=max_level).contains(&level) {
+            let label = text.replace('[', "\\[").replace(']', "\\]");
+            block.push_str(&format!("{}- [{}](#{})\n", "  ".repeat(level - min_level), label, anchor));
+        }
+    }
+
+    block.push_str(TOC_END);
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_numbers() {
+        let options = HeadingNumberOptions { min_level: 2, max_level: 4 };
+        let content = "# Title\n## 2. Moved\n### Detail\n```\n## Not a heading\n```\n## Intro\n### 1.1 Old\n#### Deep ##\n## 2024 review\n";
+
+        let (numbered, count) = apply_numbers(content, &options);
+        assert_eq!(count, 6);
+        assert_eq!(
+            numbered,
+            "# Title\n## 1. Moved\n### 1.1 Detail\n```\n## Not a heading\n```\n## 2. Intro\n### 2.1 Old\n#### 2.1.1 Deep ##\n## 3. 2024 review\n"
+        );
+        assert_eq!(apply_numbers(&numbered, &options).0, numbered);
+
+        let (stripped, removed) = remove_numbers(&numbered, &options);
+        assert_eq!(removed, 6);
+        assert_eq!(
+            stripped,
+            "# Title\n## Moved\n### Detail\n```\n## Not a heading\n```\n## Intro\n### Old\n#### Deep ##\n## 2024 review\n"
+        );
+    }
+
+    #[test]
+    fn test_toc() {
+        let content = "---\ntitle: x\n---\n# Title\nIntro.\n## Setup [v2]\n### Step\n```\n## Not a heading\n```\n#### Too deep\n## Setup [v2]\n";
+        let with_toc = insert_toc(content, &TocOptions::default());
+        assert_eq!(
+            with_toc,
+            "---\ntitle: x\n---\n# Title\n\n<!-- toc min=2 max=3 -->\n\
+             - [Setup \\[v2\\]](#setup-v2)\n  - [Step](#step)\n- [Setup \\[v2\\]](#setup-v2-1)\n<!-- tocstop -->\n\n\
+             Intro.\n## Setup [v2]\n### Step\n```\n## Not a heading\n```\n#### Too deep\n## Setup [v2]\n"
+        );
+        assert!(refresh_toc(&with_toc).is_none());
+
+        let edited = with_toc.replace("### Step", "### First step");
+        let refreshed = refresh_toc(&edited).unwrap();
+        assert!(refreshed.contains("  - [First step](#first-step)\n"));
+        assert_eq!(insert_toc(&refreshed, &TocOptions::default()), refreshed);
+        assert!(refresh_toc("No markers.\n").is_none());
+    }
+}
@@ -0,0 +1,202 @@
+//! Code-aware extraction for source files
+//!
+//! Line-based heuristics rather than a real parser: declarations are
+//! recognized by their leading keywords, which is enough to make snippets
+//! findable by symbol name. Doc comments (JSDoc, Python docstrings, Rust
+//! `///`) are extracted alongside the symbols.
+
+/// Languages with symbol extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    JavaScript,
+    TypeScript,
+    Python,
+    Rust,
+}
+
+impl Language {
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with(".min.js") {
+            return None;
+        }
+        let extension = path.rsplit_once('.')?.1;
+        match extension {
+            "js" | "mjs" | "cjs" | "jsx" => Some(Language::JavaScript),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            "py" => Some(Language::Python),
+            "rs" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+}
+
+/// Keywords that may precede a declaration without changing what it names
+const JS_MODIFIERS: [&str; 6] = ["export", "default", "async", "declare", "abstract", "static"];
+const JS_DECLARATIONS: [&str; 10] = [
+    "function", "function*", "class", "interface", "type", "enum", "namespace", "const", "let", "var",
+];
+const RUST_MODIFIERS: [&str; 7] = ["pub", "pub(crate)", "pub(super)", "async", "unsafe", "extern", "default"];
+const RUST_DECLARATIONS: [&str; 10] = [
+    "fn", "struct", "enum", "trait", "type", "mod", "const", "static", "union", "macro_rules!",
+];
+
+/// Declared symbol names, in source order
+pub fn symbols(language: Language, content: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = content
+        .lines()
+        .filter_map(|line| match language {
+            Language::JavaScript | Language::TypeScript => js_symbol(line),
+            Language::Python => python_symbol(line),
+            Language::Rust => rust_symbol(line),
+        })
+        .collect();
+
+    let mut seen = std::collections::HashSet::new();
+    symbols.retain(|symbol| seen.insert(symbol.clone()));
+    symbols
+}
+
+/// Doc comment text, one entry per block
+pub fn doc_comments(language: Language, content: &str) -> Vec<String> {
+    match language {
+        Language::JavaScript | Language::TypeScript => delimited_blocks(content, "/**", "*/")
+            .into_iter()
+            .map(|block| {
+                block
+                    .lines()
+                    .map(|line| line.trim().trim_start_matches('*').trim())
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect(),
+        Language::Python => ["\"\"\"", "'''"]
+            .iter()
+            .flat_map(|quote| delimited_blocks(content, quote, quote))
+            .map(|block| block.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect(),
+        Language::Rust => {
+            let lines: Vec<&str> = content
+                .lines()
+                .filter_map(|line| {
+                    let line = line.trim_start();
+                    line.strip_prefix("///").or_else(|| line.strip_prefix("//!"))
+                })
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect();
+            if lines.is_empty() { Vec::new() } else { vec![lines.join(" ")] }
+        }
+    }
+    .into_iter()
+    .filter(|doc| !doc.is_empty())
+    .collect()
+}
+
+/// Symbols followed by doc comments, as searchable text
+pub fn searchable_text(language: Language, content: &str) -> String {
+    let mut parts = symbols(language, content);
+    parts.extend(doc_comments(language, content));
+    parts.join(" ")
+}
+
+fn js_symbol(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace().peekable();
+    let mut exported = false;
+    while let Some(word) = words.peek() {
+        if !JS_MODIFIERS.contains(word) {
+            break;
+        }
+        exported |= *word == "export";
+        words.next();
+    }
+
+    let keyword = words.next()?;
+    if !JS_DECLARATIONS.contains(&keyword) {
+        return None;
+    }
+    // Plain variables only count when exported or bound to a function
+    if matches!(keyword, "const" | "let" | "var") && !exported && !line.contains("=>") && !line.contains("function") {
+        return None;
+    }
+
+    identifier(words.next()?.trim_start_matches('*'))
+}
+
+fn python_symbol(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    let rest = line.strip_prefix("async ").unwrap_or(line);
+    let rest = rest.strip_prefix("def ").or_else(|| rest.strip_prefix("class "))?;
+    identifier(rest.trim_start())
+}
+
+fn rust_symbol(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace().peekable();
+    while let Some(word) = words.peek() {
+        // `extern "C" fn`, `const fn`, `unsafe fn`
+        if RUST_MODIFIERS.contains(word) || word.starts_with('"') || (*word == "const" && line.contains(" fn ")) {
+            words.next();
+        } else {
+            break;
+        }
+    }
+
+    let keyword = words.next()?;
+    if !RUST_DECLARATIONS.contains(&keyword) {
+        return None;
+    }
+    identifier(words.next()?)
+}
+
+/// Leading identifier of `text` (`name(`, `Name<T>`, `NAME:` -> the name)
+fn identifier(text: &str) -> Option<String> {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(text.len());
+    let name = &text[..end];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Text between each `open` and the following `close`
+fn delimited_blocks<'a>(content: &'a str, open: &str, close: &str) -> Vec<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len()..];
+        match after.find(close) {
+            Some(end) => {
+                blocks.push(&after[..end]);
+                rest = &after[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbols_and_docs() {
+        let ts = "/**\n * Parse a config file.\n */\nexport async function parseConfig(path: string) {}\nexport default class Loader<T> {}\nconst helper = (x) => x;\nconst LIMIT = 3;\nexport interface Options {}\n";
+        assert_eq!(symbols(Language::TypeScript, ts), vec!["parseConfig", "Loader", "helper", "Options"]);
+        assert_eq!(doc_comments(Language::TypeScript, ts), vec!["Parse a config file."]);
+
+        let py = "class Graph:\n    \"\"\"Adjacency list graph.\"\"\"\n    async def shortest_path(self):\n        pass\n";
+        assert_eq!(symbols(Language::Python, py), vec!["Graph", "shortest_path"]);
+        assert_eq!(doc_comments(Language::Python, py), vec!["Adjacency list graph."]);
+
+        let rs = "/// Note index\npub(crate) struct Index<'a> {}\npub const fn max_len() -> usize { 3 }\nimpl Index<'_> {}\nmacro_rules! hit {}\n";
+        assert_eq!(symbols(Language::Rust, rs), vec!["Index", "max_len", "hit"]);
+        assert_eq!(doc_comments(Language::Rust, rs), vec!["Note index"]);
+
+        assert_eq!(Language::from_path("a/lib.min.js"), None);
+        assert_eq!(Language::from_path("a/app.tsx"), Some(Language::TypeScript));
+    }
+}
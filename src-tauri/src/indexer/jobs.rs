@@ -0,0 +1,135 @@
+//! Background index rebuild jobs
+//!
+//! At most one rebuild runs at a time. The active job's CancellationToken is
+//! held globally so it can be cancelled from IPC or during shutdown; progress
+//! is checkpointed in the database so a cancelled or interrupted rebuild
+//! resumes where it left off.
+
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::db::Database;
+use crate::models::{IndexSettings, RebuildOutcome, RebuildProgress};
+use crate::vault;
+
+/// Token of the currently running rebuild
+static ACTIVE_REBUILD: OnceLock<Mutex<Option<CancellationToken>>> = OnceLock::new();
+
+fn get_active() -> &'static Mutex<Option<CancellationToken>> {
+    ACTIVE_REBUILD.get_or_init(|| Mutex::new(None))
+}
+
+/// Registration of the running rebuild; clears itself on drop
+pub struct RebuildGuard {
+    token: CancellationToken,
+}
+
+impl RebuildGuard {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for RebuildGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = get_active().lock() {
+            *active = None;
+        }
+    }
+}
+
+/// Register a new rebuild, failing if one is already running
+pub fn begin() -> Result<RebuildGuard, String> {
+    let mut active = get_active().lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if active.is_some() {
+        return Err("An index rebuild is already running".to_string());
+    }
+
+    let token = CancellationToken::new();
+    *active = Some(token.clone());
+    Ok(RebuildGuard { token })
+}
+
+/// Cancel the running rebuild; returns whether one was running
+pub fn cancel() -> bool {
+    match get_active().lock() {
+        Ok(active) => match active.as_ref() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/// Whether a rebuild is currently running
+pub fn is_running() -> bool {
+    get_active().lock().map(|active| active.is_some()).unwrap_or(false)
+}
+
+/// Emit `index:progress` for a running rebuild
+pub fn emit_progress(app: &AppHandle, home_path: &str, indexed: u32) {
+    let payload = RebuildProgress {
+        home_path: home_path.to_string(),
+        indexed,
+    };
+    if let Err(e) = app.emit("index:progress", payload) {
+        error!("[ERROR] [indexer] Failed to emit index:progress: {}", e);
+    }
+}
+
+/// Run a rebuild on a blocking worker, emitting progress and `index:finished`
+pub fn spawn_rebuild(
+    app: AppHandle,
+    db: Arc<Database>,
+    home_path: String,
+    settings: IndexSettings,
+) -> Result<(), String> {
+    let guard = begin()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let progress_app = app.clone();
+        let progress_path = home_path.clone();
+        let on_progress = move |indexed: u32| emit_progress(&progress_app, &progress_path, indexed);
+
+        let result = super::run_rebuild(&db, &home_path, &settings, guard.token(), &on_progress);
+        drop(guard);
+
+        match result {
+            Ok(outcome) => emit_finished(&app, outcome),
+            Err(e) => error!("[ERROR] [indexer] Background rebuild failed: {}", e),
+        }
+    });
+
+    Ok(())
+}
+
+/// Resume a rebuild interrupted by a previous app exit, if any
+/// Only a rebuild of the open vault is resumed.
+pub fn resume_pending(app: AppHandle, db: Arc<Database>, settings: IndexSettings) {
+    match db.pending_rebuild() {
+        Ok(Some(home_path)) if !vault::is_root(&home_path) => {
+            info!("[INFO] [indexer] Not resuming rebuild of a vault that is no longer open: {}", home_path);
+        }
+        Ok(Some(home_path)) => {
+            info!("[INFO] [indexer] Resuming interrupted index rebuild: {}", home_path);
+            if let Err(e) = spawn_rebuild(app, db, home_path, settings) {
+                error!("[ERROR] [indexer] Failed to resume rebuild: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => error!("[ERROR] [indexer] Failed to check for pending rebuild: {}", e),
+    }
+}
+
+fn emit_finished(app: &AppHandle, outcome: RebuildOutcome) {
+    if let Err(e) = app.emit("index:finished", outcome) {
+        error!("[ERROR] [indexer] Failed to emit index:finished: {}", e);
+    }
+}
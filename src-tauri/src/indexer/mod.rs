@@ -1,16 +1,88 @@
 //! Content indexer for search functionality
 
+pub mod code;
+pub mod jobs;
+
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn, error};
 
+use crate::attachments;
+use crate::autocomplete;
 use crate::db::Database;
-use crate::models::ContentIndexEntry;
+use crate::email;
+use crate::frontmatter;
+use crate::models::{ContentIndexEntry, IndexSettings, RebuildOutcome, TitleSource};
+use crate::notebook;
+use crate::sidecar;
+use crate::stats;
+use crate::structured;
 use crate::utils;
+use crate::vault;
+
+/// Global index settings state
+pub struct IndexSettingsState(pub Mutex<IndexSettings>);
+
+/// Settings key holding the IndexSettings saved by `set_index_settings`
+pub const SETTINGS_KEY: &str = "index_settings";
+
+/// Index settings saved in the database, or the defaults, with the title
+/// sources of the open vault
+pub fn saved_settings(db: &Database) -> IndexSettings {
+    let mut settings: IndexSettings = match db.setting_json(SETTINGS_KEY) {
+        Ok(settings) => settings.unwrap_or_default(),
+        Err(e) => {
+            error!("[ERROR] [indexer] Failed to load index settings: {}", e);
+            IndexSettings::default()
+        }
+    };
+    if let Some(root) = vault::root() {
+        settings.title_sources = saved_title_sources(db, &root);
+    }
+    settings
+}
+
+/// Settings key holding the title sources of the vault at `root`
+fn title_sources_key(root: &str) -> String {
+    format!("title_sources:{}", root)
+}
+
+/// Title sources saved for the vault at `root`, or the defaults
+pub fn saved_title_sources(db: &Database, root: &str) -> Vec<TitleSource> {
+    match db.setting_json(&title_sources_key(root)) {
+        Ok(sources) => sources.unwrap_or_else(|| IndexSettings::default().title_sources),
+        Err(e) => {
+            error!("[ERROR] [indexer] Failed to load title sources for {}: {}", root, e);
+            IndexSettings::default().title_sources
+        }
+    }
+}
+
+/// Save `settings`; its title sources are kept for the vault at `root` only
+/// (and not at all without one)
+pub fn save_settings(db: &Database, root: Option<&str>, settings: &IndexSettings) -> Result<(), String> {
+    if let Some(root) = root {
+        db.set_setting_json(&title_sources_key(root), &settings.title_sources)?;
+    }
+    let shared = IndexSettings { title_sources: IndexSettings::default().title_sources, ..settings.clone() };
+    db.set_setting_json(SETTINGS_KEY, &shared)
+}
+
+impl IndexSettingsState {
+    /// Snapshot settings so the lock isn't held across indexing
+    pub fn snapshot(&self) -> Result<IndexSettings, String> {
+        self.0.lock()
+            .map(|s| s.clone())
+            .map_err(|e| format!("Failed to acquire lock: {}", e))
+    }
+}
 
 /// Index a single file
-pub async fn index_file(db: &Database, path: &str) -> Result<(), String> {
+pub async fn index_file(db: &Database, path: &str, settings: &IndexSettings) -> Result<(), String> {
     let path_obj = Path::new(path);
 
     if !path_obj.exists() {
@@ -21,35 +93,210 @@ pub async fn index_file(db: &Database, path: &str) -> Result<(), String> {
         return Err(format!("Not a file: {}", path));
     }
 
-    let entry = parse_file(path)?;
+    let entry = parse_file(path, settings)?;
     db.index_content(&entry)?;
+    autocomplete::update(std::slice::from_ref(&entry));
 
     info!("[INFO] [indexer] Indexed: {}", path);
     Ok(())
 }
 
+/// Add the word delta of a note saved by the user to today's writing stats
+/// (not called for reindexes, imports or rebuilds, which aren't writing)
+pub fn record_writing(db: &Database, path: &str, previous: Option<&str>, current: &str) {
+    if !utils::is_document_file(path) {
+        return;
+    }
+    let before = previous.map(|text| stats::word_count(frontmatter::body(text))).unwrap_or(0) as i64;
+    let after = stats::word_count(frontmatter::body(current)) as i64;
+    let delta = after - before;
+    if delta == 0 {
+        return;
+    }
+
+    let (added, removed) = if delta > 0 { (delta as u64, 0) } else { (0, (-delta) as u64) };
+    if let Err(e) = db.record_writing(&stats::today(), added, removed) {
+        warn!("[WARN] [indexer] Failed to record writing stats: {}", e);
+    }
+}
+
+/// Index several files, committing them in batched transactions
+pub async fn index_files(db: &Database, paths: &[String], settings: &IndexSettings) -> Result<u32, String> {
+    let mut batch = IndexBatch::new(db);
+
+    for path in paths {
+        match parse_file(path, settings) {
+            Ok(entry) => batch.push(entry),
+            Err(e) => warn!("[WARN] [indexer] Failed to parse {}: {}", path, e),
+        }
+    }
+
+    let count = batch.finish();
+    info!("[INFO] [indexer] Indexed {} of {} files", count, paths.len());
+    Ok(count)
+}
+
 /// Rebuild the entire search index from a directory
-pub async fn rebuild_index(db: &Database, home_path: &str) -> Result<u32, String> {
-    info!("[INFO] [indexer] Starting full index rebuild from: {}", home_path);
+/// Resumes from the checkpoint if a previous rebuild of `home_path` was interrupted
+pub async fn rebuild_index(
+    db: &Database,
+    home_path: &str,
+    settings: &IndexSettings,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(u32) + Sync),
+) -> Result<RebuildOutcome, String> {
+    run_rebuild(db, home_path, settings, cancel, on_progress)
+}
 
-    // Clear existing index
-    db.clear_index()?;
+/// Blocking rebuild (used directly by background jobs)
+pub fn run_rebuild(
+    db: &Database,
+    home_path: &str,
+    settings: &IndexSettings,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(u32) + Sync),
+) -> Result<RebuildOutcome, String> {
+    let checkpoint = db.rebuild_checkpoint(home_path)?;
+
+    let done = match checkpoint {
+        Some(done) => {
+            info!("[INFO] [indexer] Resuming index rebuild from: {} ({} files already done)", home_path, done.len());
+            done
+        }
+        None => {
+            info!("[INFO] [indexer] Starting full index rebuild from: {}", home_path);
+
+            // Record the rebuild before clearing so an interruption resumes it
+            db.begin_rebuild(home_path, now_secs())?;
+            db.clear_index()?;
+            autocomplete::clear();
+            HashMap::new()
+        }
+    };
 
     // Recursively index all files
-    let count = index_directory(db, home_path)?;
+    let mut batch = IndexBatch::new(db).with_checkpoint(on_progress);
+    let mut walk = RebuildWalk {
+        settings,
+        cancel,
+        done: &done,
+        resumed: 0,
+    };
+    index_directory(&mut batch, home_path, &mut walk)?;
+    let resumed = walk.resumed;
+    let indexed = batch.finish() + resumed;
 
-    info!("[INFO] [indexer] Index rebuild complete: {} files indexed", count);
-    Ok(count)
+    let cancelled = cancel.is_cancelled();
+    if cancelled {
+        info!("[INFO] [indexer] Index rebuild cancelled after {} files; will resume later", indexed);
+    } else {
+        db.finish_rebuild()?;
+        info!("[INFO] [indexer] Index rebuild complete: {} files indexed", indexed);
+    }
+
+    Ok(RebuildOutcome {
+        home_path: home_path.to_string(),
+        indexed,
+        resumed,
+        cancelled,
+    })
 }
 
-/// Recursively index a directory
-fn index_directory(db: &Database, dir_path: &str) -> Result<u32, String> {
-    let mut count = 0u32;
+/// Number of files written per transaction
+const BATCH_SIZE: usize = 200;
+
+/// Accumulates parsed entries and writes them in batched transactions
+struct IndexBatch<'a> {
+    db: &'a Database,
+    pending: Vec<ContentIndexEntry>,
+    count: u32,
+    /// Rebuild mode: record checkpoints and report progress after each flush
+    on_progress: Option<&'a (dyn Fn(u32) + Sync)>,
+}
+
+impl<'a> IndexBatch<'a> {
+    fn new(db: &'a Database) -> Self {
+        Self {
+            db,
+            pending: Vec::with_capacity(BATCH_SIZE),
+            count: 0,
+            on_progress: None,
+        }
+    }
+
+    fn with_checkpoint(mut self, on_progress: &'a (dyn Fn(u32) + Sync)) -> Self {
+        self.on_progress = Some(on_progress);
+        self
+    }
+
+    fn push(&mut self, entry: ContentIndexEntry) {
+        self.pending.push(entry);
+        if self.pending.len() >= BATCH_SIZE {
+            self.flush();
+        }
+    }
+
+    /// Write pending entries; on failure retry one by one so a single bad
+    /// entry doesn't drop the whole batch
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let checkpoint = self.on_progress.is_some();
+        match self.db.index_content_batch(&self.pending, checkpoint) {
+            Ok(()) => {
+                self.count += self.pending.len() as u32;
+                autocomplete::update(&self.pending);
+            }
+            Err(e) => {
+                warn!("[WARN] [indexer] Batch write failed, retrying individually: {}", e);
+                for entry in &self.pending {
+                    let result = self.db.index_content_batch(std::slice::from_ref(entry), checkpoint);
+                    if let Err(e) = result {
+                        warn!("[WARN] [indexer] Failed to index {}: {}", entry.path, e);
+                    } else {
+                        self.count += 1;
+                        autocomplete::update(std::slice::from_ref(entry));
+                    }
+                }
+            }
+        }
+
+        self.pending.clear();
 
+        if let Some(on_progress) = self.on_progress {
+            on_progress(self.count);
+        }
+    }
+
+    /// Flush remaining entries and return the number indexed
+    fn finish(mut self) -> u32 {
+        self.flush();
+        self.count
+    }
+}
+
+/// Per-walk rebuild context
+struct RebuildWalk<'a> {
+    settings: &'a IndexSettings,
+    cancel: &'a CancellationToken,
+    /// Checkpointed paths -> mtime from an interrupted run
+    done: &'a HashMap<String, u64>,
+    /// Files skipped because the checkpoint already covers them
+    resumed: u32,
+}
+
+/// Recursively index a directory
+fn index_directory(batch: &mut IndexBatch, dir_path: &str, walk: &mut RebuildWalk) -> Result<(), String> {
     let entries = fs::read_dir(dir_path)
         .map_err(|e| format!("Failed to read directory {}: {}", dir_path, e))?;
 
     for entry in entries.flatten() {
+        if walk.cancel.is_cancelled() {
+            return Ok(());
+        }
+
         let path = entry.path();
         let name = entry.file_name().to_string_lossy().to_string();
 
@@ -60,36 +307,53 @@ fn index_directory(db: &Database, dir_path: &str) -> Result<u32, String> {
 
         if path.is_dir() {
             // Recurse into subdirectory
-            match index_directory(db, &path.to_string_lossy()) {
-                Ok(sub_count) => count += sub_count,
-                Err(e) => warn!("[WARN] [indexer] Failed to index directory {:?}: {}", path, e),
+            if let Err(e) = index_directory(batch, &path.to_string_lossy(), walk) {
+                warn!("[WARN] [indexer] Failed to index directory {:?}: {}", path, e);
             }
         } else if path.is_file() {
             let path_str = utils::normalize_path(&path.to_string_lossy());
 
             // Only index supported file types
-            if utils::is_module_file(&path_str) ||
-               utils::is_page_file(&path_str) ||
-               utils::is_document_file(&path_str) {
-                match parse_file(&path_str) {
-                    Ok(entry) => {
-                        if let Err(e) = db.index_content(&entry) {
-                            warn!("[WARN] [indexer] Failed to index {}: {}", path_str, e);
-                        } else {
-                            count += 1;
-                        }
+            if utils::is_content_file(&path_str) {
+                // Already indexed by the interrupted run and unchanged since
+                if let Some(done_mtime) = walk.done.get(&path_str) {
+                    if file_mtime_secs(&path) == Some(*done_mtime) {
+                        walk.resumed += 1;
+                        continue;
                     }
+                }
+
+                match parse_file(&path_str, walk.settings) {
+                    Ok(entry) => batch.push(entry),
                     Err(e) => warn!("[WARN] [indexer] Failed to parse {}: {}", path_str, e),
                 }
             }
         }
     }
 
-    Ok(count)
+    Ok(())
+}
+
+/// File modification time in seconds since UNIX epoch
+fn file_mtime_secs(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 /// Parse a file and create an index entry
-fn parse_file(path: &str) -> Result<ContentIndexEntry, String> {
+pub fn parse_file(path: &str, settings: &IndexSettings) -> Result<ContentIndexEntry, String> {
     let path_obj = Path::new(path);
 
     // Get file metadata
@@ -103,28 +367,89 @@ fn parse_file(path: &str) -> Result<ContentIndexEntry, String> {
         .map(|d| d.as_secs())
         .unwrap_or(0);
 
+    let created_at = metadata
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
     let indexed_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs())
         .unwrap_or(0);
 
+    if let Some(file) = sidecar::annotated_file(path) {
+        return parse_sidecar(path, file, modified_at, indexed_at);
+    }
+
+    // Don't pull huge files into memory; index them by file name only
+    if metadata.len() > settings.max_file_bytes {
+        warn!(
+            "[WARN] [indexer] {} is {} bytes (limit {}), indexing title only",
+            path,
+            metadata.len(),
+            settings.max_file_bytes
+        );
+        return Ok(ContentIndexEntry {
+            id: utils::note_uid(&vault::relative_path(path), None),
+            path: utils::normalize_path(path),
+            title: utils::path_to_title(path),
+            content_type: content_type_for(path).to_string(),
+            body: Some(String::new()),
+            modified_at,
+            indexed_at,
+            created_at,
+            truncated: true,
+            aliases: Vec::new(),
+            tags: Vec::new(),
+            properties: Vec::new(),
+            links: Vec::new(),
+        });
+    }
+
+    if email::is_email_file(path) {
+        return parse_email(path, settings, modified_at, indexed_at, created_at);
+    }
+
     // Read file content
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
-    // Determine content type
-    let content_type = if utils::is_module_file(path) {
-        "module"
-    } else if utils::is_page_file(path) {
-        "page"
-    } else {
-        "document"
-    };
+    let content_type = content_type_for(path);
 
     // Extract title and body based on content type
-    let (title, body) = extract_content(path, &content, content_type)?;
+    let (title, body) = extract_content(path, &content, content_type, settings)?;
 
-    let id = utils::path_to_id(path);
+    // Apply index size limits
+    let (body, truncated) = truncate_body(body, settings.body_limit_for(content_type));
+    if truncated {
+        info!("[INFO] [indexer] Body truncated for: {}", path);
+    }
+
+    // Data files and notebooks have no frontmatter; a leading YAML `---` isn't one
+    let uid_source = match content_type {
+        "data" | "notebook" => None,
+        _ => Some(content.as_str()),
+    };
+    let id = utils::note_uid(&vault::relative_path(path), uid_source);
+    let (aliases, tags, properties, links) = if content_type == "document" {
+        let metadata = frontmatter::parse(&content);
+        let mut links = note_links(&vault::relative_path(path), &content);
+        for mention in utils::extract_mentions(&content) {
+            let link = (utils::mention_key(&settings.people_folder, &mention.name), "mention".to_string());
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+        (
+            metadata.aliases.clone(),
+            utils::extract_tags(&content),
+            frontmatter::property_pairs(&metadata),
+            links,
+        )
+    } else {
+        Default::default()
+    };
 
     Ok(ContentIndexEntry {
         id,
@@ -134,15 +459,142 @@ fn parse_file(path: &str) -> Result<ContentIndexEntry, String> {
         body: Some(body),
         modified_at,
         indexed_at,
+        created_at,
+        truncated,
+        aliases,
+        tags,
+        properties,
+        links,
+    })
+}
+
+/// Index entry for the file a sidecar annotates, filed under that file's
+/// path so search results point at it
+fn parse_sidecar(path: &str, file: &str, modified_at: u64, indexed_at: u64) -> Result<ContentIndexEntry, String> {
+    let file_meta = fs::metadata(file).map_err(|e| format!("Annotated file {}: {}", file, e))?;
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let annotation = sidecar::parse(&content).map_err(|e| format!("Invalid sidecar {}: {}", path, e))?;
+
+    Ok(ContentIndexEntry {
+        id: utils::note_uid(&vault::relative_path(file), None),
+        path: utils::normalize_path(file),
+        title: file.rsplit(['/', '\\']).next().unwrap_or(file).to_string(),
+        content_type: "attachment".to_string(),
+        body: Some(sidecar::searchable_text(&annotation)),
+        modified_at,
+        indexed_at,
+        created_at: file_meta
+            .created()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        truncated: false,
+        aliases: Vec::new(),
+        tags: annotation.tags,
+        properties: Vec::new(),
+        links: Vec::new(),
     })
 }
 
+/// Index entry for an .eml message: headers and body text as the body, and
+/// sender, recipients, subject and date as properties
+fn parse_email(
+    path: &str,
+    settings: &IndexSettings,
+    modified_at: u64,
+    indexed_at: u64,
+    created_at: Option<u64>,
+) -> Result<ContentIndexEntry, String> {
+    let raw = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (message, _) = email::parse(&raw)?;
+    let (body, truncated) = truncate_body(email::searchable_text(&message), settings.body_limit_for("email"));
+
+    Ok(ContentIndexEntry {
+        id: utils::note_uid(&vault::relative_path(path), None),
+        path: utils::normalize_path(path),
+        title: email::title(path, &message),
+        content_type: "email".to_string(),
+        body: Some(body),
+        modified_at,
+        indexed_at,
+        created_at,
+        truncated,
+        aliases: Vec::new(),
+        tags: Vec::new(),
+        properties: email::properties(&message),
+        links: Vec::new(),
+    })
+}
+
+/// Outgoing links of a note as (link key, link type)
+/// Markdown links are resolved against the note's folder; those leaving
+/// the vault are dropped.
+fn note_links(relative_path: &str, content: &str) -> Vec<(String, String)> {
+    let note_dir = relative_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let mut links: Vec<(String, String)> = Vec::new();
+    for link in utils::extract_links(content) {
+        let (target, link_type) = if link.wiki {
+            (Some(link.target), "wiki")
+        } else {
+            let target = match link.target.strip_prefix('/') {
+                Some(absolute) => Some(absolute.to_string()),
+                None => attachments::resolve_relative(note_dir, &link.target),
+            };
+            (target, "markdown")
+        };
+        let Some(target) = target else { continue };
+        let link = (utils::link_key(&target), link_type.to_string());
+        if !link.0.is_empty() && !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+/// Index content type of a path
+fn content_type_for(path: &str) -> &'static str {
+    if utils::is_module_file(path) {
+        "module"
+    } else if utils::is_page_file(path) {
+        "page"
+    } else if utils::is_data_file(path) {
+        "data"
+    } else if utils::is_code_file(path) {
+        "code"
+    } else if notebook::is_notebook_file(path) {
+        "notebook"
+    } else if email::is_email_file(path) {
+        "email"
+    } else {
+        "document"
+    }
+}
+
+/// Cap body at `limit` bytes (on a char boundary)
+/// Returns the body and whether anything was cut
+fn truncate_body(mut body: String, limit: usize) -> (String, bool) {
+    if body.len() <= limit {
+        return (body, false);
+    }
+
+    let mut end = limit;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    body.truncate(end);
+
+    (body, true)
+}
+
 /// Extract title and body from file content
-fn extract_content(path: &str, content: &str, content_type: &str) -> Result<(String, String), String> {
+fn extract_content(path: &str, content: &str, content_type: &str, settings: &IndexSettings) -> Result<(String, String), String> {
     match content_type {
         "module" => extract_module_content(path, content),
-        "page" => extract_html_content(path, content),
-        "document" => extract_document_content(path, content),
+        "page" => extract_html_content(path, content, settings),
+        "document" => extract_document_content(path, content, settings),
+        "data" => extract_data_content(path, content),
+        "code" => extract_code_content(path, content),
+        "notebook" => extract_notebook_content(path, content),
         _ => Ok((utils::path_to_title(path), content.to_string())),
     }
 }
@@ -161,8 +613,12 @@ fn extract_module_content(path: &str, content: &str) -> Result<(String, String),
         }
     }
 
-    // For body, extract comments and string literals as searchable content
-    let body = extract_js_searchable_text(content);
+    // For body, symbols and JSDoc first, then comments and string literals
+    let body = format!(
+        "{} {}",
+        code::searchable_text(code::Language::JavaScript, content),
+        extract_js_searchable_text(content)
+    );
 
     Ok((title, body))
 }
@@ -204,16 +660,38 @@ fn extract_js_searchable_text(content: &str) -> String {
     searchable.join(" ")
 }
 
-/// Extract content from HTML page
-fn extract_html_content(path: &str, content: &str) -> Result<(String, String), String> {
-    let mut title = utils::path_to_title(path);
+/// Extract symbols and doc comments from a source file
+fn extract_code_content(path: &str, content: &str) -> Result<(String, String), String> {
+    let language = code::Language::from_path(path)
+        .ok_or_else(|| format!("Not a source file: {}", path))?;
+    Ok((utils::path_to_title(path), code::searchable_text(language, content)))
+}
+
+/// Extract markdown and code cells from a Jupyter notebook
+fn extract_notebook_content(path: &str, content: &str) -> Result<(String, String), String> {
+    let notebook = notebook::parse(content)?;
+    Ok((notebook::title(path, &notebook), notebook::searchable_text(&notebook)))
+}
 
-    // Try to extract title from <title> tag
-    if let Some(start) = content.find("<title>") {
-        if let Some(end) = content[start..].find("</title>") {
-            title = content[start + 7..start + end].trim().to_string();
+/// Extract content from a JSON/YAML/TOML data file
+/// Unparseable files are still indexed, by file name and raw text
+fn extract_data_content(path: &str, content: &str) -> Result<(String, String), String> {
+    let parsed = structured::Format::from_path(path)
+        .ok_or_else(|| format!("Not a data file: {}", path))
+        .and_then(|format| structured::parse(format, content));
+
+    match parsed {
+        Ok(data) => Ok((structured::title(path, &data), structured::searchable_text(&data))),
+        Err(e) => {
+            warn!("[WARN] [indexer] Failed to parse data file {}: {}", path, e);
+            Ok((utils::path_to_title(path), content.to_string()))
         }
     }
+}
+
+/// Extract content from HTML page
+fn extract_html_content(path: &str, content: &str, settings: &IndexSettings) -> Result<(String, String), String> {
+    let title = utils::note_title(path, content, &settings.title_sources);
 
     // Strip HTML tags for body
     let body = strip_html_tags(content);
@@ -242,28 +720,32 @@ fn strip_html_tags(html: &str) -> String {
 }
 
 /// Extract content from markdown/text document
-fn extract_document_content(path: &str, content: &str) -> Result<(String, String), String> {
-    let mut title = utils::path_to_title(path);
+fn extract_document_content(path: &str, content: &str, settings: &IndexSettings) -> Result<(String, String), String> {
+    let title = utils::note_title(path, content, &settings.title_sources);
+    Ok((title, content.to_string()))
+}
 
-    // Try to extract title from first heading or YAML frontmatter
-    let lines: Vec<&str> = content.lines().collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_body() {
+        assert_eq!(truncate_body("hello".to_string(), 10), ("hello".to_string(), false));
+        assert_eq!(truncate_body("hello".to_string(), 3), ("hel".to_string(), true));
+        assert_eq!(truncate_body("hello".to_string(), 0), (String::new(), true));
+        // Never split a multi-byte character
+        assert_eq!(truncate_body("日本語".to_string(), 4), ("日".to_string(), true));
+    }
 
-    for line in &lines {
-        let trimmed = line.trim();
+    #[test]
+    fn test_body_limit_for() {
+        let mut settings = IndexSettings::default();
+        settings.type_max_body_bytes.insert("page".to_string(), 100);
+        settings.skip_body_types.push("module".to_string());
 
-        // Markdown heading
-        if trimmed.starts_with("# ") {
-            title = trimmed[2..].trim().to_string();
-            break;
-        }
-
-        // YAML frontmatter title
-        if trimmed.starts_with("title:") {
-            let value = trimmed[6..].trim().trim_matches(['\'', '"']);
-            title = value.to_string();
-            break;
-        }
+        assert_eq!(settings.body_limit_for("document"), settings.max_body_bytes);
+        assert_eq!(settings.body_limit_for("page"), 100);
+        assert_eq!(settings.body_limit_for("module"), 0);
     }
-
-    Ok((title, content.to_string()))
 }
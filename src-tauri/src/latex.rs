@@ -0,0 +1,528 @@
+//! LaTeX Export - Academic notes as .tex sources
+//!
+//! Math (`$...$`, `$$...$$`) passes through untouched, and pandoc-style
+//! citations (`[@key]`, `[see @key, p. 4]`, `[@a; @b]`) become natbib
+//! `\cite` commands. Math and citations are swapped for placeholders before
+//! the markdown is parsed, so emphasis rules can't mangle them; everything
+//! else is rendered from pulldown-cmark events with LaTeX special characters
+//! escaped. The result fills a template's `$body$` slot, along with
+//! `$title$`, `$author$`, `$date$` and `$bibliography$` from the frontmatter.
+//! A leading level-1 heading that is the note's only one becomes the title.
+
+use pulldown_cmark::{Alignment, Event, Options, Parser, Tag};
+use serde_json::Value as JsonValue;
+
+use crate::attachments;
+use crate::embeds::NoteResolver;
+use crate::frontmatter;
+use crate::utils;
+
+/// Template used when none is given
+pub const DEFAULT_TEMPLATE: &str = r"\documentclass[11pt]{article}
+\usepackage[utf8]{inputenc}
+\usepackage[T1]{fontenc}
+\usepackage{amsmath,amssymb}
+\usepackage{graphicx}
+\usepackage[normalem]{ulem}
+\usepackage[numbers]{natbib}
+\usepackage{hyperref}
+
+\title{$title$}
+\author{$author$}
+\date{$date$}
+
+\begin{document}
+\maketitle
+
+$body$
+$bibliography$
+\end{document}
+";
+
+/// Brackets a placeholder index (private-use characters, never in notes)
+const TOKEN_START: char = '\u{E000}';
+const TOKEN_END: char = '\u{E001}';
+
+/// Sectioning commands by heading level (after the title shift)
+const SECTIONS: [&str; 6] = ["section", "subsection", "subsubsection", "paragraph", "subparagraph", "subparagraph"];
+
+/// A rendered .tex document and the citation keys it uses
+pub struct Exported {
+    pub tex: String,
+    pub citations: Vec<String>,
+}
+
+/// Render the note at vault-relative `note_path` into `template`
+/// Image paths are written relative to the note's folder.
+pub fn export(content: &str, note_path: &str, files: &[String], template: &str) -> Result<Exported, String> {
+    if !template.contains("$body$") {
+        return Err("The LaTeX template has no $body$ placeholder".to_string());
+    }
+    let metadata = frontmatter::parse(content);
+    let body = frontmatter::body(content);
+
+    let outline = utils::outline(body);
+    let heading_title = match outline.first() {
+        Some(first) if first.level == 1 && outline.iter().filter(|h| h.level == 1).count() == 1 => Some(first.text.clone()),
+        _ => None,
+    };
+
+    let mut protected = Protected::default();
+    let markdown = protected.extract(body);
+
+    let resolver = NoteResolver::new(files);
+    let mut writer = Writer {
+        out: String::new(),
+        note_path,
+        resolver: &resolver,
+        protected: &protected,
+        shift: usize::from(heading_title.is_some()),
+        skip_heading: heading_title.is_some(),
+        in_heading: false,
+        lists: Vec::new(),
+        table: None,
+        in_link: false,
+        image: None,
+        code_block: false,
+    };
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(&markdown, options) {
+        writer.event(event);
+    }
+    let body_tex = writer.out.trim().to_string();
+
+    let title = metadata.title.clone().or(heading_title).unwrap_or_else(|| utils::path_to_title(note_path));
+    let property = |key: &str| metadata.properties.get(key);
+    let author = match property("author").or_else(|| property("authors")) {
+        Some(JsonValue::Array(names)) => names.iter().filter_map(JsonValue::as_str).map(escape).collect::<Vec<_>>().join(r" \and "),
+        Some(JsonValue::String(name)) => escape(name),
+        _ => String::new(),
+    };
+    let date = property("date").and_then(JsonValue::as_str).map(str::to_string).or(metadata.created.clone());
+    let bibliography = match property("bibliography").and_then(JsonValue::as_str) {
+        Some(bib) => format!("\\bibliographystyle{{plainnat}}\n\\bibliography{{{}}}\n", bib.trim_end_matches(".bib")),
+        None => String::new(),
+    };
+
+    let tex = template
+        .replace("$title$", &escape(&title))
+        .replace("$author$", &author)
+        .replace("$date$", &date.map(|d| escape(&d)).unwrap_or_default())
+        .replace("$bibliography$", &bibliography)
+        .replace("$body$", &body_tex);
+
+    let mut citations = protected.citations;
+    citations.sort();
+    citations.dedup();
+    Ok(Exported { tex, citations })
+}
+
+/// Escape LaTeX special characters in plain text
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str(r"\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str(r"\textasciicircum{}"),
+            '~' => out.push_str(r"\textasciitilde{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Math and citations pulled out of the markdown, as ready LaTeX
+#[derive(Default)]
+struct Protected {
+    latex: Vec<String>,
+    citations: Vec<String>,
+}
+
+impl Protected {
+    fn token(&mut self, latex: String) -> String {
+        self.latex.push(latex);
+        format!("{}{}{}", TOKEN_START, self.latex.len() - 1, TOKEN_END)
+    }
+
+    /// Replace math and citations outside code with placeholders
+    fn extract(&mut self, body: &str) -> String {
+        let mut out = String::with_capacity(body.len());
+        let mut in_fence = false;
+        let mut rest = body;
+
+        while !rest.is_empty() {
+            let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            let line = &rest[..line_end];
+            if utils::is_code_fence(line) {
+                in_fence = !in_fence;
+                out.push_str(line);
+                rest = &rest[line_end..];
+                continue;
+            }
+            if in_fence {
+                out.push_str(line);
+                rest = &rest[line_end..];
+                continue;
+            }
+            rest = self.extract_line(rest, line_end, &mut out);
+        }
+        out
+    }
+
+    /// Copy the line starting `rest` (up to `line_end`) to `out`, replacing
+    /// math and citations; display math may run past the line. Returns what
+    /// is left after the consumed text.
+    fn extract_line<'a>(&mut self, rest: &'a str, line_end: usize, out: &mut String) -> &'a str {
+        let mut i = 0;
+        while i < line_end {
+            let tail = &rest[i..];
+            if tail.starts_with('\\') && tail.len() > 1 {
+                let len = 1 + tail[1..].chars().next().map_or(0, char::len_utf8);
+                out.push_str(&tail[..len]);
+                i += len;
+            } else if tail.starts_with('`') {
+                // Code span: copy through the matching backtick run
+                let ticks = tail.len() - tail.trim_start_matches('`').len();
+                let close = tail[ticks..line_end - i].find(&tail[..ticks]).map(|p| ticks + p + ticks);
+                let len = close.unwrap_or(ticks);
+                out.push_str(&tail[..len]);
+                i += len;
+            } else if let Some(math) = tail.strip_prefix("$$") {
+                match math.find("$$") {
+                    Some(end) => {
+                        let token = self.token(format!("\\[{}\\]", math[..end].trim()));
+                        out.push_str(&token);
+                        // May continue past this line
+                        return &rest[i + 2 + end + 2..];
+                    }
+                    None => {
+                        out.push_str("$$");
+                        i += 2;
+                    }
+                }
+            } else if let Some(math) = tail[..line_end - i].strip_prefix('$').and_then(inline_math) {
+                let token = self.token(format!("${}$", math));
+                out.push_str(&token);
+                i += math.len() + 2;
+            } else if let Some((citation, len)) = tail[..line_end - i].strip_prefix("[").and_then(citation) {
+                self.citations.extend(citation.keys.iter().cloned());
+                let token = self.token(citation.latex());
+                out.push_str(&token);
+                i += len + 1;
+            } else {
+                let len = tail.chars().next().map_or(1, char::len_utf8);
+                out.push_str(&tail[..len]);
+                i += len;
+            }
+        }
+        &rest[line_end..]
+    }
+}
+
+/// Inline math after an opening `$`: no space just inside either `$`, and
+/// the closing `$` not followed by a digit (so "$5 and $6" isn't math)
+fn inline_math(after: &str) -> Option<&str> {
+    if after.starts_with(char::is_whitespace) || after.starts_with('$') {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in after.char_indices() {
+        match c {
+            '\\' => escaped = !escaped,
+            '$' if !escaped => {
+                let math = &after[..i];
+                let next_is_digit = after[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+                return (!math.is_empty() && !math.ends_with(char::is_whitespace) && !next_is_digit).then_some(math);
+            }
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// A `[prefix @key, locator; ...]` citation group
+struct Citation {
+    keys: Vec<String>,
+    prefix: String,
+    locator: String,
+}
+
+impl Citation {
+    fn latex(&self) -> String {
+        let keys = self.keys.join(",");
+        match (self.prefix.is_empty(), self.locator.is_empty()) {
+            (true, true) => format!("\\cite{{{}}}", keys),
+            (true, false) => format!("\\cite[{}]{{{}}}", escape(&self.locator), keys),
+            (false, _) => format!("\\cite[{}][{}]{{{}}}", escape(&self.prefix), escape(&self.locator), keys),
+        }
+    }
+}
+
+/// Parse a citation after its `[`; returns it and the length through `]`
+/// With several keys, the first prefix and locator given are kept.
+fn citation(after: &str) -> Option<(Citation, usize)> {
+    let end = after.find(']')?;
+    let inner = &after[..end];
+    if !inner.contains('@') || after[end + 1..].starts_with(['(', '[']) {
+        return None;
+    }
+
+    let mut citation = Citation { keys: Vec::new(), prefix: String::new(), locator: String::new() };
+    for (n, part) in inner.split(';').enumerate() {
+        let (prefix, cite) = part.split_once('@')?;
+        // "[mail me@example.com]" isn't a citation
+        if !prefix.is_empty() && !prefix.ends_with(char::is_whitespace) {
+            return None;
+        }
+        let key_len = cite
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '/')))
+            .unwrap_or(cite.len());
+        let key = cite[..key_len].trim_end_matches(['.', ':']);
+        if key.is_empty() {
+            return None;
+        }
+        citation.keys.push(key.to_string());
+        if n == 0 {
+            citation.prefix = prefix.trim().to_string();
+        }
+        if citation.locator.is_empty() {
+            citation.locator = cite[key.len()..].trim_start_matches(',').trim().to_string();
+        }
+    }
+    Some((citation, end + 1))
+}
+
+/// Renders parser events as LaTeX
+struct Writer<'a> {
+    out: String,
+    note_path: &'a str,
+    resolver: &'a NoteResolver<'a>,
+    protected: &'a Protected,
+    /// Levels to promote headings by (1 when the H1 is the title)
+    shift: usize,
+    /// The title heading is still to come and is dropped
+    skip_heading: bool,
+    in_heading: bool,
+    /// Whether each open list is numbered
+    lists: Vec<bool>,
+    /// Column alignments and the cell index in the current row
+    table: Option<(Vec<Alignment>, usize)>,
+    /// An `\href` is open
+    in_link: bool,
+    /// Image source and alt text
+    image: Option<(String, String)>,
+    code_block: bool,
+}
+
+impl Writer<'_> {
+    fn event(&mut self, event: Event) {
+        if self.skip_heading && self.in_heading && !matches!(event, Event::End(Tag::Heading(..))) {
+            return;
+        }
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => self.out.push_str(&text),
+            Event::Text(text) => match self.image.as_mut() {
+                Some((_, alt)) => alt.push_str(&text),
+                None => {
+                    let tex = self.text(&text);
+                    self.out.push_str(&tex);
+                }
+            },
+            Event::Code(code) => self.out.push_str(&format!("\\texttt{{{}}}", escape(&code))),
+            Event::SoftBreak => self.out.push('\n'),
+            Event::HardBreak => self.out.push_str("\\\\\n"),
+            Event::Rule => self.out.push_str("\n\\noindent\\rule{\\linewidth}{0.4pt}\n\n"),
+            Event::TaskListMarker(checked) => self.out.push_str(if checked { "$\\boxtimes$ " } else { "$\\square$ " }),
+            Event::FootnoteReference(label) => self.out.push_str(&escape(&format!("[^{}]", label))),
+            Event::Html(_) => {}
+        }
+    }
+
+    /// Escaped text with placeholders restored
+    fn text(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(TOKEN_START) {
+            out.push_str(&escape(&rest[..start]));
+            let after = &rest[start + TOKEN_START.len_utf8()..];
+            let Some(end) = after.find(TOKEN_END) else { break };
+            if let Some(latex) = after[..end].parse::<usize>().ok().and_then(|i| self.protected.latex.get(i)) {
+                out.push_str(latex);
+            }
+            rest = &after[end + TOKEN_END.len_utf8()..];
+        }
+        out.push_str(&escape(rest));
+        out
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {}
+            Tag::Heading(level, _, _) => {
+                self.in_heading = true;
+                if !self.skip_heading {
+                    let index = (level as usize).saturating_sub(1 + self.shift).min(SECTIONS.len() - 1);
+                    self.out.push_str(&format!("\\{}{{", SECTIONS[index]));
+                }
+            }
+            Tag::BlockQuote => self.out.push_str("\\begin{quote}\n"),
+            Tag::CodeBlock(_) => {
+                self.code_block = true;
+                self.out.push_str("\\begin{verbatim}\n");
+            }
+            Tag::List(start) => {
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.lists.push(start.is_some());
+                match start {
+                    Some(start) => {
+                        self.out.push_str("\\begin{enumerate}\n");
+                        if start != 1 && self.lists.len() <= 4 {
+                            let counter = ["i", "ii", "iii", "iv"][self.lists.len() - 1];
+                            self.out.push_str(&format!("\\setcounter{{enum{}}}{{{}}}\n", counter, start.saturating_sub(1)));
+                        }
+                    }
+                    None => self.out.push_str("\\begin{itemize}\n"),
+                }
+            }
+            Tag::Item => self.out.push_str("\\item "),
+            Tag::FootnoteDefinition(_) => {}
+            Tag::Table(alignments) => {
+                let columns: String = alignments
+                    .iter()
+                    .map(|a| match a {
+                        Alignment::Center => 'c',
+                        Alignment::Right => 'r',
+                        _ => 'l',
+                    })
+                    .collect();
+                self.out.push_str(&format!("\\begin{{tabular}}{{{}}}\n\\hline\n", columns));
+                self.table = Some((alignments, 0));
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some((_, cell)) = self.table.as_mut() {
+                    *cell = 0;
+                }
+            }
+            Tag::TableCell => {
+                if let Some((_, cell)) = self.table.as_mut() {
+                    if *cell > 0 {
+                        self.out.push_str(" & ");
+                    }
+                    *cell += 1;
+                }
+            }
+            Tag::Emphasis => self.out.push_str("\\emph{"),
+            Tag::Strong => self.out.push_str("\\textbf{"),
+            Tag::Strikethrough => self.out.push_str("\\sout{"),
+            Tag::Link(_, url, _) => {
+                // Links to notes and files only mean something in the vault
+                self.in_link = url.contains("://") || url.starts_with("mailto:");
+                if self.in_link {
+                    self.out.push_str(&format!("\\href{{{}}}{{", url.replace('%', "\\%").replace('#', "\\#")));
+                }
+            }
+            Tag::Image(_, url, _) => self.image = Some((url.to_string(), String::new())),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.out.push_str("\n\n"),
+            Tag::Heading(..) => {
+                self.in_heading = false;
+                if self.skip_heading {
+                    self.skip_heading = false;
+                } else {
+                    self.out.push_str("}\n\n");
+                }
+            }
+            Tag::BlockQuote => self.out.push_str("\\end{quote}\n\n"),
+            Tag::CodeBlock(_) => {
+                self.code_block = false;
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("\\end{verbatim}\n\n");
+            }
+            Tag::List(_) => {
+                let numbered = self.lists.pop().unwrap_or(false);
+                self.out.push_str(if numbered { "\\end{enumerate}\n" } else { "\\end{itemize}\n" });
+                if self.lists.is_empty() {
+                    self.out.push('\n');
+                }
+            }
+            Tag::Item => {
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+            }
+            Tag::FootnoteDefinition(_) => {}
+            Tag::Table(_) => {
+                self.out.push_str("\\hline\n\\end{tabular}\n\n");
+                self.table = None;
+            }
+            Tag::TableHead => self.out.push_str(" \\\\\n\\hline\n"),
+            Tag::TableRow => self.out.push_str(" \\\\\n"),
+            Tag::TableCell => {}
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => self.out.push('}'),
+            Tag::Link(..) => {
+                if self.in_link {
+                    self.in_link = false;
+                    self.out.push('}');
+                }
+            }
+            Tag::Image(..) => {
+                let Some((src, alt)) = self.image.take() else { return };
+                let note_dir = self.note_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+                match self.resolver.markdown_link(self.note_path, &src) {
+                    Some(path) if !src.contains("://") => {
+                        let relative = attachments::relative_path(note_dir, path);
+                        self.out.push_str(&format!("\\includegraphics[width=0.8\\linewidth]{{{}}}", relative));
+                    }
+                    _ => {
+                        let tex = self.text(&format!("[{}]", alt));
+                        self.out.push_str(&tex);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let note = "---\nauthor: [Ada Lovelace, Alan Turing]\nbibliography: refs.bib\n---\n\
+            # On Engines\n\n## Intro\n\nCosts 5% of $x_1 + y_1$, not $5 and $6.\n\n\
+            $$\n\\sum_{i} a_i\n$$\n\nAs shown [see @babbage1864, p. 4; @menabrea1842].\n\n\
+            - *one* `a_b`\n\n![fig](img/engine.png)\n";
+        let files = vec!["Papers/img/engine.png".to_string()];
+        let exported = export(note, "Papers/Engines.md", &files, DEFAULT_TEMPLATE).unwrap();
+        let tex = &exported.tex;
+
+        assert!(tex.contains("\\title{On Engines}"));
+        assert!(tex.contains("\\author{Ada Lovelace \\and Alan Turing}"));
+        assert!(tex.contains("\\section{Intro}"));
+        assert!(!tex.contains("\\section{On Engines}"));
+        assert!(tex.contains("Costs 5\\% of $x_1 + y_1$, not \\$5 and \\$6."));
+        assert!(tex.contains("\\[\\sum_{i} a_i\\]"));
+        assert!(tex.contains("As shown \\cite[see][p. 4]{babbage1864,menabrea1842}."));
+        assert!(tex.contains("\\item \\emph{one} \\texttt{a\\_b}"));
+        assert!(tex.contains("\\includegraphics[width=0.8\\linewidth]{img/engine.png}"));
+        assert!(tex.contains("\\bibliography{refs}"));
+        assert_eq!(exported.citations, vec!["babbage1864", "menabrea1842"]);
+
+        assert!(export(note, "Engines.md", &files, "no body").is_err());
+    }
+}
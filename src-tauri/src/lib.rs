@@ -3,45 +3,119 @@
 //! Rust backend for the Unstablon Personal Knowledge Management application.
 //! Provides file operations, SQLite indexing, and IPC commands.
 
+mod access;
+mod attachments;
+mod autocomplete;
+mod backup;
+mod calendar;
+mod collation;
 mod commands;
 mod db;
+mod deeplink;
+mod docx;
+mod email;
+mod embeds;
 mod error;
+mod error_log;
+mod footnotes;
+mod formatter;
+mod frontmatter;
+mod headings;
 mod indexer;
+mod latex;
+mod lint;
+mod logging;
+mod merge;
+mod metrics;
 mod models;
+mod notebook;
+mod opml;
+mod platform;
+mod query;
+mod scheduler;
+mod secrets;
+mod sidecar;
+mod stats;
+mod structured;
+mod suggest;
+mod synonyms;
+mod tables;
+mod textdiff;
+mod thumbnails;
+mod undo;
 mod utils;
+mod vault;
+mod vault_key;
 mod watcher;
+mod webpage;
 mod write_tracker;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tauri::{Emitter, Manager, WindowEvent};
-use tracing::info;
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// Hot paths exposed for the criterion benches in `benches/`
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::indexer::parse_file;
+    pub use crate::models::IndexSettings;
+}
+
 use db::DbState;
+use indexer::IndexSettingsState;
 
 /// Initialize and run the Tauri application
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize logging
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "unstablon_pkm=info".into()),
-        )
+        .with(logging::filter_layer())
         .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(logging::FileWriter))
+        .with(error_log::ErrorLogLayer)
         .init();
 
     info!("[INFO] [lib] Starting Unstablon PKM");
 
-    tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+
+    // A second launch (e.g. opening an unstablon:// link on Windows/Linux)
+    // hands its arguments to this instance; the deep-link feature forwards
+    // the URL to the deep-link plugin. Must be registered first.
+    #[cfg(desktop)]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }));
+    }
+
+    builder
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             info!("[INFO] [lib] Application setup starting");
 
             // Initialize database synchronously during setup
             let app_handle = app.handle().clone();
 
+            // Forward severe backend errors to the frontend
+            error_log::set_app_handle(app_handle.clone());
+
+            // Start writing log files (stdout alone is invisible in packaged builds)
+            match app_handle.path().app_log_dir() {
+                Ok(log_dir) => {
+                    if let Err(e) = logging::init(&log_dir) {
+                        error!("[ERROR] [lib] {}", e);
+                    }
+                }
+                Err(e) => error!("[ERROR] [lib] Failed to get app log dir: {}", e),
+            }
+
             // Get app data directory
             let app_data_dir = app_handle.path()
                 .app_data_dir()
@@ -67,11 +141,22 @@ pub fn run() {
                     type TEXT NOT NULL,
                     body TEXT,
                     modified_at INTEGER,
-                    indexed_at INTEGER
+                    indexed_at INTEGER,
+                    truncated INTEGER NOT NULL DEFAULT 0,
+                    created_at INTEGER
                 )",
                 [],
             ).expect("Failed to create content table");
 
+            // Databases created before index size limits lack the truncated column
+            conn.execute(
+                "ALTER TABLE content ADD COLUMN truncated INTEGER NOT NULL DEFAULT 0",
+                [],
+            ).ok();
+
+            // ... and before activity heatmaps, the created_at column
+            conn.execute("ALTER TABLE content ADD COLUMN created_at INTEGER", []).ok();
+
             conn.execute(
                 "CREATE TABLE IF NOT EXISTS links (
                     id INTEGER PRIMARY KEY,
@@ -94,6 +179,126 @@ pub fn run() {
                 [],
             ).expect("Failed to create tags table");
 
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS rebuild_checkpoint (
+                    path TEXT PRIMARY KEY,
+                    modified_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create rebuild_checkpoint table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS rebuild_state (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    home_path TEXT NOT NULL,
+                    started_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create rebuild_state table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS note_aliases (
+                    note_id TEXT NOT NULL,
+                    alias TEXT NOT NULL,
+                    PRIMARY KEY (note_id, alias)
+                )",
+                [],
+            ).expect("Failed to create note_aliases table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS note_properties (
+                    note_id TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    value TEXT NOT NULL
+                )",
+                [],
+            ).expect("Failed to create note_properties table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS pinned_notes (
+                    path TEXT PRIMARY KEY,
+                    position INTEGER NOT NULL,
+                    pinned_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create pinned_notes table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS folder_order (
+                    folder TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    position INTEGER NOT NULL,
+                    PRIMARY KEY (folder, name)
+                )",
+                [],
+            ).expect("Failed to create folder_order table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS writing_daily (
+                    day TEXT PRIMARY KEY,
+                    words_added INTEGER NOT NULL DEFAULT 0,
+                    words_removed INTEGER NOT NULL DEFAULT 0
+                )",
+                [],
+            ).expect("Failed to create writing_daily table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS note_opens (
+                    path TEXT NOT NULL,
+                    opened_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create note_opens table");
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_note_opens_time ON note_opens(opened_at)",
+                [],
+            ).ok();
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS link_previews (
+                    url TEXT PRIMARY KEY,
+                    title TEXT,
+                    description TEXT,
+                    image TEXT,
+                    favicon TEXT,
+                    fetched_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create link_previews table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS vault_keys (
+                    home_path TEXT PRIMARY KEY,
+                    salt BLOB NOT NULL,
+                    nonce BLOB NOT NULL,
+                    wrapped_key BLOB NOT NULL,
+                    m_cost INTEGER NOT NULL,
+                    t_cost INTEGER NOT NULL,
+                    p_cost INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                )",
+                [],
+            ).expect("Failed to create vault_keys table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS scheduled_task_runs (
+                    task_id TEXT PRIMARY KEY,
+                    ran_at INTEGER NOT NULL,
+                    ok INTEGER NOT NULL,
+                    message TEXT NOT NULL
+                )",
+                [],
+            ).expect("Failed to create scheduled_task_runs table");
+
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS app_settings (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL
+                )",
+                [],
+            ).expect("Failed to create app_settings table");
+
             conn.execute(
                 "CREATE INDEX IF NOT EXISTS idx_content_path ON content(path)",
                 [],
@@ -114,6 +319,16 @@ pub fn run() {
                 [],
             ).ok();
 
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_note_properties_key ON note_properties(key, value)",
+                [],
+            ).ok();
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_note_properties_note ON note_properties(note_id)",
+                [],
+            ).ok();
+
             conn.execute(
                 "CREATE VIRTUAL TABLE IF NOT EXISTS content_fts USING fts5(
                     title,
@@ -126,37 +341,228 @@ pub fn run() {
 
             // Create database wrapper and manage state
             let database = Arc::new(db::Database::from_connection(conn));
-            app.manage(DbState(database));
+            app.manage(DbState(database.clone()));
+
+            // Indexes built before paths were compared in NFC can hold the
+            // same file twice (macOS NFD name and NFC name)
+            match database.merge_unicode_duplicates() {
+                Ok(0) => {}
+                Ok(count) => info!("[INFO] [lib] Merged {} index entries duplicated under NFD/NFC paths", count),
+                Err(e) => error!("[ERROR] [lib] Failed to merge duplicate index entries: {}", e),
+            }
+            // Settings saved in earlier sessions
+            if let Err(e) = database.load_ranking() {
+                error!("[ERROR] [lib] Failed to load search ranking: {}", e);
+            }
+            commands::fileops::restore_settings(&database);
+            commands::notes::restore_settings(&database);
+            commands::access::restore_settings(&database);
+            commands::security::restore_settings(&database);
+            scheduler::restore_settings(&database);
+
+            // Reopen the vault picked in the last session, if it still exists
+            match database.setting(commands::fileops::VAULT_ROOT_SETTING) {
+                Ok(Some(root)) if std::path::Path::new(&root).is_dir() => {
+                    info!("[INFO] [lib] Restoring vault: {}", root);
+                    vault::set_root(&root);
+                }
+                Ok(Some(root)) => warn!("[WARN] [lib] Last vault is no longer accessible: {}", root),
+                Ok(None) => {}
+                Err(e) => error!("[ERROR] [lib] Failed to load last vault: {}", e),
+            }
+            // Title sources are per vault, so loaded after the vault is restored
+            let index_settings = indexer::saved_settings(&database);
+            app.manage(IndexSettingsState(Mutex::new(index_settings.clone())));
+
+            // Load autocomplete terms from the existing index
+            let autocomplete_db = database.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match autocomplete::load(&autocomplete_db) {
+                    Ok(count) => info!("[INFO] [lib] Autocomplete loaded from {} indexed notes", count),
+                    Err(e) => error!("[ERROR] [lib] Failed to load autocomplete: {}", e),
+                }
+            });
+
+            // unstablon:// links, both the one that launched the app and later ones
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                // Installers register the scheme; dev builds and unregistered
+                // AppImages need it done at runtime
+                #[cfg(all(debug_assertions, any(windows, target_os = "linux")))]
+                if let Err(e) = app.deep_link().register_all() {
+                    error!("[ERROR] [lib] Failed to register {}:// scheme: {}", deeplink::SCHEME, e);
+                }
+
+                let link_handle = app_handle.clone();
+                app.deep_link().on_open_url(move |event| deeplink::handle_urls(&link_handle, event.urls()));
+                match app.deep_link().get_current() {
+                    Ok(Some(urls)) => deeplink::handle_urls(&app_handle, urls),
+                    Ok(None) => {}
+                    Err(e) => error!("[ERROR] [lib] Failed to read launch URL: {}", e),
+                }
+            }
+
+            // Nightly optimize, scheduled backups and auto-commits
+            scheduler::start(database.clone());
+
+            // Pick up an index rebuild interrupted by the last exit
+            indexer::jobs::resume_pending(app_handle.clone(), database, index_settings);
 
             info!("[INFO] [lib] Application setup complete");
 
             // Intercept OS window close button to allow frontend to handle unsaved changes
             let main_window = app.get_webview_window("main")
                 .expect("Failed to get main window");
+            if let Ok(scale_factor) = main_window.scale_factor() {
+                platform::init_scale_factor(scale_factor);
+            }
+            platform::refresh_os_preferences(app.handle().clone());
             let app_handle = app.handle().clone();
             main_window.on_window_event(move |event| {
+                // Moving between monitors changes DPI; let the frontend re-measure
+                if let WindowEvent::ScaleFactorChanged { scale_factor, .. } = event {
+                    platform::on_scale_factor_changed(&app_handle, *scale_factor);
+                }
+                // OS preferences may have changed while the app was in the background
+                if matches!(event, WindowEvent::Focused(true) | WindowEvent::ThemeChanged(_)) {
+                    platform::refresh_os_preferences(app_handle.clone());
+                }
                 if let WindowEvent::CloseRequested { api, .. } = event {
                     // Prevent default close so frontend can show unsaved-changes modal
                     api.prevent_close();
                     // Notify frontend to decide whether to show confirmation or close directly
-                    app_handle
-                        .get_webview_window("main")
-                        .map(|w| w.emit("close-requested", ()));
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if let Err(e) = window.emit("close-requested", ()) {
+                            error!("[ERROR] [lib] Failed to emit close-requested: {}", e);
+                        }
+                    }
                 }
             });
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            commands::assets::get_thumbnail,
+            commands::assets::find_duplicate_attachments,
+            commands::assets::deduplicate_attachments,
+            commands::assets::import_external_file,
+            commands::assets::convert_email_to_note,
+            commands::assets::get_file_annotation,
+            commands::assets::set_file_annotation,
             commands::fileops::read_file,
+            commands::fileops::get_read_limit,
+            commands::fileops::set_read_limit,
+            commands::fileops::read_file_ex,
+            commands::fileops::read_structured,
+            commands::fileops::read_notebook,
+            commands::fileops::read_email,
             commands::fileops::write_file,
+            commands::fileops::write_file_checked,
             commands::fileops::list_directory,
+            commands::fileops::get_sort_settings,
+            commands::fileops::set_sort_settings,
+            commands::fileops::preview_rename,
+            commands::fileops::diff_texts,
+            commands::fileops::get_undo_operation,
+            commands::fileops::undo_last_operation,
+            commands::fileops::open_vault,
+            commands::fileops::get_vault,
             commands::fileops::get_navigation_tree,
+            commands::fileops::filter_navigation_tree,
+            commands::fileops::set_folder_order,
             commands::fileops::get_file_mtime,
+            commands::fileops::get_file_mtimes,
             commands::fileops::start_watching_vault,
             commands::search::search_content,
+            commands::search::run_query_block,
+            commands::search::suggest_links,
+            commands::search::autocomplete,
+            commands::search::autocomplete_tags,
+            commands::search::autocomplete_property_values,
             commands::search::index_content,
+            commands::search::index_content_batch,
             commands::search::rebuild_index,
+            commands::search::start_index_rebuild,
+            commands::search::cancel_index_rebuild,
+            commands::search::get_index_settings,
+            commands::search::set_index_settings,
+            commands::search::get_search_ranking,
+            commands::search::set_search_ranking,
+            commands::notes::get_note_metadata,
+            commands::notes::set_note_property,
+            commands::notes::get_frontmatter_block,
+            commands::notes::set_frontmatter,
+            commands::notes::bulk_set_property,
+            commands::notes::remove_note_property,
+            commands::notes::assign_note_id,
+            commands::notes::get_table,
+            commands::notes::apply_table_edit,
+            commands::notes::format_table,
+            commands::notes::format_tables,
+            commands::notes::format_document,
+            commands::notes::apply_heading_numbers,
+            commands::notes::remove_heading_numbers,
+            commands::notes::insert_toc,
+            commands::notes::get_note_overview,
+            commands::notes::get_outgoing_links,
+            commands::notes::get_mentions,
+            commands::notes::get_new_note_folder,
+            commands::notes::set_new_note_folder,
+            commands::notes::create_note_for_link,
+            commands::web::resolve_url_title,
+            commands::security::get_vault_lock_status,
+            commands::security::set_vault_passphrase,
+            commands::security::change_passphrase,
+            commands::security::unlock_vault,
+            commands::security::unlock_vault_from_keychain,
+            commands::security::forget_vault_key,
+            commands::security::store_secret,
+            commands::security::get_secret,
+            commands::security::delete_secret,
+            commands::maintenance::list_scheduled_tasks,
+            commands::maintenance::configure_scheduled_task,
+            commands::maintenance::run_scheduled_task,
+            commands::maintenance::get_backup_folder,
+            commands::maintenance::set_backup_folder,
+            commands::maintenance::get_calendar_source,
+            commands::maintenance::set_calendar_source,
+            commands::maintenance::snapshot_vault,
+            commands::security::lock_vault,
+            commands::security::set_auto_lock_timeout,
+            commands::web::get_link_preview,
+            commands::stats::get_activity_heatmap,
+            commands::access::get_access_tracking,
+            commands::access::set_access_tracking,
+            commands::access::record_note_open,
+            commands::access::clear_note_access,
+            commands::search::quick_switch,
+            commands::notes::normalize_footnotes,
+            commands::notes::resolve_embed,
+            commands::notes::export_merged,
+            commands::notes::export_note_docx,
+            commands::notes::export_note_latex,
+            commands::notes::import_opml,
+            commands::notes::export_opml,
+            commands::notes::import_ics,
+            commands::pins::pin_note,
+            commands::pins::unpin_note,
+            commands::pins::list_pinned,
+            commands::stats::get_writing_stats,
+            commands::platform::get_platform_info,
+            commands::platform::list_system_fonts,
+            commands::platform::take_pending_navigation,
+            commands::diagnostics::get_performance_metrics,
+            commands::diagnostics::reset_performance_metrics,
+            commands::diagnostics::get_recent_errors,
+            commands::diagnostics::clear_recent_errors,
+            commands::diagnostics::export_diagnostics,
+            commands::diagnostics::get_recent_logs,
+            commands::diagnostics::open_log_folder,
+            commands::diagnostics::get_log_level,
+            commands::diagnostics::set_log_level,
+            commands::diagnostics::lint_vault,
+            commands::lifecycle::prepare_shutdown,
             force_close_window,
         ])
         .run(tauri::generate_context!())
@@ -164,8 +570,11 @@ pub fn run() {
 }
 
 /// Force-close the main window after the frontend has handled unsaved-changes logic.
+/// Skips backend cleanup; prefer `prepare_shutdown` with `close`.
 #[tauri::command]
 async fn force_close_window(window: tauri::WebviewWindow) -> Result<(), String> {
-    info!("[INFO] [lib] Force closing window");
-    window.destroy().map_err(|e| e.to_string())
+    metrics::measure("force_close_window", 0, async move {
+        info!("[INFO] [lib] Force closing window");
+        window.destroy().map_err(|e| e.to_string())
+    }).await
 }
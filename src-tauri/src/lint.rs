@@ -0,0 +1,151 @@
+//! Lint - Vault health checks
+//!
+//! Checks run against the files on disk rather than the index, so the report
+//! is accurate even when the index is stale. Markdown notes are checked for
+//! broken links, missing frontmatter and empty bodies; every content file is
+//! checked for size and encoding. Duplicate titles come from the index (see
+//! `Database::duplicate_titles`).
+//!
+//! Link resolution is deliberately lenient: a wikilink resolves if any file
+//! matches its path or file name, with or without `.md`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::attachments;
+use crate::email;
+use crate::frontmatter;
+use crate::models::{BrokenLink, LintOptions, LintReport, OversizedFile};
+use crate::utils;
+
+/// Check the files under `root`
+/// `max_file_bytes` is the size above which a file counts as oversized.
+pub fn lint(root: &Path, options: &LintOptions, max_file_bytes: u64) -> LintReport {
+    let files = attachments::walk_files(root);
+    let targets = LinkTargets::new(&files);
+    let mut report = LintReport::default();
+
+    for path in &files {
+        if !utils::is_content_file(path) {
+            continue;
+        }
+        let full_path = root.join(path);
+        let size = match fs::metadata(&full_path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => continue,
+        };
+        if size > max_file_bytes {
+            report.oversized.push(OversizedFile { path: path.clone(), size });
+            continue;
+        }
+        // Emails declare a charset per MIME part, so needn't be UTF-8
+        if email::is_email_file(path) {
+            continue;
+        }
+
+        let bytes = match fs::read(&full_path) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+        let content = match String::from_utf8(bytes) {
+            Ok(content) => content,
+            Err(_) => {
+                report.non_utf8.push(path.clone());
+                continue;
+            }
+        };
+        if !path.ends_with(".md") {
+            continue;
+        }
+
+        if frontmatter::body(&content).trim().is_empty() {
+            report.empty_notes.push(path.clone());
+        }
+        if requires_frontmatter(path, &options.frontmatter_folders) && frontmatter::split(&content).is_none() {
+            report.missing_frontmatter.push(path.clone());
+        }
+        report.broken_links.extend(
+            utils::extract_links(&content)
+                .into_iter()
+                .filter(|link| !targets.resolves(path, link))
+                .map(|link| BrokenLink { source: path.clone(), target: link.target, line: link.line }),
+        );
+    }
+
+    report
+}
+
+/// Vault files, by path and by file name
+struct LinkTargets<'a> {
+    paths: HashSet<&'a str>,
+    names: HashSet<&'a str>,
+}
+
+impl<'a> LinkTargets<'a> {
+    fn new(files: &'a [String]) -> Self {
+        Self {
+            paths: files.iter().map(String::as_str).collect(),
+            names: files.iter().map(|f| f.rsplit('/').next().unwrap_or(f)).collect(),
+        }
+    }
+
+    fn resolves(&self, note_path: &str, link: &utils::NoteLink) -> bool {
+        if link.wiki {
+            let target = link.target.trim_start_matches('/');
+            return [target.to_string(), format!("{}.md", target)].iter().any(|candidate| {
+                self.paths.contains(candidate.as_str())
+                    || (!candidate.contains('/') && self.names.contains(candidate.as_str()))
+            });
+        }
+
+        let note_dir = note_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        let resolved = match link.target.strip_prefix('/') {
+            Some(absolute) => Some(absolute.to_string()),
+            None => attachments::resolve_relative(note_dir, &link.target),
+        };
+        resolved.is_some_and(|path| {
+            let path = path.trim_end_matches('/');
+            // Folder links are valid if anything lives under the folder
+            self.paths.contains(path) || self.paths.iter().any(|p| p.strip_prefix(path).is_some_and(|r| r.starts_with('/')))
+        })
+    }
+}
+
+fn requires_frontmatter(path: &str, folders: &[String]) -> bool {
+    folders.iter().any(|folder| {
+        let folder = folder.trim_matches('/');
+        folder.is_empty() || folder == "." || path.strip_prefix(folder).is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let dir = std::env::temp_dir().join(format!("unstablon-lint-{}", std::process::id()));
+        fs::create_dir_all(dir.join("Journal")).unwrap();
+        fs::create_dir_all(dir.join("img")).unwrap();
+        fs::write(dir.join("img/cat.png"), b"png").unwrap();
+        fs::write(dir.join("Ideas.md"), "# Ideas\n[[Journal/today]] [[Missing]] ![](img/cat.png)\n```\n[[Ignored]]\n```\n[x](../outside.md) [y](https://a.b)\n").unwrap();
+        fs::write(dir.join("Journal/today.md"), "").unwrap();
+        fs::write(dir.join("Journal/tomorrow.md"), "---\ntitle: T\n---\nPlans").unwrap();
+        fs::write(dir.join("broken.txt"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let options = LintOptions { frontmatter_folders: vec!["Journal".to_string()] };
+        let report = lint(&dir, &options, 1024);
+
+        let broken: Vec<(&str, usize)> = report.broken_links.iter().map(|l| (l.target.as_str(), l.line)).collect();
+        assert_eq!(broken, vec![("Missing", 2), ("../outside.md", 6)]);
+        assert_eq!(report.missing_frontmatter, vec!["Journal/today.md"]);
+        assert_eq!(report.empty_notes, vec!["Journal/today.md"]);
+        assert_eq!(report.non_utf8, vec!["broken.txt"]);
+
+        let report = lint(&dir, &LintOptions::default(), 10);
+        assert_eq!(report.oversized.len(), 2);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,194 @@
+//! Logging - Rolling log files in the app log directory
+//!
+//! The tracing subscriber is installed before Tauri knows the app's
+//! directories, so the file layer writes through `FileWriter`, which drops
+//! output until setup calls `init` with the log directory. Files rotate daily
+//! (`unstablon.YYYY-MM-DD.log`) and only the newest `MAX_LOG_FILES` are kept.
+//!
+//! The level filter sits behind a reload layer so it can be changed at
+//! runtime (e.g. `unstablon_pkm::watcher=debug` while reproducing an issue).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, RollingWriter, Rotation};
+use tracing_subscriber::fmt::writer::{MakeWriter, OptionalWriter};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter used when `RUST_LOG` is unset, and restored by an empty filter
+pub const DEFAULT_FILTER: &str = "unstablon_pkm=info";
+
+const FILE_PREFIX: &str = "unstablon";
+const FILE_SUFFIX: &str = "log";
+
+/// Daily files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+static APPENDER: OnceLock<RollingFileAppender> = OnceLock::new();
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// `MakeWriter` for the file layer (a no-op until `init`)
+pub struct FileWriter;
+
+impl<'a> MakeWriter<'a> for FileWriter {
+    type Writer = OptionalWriter<RollingWriter<'static>>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        match APPENDER.get() {
+            Some(appender) => OptionalWriter::some(appender.make_writer()),
+            None => OptionalWriter::none(),
+        }
+    }
+}
+
+/// Reloadable level filter layer: `RUST_LOG` if set, else `DEFAULT_FILTER`
+pub fn filter_layer() -> reload::Layer<EnvFilter, Registry> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let (layer, handle) = reload::Layer::new(filter);
+    let _ = FILTER.set(handle);
+    layer
+}
+
+/// Current filter directives
+pub fn filter() -> Result<String, String> {
+    let handle = FILTER.get().ok_or("Logging is not initialized")?;
+    handle.with_current(|filter| filter.to_string()).map_err(|e| e.to_string())
+}
+
+/// Replace the filter with `directives` (EnvFilter syntax); empty restores the default
+pub fn set_filter(directives: &str) -> Result<(), String> {
+    let directives = match directives.trim() {
+        "" => DEFAULT_FILTER,
+        directives => directives,
+    };
+    let filter = EnvFilter::try_new(directives).map_err(|e| format!("Invalid log filter: {}", e))?;
+    let handle = FILTER.get().ok_or("Logging is not initialized")?;
+    handle.reload(filter).map_err(|e| format!("Failed to set log filter: {}", e))
+}
+
+/// Start writing log files to `dir`
+pub fn init(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+
+    let _ = LOG_DIR.set(dir.to_path_buf());
+    let _ = APPENDER.set(appender);
+    Ok(())
+}
+
+/// Directory log files are written to, once initialized
+pub fn log_dir() -> Option<&'static Path> {
+    LOG_DIR.get().map(PathBuf::as_path)
+}
+
+/// The last `limit` log entries at `min_level` or more severe, oldest first
+/// Multi-line entries are returned as one string.
+pub fn recent(limit: usize, min_level: Option<Level>) -> Result<Vec<String>, String> {
+    let Some(dir) = log_dir() else {
+        return Ok(Vec::new());
+    };
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_log_file(path))
+        .collect();
+    // Date-stamped names sort chronologically; walk newest first
+    files.sort();
+
+    let mut entries = Vec::new();
+    for file in files.iter().rev() {
+        if entries.len() >= limit {
+            break;
+        }
+        let bytes = fs::read(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+        let mut older = parse_entries(&String::from_utf8_lossy(&bytes), min_level);
+        older.append(&mut entries);
+        entries = older;
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(skip))
+}
+
+/// Parse a level filter name (`"warn"`, `"DEBUG"`, ...)
+pub fn parse_level(level: &str) -> Result<Level, String> {
+    Level::from_str(level.trim()).map_err(|_| format!("Unknown log level: {}", level))
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.starts_with(&format!("{}.", FILE_PREFIX)) && name.ends_with(&format!(".{}", FILE_SUFFIX))
+        })
+}
+
+/// Split file contents into entries, keeping those at `min_level` or above
+/// A line whose second field isn't a level continues the previous entry.
+fn parse_entries(content: &str, min_level: Option<Level>) -> Vec<String> {
+    let mut entries: Vec<(Level, String)> = Vec::new();
+    for line in content.lines() {
+        let level = line.split_whitespace().nth(1).and_then(header_level);
+        match (level, entries.last_mut()) {
+            (Some(level), _) => entries.push((level, line.to_string())),
+            (None, Some((_, entry))) => {
+                entry.push('\n');
+                entry.push_str(line);
+            }
+            (None, None) => {}
+        }
+    }
+
+    entries
+        .into_iter()
+        // Levels order by verbosity: ERROR < WARN < ... < TRACE
+        .filter(|(level, _)| min_level.map_or(true, |min| *level <= min))
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+/// Level field of an entry header (the formatter writes it upper-case)
+fn header_level(field: &str) -> Option<Level> {
+    match field {
+        "ERROR" => Some(Level::ERROR),
+        "WARN" => Some(Level::WARN),
+        "INFO" => Some(Level::INFO),
+        "DEBUG" => Some(Level::DEBUG),
+        "TRACE" => Some(Level::TRACE),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entries() {
+        let content = "\
+2026-10-18T09:00:00.000000Z  INFO unstablon_pkm: [INFO] [lib] Starting
+2026-10-18T09:00:01.000000Z  WARN unstablon_pkm::watcher: [WARN] [Watcher] Slow event
+2026-10-18T09:00:02.000000Z ERROR unstablon_pkm::db: [ERROR] [db] Query failed:
+near \"x\": syntax error
+step 2 failed
+";
+        assert_eq!(parse_entries(content, None).len(), 3);
+
+        let warnings = parse_entries(content, Some(Level::WARN));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].ends_with("Query failed:\nnear \"x\": syntax error\nstep 2 failed"));
+
+        assert_eq!(parse_level("debug"), Ok(Level::DEBUG));
+        assert!(parse_level("loud").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! Merge - Concatenate notes into a single markdown document
+//!
+//! Each note becomes a section: an anchor, its title as a level-1 heading,
+//! then its body with headings demoted one level (a leading heading that
+//! repeats the title is dropped). Embeds are inlined (see `embeds`), and
+//! links to notes in the selection are rewritten to the section anchors.
+//! Links to notes outside the selection are left as-is.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::attachments;
+use crate::embeds::{self, NoteResolver};
+use crate::error::AppError;
+use crate::frontmatter;
+use crate::utils;
+
+/// Merged markdown and the embeds that couldn't be inlined
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub markdown: String,
+    pub unresolved_embeds: Vec<String>,
+}
+
+/// Merge vault-relative `paths` (in order) under `root`
+pub fn merge(root: &Path, paths: &[String]) -> Result<Merged, AppError> {
+    let files = attachments::walk_files(root);
+    let resolver = NoteResolver::new(&files);
+
+    let mut anchors: HashMap<String, String> = HashMap::new();
+    let mut used = HashSet::new();
+    for path in paths {
+        let base = slugify(path.strip_suffix(".md").unwrap_or(path));
+        let mut anchor = base.clone();
+        let mut n = 1;
+        while !used.insert(anchor.clone()) {
+            anchor = format!("{}-{}", base, n);
+            n += 1;
+        }
+        anchors.insert(path.clone(), anchor);
+    }
+
+    let mut merged = Merged::default();
+    let mut sections = Vec::with_capacity(paths.len());
+    for path in paths {
+        let content = fs::read_to_string(root.join(path))?;
+        let title = utils::extract_title_from_content(&content).unwrap_or_else(|| utils::path_to_title(path));
+
+        let mut stack = vec![path.clone()];
+        let body = embeds::expand(root, &resolver, frontmatter::body(&content), &mut stack, &mut merged.unresolved_embeds);
+        let body = demote_headings(&drop_title_heading(&body, &title));
+        let body = link_to_anchors(&body, path, &resolver, &anchors);
+
+        sections.push(format!("<a id=\"{}\"></a>\n\n# {}\n\n{}", anchors[path], title, body.trim()));
+    }
+
+    merged.markdown = sections.join("\n\n") + "\n";
+    Ok(merged)
+}
+
+/// Remove a first heading that repeats the section title
+fn drop_title_heading(body: &str, title: &str) -> String {
+    let trimmed = body.trim_start();
+    let first = trimmed.lines().next().unwrap_or_default();
+    match utils::atx_heading(first) {
+        Some((_, text)) if text == title => trimmed[first.len()..].to_string(),
+        _ => body.to_string(),
+    }
+}
+
+/// Add one `#` to every heading (up to level 6), outside code fences
+fn demote_headings(body: &str) -> String {
+    let mut in_fence = false;
+    body.split_inclusive('\n')
+        .map(|line| {
+            if utils::is_code_fence(line) {
+                in_fence = !in_fence;
+            }
+            match utils::atx_heading(line) {
+                Some((level, _)) if !in_fence && level < 6 => format!("#{}", line.trim_start()),
+                _ => line.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Point links at notes in the selection to their section anchors
+fn link_to_anchors(body: &str, note_path: &str, resolver: &NoteResolver, anchors: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        if utils::is_code_fence(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find('[') {
+            let (before, tail) = rest.split_at(pos);
+            out.push_str(before);
+            let embed = before.ends_with('!');
+
+            // [[target]] / [[target|label]] / [[target#heading]]
+            if let Some(end) = tail.strip_prefix("[[").and_then(|t| t.find("]]")) {
+                let inner = &tail[2..2 + end];
+                let (link, label) = match inner.split_once('|') {
+                    Some((link, label)) => (link, label.trim()),
+                    None => (inner, inner.trim()),
+                };
+                let target = link.split('#').next().unwrap_or(link);
+                match resolver.wikilink(target).and_then(|path| anchors.get(path)) {
+                    Some(anchor) if !embed => out.push_str(&format!("[{}](#{})", label, anchor)),
+                    _ => out.push_str(&tail[..end + 4]),
+                }
+                rest = &tail[end + 4..];
+                continue;
+            }
+
+            // [label](target)
+            if let Some((label_end, target_end)) = tail.find("](").and_then(|l| Some((l, l + 2 + tail[l + 2..].find(')')?))) {
+                let label = &tail[1..label_end];
+                let target = tail[label_end + 2..target_end].trim();
+                let target = target.strip_prefix('<').and_then(|t| t.strip_suffix('>')).unwrap_or(target);
+                let anchor = if label.contains('[') || target.contains("://") {
+                    None
+                } else {
+                    resolver.markdown_link(note_path, target).and_then(|path| anchors.get(path))
+                };
+                match anchor {
+                    Some(anchor) if !embed => out.push_str(&format!("[{}](#{})", label, anchor)),
+                    _ => out.push_str(&tail[..target_end + 1]),
+                }
+                rest = &tail[target_end + 1..];
+                continue;
+            }
+
+            out.push('[');
+            rest = &tail[1..];
+        }
+        out.push_str(rest);
+    }
+
+    out
+}
+
+/// Lowercase, alphanumerics kept, everything else collapsed to `-`
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() { "note".to_string() } else { slug.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let dir = std::env::temp_dir().join(format!("unstablon-merge-{}", std::process::id()));
+        fs::create_dir_all(dir.join("Lab")).unwrap();
+        fs::write(dir.join("Lab/Intro.md"), "---\ntags: [x]\n---\n# Intro\nSee [[Results|the results]] and [method](Method.md).\n![[Method#Setup]]\n").unwrap();
+        fs::write(dir.join("Lab/Method.md"), "# Method\n## Setup\nPipettes.\n![[Intro]]\n## Other\nNo.\n").unwrap();
+        fs::write(dir.join("Results.md"), "## Table\n```\n## not a heading\n```\n![[Nowhere]]\n").unwrap();
+
+        let paths = vec!["Lab/Intro.md".to_string(), "Results.md".to_string()];
+        let merged = merge(&dir, &paths).unwrap();
+
+        assert_eq!(
+            merged.markdown,
+            "<a id=\"lab-intro\"></a>\n\n# Intro\n\n\
+             See [the results](#results) and [method](Method.md).\n\
+             ### Setup\nPipettes.\n![[Intro]]\n\n\
+             <a id=\"results\"></a>\n\n# Results\n\n\
+             ### Table\n```\n## not a heading\n```\n![[Nowhere]]\n"
+        );
+        assert_eq!(merged.unresolved_embeds, vec!["![[Nowhere]]"]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,145 @@
+//! Command Metrics - Timing and payload size aggregation for IPC commands
+//!
+//! Every Tauri command runs inside `measure`, which:
+//! - Wraps the command in a `command` tracing span
+//! - Records duration, request size, and whether it failed
+//! - Aggregates per-command counters and a latency histogram
+//!
+//! Response sizes aren't recorded: measuring them means serializing every
+//! response a second time.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::{debug, info_span, Instrument};
+
+use crate::models::{CommandMetrics, PerformanceMetrics};
+
+/// Histogram bucket upper bounds in milliseconds (last bucket is open-ended)
+pub const LATENCY_BUCKETS_MS: [u64; 7] = [1, 5, 10, 50, 100, 500, 1000];
+
+/// Global metrics registry
+static METRICS: OnceLock<Mutex<MetricsRegistry>> = OnceLock::new();
+
+struct MetricsRegistry {
+    started_at: Instant,
+    commands: HashMap<&'static str, CommandStats>,
+}
+
+#[derive(Default)]
+struct CommandStats {
+    calls: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    request_bytes: u64,
+}
+
+impl MetricsRegistry {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            commands: HashMap::new(),
+        }
+    }
+}
+
+fn get_registry() -> &'static Mutex<MetricsRegistry> {
+    METRICS.get_or_init(|| Mutex::new(MetricsRegistry::new()))
+}
+
+/// Record a completed command invocation
+fn record(command: &'static str, elapsed: Duration, request_bytes: u64, is_error: bool) {
+    let mut registry = get_registry().lock().unwrap_or_else(PoisonError::into_inner);
+    let stats = registry.commands.entry(command).or_default();
+
+    stats.calls += 1;
+    if is_error {
+        stats.errors += 1;
+    }
+    stats.total += elapsed;
+    stats.max = stats.max.max(elapsed);
+
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let bucket = LATENCY_BUCKETS_MS
+        .iter()
+        .position(|bound| elapsed_ms < *bound)
+        .unwrap_or(LATENCY_BUCKETS_MS.len());
+    stats.buckets[bucket] += 1;
+
+    stats.request_bytes += request_bytes;
+}
+
+/// Run an async command body, recording timing, request size and errors
+/// Errors may be plain strings or structured `AppError`s
+pub async fn measure<T, E, F>(command: &'static str, request_bytes: usize, fut: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let span = info_span!("command", name = command, request_bytes);
+    let start = Instant::now();
+
+    let result = fut.instrument(span).await;
+
+    let elapsed = start.elapsed();
+    debug!("[DEBUG] [metrics] {} took {:?} (request {} B)", command, elapsed, request_bytes);
+    record(command, elapsed, request_bytes as u64, result.is_err());
+
+    result
+}
+
+/// Synchronous variant of `measure` for non-async commands
+pub fn measure_sync<T, E, F>(command: &'static str, request_bytes: usize, f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    let span = info_span!("command", name = command, request_bytes);
+    let start = Instant::now();
+
+    let result = span.in_scope(f);
+
+    let elapsed = start.elapsed();
+    record(command, elapsed, request_bytes as u64, result.is_err());
+
+    result
+}
+
+/// Snapshot of all command metrics, slowest (by total time) first
+pub fn snapshot() -> PerformanceMetrics {
+    let registry = get_registry().lock().unwrap_or_else(PoisonError::into_inner);
+
+    let mut commands: Vec<CommandMetrics> = registry
+        .commands
+        .iter()
+        .map(|(name, stats)| CommandMetrics {
+            command: name.to_string(),
+            calls: stats.calls,
+            errors: stats.errors,
+            total_ms: stats.total.as_secs_f64() * 1000.0,
+            avg_ms: if stats.calls > 0 {
+                stats.total.as_secs_f64() * 1000.0 / stats.calls as f64
+            } else {
+                0.0
+            },
+            max_ms: stats.max.as_secs_f64() * 1000.0,
+            latency_histogram: stats.buckets.to_vec(),
+            request_bytes: stats.request_bytes,
+        })
+        .collect();
+
+    commands.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+
+    PerformanceMetrics {
+        uptime_ms: registry.started_at.elapsed().as_millis() as u64,
+        latency_buckets_ms: LATENCY_BUCKETS_MS.to_vec(),
+        commands,
+    }
+}
+
+/// Clear all recorded command metrics
+pub fn reset() {
+    let mut registry = get_registry().lock().unwrap_or_else(PoisonError::into_inner);
+    registry.commands.clear();
+}
@@ -1,6 +1,7 @@
 //! Data models for Unstablon PKM
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// File entry returned by list_directory
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +14,121 @@ pub struct FileEntry {
     pub modified_at: Option<u64>,
 }
 
+/// File content with the metadata needed as a conflict baseline
+/// (all taken from the same open file handle)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSnapshot {
+    pub content: String,
+    /// Modification time in milliseconds since UNIX epoch
+    pub mtime: u64,
+    /// SHA-256 of the content (hex)
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Version of a file after a checked write (the caller's next baseline)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    pub mtime: u64,
+    pub hash: String,
+    pub size: u64,
+}
+
+/// A pinned note (path is vault-relative)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedNote {
+    pub path: String,
+    pub position: u32,
+    pub pinned_at: u64,
+}
+
+/// Date range for statistics (`YYYY-MM-DD`, inclusive, local time)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Words written and removed on one day (counted from saves made in the app)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyWords {
+    pub day: String,
+    pub words_added: u64,
+    pub words_removed: u64,
+}
+
+/// A term and its number of occurrences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TermCount {
+    pub term: String,
+    pub count: u32,
+}
+
+/// Vault writing statistics over a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingStats {
+    pub from: String,
+    pub to: String,
+    /// Days with recorded writing, oldest first
+    pub daily: Vec<DailyWords>,
+    pub total_words_added: u64,
+    pub total_words_removed: u64,
+    /// Most frequent terms across notes modified in the range
+    pub top_terms: Vec<TermCount>,
+    /// Notes modified in the range and their average length in words
+    pub note_count: u32,
+    pub average_note_words: f64,
+}
+
+/// Per-day activity counts for one year, for a heatmap
+/// Each array has one entry per day starting January 1st (local time).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityHeatmap {
+    pub year: i32,
+    pub days: u32,
+    /// Notes created each day
+    pub created: Vec<u32>,
+    /// Notes last modified each day
+    pub modified: Vec<u32>,
+    /// Note opens each day (empty while opens aren't tracked)
+    pub opens: Vec<u32>,
+    /// Largest daily total (created + modified + opens), for color scaling
+    pub max: u32,
+}
+
+/// A note proposed as a link target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkSuggestion {
+    pub id: String,
+    pub path: String,
+    pub title: String,
+    /// Alias that matched, if the match was on an alias
+    pub alias: Option<String>,
+    /// What matched: "title", "alias", or "content"
+    pub source: String,
+    pub score: f64,
+}
+
+/// One autocomplete suggestion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutocompleteItem {
+    pub value: String,
+    /// Number of notes containing the value
+    pub count: u32,
+    /// The note, when exactly one contains the value
+    pub note_id: Option<String>,
+}
+
 /// Navigation node types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -25,6 +141,10 @@ pub enum NavigationNode {
     Page(PageNode),
     #[serde(rename = "document")]
     Document(DocumentNode),
+    #[serde(rename = "data")]
+    Data(DataNode),
+    #[serde(rename = "attachment")]
+    Attachment(AttachmentNode),
 }
 
 /// Folder node containing children
@@ -35,38 +155,687 @@ pub struct FolderNode {
     pub name: String,
     pub path: String,
     pub children: Vec<NavigationNode>,
+    /// Vault-relative path of the folder's note (`Folder/Folder.md` or
+    /// `Folder/index.md`), which is left out of `children`
+    #[serde(default, rename = "folderNote")]
+    pub folder_note: Option<String>,
 }
 
 /// Module node (JavaScript file)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleNode {
     pub id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    #[serde(default)]
+    pub uid: String,
     pub name: String,
     pub path: String,
     pub title: String,
     pub file: String,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 /// Page node (HTML file)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageNode {
     pub id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    #[serde(default)]
+    pub uid: String,
     pub name: String,
     pub path: String,
     pub title: String,
     pub file: String,
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 /// Document node (Markdown/text file)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentNode {
     pub id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    #[serde(default)]
+    pub uid: String,
     pub name: String,
     pub path: String,
     pub title: String,
     pub file: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Attachment node (non-content file with a sidecar annotation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentNode {
+    pub id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    #[serde(default)]
+    pub uid: String,
+    pub name: String,
+    pub path: String,
+    pub title: String,
+    pub file: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Data node (JSON/YAML/TOML file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataNode {
+    pub id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    #[serde(default)]
+    pub uid: String,
+    pub name: String,
+    pub path: String,
+    pub title: String,
+    pub file: String,
+    /// "json", "yaml", or "toml"
+    pub format: String,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Parsed content of a data file
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredData {
+    pub format: String,
+    pub data: serde_json::Value,
+}
+
+/// Parsed Jupyter notebook (read-only view)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notebook {
+    /// Kernel language, if declared
+    pub language: Option<String>,
+    pub cells: Vec<NotebookCell>,
+}
+
+/// A notebook cell
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCell {
+    /// "markdown", "code", or "raw"
+    pub cell_type: String,
+    pub source: String,
+    pub execution_count: Option<u32>,
+    pub outputs: Vec<NotebookOutput>,
+}
+
+/// A code cell output reduced to what can be rendered read-only
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookOutput {
+    pub output_type: String,
+    /// Plain text (stream text, text/plain data, or error summary)
+    pub text: Option<String>,
+    /// Base64 PNG image data
+    pub image_png: Option<String>,
+}
+
+/// Parsed email message (read-only view)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailMessage {
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: Option<String>,
+    /// RFC 3339 (UTC), if the Date header parses
+    pub date: Option<String>,
+    /// Plain text body (the HTML body with tags stripped if there's no text part)
+    pub text: String,
+    pub html: Option<String>,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// A file attached to an email
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailAttachment {
+    pub name: String,
+    pub content_type: String,
+    /// Decoded size in bytes
+    pub size: usize,
+}
+
+/// Result of converting an email to a note
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailConversion {
+    /// Vault-relative path of the new note
+    pub note: String,
+    /// Vault-relative paths of the extracted attachments
+    pub attachments: Vec<String>,
+}
+
+/// Result of `import_ics`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarImport {
+    /// Events in the import window
+    pub events: usize,
+    /// Vault-relative paths of new meeting notes
+    pub created: Vec<String>,
+    /// Vault-relative paths of meeting notes whose properties changed
+    pub updated: Vec<String>,
+}
+
+/// A markdown table as a cell matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableData {
+    /// Rows of cells; row 0 is the header
+    pub cells: Vec<Vec<String>>,
+    /// Per column: "none", "left", "center", or "right"
+    pub alignments: Vec<String>,
+}
+
+/// A single table edit (row 0 is the header and can't be inserted or deleted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum TableOp {
+    SetCell { row: usize, column: usize, value: String },
+    InsertRow { index: usize },
+    DeleteRow { index: usize },
+    InsertColumn { index: usize },
+    DeleteColumn { index: usize },
+}
+
+/// A cached (or original, if already small) image for display
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    /// Absolute path of the image to display
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// Served from the cache without decoding the source
+    pub cached: bool,
+}
+
+/// Byte-identical attachments; the first path is the copy to keep
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    /// Vault-relative paths
+    pub paths: Vec<String>,
+}
+
+/// Outcome of `deduplicate_attachments`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupReport {
+    /// Vault-relative paths of deleted copies
+    pub removed: Vec<String>,
+    /// Vault-relative paths of notes whose links were rewritten
+    pub updated_notes: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+/// Links a rename would rewrite, returned by `preview_rename`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePreview {
+    /// Vault-relative (old, new) path of every file moved (several for a folder)
+    pub moved: Vec<(String, String)>,
+    pub files: Vec<RenameFileChange>,
+    /// Lines changed across all files
+    pub total_lines: usize,
+}
+
+/// A note whose links a rename would rewrite
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameFileChange {
+    /// Vault-relative path
+    pub path: String,
+    pub lines: Vec<ChangedLine>,
+}
+
+/// A line before and after a rewrite
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedLine {
+    /// 1-based line number
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Note and tags on a non-markdown file, stored in its sidecar (see `sidecar`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileAnnotation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl FileAnnotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.as_deref().map_or(true, |note| note.trim().is_empty()) && self.tags.is_empty()
+    }
+}
+
+/// Options for `import_external_file`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportOptions {
+    /// Move instead of copy
+    pub move_file: bool,
+    /// Convert supported formats (.txt, .csv) to a markdown note
+    pub convert: bool,
+    /// Note the import is inserted into; links are made relative to it
+    pub note_path: Option<String>,
+}
+
+/// An imported file and the markdown to insert for it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    /// Vault-relative path of the created file
+    pub path: String,
+    pub insert_text: String,
+    pub converted: bool,
+}
+
+/// Result of `normalize_footnotes`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FootnoteReport {
+    /// Footnotes whose label changed
+    pub renumbered: usize,
+    /// Labels of definitions removed because nothing referred to them
+    pub removed: Vec<String>,
+    /// Labels referenced without a definition (left as-is)
+    pub undefined: Vec<String>,
+}
+
+/// Rules applied by `format_document`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatOptions {
+    /// One blank line before and after each heading
+    pub heading_spacing: bool,
+    /// Bullet marker for unordered lists (`-`, `*` or `+`; None keeps them)
+    pub list_marker: Option<String>,
+    /// Strip trailing whitespace (hard breaks keep two spaces) and end
+    /// with a single newline
+    pub trim_trailing_whitespace: bool,
+    /// Code fence marker (`` ``` `` or `~~~`; None keeps them)
+    pub fence_style: Option<String>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            heading_spacing: true,
+            list_marker: None,
+            trim_trailing_whitespace: true,
+            fence_style: None,
+        }
+    }
+}
+
+/// Result of `format_document`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatResult {
+    /// Formatting would change the note
+    pub changed: bool,
+    /// Unified diff from the current note to the formatted one
+    pub diff: String,
+    /// The formatted note was saved (false for a preview)
+    pub written: bool,
+}
+
+/// Heading levels `apply_heading_numbers` / `remove_heading_numbers` touch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeadingNumberOptions {
+    /// Level numbered `1.`, `2.`, ... (2 leaves a `# Title` alone)
+    pub min_level: usize,
+    /// Deepest level numbered
+    pub max_level: usize,
+}
+
+impl Default for HeadingNumberOptions {
+    fn default() -> Self {
+        Self { min_level: 2, max_level: 6 }
+    }
+}
+
+/// Heading levels listed by `insert_toc`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TocOptions {
+    pub min_level: usize,
+    pub max_level: usize,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self { min_level: 2, max_level: 3 }
+    }
+}
+
+/// A vault key encrypted with a passphrase-derived key (see `vault_key`)
+#[derive(Debug, Clone)]
+pub struct WrappedVaultKey {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub wrapped_key: Vec<u8>,
+    /// Argon2 memory cost (KiB), iterations and parallelism
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+/// The open vault, returned by `open_vault` and `get_vault`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedVault {
+    pub path: String,
+    /// Folder name, for display
+    pub name: String,
+}
+
+/// Returned by `get_vault_lock_status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultLockStatus {
+    /// A passphrase has been set for the open vault
+    pub has_passphrase: bool,
+    pub unlocked: bool,
+    /// Seconds after unlocking before the vault locks itself (0 = never)
+    pub auto_lock_secs: u64,
+}
+
+/// Outcome of one run of a scheduled task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTaskRun {
+    pub task_id: String,
+    /// Seconds since UNIX epoch
+    pub ran_at: u64,
+    pub ok: bool,
+    /// Summary of what was done, or the error
+    pub message: String,
+}
+
+/// A recurring maintenance task returned by `list_scheduled_tasks`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    /// "db-optimize", "vault-backup" or "git-commit"
+    pub id: String,
+    pub enabled: bool,
+    pub interval_secs: u64,
+    pub running: bool,
+    pub last_run: Option<ScheduledTaskRun>,
+    /// When it is next due (None while disabled)
+    pub next_run_at: Option<u64>,
+}
+
+/// Payload for `snapshot:progress` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotProgress {
+    pub archived: usize,
+    pub total: usize,
+}
+
+/// Returned by `snapshot_vault`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultSnapshot {
+    /// The zip written
+    pub path: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Rich preview of an external link
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// `og:image` (absolute URL)
+    pub image: Option<String>,
+    /// Absolute URL of the site icon
+    pub favicon: Option<String>,
+    /// When the page was fetched (unix seconds)
+    pub fetched_at: u64,
+    /// Past its TTL but the page couldn't be refetched (offline)
+    pub stale: bool,
+}
+
+/// Everything the note info panel shows, returned by `get_note_overview`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteOverview {
+    /// Vault-relative path
+    pub path: String,
+    pub title: String,
+    pub headings: Vec<OutlineHeading>,
+    /// Words in the body (frontmatter excluded)
+    pub word_count: u32,
+    pub tags: Vec<String>,
+    /// Indexed notes linking here (the note itself excluded)
+    pub backlink_count: usize,
+    pub outgoing_link_count: usize,
+    /// Seconds since UNIX epoch
+    pub modified_at: Option<u64>,
+}
+
+/// A heading in a note's outline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineHeading {
+    pub level: usize,
+    pub text: String,
+    /// Anchor ID, as in generated TOCs (see `utils::slugify_heading`)
+    pub slug: String,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// A `@Name` mention of a person, as returned by `get_mentions`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mention {
+    /// Vault-relative path of the mentioning note
+    pub path: String,
+    pub title: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The line the mention is on
+    pub text: String,
+    /// Seconds since UNIX epoch
+    pub modified_at: u64,
+}
+
+/// A link from a note, as returned by `get_outgoing_links`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingLink {
+    /// Target as written (without heading or alias)
+    pub target: String,
+    /// Vault-relative path it resolves to; None if nothing matches
+    pub resolved_path: Option<String>,
+    /// "wikilink", "markdown", or "embed"
+    pub link_type: String,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// Content of a resolved `![[...]]` embed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedContent {
+    /// Vault-relative path of the embedded note
+    pub path: String,
+    /// Selected markdown, with nested embeds expanded
+    pub content: String,
+    /// Nested embeds left as-is because their target wasn't found
+    pub unresolved: Vec<String>,
+}
+
+/// Token size of `diff_texts`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffGranularity {
+    #[default]
+    Line,
+    Word,
+}
+
+/// Diff algorithm of `diff_texts`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffAlgorithm {
+    #[default]
+    Myers,
+    Patience,
+}
+
+/// Structured diff returned by `diff_texts`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResult {
+    pub hunks: Vec<DiffHunk>,
+    /// Inserted lines or words
+    pub insertions: usize,
+    /// Deleted lines or words
+    pub deletions: usize,
+}
+
+/// A run of changes with surrounding context
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// 1-based first line in the old text
+    pub old_start: usize,
+    pub old_lines: usize,
+    /// 1-based first line in the new text
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub changes: Vec<DiffChange>,
+}
+
+/// Text that is unchanged, inserted or deleted
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffChange {
+    /// "equal", "insert", or "delete"
+    pub kind: String,
+    pub text: String,
+}
+
+/// Outcome of `undo_last_operation` (paths vault-relative)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+    /// Label of the undone operation
+    pub label: String,
+    /// Files put back to their previous content
+    pub restored: Vec<String>,
+    /// Files the operation had created, now removed
+    pub removed: Vec<String>,
+}
+
+/// Outcome of `bulk_set_property`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkPropertyResult {
+    /// Nothing was written
+    pub dry_run: bool,
+    /// Notes whose frontmatter changed (or would change)
+    pub changed: usize,
+    pub files: Vec<BulkFileResult>,
+}
+
+/// Per-note result of `bulk_set_property`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkFileResult {
+    /// Vault-relative path
+    pub path: String,
+    /// The property was added or changed (false if it already had the value)
+    pub changed: bool,
+    /// Why the note was skipped
+    pub error: Option<String>,
+}
+
+/// Section order for `export_merged`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeOrder {
+    /// As listed (search relevance for a query)
+    #[default]
+    Given,
+    Path,
+    /// Oldest first
+    Modified,
+}
+
+/// Result of `export_merged`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedExport {
+    pub path: String,
+    /// Notes included, in output order (vault-relative)
+    pub notes: Vec<String>,
+    /// Embeds left as-is because their target wasn't found
+    pub unresolved_embeds: Vec<String>,
+}
+
+/// Result of `export_note_docx`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocxExport {
+    pub path: String,
+    /// Image sources left as alt text (remote, missing or unreadable)
+    pub skipped_images: Vec<String>,
+}
+
+/// Result of `export_note_latex`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatexExport {
+    pub path: String,
+    /// Citation keys used, for checking against the bibliography
+    pub citations: Vec<String>,
+}
+
+/// Outcome of `prepare_shutdown`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    /// A running index rebuild was cancelled (it resumes on next launch)
+    pub rebuild_cancelled: bool,
+    /// The database WAL was checkpointed
+    pub wal_checkpointed: bool,
+    /// Work still in flight when the report was made
+    pub pending: Vec<String>,
+    /// Steps that failed (shutdown continues past them)
+    pub errors: Vec<String>,
+    /// The window was closed
+    pub closed: bool,
 }
 
 /// Search result from FTS5 query
@@ -80,6 +849,32 @@ pub struct SearchResult {
     pub content_type: String,
     pub score: f64,
     pub snippet: Option<String>,
+    /// Body was cut by index size limits; matches may be incomplete
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Notes matching a query block, as returned by `run_query_block`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    /// Property columns requested by the block's `fields`
+    pub fields: Vec<String>,
+    pub rows: Vec<QueryRow>,
+}
+
+/// A note in a query result
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRow {
+    /// Vault-relative path
+    pub path: String,
+    pub title: String,
+    /// Seconds since UNIX epoch
+    pub modified_at: Option<u64>,
+    pub created_at: Option<u64>,
+    /// Values of each requested field the note has (lists give several)
+    pub values: HashMap<String, Vec<String>>,
 }
 
 /// Content index entry for SQLite
@@ -94,4 +889,358 @@ pub struct ContentIndexEntry {
     pub body: Option<String>,
     pub modified_at: u64,
     pub indexed_at: u64,
+    /// File creation time (None where the filesystem doesn't record it)
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    /// Body was truncated or skipped by index size limits
+    #[serde(default)]
+    pub truncated: bool,
+    /// Frontmatter aliases (documents only)
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Frontmatter and inline tags (documents only)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Frontmatter (key, value) pairs for untyped properties; list values
+    /// contribute one pair per item (documents only)
+    #[serde(default)]
+    pub properties: Vec<(String, String)>,
+    /// Outgoing links as (link key, "wiki" | "markdown"); see
+    /// `utils::link_key` (documents only)
+    #[serde(default)]
+    pub links: Vec<(String, String)>,
+}
+
+/// Search ranking knobs applied by `Database::search`
+/// The bm25 score (with per-column weights) is multiplied by
+/// `1 + tag boost + recency boost + link boost`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchRanking {
+    /// bm25 weight of title matches
+    pub title_weight: f64,
+    /// bm25 weight of body matches
+    pub body_weight: f64,
+    /// Added per note tag equal to a search term
+    pub tag_boost: f64,
+    /// Added for a note modified just now, halving every `recency_half_life_days`
+    pub recency_boost: f64,
+    pub recency_half_life_days: f64,
+    /// Multiplied by ln(1 + inbound links)
+    pub link_boost: f64,
+}
+
+impl Default for SearchRanking {
+    fn default() -> Self {
+        Self {
+            title_weight: 5.0,
+            body_weight: 1.0,
+            tag_boost: 0.5,
+            recency_boost: 0.3,
+            recency_half_life_days: 30.0,
+            link_boost: 0.1,
+        }
+    }
+}
+
+/// A place a note or page title can come from (see `IndexSettings::title_sources`)
+/// Serialized as `"heading"`, or `{ "frontmatter": "alias" }` for a field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TitleSource {
+    /// A frontmatter field (the first item of a list field)
+    Frontmatter(String),
+    /// The first `# ` heading
+    Heading,
+    /// The first `<h1>` element
+    HtmlHeading,
+    /// The `<title>` element
+    HtmlTitle,
+    /// The file name without its extension
+    FileName,
+}
+
+/// Limits applied to indexed bodies, where `@Name` mentions point, and how
+/// titles are chosen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IndexSettings {
+    /// Maximum body size in bytes for any file
+    pub max_body_bytes: usize,
+    /// Per-type overrides of `max_body_bytes` (keyed by content type)
+    pub type_max_body_bytes: HashMap<String, usize>,
+    /// Content types whose bodies are never indexed (title only)
+    pub skip_body_types: Vec<String>,
+    /// Files larger than this are indexed by title only, without being read
+    pub max_file_bytes: u64,
+    /// Folder (vault-relative) of people notes: `@Name` links to `<folder>/Name.md`
+    /// Notes must be re-indexed for a change to apply to them.
+    pub people_folder: String,
+    /// Where document and page titles come from, in priority order, for both
+    /// the index and the navigation tree (which uses it for modules too); the
+    /// file name is the fallback. Saved per vault. Notes must be re-indexed
+    /// for a change to apply to their indexed titles.
+    pub title_sources: Vec<TitleSource>,
+}
+
+impl Default for IndexSettings {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 1024 * 1024,
+            type_max_body_bytes: HashMap::new(),
+            skip_body_types: Vec::new(),
+            max_file_bytes: 50 * 1024 * 1024,
+            people_folder: "People".to_string(),
+            title_sources: vec![
+                TitleSource::Frontmatter("title".to_string()),
+                TitleSource::Heading,
+                TitleSource::HtmlHeading,
+                TitleSource::HtmlTitle,
+            ],
+        }
+    }
+}
+
+impl IndexSettings {
+    /// Body size limit for a content type (0 = body skipped)
+    pub fn body_limit_for(&self, content_type: &str) -> usize {
+        if self.skip_body_types.iter().any(|t| t == content_type) {
+            return 0;
+        }
+        self.type_max_body_bytes
+            .get(content_type)
+            .copied()
+            .unwrap_or(self.max_body_bytes)
+    }
+}
+
+/// Per-path entry of `get_file_mtimes`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMtime {
+    /// Milliseconds since UNIX epoch
+    pub mtime: Option<u64>,
+    /// The file doesn't exist (deleted or moved)
+    pub missing: bool,
+    /// Any other failure (bad path, permissions, ...)
+    pub error: Option<String>,
+}
+
+/// How file listings and the nav tree order names
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SortSettings {
+    /// Compare digit runs numerically ("note2" before "note10")
+    pub natural: bool,
+}
+
+/// Sidebar filter for `filter_navigation_tree`; every part given must match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NavigationFilter {
+    /// Case-insensitive text in the name or title
+    pub name: Option<String>,
+    /// Indexed tag, without `#` (nested tags match too)
+    pub tag: Option<String>,
+    /// Node types to keep ("document", "page", "module", "data", "attachment")
+    pub types: Vec<String>,
+}
+
+/// Aggregated metrics for a single IPC command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    /// Call counts per latency bucket (see `PerformanceMetrics::latency_buckets_ms`)
+    pub latency_histogram: Vec<u64>,
+    pub request_bytes: u64,
+}
+
+/// Performance diagnostics returned by get_performance_metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceMetrics {
+    pub uptime_ms: u64,
+    /// Upper bounds of histogram buckets; the final bucket is open-ended
+    pub latency_buckets_ms: Vec<u64>,
+    pub commands: Vec<CommandMetrics>,
+}
+
+/// Captured warning/error event returned by get_recent_errors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLogEntry {
+    /// Milliseconds since UNIX epoch
+    pub timestamp: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Result of an index rebuild (also the `index:finished` event payload)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildOutcome {
+    pub home_path: String,
+    /// Files in the index after this run (including resumed ones)
+    pub indexed: u32,
+    /// Files carried over from an interrupted run's checkpoint
+    pub resumed: u32,
+    /// Stopped early; the next rebuild resumes from the checkpoint
+    pub cancelled: bool,
+}
+
+/// Payload for `index:progress` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildProgress {
+    pub home_path: String,
+    /// Files indexed so far by this run
+    pub indexed: u32,
+}
+
+/// Options for `lint_vault`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintOptions {
+    /// Vault-relative folders whose notes must have frontmatter ("" for all)
+    pub frontmatter_folders: Vec<String>,
+}
+
+/// Vault health report returned by `lint_vault` (all paths vault-relative)
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub broken_links: Vec<BrokenLink>,
+    pub missing_frontmatter: Vec<String>,
+    pub duplicate_titles: Vec<TitleGroup>,
+    pub empty_notes: Vec<String>,
+    /// Content files over the index size limit
+    pub oversized: Vec<OversizedFile>,
+    pub non_utf8: Vec<String>,
+}
+
+/// A link whose target doesn't exist
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLink {
+    pub source: String,
+    pub target: String,
+    /// 1-based line in the source note
+    pub line: usize,
+}
+
+/// Indexed files sharing a title (compared case-insensitively)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleGroup {
+    pub title: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizedFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Database summary included in diagnostics bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbStats {
+    /// `PRAGMA user_version`
+    pub schema_version: i64,
+    pub sqlite_version: String,
+    pub size_bytes: u64,
+    pub tables: Vec<TableStats>,
+}
+
+/// Row count of one database table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    pub name: String,
+    pub rows: u64,
+}
+
+/// Vault watcher state included in diagnostics bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub active: bool,
+    pub path: Option<String>,
+}
+
+/// Payload for `navigate` events (deep links)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigatePayload {
+    /// Vault-relative path of the target note
+    pub path: String,
+    pub note_id: String,
+    pub heading: Option<String>,
+}
+
+/// Platform details returned by get_platform_info
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    pub os: String,
+    pub arch: String,
+    /// Scale factor of the main window's current monitor
+    pub device_pixel_ratio: f64,
+    /// OS accent color as `#rrggbb`, if the platform exposes one
+    pub accent_color: Option<String>,
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+}
+
+/// Payload for `platform:scale-changed` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleChangedPayload {
+    pub previous: f64,
+    pub device_pixel_ratio: f64,
+}
+
+/// Installed font family returned by list_system_fonts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemFont {
+    pub family: String,
+    /// At least one face of the family is monospaced
+    pub monospace: bool,
+}
+
+/// A note's raw frontmatter, returned by get_frontmatter_block
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontmatterBlock {
+    /// YAML between the `---` lines (empty if there is no block)
+    pub yaml: String,
+    pub present: bool,
+}
+
+/// Typed YAML frontmatter returned by get_note_metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteMetadata {
+    /// Stable note ID (frontmatter `id`)
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    /// All other frontmatter fields
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    pub has_frontmatter: bool,
+    /// Set when frontmatter exists but isn't valid YAML
+    pub error: Option<String>,
 }
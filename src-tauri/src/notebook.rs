@@ -0,0 +1,136 @@
+//! Notebooks - Jupyter (.ipynb) parsing
+//!
+//! Notebooks are nbformat 4 JSON. Cell sources may be a string or a list of
+//! lines; both are joined into a single string. Outputs are reduced to their
+//! plain text and PNG images, which is what a read-only view renders.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::models::{Notebook, NotebookCell, NotebookOutput};
+use crate::utils;
+
+#[derive(Deserialize)]
+struct RawNotebook {
+    #[serde(default)]
+    cells: Vec<RawCell>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Deserialize)]
+struct RawCell {
+    cell_type: String,
+    #[serde(default)]
+    source: Value,
+    #[serde(default)]
+    execution_count: Option<u32>,
+    #[serde(default)]
+    outputs: Vec<Value>,
+}
+
+/// Check if path is a Jupyter notebook
+pub fn is_notebook_file(path: &str) -> bool {
+    path.ends_with(".ipynb")
+}
+
+/// Parse notebook JSON
+pub fn parse(content: &str) -> Result<Notebook, String> {
+    let raw: RawNotebook = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid notebook: {}", e))?;
+
+    let language = raw.metadata
+        .pointer("/language_info/name")
+        .or_else(|| raw.metadata.pointer("/kernelspec/language"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let cells = raw.cells
+        .into_iter()
+        .map(|cell| NotebookCell {
+            outputs: cell.outputs.iter().filter_map(parse_output).collect(),
+            source: join_text(&cell.source),
+            cell_type: cell.cell_type,
+            execution_count: cell.execution_count,
+        })
+        .collect();
+
+    Ok(Notebook { language, cells })
+}
+
+/// Title from the first markdown heading, else the file name
+pub fn title(path: &str, notebook: &Notebook) -> String {
+    notebook.cells
+        .iter()
+        .filter(|cell| cell.cell_type == "markdown")
+        .find_map(|cell| utils::extract_title_from_content(&cell.source))
+        .unwrap_or_else(|| utils::path_to_title(path))
+}
+
+/// Markdown and code cell sources as searchable text (outputs excluded)
+pub fn searchable_text(notebook: &Notebook) -> String {
+    notebook.cells
+        .iter()
+        .filter(|cell| cell.cell_type == "markdown" || cell.cell_type == "code")
+        .map(|cell| cell.source.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn parse_output(output: &Value) -> Option<NotebookOutput> {
+    let output_type = output.get("output_type")?.as_str()?.to_string();
+
+    let text = match output_type.as_str() {
+        "stream" => output.get("text").map(join_text),
+        "error" => {
+            let name = output.get("ename").and_then(Value::as_str).unwrap_or_default();
+            let value = output.get("evalue").and_then(Value::as_str).unwrap_or_default();
+            Some(format!("{}: {}", name, value))
+        }
+        _ => output.pointer("/data/text~1plain").map(join_text),
+    };
+    let image_png = output
+        .pointer("/data/image~1png")
+        .map(join_text)
+        .map(|data| data.split_whitespace().collect());
+
+    Some(NotebookOutput { output_type, text, image_png })
+}
+
+/// nbformat multiline string: a string or a list of lines
+fn join_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notebook() {
+        let content = r##"{
+            "metadata": {"language_info": {"name": "python"}},
+            "nbformat": 4,
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["# Loss curves\n", "Training notes"]},
+                {"cell_type": "code", "execution_count": 2, "metadata": {}, "source": "print(1)",
+                 "outputs": [{"output_type": "stream", "name": "stdout", "text": ["1\n"]}]},
+                {"cell_type": "raw", "metadata": {}, "source": "ignored"}
+            ]
+        }"##;
+
+        let notebook = parse(content).unwrap();
+        assert_eq!(notebook.language.as_deref(), Some("python"));
+        assert_eq!(notebook.cells.len(), 3);
+        assert_eq!(notebook.cells[1].execution_count, Some(2));
+        assert_eq!(notebook.cells[1].outputs[0].text.as_deref(), Some("1\n"));
+
+        assert_eq!(title("/v/train.ipynb", &notebook), "Loss curves");
+        assert_eq!(searchable_text(&notebook), "# Loss curves\nTraining notes\n\nprint(1)");
+        assert!(parse("not json").is_err());
+    }
+}
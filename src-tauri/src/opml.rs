@@ -0,0 +1,339 @@
+//! OPML - Outline import/export for mind-mapping and outliner tools
+//!
+//! An OPML outline maps to nested `- ` list items, with an item's `_note`
+//! attribute (the OmniOutliner/Workflowy convention) as indented text under
+//! it. Going the other way, a note's headings and list items become nested
+//! outlines, and paragraph text becomes the `_note` of the item it sits
+//! under. A folder exports as one outline per note, nested by subfolder.
+
+use crate::frontmatter;
+use crate::utils;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outline {
+    pub text: String,
+    pub note: Option<String>,
+    pub children: Vec<Outline>,
+}
+
+impl Outline {
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_string(), note: None, children: Vec::new() }
+    }
+}
+
+/// A parsed OPML file
+#[derive(Debug)]
+pub struct Document {
+    pub title: Option<String>,
+    pub outlines: Vec<Outline>,
+}
+
+/// Parse OPML 1.0/2.0
+pub fn parse(xml: &str) -> Result<Document, String> {
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    let doc = roxmltree::Document::parse_with_options(xml, options).map_err(|e| format!("Invalid OPML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("opml") {
+        return Err(format!("Not an OPML file: root element is <{}>", root.tag_name().name()));
+    }
+
+    let title = root
+        .children()
+        .find(|n| n.has_tag_name("head"))
+        .and_then(|head| head.children().find(|n| n.has_tag_name("title")))
+        .and_then(|title| title.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    let body = root
+        .children()
+        .find(|n| n.has_tag_name("body"))
+        .ok_or("Invalid OPML: missing <body>")?;
+
+    Ok(Document { title, outlines: read_outlines(body) })
+}
+
+fn read_outlines(parent: roxmltree::Node) -> Vec<Outline> {
+    parent
+        .children()
+        .filter(|n| n.has_tag_name("outline"))
+        .map(|node| Outline {
+            text: node.attribute("text").or_else(|| node.attribute("title")).unwrap_or("").trim().to_string(),
+            note: node.attribute("_note").map(|note| note.trim().to_string()).filter(|note| !note.is_empty()),
+            children: read_outlines(node),
+        })
+        .collect()
+}
+
+/// A note with `outlines` as a nested list under a `# title` heading, and
+/// `intro` as a paragraph before the list
+pub fn to_markdown(title: &str, intro: Option<&str>, outlines: &[Outline]) -> String {
+    let mut out = format!("# {}\n", title);
+    if let Some(intro) = intro {
+        out.push_str(&format!("\n{}\n", intro));
+    }
+    if !outlines.is_empty() {
+        out.push('\n');
+        write_items(&mut out, outlines, 0);
+    }
+    out
+}
+
+fn write_items(out: &mut String, outlines: &[Outline], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for outline in outlines {
+        // Line breaks in the text would end the list item
+        let text = outline.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        out.push_str(&format!("{}- {}\n", indent, text));
+        for line in outline.note.iter().flat_map(|note| note.lines()).filter(|l| !l.trim().is_empty()) {
+            out.push_str(&format!("{}  {}\n", indent, line.trim()));
+        }
+        write_items(out, &outline.children, depth + 1);
+    }
+}
+
+/// Where an open outline came from, for deciding what nests under it
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Heading(usize),
+    /// List item whose marker is at this column
+    Item(usize),
+    Paragraph,
+}
+
+/// Headings, list items and paragraph text of a note as nested outlines
+/// A note that is a single `# Title` section yields that section's
+/// contents; use `note_outline` to keep the title.
+pub fn from_markdown(content: &str) -> Vec<Outline> {
+    let mut roots = Vec::new();
+    let mut open: Vec<(Kind, Outline)> = Vec::new();
+    let mut in_code = false;
+
+    for line in frontmatter::body(content).lines() {
+        if utils::is_code_fence(line) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || line.trim().is_empty() {
+            continue;
+        }
+        let column = line.len() - line.trim_start().len();
+
+        let (kind, text) = if let Some((level, text)) = utils::atx_heading(line) {
+            (Kind::Heading(level), text)
+        } else if let Some(text) = list_item(line.trim_start()) {
+            (Kind::Item(column), text)
+        } else {
+            // Text belongs to the innermost item it's indented under, or the heading
+            while matches!(open.last(), Some((Kind::Item(marker), _)) if column < marker + 2) {
+                close(&mut open, &mut roots);
+            }
+            match open.last_mut() {
+                Some((_, outline)) => {
+                    let note = outline.note.get_or_insert_with(String::new);
+                    if !note.is_empty() {
+                        note.push('\n');
+                    }
+                    note.push_str(line.trim());
+                }
+                None => open.push((Kind::Paragraph, Outline::new(line.trim()))),
+            }
+            continue;
+        };
+
+        while open.last().is_some_and(|(open_kind, _)| !nests_under(kind, *open_kind)) {
+            close(&mut open, &mut roots);
+        }
+        open.push((kind, Outline::new(text.trim())));
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
+
+    match roots.as_slice() {
+        [single] if single.note.is_none() && !single.children.is_empty() && content_title(content).is_some() => {
+            roots.pop().map(|single| single.children).unwrap_or_default()
+        }
+        _ => roots,
+    }
+}
+
+/// A whole note as one outline titled after it
+pub fn note_outline(title: &str, content: &str) -> Outline {
+    Outline {
+        text: title.to_string(),
+        note: None,
+        children: from_markdown(content),
+    }
+}
+
+/// The text of a note's only top-level `#` heading, if it has exactly one
+fn content_title(content: &str) -> Option<String> {
+    let mut in_code = false;
+    let mut titles = frontmatter::body(content).lines().filter_map(|line| {
+        if utils::is_code_fence(line) {
+            in_code = !in_code;
+        }
+        utils::atx_heading(line).filter(|(level, _)| !in_code && *level == 1).map(|(_, text)| text.trim().to_string())
+    });
+    let title = titles.next()?;
+    titles.next().is_none().then_some(title)
+}
+
+/// Title for an exported note: its only `#` heading, else `fallback`
+pub fn title(content: &str, fallback: &str) -> String {
+    content_title(content).unwrap_or_else(|| fallback.to_string())
+}
+
+fn nests_under(kind: Kind, parent: Kind) -> bool {
+    match (kind, parent) {
+        (Kind::Heading(level), Kind::Heading(parent_level)) => level > parent_level,
+        (Kind::Heading(_), _) => false,
+        (Kind::Item(column), Kind::Item(parent_column)) => column > parent_column,
+        (Kind::Item(_), _) => true,
+        (Kind::Paragraph, _) => false,
+    }
+}
+
+/// Close the innermost open outline, attaching it to its parent
+fn close(open: &mut Vec<(Kind, Outline)>, roots: &mut Vec<Outline>) {
+    if let Some((_, outline)) = open.pop() {
+        match open.last_mut() {
+            Some((_, parent)) => parent.children.push(outline),
+            None => roots.push(outline),
+        }
+    }
+}
+
+/// The text of a `-`/`*`/`+`/`1.`/`1)` list item, without any task checkbox
+fn list_item(line: &str) -> Option<&str> {
+    let rest = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                return None;
+            }
+            line[digits..].strip_prefix(['.', ')'])?
+        }
+    };
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    Some(["[ ] ", "[x] ", "[X] "].iter().find_map(|checkbox| rest.strip_prefix(checkbox)).unwrap_or(rest))
+}
+
+/// Add `note` under nested folder outlines for the directories of `rel_path`
+/// (relative to the exported folder), creating them as needed.
+pub fn insert_at_path(roots: &mut Vec<Outline>, rel_path: &str, note: Outline) {
+    let mut level = roots;
+    let dirs: Vec<&str> = rel_path.split('/').collect();
+    for dir in &dirs[..dirs.len() - 1] {
+        let index = match level.iter().position(|outline| outline.text == *dir && outline.note.is_none()) {
+            Some(index) => index,
+            None => {
+                level.push(Outline::new(dir));
+                level.len() - 1
+            }
+        };
+        level = &mut level[index].children;
+    }
+    level.push(note);
+}
+
+/// OPML 2.0 for `outlines`
+pub fn render(title: &str, outlines: &[Outline]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    out.push_str(&format!("  <head>\n    <title>{}</title>\n  </head>\n  <body>\n", escape(title)));
+    write_outlines(&mut out, outlines, 2);
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn write_outlines(out: &mut String, outlines: &[Outline], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for outline in outlines {
+        out.push_str(&format!("{}<outline text=\"{}\"", indent, escape(&outline.text)));
+        if let Some(note) = &outline.note {
+            out.push_str(&format!(" _note=\"{}\"", escape(note)));
+        }
+        if outline.children.is_empty() {
+            out.push_str("/>\n");
+        } else {
+            out.push_str(">\n");
+            write_outlines(out, &outline.children, depth + 1);
+            out.push_str(&format!("{}</outline>\n", indent));
+        }
+    }
+}
+
+/// Escape text for an XML attribute or element
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let xml = r#"<?xml version="1.0"?>
+<opml version="2.0">
+  <head><title>Trip &amp; plans</title></head>
+  <body>
+    <outline text="Packing" _note="Check the weather">
+      <outline text="Boots"/>
+      <outline text="Tent">
+        <outline text="Pegs"/>
+      </outline>
+    </outline>
+    <outline title="Route"/>
+  </body>
+</opml>"#;
+        let doc = parse(xml).unwrap();
+        assert_eq!(doc.title.as_deref(), Some("Trip & plans"));
+
+        let markdown = to_markdown("Trip & plans", None, &doc.outlines);
+        assert_eq!(
+            markdown,
+            "# Trip & plans\n\n- Packing\n  Check the weather\n  - Boots\n  - Tent\n    - Pegs\n- Route\n"
+        );
+        assert_eq!(from_markdown(&markdown), doc.outlines);
+
+        let rendered = render("Trip & plans", &from_markdown(&markdown));
+        assert!(rendered.contains("<title>Trip &amp; plans</title>"));
+        assert_eq!(parse(&rendered).unwrap().outlines, doc.outlines);
+
+        let note = "# Notes\n\n## Ideas\nSome text\n1. First\n   - [ ] Sub\n\n## Later\n";
+        let outlines = from_markdown(note);
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].note.as_deref(), Some("Some text"));
+        assert_eq!(outlines[0].children[0].children, vec![Outline::new("Sub")]);
+        assert!(parse("<html><body/></html>").is_err());
+    }
+
+    #[test]
+    fn test_insert_at_path() {
+        let mut roots = Vec::new();
+        insert_at_path(&mut roots, "a.md", Outline::new("A"));
+        insert_at_path(&mut roots, "Sub/b.md", Outline::new("B"));
+        insert_at_path(&mut roots, "Sub/c.md", Outline::new("C"));
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[1].text, "Sub");
+        assert_eq!(roots[1].children, vec![Outline::new("B"), Outline::new("C")]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Platform State - Tracks OS/display properties that can change at runtime
+//!
+//! The main window's scale factor is captured at startup and updated from
+//! `WindowEvent::ScaleFactorChanged` (e.g. moving between monitors).
+//! Accent color, high contrast, and reduced motion are read from OS settings
+//! at startup and re-read when the window regains focus or the theme changes.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info};
+
+use crate::models::{PlatformInfo, ScaleChangedPayload, SystemFont};
+
+/// Installed font families (enumerated once; scanning font dirs is slow)
+static SYSTEM_FONTS: OnceLock<Vec<SystemFont>> = OnceLock::new();
+
+/// Global platform info instance
+static PLATFORM_INFO: OnceLock<Mutex<PlatformInfo>> = OnceLock::new();
+
+fn get_info() -> &'static Mutex<PlatformInfo> {
+    PLATFORM_INFO.get_or_init(|| {
+        Mutex::new(PlatformInfo {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            device_pixel_ratio: 1.0,
+            accent_color: None,
+            high_contrast: false,
+            reduced_motion: false,
+        })
+    })
+}
+
+/// Current platform info snapshot
+pub fn current() -> PlatformInfo {
+    get_info().lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Record the initial scale factor (no event)
+pub fn init_scale_factor(scale_factor: f64) {
+    get_info().lock().unwrap_or_else(PoisonError::into_inner).device_pixel_ratio = scale_factor;
+}
+
+/// Handle a scale-factor change: update state and emit `platform:scale-changed`
+pub fn on_scale_factor_changed(app: &AppHandle, scale_factor: f64) {
+    let previous = {
+        let mut info = get_info().lock().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut info.device_pixel_ratio, scale_factor)
+    };
+
+    if (previous - scale_factor).abs() < f64::EPSILON {
+        return;
+    }
+
+    info!("[INFO] [platform] Scale factor changed: {} -> {}", previous, scale_factor);
+
+    let payload = ScaleChangedPayload {
+        previous,
+        device_pixel_ratio: scale_factor,
+    };
+    if let Err(e) = app.emit("platform:scale-changed", payload) {
+        error!("[ERROR] [platform] Failed to emit platform:scale-changed: {}", e);
+    }
+}
+
+/// Installed font families sorted by name
+pub fn system_fonts() -> &'static [SystemFont] {
+    SYSTEM_FONTS.get_or_init(|| {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+
+        // Family name -> any face monospaced
+        let mut families: BTreeMap<String, bool> = BTreeMap::new();
+        for face in db.faces() {
+            for (family, _) in &face.families {
+                let monospace = families.entry(family.clone()).or_insert(false);
+                *monospace |= face.monospaced;
+            }
+        }
+
+        info!("[INFO] [platform] Found {} system font families", families.len());
+
+        families
+            .into_iter()
+            .map(|(family, monospace)| SystemFont { family, monospace })
+            .collect()
+    })
+}
+
+/// OS appearance/accessibility preferences
+#[derive(Debug, Clone, Default, PartialEq)]
+struct OsPreferences {
+    accent_color: Option<String>,
+    high_contrast: bool,
+    reduced_motion: bool,
+}
+
+/// Re-query OS preferences; emits `platform:preferences-changed` when they differ
+/// Runs the (process-spawning) queries off the calling thread
+pub fn refresh_os_preferences(app: AppHandle) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let prefs = os::query_preferences();
+
+        let changed = {
+            let mut info = get_info().lock().unwrap_or_else(PoisonError::into_inner);
+            let previous = OsPreferences {
+                accent_color: info.accent_color.clone(),
+                high_contrast: info.high_contrast,
+                reduced_motion: info.reduced_motion,
+            };
+            if previous == prefs {
+                None
+            } else {
+                info.accent_color = prefs.accent_color;
+                info.high_contrast = prefs.high_contrast;
+                info.reduced_motion = prefs.reduced_motion;
+                Some(info.clone())
+            }
+        };
+
+        if let Some(info) = changed {
+            info!(
+                "[INFO] [platform] OS preferences changed: accent={:?} high_contrast={} reduced_motion={}",
+                info.accent_color, info.high_contrast, info.reduced_motion
+            );
+            if let Err(e) = app.emit("platform:preferences-changed", info) {
+                error!("[ERROR] [platform] Failed to emit platform:preferences-changed: {}", e);
+            }
+        }
+    });
+}
+
+/// Open a file or folder with the OS default handler (file manager for folders)
+pub fn open_path(path: &Path) -> Result<(), String> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+
+    std::process::Command::new(program)
+        .arg(path)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))
+}
+
+/// Run a command and return trimmed stdout if it succeeded
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+
+    // Don't flash a console window for each query
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use super::{command_output, OsPreferences};
+
+    pub fn query_preferences() -> OsPreferences {
+        // AppleAccentColor is absent when the default (blue) is selected
+        let accent = command_output("defaults", &["read", "-g", "AppleAccentColor"]);
+        let accent_color = match accent.as_deref() {
+            Some("-1") => "#8e8e93",
+            Some("0") => "#ff3b30",
+            Some("1") => "#ff9500",
+            Some("2") => "#ffcc00",
+            Some("3") => "#28cd41",
+            Some("4") => "#af52de",
+            Some("5") => "#ff2d55",
+            _ => "#007aff",
+        };
+
+        let flag = |key: &str| {
+            command_output("defaults", &["read", "com.apple.universalaccess", key]).as_deref() == Some("1")
+        };
+
+        OsPreferences {
+            accent_color: Some(accent_color.to_string()),
+            high_contrast: flag("increaseContrast"),
+            reduced_motion: flag("reduceMotion"),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod os {
+    use super::{command_output, OsPreferences};
+
+    /// Last token of a `reg query` value line, e.g. "AccentColor REG_DWORD 0xffd77800"
+    fn reg_value(key: &str, name: &str) -> Option<String> {
+        let output = command_output("reg", &["query", key, "/v", name])?;
+        output
+            .lines()
+            .find(|line| line.trim_start().starts_with(name))
+            .and_then(|line| line.split_whitespace().last())
+            .map(|v| v.to_string())
+    }
+
+    pub fn query_preferences() -> OsPreferences {
+        // DWM stores the accent as 0xAABBGGRR
+        let accent_color = reg_value(r"HKCU\Software\Microsoft\Windows\DWM", "AccentColor")
+            .and_then(|v| u32::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .map(|abgr| {
+                let r = abgr & 0xff;
+                let g = (abgr >> 8) & 0xff;
+                let b = (abgr >> 16) & 0xff;
+                format!("#{:02x}{:02x}{:02x}", r, g, b)
+            });
+
+        // HCF_HIGHCONTRASTON = 0x1
+        let high_contrast = reg_value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")
+            .and_then(|v| v.parse::<u32>().ok())
+            .map(|flags| flags & 0x1 != 0)
+            .unwrap_or(false);
+
+        // "Show animations in Windows" off => MinAnimate = 0
+        let reduced_motion = reg_value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate")
+            .map(|v| v == "0")
+            .unwrap_or(false);
+
+        OsPreferences {
+            accent_color,
+            high_contrast,
+            reduced_motion,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod os {
+    use super::{command_output, OsPreferences};
+
+    fn gsetting(schema: &str, key: &str) -> Option<String> {
+        command_output("gsettings", &["get", schema, key])
+            .map(|v| v.trim_matches('\'').to_string())
+    }
+
+    pub fn query_preferences() -> OsPreferences {
+        // GNOME 47+ named accents
+        let accent_color = gsetting("org.gnome.desktop.interface", "accent-color").and_then(|name| {
+            let hex = match name.as_str() {
+                "blue" => "#3584e4",
+                "teal" => "#2190a4",
+                "green" => "#3a944a",
+                "yellow" => "#c88800",
+                "orange" => "#ed5b00",
+                "red" => "#e62d42",
+                "pink" => "#d56199",
+                "purple" => "#9141ac",
+                "slate" => "#6f8396",
+                _ => return None,
+            };
+            Some(hex.to_string())
+        });
+
+        let high_contrast = gsetting("org.gnome.desktop.a11y.interface", "high-contrast")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let reduced_motion = gsetting("org.gnome.desktop.interface", "enable-animations")
+            .map(|v| v == "false")
+            .unwrap_or(false);
+
+        OsPreferences {
+            accent_color,
+            high_contrast,
+            reduced_motion,
+        }
+    }
+}
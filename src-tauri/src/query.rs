@@ -0,0 +1,188 @@
+//! Query blocks - Dynamic note lists from fenced `pkm-query` blocks
+//!
+//! A block is one `key: value` clause per line, all of which must hold:
+//!
+//! ```text
+//! from: Projects            notes under a folder
+//! tag: project              has the tag (or a nested one, project/...)
+//! where: status = active    property equals (also !=, or a bare key for
+//!                           "has it" and !key for "lacks it")
+//! search: launch plan       full-text match
+//! type: document            content type (document by default)
+//! sort: modified desc       modified, created, title, path or a property
+//! limit: 20
+//! fields: status, due       properties returned per note
+//! ```
+//!
+//! `tag` and `where` may repeat. Blank lines and `#` comments are skipped.
+
+/// Notes returned when the block sets no limit
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Largest accepted `limit`
+pub const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Has(String),
+    Missing(String),
+    Equals(String, String),
+    NotEquals(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKey {
+    Modified,
+    Created,
+    Title,
+    Path,
+    Property(String),
+}
+
+/// A parsed query block
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// Vault-relative folder
+    pub folder: Option<String>,
+    /// Lowercase tags, without `#`
+    pub tags: Vec<String>,
+    pub conditions: Vec<Condition>,
+    /// Full-text query as written
+    pub search: Option<String>,
+    pub content_type: String,
+    pub sort: SortKey,
+    pub descending: bool,
+    pub limit: usize,
+    pub fields: Vec<String>,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            tags: Vec::new(),
+            conditions: Vec::new(),
+            search: None,
+            content_type: "document".to_string(),
+            sort: SortKey::Modified,
+            descending: true,
+            limit: DEFAULT_LIMIT,
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Parse a block's contents (the fence lines themselves may be included)
+pub fn parse(source: &str) -> Result<Query, String> {
+    let mut query = Query::default();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("```") || line.starts_with("~~~") {
+            continue;
+        }
+        let error = |message: &str| format!("Line {}: {}", index + 1, message);
+        let (key, value) = line.split_once(':').ok_or_else(|| error("expected `key: value`"))?;
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(error(&format!("`{}` needs a value", key.trim())));
+        }
+
+        match key.trim().to_ascii_lowercase().as_str() {
+            "from" => query.folder = Some(value.trim_matches('/').to_string()),
+            "tag" | "tags" => query.tags.extend(
+                value
+                    .split([',', ' '])
+                    .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+                    .filter(|tag| !tag.is_empty()),
+            ),
+            "where" => query.conditions.push(parse_condition(value).ok_or_else(|| error("invalid condition"))?),
+            "search" => query.search = Some(value.to_string()),
+            "type" => query.content_type = value.to_ascii_lowercase(),
+            "sort" => {
+                let mut words = value.split_whitespace();
+                let key = words.next().unwrap_or_default();
+                query.sort = match key.to_ascii_lowercase().as_str() {
+                    "modified" => SortKey::Modified,
+                    "created" => SortKey::Created,
+                    "title" => SortKey::Title,
+                    "path" => SortKey::Path,
+                    _ => SortKey::Property(key.to_string()),
+                };
+                query.descending = match words.next().map(|w| w.to_ascii_lowercase()) {
+                    Some(order) if order == "desc" => true,
+                    Some(order) if order == "asc" => false,
+                    Some(_) => return Err(error("sort order must be asc or desc")),
+                    // Newest first for dates, A-Z otherwise
+                    None => matches!(query.sort, SortKey::Modified | SortKey::Created),
+                };
+            }
+            "limit" => {
+                query.limit = value
+                    .parse()
+                    .ok()
+                    .filter(|limit| (1..=MAX_LIMIT).contains(limit))
+                    .ok_or_else(|| error(&format!("limit must be 1 to {}", MAX_LIMIT)))?;
+            }
+            "fields" => query.fields.extend(
+                value.split(',').map(|field| field.trim().to_string()).filter(|field| !field.is_empty()),
+            ),
+            other => return Err(error(&format!("unknown clause `{}`", other))),
+        }
+    }
+
+    Ok(query)
+}
+
+fn parse_condition(text: &str) -> Option<Condition> {
+    if let Some((key, value)) = text.split_once("!=") {
+        let key = key.trim();
+        return (!key.is_empty()).then(|| Condition::NotEquals(key.to_string(), unquote(value)));
+    }
+    if let Some((key, value)) = text.split_once('=') {
+        let key = key.trim();
+        return (!key.is_empty()).then(|| Condition::Equals(key.to_string(), unquote(value)));
+    }
+    match text.strip_prefix('!') {
+        Some(key) if !key.trim().is_empty() => Some(Condition::Missing(key.trim().to_string())),
+        Some(_) => None,
+        None => Some(Condition::Has(text.to_string())),
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote).and_then(|v| v.strip_suffix(*quote)))
+        .unwrap_or(value)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let query = parse(
+            "```pkm-query\nfrom: /Projects/\ntag: #Project, active\nwhere: status != \"done\"\nwhere: !archived\n\
+             sort: due\nlimit: 10\nfields: status, due\n```",
+        )
+        .unwrap();
+        assert_eq!(query.folder.as_deref(), Some("Projects"));
+        assert_eq!(query.tags, vec!["project", "active"]);
+        assert_eq!(
+            query.conditions,
+            vec![Condition::NotEquals("status".to_string(), "done".to_string()), Condition::Missing("archived".to_string())]
+        );
+        assert_eq!(query.sort, SortKey::Property("due".to_string()));
+        assert!(!query.descending);
+        assert_eq!(query.limit, 10);
+        assert_eq!(query.fields, vec!["status", "due"]);
+
+        assert_eq!(parse("").unwrap(), Query::default());
+        assert_eq!(parse("limit: 0").unwrap_err(), "Line 1: limit must be 1 to 1000");
+        assert!(parse("group: x").unwrap_err().contains("unknown clause"));
+    }
+}
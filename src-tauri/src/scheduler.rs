@@ -0,0 +1,371 @@
+//! Scheduler - Recurring maintenance tasks while the app is open
+//!
+//! Four built-in tasks: database optimize (nightly), vault backup zip
+//! (weekly), git auto-commit (daily) and calendar import (hourly). A task
+//! runs once its interval has passed since its last run, which is recorded
+//! in the database so restarts don't rerun it. Only optimize is enabled by
+//! default: backups need a folder, auto-commit only applies to vaults that
+//! are git repos, and calendar import needs a calendar. Tasks run one at a
+//! time, off the async runtime.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::backup;
+use crate::calendar;
+use crate::db::Database;
+use crate::models::{ScheduledTask, ScheduledTaskRun};
+use crate::vault;
+
+pub const DB_OPTIMIZE: &str = "db-optimize";
+pub const VAULT_BACKUP: &str = "vault-backup";
+pub const GIT_COMMIT: &str = "git-commit";
+pub const CALENDAR_IMPORT: &str = "calendar-import";
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Shortest accepted interval
+pub const MIN_INTERVAL_SECS: u64 = 60 * 60;
+
+/// How often due tasks are checked for
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Wait after launch before the first check, to stay out of startup indexing
+const STARTUP_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// Scheduled backups kept per vault (oldest deleted first)
+const KEEP_BACKUPS: usize = 8;
+
+struct TaskConfig {
+    id: &'static str,
+    enabled: bool,
+    interval_secs: u64,
+}
+
+static TASKS: Mutex<[TaskConfig; 4]> = Mutex::new([
+    TaskConfig { id: DB_OPTIMIZE, enabled: true, interval_secs: DAY_SECS },
+    TaskConfig { id: VAULT_BACKUP, enabled: false, interval_secs: 7 * DAY_SECS },
+    TaskConfig { id: GIT_COMMIT, enabled: false, interval_secs: DAY_SECS },
+    TaskConfig { id: CALENDAR_IMPORT, enabled: false, interval_secs: MIN_INTERVAL_SECS },
+]);
+
+/// Folder scheduled backups are written to
+static BACKUP_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Calendar (URL or file) scheduled imports read
+static CALENDAR_SOURCE: Mutex<Option<String>> = Mutex::new(None);
+
+/// The task currently running, if any
+static RUNNING: Mutex<Option<&'static str>> = Mutex::new(None);
+
+/// Settings keys for task configs, the backup folder and the calendar
+const TASKS_SETTING: &str = "scheduled_tasks";
+const BACKUP_DIR_SETTING: &str = "backup_folder";
+const CALENDAR_SOURCE_SETTING: &str = "calendar_source";
+
+/// A task's settings as saved in the database
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedTask {
+    id: String,
+    enabled: bool,
+    interval_secs: u64,
+}
+
+/// Restore task settings, the backup folder and the calendar saved in
+/// earlier sessions
+pub fn restore_settings(db: &Database) {
+    match db.setting_json::<Vec<SavedTask>>(TASKS_SETTING) {
+        Ok(Some(saved)) => {
+            let mut tasks = TASKS.lock().unwrap_or_else(PoisonError::into_inner);
+            for saved in saved {
+                // Tasks from other versions are ignored
+                if let Some(task) = tasks.iter_mut().find(|t| t.id == saved.id) {
+                    task.enabled = saved.enabled;
+                    task.interval_secs = saved.interval_secs.max(MIN_INTERVAL_SECS);
+                }
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [scheduler] Failed to load task settings: {}", e),
+    }
+    match db.setting_json::<Option<PathBuf>>(BACKUP_DIR_SETTING) {
+        Ok(Some(dir)) => *BACKUP_DIR.lock().unwrap_or_else(PoisonError::into_inner) = dir,
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [scheduler] Failed to load backup folder: {}", e),
+    }
+    match db.setting_json::<Option<String>>(CALENDAR_SOURCE_SETTING) {
+        Ok(Some(source)) => *CALENDAR_SOURCE.lock().unwrap_or_else(PoisonError::into_inner) = source,
+        Ok(None) => {}
+        Err(e) => warn!("[WARN] [scheduler] Failed to load calendar source: {}", e),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn task_id(id: &str) -> Result<&'static str, String> {
+    [DB_OPTIMIZE, VAULT_BACKUP, GIT_COMMIT, CALENDAR_IMPORT]
+        .into_iter()
+        .find(|known| *known == id)
+        .ok_or_else(|| format!("Unknown scheduled task: {}", id))
+}
+
+/// Enable/disable a task or change its interval (saved for later sessions)
+pub fn configure(db: &Database, id: &str, enabled: Option<bool>, interval_secs: Option<u64>) -> Result<(), String> {
+    if interval_secs.is_some_and(|secs| secs < MIN_INTERVAL_SECS) {
+        return Err(format!("Interval must be at least {} seconds", MIN_INTERVAL_SECS));
+    }
+    let mut tasks = TASKS.lock().unwrap_or_else(PoisonError::into_inner);
+    let task = tasks
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("Unknown scheduled task: {}", id))?;
+    if let Some(enabled) = enabled {
+        task.enabled = enabled;
+    }
+    if let Some(secs) = interval_secs {
+        task.interval_secs = secs;
+    }
+
+    let saved: Vec<SavedTask> = tasks
+        .iter()
+        .map(|task| SavedTask { id: task.id.to_string(), enabled: task.enabled, interval_secs: task.interval_secs })
+        .collect();
+    db.set_setting_json(TASKS_SETTING, &saved)
+}
+
+pub fn backup_dir() -> Option<PathBuf> {
+    BACKUP_DIR.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Set the backup folder (saved for later sessions)
+pub fn set_backup_dir(db: &Database, dir: Option<PathBuf>) -> Result<(), String> {
+    db.set_setting_json(BACKUP_DIR_SETTING, &dir)?;
+    *BACKUP_DIR.lock().unwrap_or_else(PoisonError::into_inner) = dir;
+    Ok(())
+}
+
+pub fn calendar_source() -> Option<String> {
+    CALENDAR_SOURCE.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Set the calendar source (saved for later sessions)
+pub fn set_calendar_source(db: &Database, source: Option<String>) -> Result<(), String> {
+    db.set_setting_json(CALENDAR_SOURCE_SETTING, &source)?;
+    *CALENDAR_SOURCE.lock().unwrap_or_else(PoisonError::into_inner) = source;
+    Ok(())
+}
+
+/// All tasks with their settings and last run
+pub fn list(db: &Database) -> Result<Vec<ScheduledTask>, String> {
+    let runs = db.scheduled_task_runs()?;
+    let running = *RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+    let now = now_secs();
+    let tasks = TASKS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    Ok(tasks
+        .iter()
+        .map(|task| {
+            let last_run = runs.iter().find(|run| run.task_id == task.id).cloned();
+            let next_run_at = task
+                .enabled
+                .then(|| last_run.as_ref().map_or(now, |run| run.ran_at + task.interval_secs).max(now));
+            ScheduledTask {
+                id: task.id.to_string(),
+                enabled: task.enabled,
+                interval_secs: task.interval_secs,
+                running: running == Some(task.id),
+                last_run,
+                next_run_at,
+            }
+        })
+        .collect())
+}
+
+/// Enabled tasks whose interval has passed since their last run
+fn due(tasks: &[TaskConfig], runs: &[ScheduledTaskRun], now: u64) -> Vec<&'static str> {
+    tasks
+        .iter()
+        .filter(|task| task.enabled)
+        .filter(|task| {
+            runs.iter()
+                .find(|run| run.task_id == task.id)
+                .map_or(true, |run| now.saturating_sub(run.ran_at) >= task.interval_secs)
+        })
+        .map(|task| task.id)
+        .collect()
+}
+
+/// Check for due tasks every `CHECK_INTERVAL` for the rest of the session
+pub fn start(db: Arc<Database>) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let due_tasks = match db.scheduled_task_runs() {
+                Ok(runs) => due(&TASKS.lock().unwrap_or_else(PoisonError::into_inner)[..], &runs, now_secs()),
+                Err(e) => {
+                    warn!("[WARN] [scheduler] Failed to read task runs: {}", e);
+                    Vec::new()
+                }
+            };
+            for id in due_tasks {
+                if let Err(e) = run(db.clone(), id).await {
+                    warn!("[WARN] [scheduler] {}", e);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Run a task now and record the outcome
+/// Fails without running if another task is still running; a failure of
+/// the task itself is recorded and returned as an unsuccessful run.
+pub async fn run(db: Arc<Database>, id: &str) -> Result<ScheduledTaskRun, String> {
+    let id = task_id(id)?;
+    {
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(other) = *running {
+            return Err(format!("Cannot run {}: {} is still running", id, other));
+        }
+        *running = Some(id);
+    }
+    info!("[INFO] [scheduler] Running {}", id);
+
+    let task_db = db.clone();
+    let outcome = tauri::async_runtime::spawn_blocking(move || execute(&task_db, id))
+        .await
+        .unwrap_or_else(|e| Err(format!("Task failed: {}", e)));
+    *RUNNING.lock().unwrap_or_else(PoisonError::into_inner) = None;
+
+    let run = ScheduledTaskRun {
+        task_id: id.to_string(),
+        ran_at: now_secs(),
+        ok: outcome.is_ok(),
+        message: outcome.unwrap_or_else(|e| e),
+    };
+    if run.ok {
+        info!("[INFO] [scheduler] {}: {}", id, run.message);
+    } else {
+        warn!("[WARN] [scheduler] {} failed: {}", id, run.message);
+    }
+    db.save_scheduled_task_run(&run)?;
+    Ok(run)
+}
+
+/// Run a task, returning a summary of what it did
+fn execute(db: &Database, id: &str) -> Result<String, String> {
+    match id {
+        DB_OPTIMIZE => {
+            db.optimize()?;
+            db.checkpoint()?;
+            Ok("Database optimized".to_string())
+        }
+        VAULT_BACKUP => backup_vault(),
+        GIT_COMMIT => git_commit(),
+        CALENDAR_IMPORT => import_calendar(),
+        _ => Err(format!("Unknown scheduled task: {}", id)),
+    }
+}
+
+fn open_vault() -> Result<PathBuf, String> {
+    vault::root().map(PathBuf::from).ok_or_else(|| "No vault is open".to_string())
+}
+
+fn backup_vault() -> Result<String, String> {
+    let root = open_vault()?;
+    let dir = backup_dir().ok_or("No backup folder is set")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "vault".to_string());
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let target = backup::outside_path(&root, &dir.join(format!("{}-{}.zip", name, stamp)))?;
+    let files = backup::backup_files(&root, true);
+    let count = backup::write_zip(&root, &files, &target, false, &|_, _| {})?;
+    let pruned = backup::prune(&dir, &name, KEEP_BACKUPS);
+
+    Ok(format!("Backed up {} files to {:?} ({} old backups removed)", count, target, pruned.len()))
+}
+
+fn git_commit() -> Result<String, String> {
+    let root = open_vault()?;
+    if !root.join(".git").exists() {
+        return Err("The vault is not a git repository".to_string());
+    }
+
+    let status = git(&root, &["status", "--porcelain"])?;
+    let changed = status.lines().count();
+    if changed == 0 {
+        return Ok("Nothing to commit".to_string());
+    }
+    git(&root, &["add", "-A"])?;
+    let message = format!("Vault auto-commit {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    git(&root, &["commit", "-q", "-m", &message])?;
+    Ok(format!("Committed {} changed files", changed))
+}
+
+fn import_calendar() -> Result<String, String> {
+    let root = open_vault()?;
+    let source = calendar_source().ok_or("No calendar is set")?;
+    let ics = tauri::async_runtime::block_on(calendar::load(&source))?;
+    let events = calendar::parse(&ics)?;
+    let folder = root.join(calendar::DEFAULT_FOLDER);
+    let writes = calendar::plan(&folder, &events, chrono::Local::now().date_naive(), calendar::DEFAULT_DAYS)?;
+
+    let created = writes.iter().filter(|write| write.created).count();
+    std::fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {:?}: {}", folder, e))?;
+    for write in &writes {
+        // Written untracked so the watcher indexes them
+        std::fs::write(&write.path, &write.content).map_err(|e| format!("Failed to write {:?}: {}", write.path, e))?;
+    }
+    Ok(format!("Created {} and updated {} meeting notes", created, writes.len() - created))
+}
+
+/// Run git in `repo`, returning stdout (stderr as the error on failure)
+fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(repo).args(args);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command.output().map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due() {
+        let tasks = [
+            TaskConfig { id: DB_OPTIMIZE, enabled: true, interval_secs: DAY_SECS },
+            TaskConfig { id: VAULT_BACKUP, enabled: true, interval_secs: 7 * DAY_SECS },
+            TaskConfig { id: GIT_COMMIT, enabled: false, interval_secs: DAY_SECS },
+            TaskConfig { id: CALENDAR_IMPORT, enabled: false, interval_secs: MIN_INTERVAL_SECS },
+        ];
+        let run = |id: &str, ran_at| ScheduledTaskRun { task_id: id.to_string(), ran_at, ok: true, message: String::new() };
+        let runs = vec![run(DB_OPTIMIZE, 1000), run(VAULT_BACKUP, 1000)];
+
+        assert!(due(&tasks, &runs, 1000 + DAY_SECS - 1).is_empty());
+        assert_eq!(due(&tasks, &runs, 1000 + DAY_SECS), vec![DB_OPTIMIZE]);
+        assert_eq!(due(&tasks, &[], 0), vec![DB_OPTIMIZE, VAULT_BACKUP]);
+    }
+}
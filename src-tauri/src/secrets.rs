@@ -0,0 +1,78 @@
+//! Secrets - Credentials kept in the OS keychain
+//!
+//! API tokens (sync, AI providers, publish targets) and remembered vault
+//! keys are stored in the platform keychain (Keychain on macOS, Credential
+//! Manager on Windows, Secret Service on Linux) under `SERVICE`, never in
+//! settings or the database. Names are namespaced by the caller, e.g.
+//! `sync.token` or `ai.openai`.
+
+use keyring::{Entry, Error as KeyringError};
+
+/// Keychain service every secret is filed under
+const SERVICE: &str = "unstablon-pkm";
+
+/// Longest accepted secret name
+const MAX_NAME_CHARS: usize = 128;
+
+/// Prefix of remembered vault keys (followed by the vault root)
+const VAULT_KEY_PREFIX: &str = "vault-key:";
+
+/// Check a caller-supplied secret name (`vault-key:` names are reserved)
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':'));
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS || !valid_chars {
+        return Err(format!("Invalid secret name: {:?}", name));
+    }
+    if name.starts_with(VAULT_KEY_PREFIX) {
+        return Err(format!("Reserved secret name: {}", name));
+    }
+    Ok(())
+}
+
+fn entry(name: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, name).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+/// Store (or replace) a secret
+pub fn store(name: &str, value: &[u8]) -> Result<(), String> {
+    entry(name)?
+        .set_secret(value)
+        .map_err(|e| format!("Failed to store secret {}: {}", name, e))
+}
+
+/// A stored secret; None if there is none by that name
+pub fn get(name: &str) -> Result<Option<Vec<u8>>, String> {
+    match entry(name)?.get_secret() {
+        Ok(value) => Ok(Some(value)),
+        Err(KeyringError::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret {}: {}", name, e)),
+    }
+}
+
+/// Delete a secret; false if there was none
+pub fn delete(name: &str) -> Result<bool, String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(KeyringError::NoEntry) => Ok(false),
+        Err(e) => Err(format!("Failed to delete secret {}: {}", name, e)),
+    }
+}
+
+/// Name the vault key of the vault at `root` is remembered under
+pub fn vault_key_name(root: &str) -> String {
+    format!("{}{}", VAULT_KEY_PREFIX, root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("sync.token").is_ok());
+        assert!(validate_name("ai:openai-key_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name(&vault_key_name("/home/me/Vault")).is_err());
+    }
+}
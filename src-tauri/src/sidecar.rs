@@ -0,0 +1,75 @@
+//! Sidecars - Notes and tags on non-markdown files
+//!
+//! An attachment (PDF, image, ...) is annotated by a YAML file next to it
+//! named after it plus `.meta.yaml` (`paper.pdf` -> `paper.pdf.meta.yaml`)
+//! with optional `note` and `tags` keys. Sidecars live in the vault, so they
+//! move with it and survive index rebuilds; the indexer files a sidecar's
+//! annotation under the annotated file's path, which is what search returns.
+
+use std::fs;
+
+use crate::models::FileAnnotation;
+
+/// Appended to the annotated file's name
+pub const SUFFIX: &str = ".meta.yaml";
+
+/// Sidecar path for `file`
+pub fn path_for(file: &str) -> String {
+    format!("{}{}", file, SUFFIX)
+}
+
+/// The file a sidecar annotates; None if `path` isn't a sidecar
+pub fn annotated_file(path: &str) -> Option<&str> {
+    path.strip_suffix(SUFFIX).filter(|file| !file.is_empty() && !file.ends_with('/'))
+}
+
+pub fn is_sidecar(path: &str) -> bool {
+    annotated_file(path).is_some()
+}
+
+/// Parse sidecar YAML (an empty file is an empty annotation)
+pub fn parse(content: &str) -> Result<FileAnnotation, String> {
+    if content.trim().is_empty() {
+        return Ok(FileAnnotation::default());
+    }
+    serde_yaml::from_str(content).map_err(|e| e.to_string())
+}
+
+/// Sidecar YAML for an annotation
+pub fn render(annotation: &FileAnnotation) -> Result<String, String> {
+    serde_yaml::to_string(annotation).map_err(|e| e.to_string())
+}
+
+/// Annotation of `file` from its sidecar, if it has a readable one
+pub fn read(file: &str) -> Option<FileAnnotation> {
+    let content = fs::read_to_string(path_for(file)).ok()?;
+    parse(&content).ok()
+}
+
+/// Searchable text of an annotation: the note, then the tags
+pub fn searchable_text(annotation: &FileAnnotation) -> String {
+    let mut text = annotation.note.clone().unwrap_or_default();
+    for tag in &annotation.tags {
+        text.push(' ');
+        text.push_str(tag);
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar() {
+        assert_eq!(annotated_file("Refs/paper.pdf.meta.yaml"), Some("Refs/paper.pdf"));
+        assert_eq!(annotated_file("Refs/.meta.yaml"), None);
+        assert_eq!(annotated_file("Refs/data.yaml"), None);
+
+        let annotation = parse("note: Key figures on p. 4\ntags: [reading, ml]\n").unwrap();
+        assert_eq!(annotation.tags, vec!["reading", "ml"]);
+        assert_eq!(searchable_text(&annotation), "Key figures on p. 4 reading ml");
+        assert_eq!(parse(&render(&annotation).unwrap()).unwrap(), annotation);
+        assert_eq!(parse("").unwrap(), FileAnnotation::default());
+    }
+}
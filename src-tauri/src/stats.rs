@@ -0,0 +1,182 @@
+//! Writing Statistics - Word counts, daily writing deltas, and top terms
+//!
+//! Daily totals come from save deltas: whenever a single note is re-indexed
+//! (the path every save takes), the word count of the new body is compared
+//! with the previously indexed one and the difference is added to today.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use std::collections::HashMap;
+
+use crate::models::{ActivityHeatmap, TermCount};
+
+/// Common words excluded from top terms
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "this", "that", "with", "from", "they", "will", "would",
+    "there", "their", "what", "about", "which", "when", "were", "been", "into", "then", "than",
+    "them", "these", "some", "its", "also", "just", "only", "more", "most", "other", "such",
+    "should", "could", "your", "his", "she", "him", "who", "how", "why", "where", "here", "each",
+    "very", "over", "after", "before", "because", "while", "does", "did", "doing", "being",
+];
+
+/// Minimum term length (in chars) considered for top terms
+const MIN_TERM_CHARS: usize = 3;
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\''))
+        .filter(|w| !w.is_empty())
+}
+
+/// Meaningful lowercase terms in `text` (no stop words, short words, or numbers)
+pub fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    words(text)
+        .filter(|w| w.chars().count() >= MIN_TERM_CHARS && !w.chars().all(|c| c.is_numeric()))
+        .map(|w| w.to_lowercase())
+        .filter(|t| !STOP_WORDS.contains(&t.as_str()))
+}
+
+/// Number of words in `text`
+pub fn word_count(text: &str) -> u32 {
+    words(text).count() as u32
+}
+
+/// Accumulates term frequencies across note bodies
+#[derive(Default)]
+pub struct TermCounter {
+    counts: HashMap<String, u32>,
+}
+
+impl TermCounter {
+    pub fn add(&mut self, text: &str) {
+        for term in terms(text) {
+            *self.counts.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    /// Most frequent terms (ties broken alphabetically)
+    pub fn top(self, limit: usize) -> Vec<TermCount> {
+        let mut terms: Vec<TermCount> = self
+            .counts
+            .into_iter()
+            .map(|(term, count)| TermCount { term, count })
+            .collect();
+        terms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.term.cmp(&b.term)));
+        terms.truncate(limit);
+        terms
+    }
+}
+
+/// Today's local date as `YYYY-MM-DD`
+pub fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Local date `days` before today as `YYYY-MM-DD`
+pub fn days_ago(days: u32) -> String {
+    (Local::now() - chrono::Duration::days(days as i64)).format("%Y-%m-%d").to_string()
+}
+
+/// Unix seconds at local midnight starting `day` (`YYYY-MM-DD`)
+pub fn day_start_secs(day: &str) -> Result<u64, String> {
+    let date = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {:?}: {}", day, e))?;
+    date_start_secs(date)
+}
+
+/// Unix seconds at local midnight starting `date`
+fn date_start_secs(date: NaiveDate) -> Result<u64, String> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    let start = Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .ok_or_else(|| format!("Invalid local date: {}", date))?;
+    Ok(start.timestamp().max(0) as u64)
+}
+
+/// Jan 1st of `year` and of the year after, if chrono can represent both
+fn year_dates(year: i32) -> Result<(NaiveDate, NaiveDate), String> {
+    let jan_first = |year: i32| NaiveDate::from_ymd_opt(year, 1, 1);
+    year.checked_add(1)
+        .and_then(|next| Some((jan_first(year)?, jan_first(next)?)))
+        .ok_or_else(|| format!("Year out of range: {}", year))
+}
+
+/// Unix seconds spanning local `year`: [Jan 1st, next Jan 1st)
+pub fn year_bounds(year: i32) -> Result<(u64, u64), String> {
+    let (first, next) = year_dates(year)?;
+    Ok((date_start_secs(first)?, date_start_secs(next)?))
+}
+
+/// Bucket creation/modification/open times (unix seconds) into local days
+/// of `year`; times outside the year are ignored
+pub fn heatmap(year: i32, created: &[u64], modified: &[u64], opens: Option<&[u64]>) -> Result<ActivityHeatmap, String> {
+    let (first, next) = year_dates(year)?;
+    let days = next.signed_duration_since(first).num_days() as usize;
+
+    let count = |times: &[u64]| {
+        let mut counts = vec![0u32; days];
+        for &secs in times {
+            let Some(local) = Local.timestamp_opt(secs as i64, 0).single() else { continue };
+            let day = local.date_naive().signed_duration_since(first).num_days();
+            if let Some(slot) = usize::try_from(day).ok().and_then(|d| counts.get_mut(d)) {
+                *slot += 1;
+            }
+        }
+        counts
+    };
+
+    let created = count(created);
+    let modified = count(modified);
+    let opens = opens.map(count).unwrap_or_default();
+    let max = (0..days)
+        .map(|d| created[d] + modified[d] + opens.get(d).copied().unwrap_or(0))
+        .max()
+        .unwrap_or(0);
+
+    Ok(ActivityHeatmap { year, days: days as u32, created, modified, opens, max })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_count() {
+        assert_eq!(word_count("Hello, world! It's 2024."), 4);
+        assert_eq!(word_count("  \n# Heading\n- item  "), 2);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn test_top_terms() {
+        let mut counter = TermCounter::default();
+        counter.add("Rust is fast. Rust is safe; the borrow checker keeps rust safe.");
+        counter.add("Safe code 2024");
+
+        // Ties sort alphabetically; stop words, short words, and numbers are skipped
+        let top = counter.top(3);
+        assert_eq!((top[0].term.as_str(), top[0].count), ("rust", 3));
+        assert_eq!((top[1].term.as_str(), top[1].count), ("safe", 3));
+        assert_eq!((top[2].term.as_str(), top[2].count), ("borrow", 1));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let (start, end) = year_bounds(2024).unwrap();
+        let day = 24 * 60 * 60;
+        let created = [start, start + 2 * day + 60, end];
+        let modified = [start + 2 * day, start + 2 * day + 5, start - 1];
+
+        let map = heatmap(2024, &created, &modified, None).unwrap();
+        assert_eq!(map.days, 366);
+        assert_eq!(&map.created[..3], &[1, 0, 1]);
+        assert_eq!(&map.modified[..3], &[0, 0, 2]);
+        assert_eq!(map.created.iter().sum::<u32>(), 2);
+        assert!(map.opens.is_empty());
+        assert_eq!(map.max, 3);
+
+        assert!(year_bounds(i32::MAX).is_err());
+        assert!(heatmap(i32::MAX, &[], &[], None).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Structured Data - JSON, YAML, and TOML files in the vault
+//!
+//! Data files are parsed into a JSON value so the frontend gets one shape
+//! regardless of format. For search, keys and string values are flattened
+//! into plain text.
+
+use serde_json::Value;
+
+use crate::utils;
+
+/// Supported data file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// Format of a path by extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        }
+    }
+}
+
+/// Parse data file content into a JSON value
+pub fn parse(format: Format, content: &str) -> Result<Value, String> {
+    match format {
+        Format::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        Format::Toml => toml::from_str(content).map_err(|e: toml::de::Error| e.to_string()),
+    }
+}
+
+/// Title from a top-level `title` or `name` string, else the file name
+pub fn title(path: &str, data: &Value) -> String {
+    ["title", "name"]
+        .iter()
+        .find_map(|key| data.get(key).and_then(Value::as_str))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| utils::path_to_title(path))
+}
+
+/// Keys and string values as searchable text
+pub fn searchable_text(data: &Value) -> String {
+    let mut parts = Vec::new();
+    collect_text(data, &mut parts);
+    parts.join(" ")
+}
+
+fn collect_text<'a>(value: &'a Value, parts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => parts.push(s),
+        Value::Array(items) => items.iter().for_each(|item| collect_text(item, parts)),
+        Value::Object(map) => {
+            for (key, value) in map {
+                parts.push(key);
+                collect_text(value, parts);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(Format::from_path("a/b.YML"), Some(Format::Yaml));
+        assert_eq!(Format::from_path("notes.md"), None);
+
+        let toml = parse(Format::Toml, "title = \"Reading list\"\n[[books]]\nname = \"Dune\"\npages = 412\n").unwrap();
+        let yaml = parse(Format::Yaml, "title: Reading list\nbooks:\n  - name: Dune\n    pages: 412\n").unwrap();
+        let json = parse(Format::Json, r#"{"title": "Reading list", "books": [{"name": "Dune", "pages": 412}]}"#).unwrap();
+        assert_eq!(toml, json);
+        assert_eq!(yaml, json);
+
+        assert_eq!(title("/v/list.json", &json), "Reading list");
+        assert_eq!(searchable_text(&json), "books name Dune pages title Reading list");
+        assert!(parse(Format::Json, "{").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Link Suggestions - Ranking helpers for `[[` autocomplete
+//!
+//! The editor sends the text before the cursor. An unclosed `[[` marks the
+//! typed link text (matched against titles and aliases); the sentence around
+//! it is used as an FTS query for notes about the same topic.
+
+use crate::stats;
+
+/// What the user is typing, split out of the text before the cursor
+#[derive(Debug, PartialEq)]
+pub struct LinkContext<'a> {
+    /// Text after an unclosed `[[`, if any
+    pub prefix: Option<&'a str>,
+    /// The sentence being written (excluding the link text)
+    pub sentence: &'a str,
+}
+
+/// Split the text before the cursor into link prefix and current sentence
+pub fn link_context(text: &str) -> LinkContext<'_> {
+    let (before, prefix) = match text.rfind("[[") {
+        Some(start) if !text[start..].contains("]]") => {
+            (&text[..start], Some(text[start + 2..].trim()))
+        }
+        _ => (text, None),
+    };
+
+    let sentence_start = before
+        .rfind(['.', '!', '?', '\n'])
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    LinkContext {
+        prefix,
+        sentence: before[sentence_start..].trim(),
+    }
+}
+
+/// FTS5 query OR-ing the meaningful terms of `text` (None if there are none)
+pub fn fts_query(text: &str) -> Option<String> {
+    let mut terms: Vec<String> = stats::terms(text).collect();
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return None;
+    }
+
+    let quoted: Vec<String> = terms
+        .iter()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect();
+    Some(quoted.join(" OR "))
+}
+
+/// Score how well `candidate` (a title or alias) matches typed `prefix`
+/// Exact > starts with > a word starts with > contains; None if no match
+pub fn match_score(prefix: &str, candidate: &str) -> Option<f64> {
+    let prefix = prefix.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    if candidate == prefix {
+        Some(4.0)
+    } else if candidate.starts_with(&prefix) {
+        Some(3.0)
+    } else if candidate
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| !word.is_empty() && word.starts_with(&prefix))
+    {
+        Some(2.0)
+    } else if candidate.contains(&prefix) {
+        Some(1.0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_context() {
+        assert_eq!(
+            link_context("First idea. Rust ownership is like [[Borr"),
+            LinkContext { prefix: Some("Borr"), sentence: "Rust ownership is like" }
+        );
+        assert_eq!(
+            link_context("See [[Done]] and more text"),
+            LinkContext { prefix: None, sentence: "See [[Done]] and more text" }
+        );
+    }
+
+    #[test]
+    fn test_match_score() {
+        assert_eq!(match_score("rust", "Rust"), Some(4.0));
+        assert_eq!(match_score("rust", "Rust Ownership"), Some(3.0));
+        assert_eq!(match_score("own", "Rust Ownership"), Some(2.0));
+        assert_eq!(match_score("ship", "Rust Ownership"), Some(1.0));
+        assert_eq!(match_score("go", "Rust"), None);
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("the borrow checker and the borrow").as_deref(), Some("\"borrow\" OR \"checker\""));
+        assert_eq!(fts_query("a of the"), None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Search Synonyms - Per-vault query-time synonym expansion
+//!
+//! Read from `<vault>/.unstablon/synonyms.txt`, one group per line:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! js = javascript, ecmascript
+//! k8s = kubernetes
+//! ```
+//!
+//! Groups are symmetric: searching any member also matches the others.
+//! The file is re-read whenever its mtime changes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+use crate::vault;
+
+/// Synonyms file location relative to the vault root
+const SYNONYMS_FILE: &str = ".unstablon/synonyms.txt";
+
+/// Lowercase term -> the other members of its group(s)
+#[derive(Debug, Default, PartialEq)]
+pub struct Synonyms(HashMap<String, Vec<String>>);
+
+impl Synonyms {
+    /// Parse the synonyms file format
+    pub fn parse(text: &str) -> Self {
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let group: Vec<String> = line
+                .split(['=', ','])
+                .map(|term| term.trim().to_lowercase())
+                .filter(|term| !term.is_empty())
+                .collect();
+
+            for term in &group {
+                let others = map.entry(term.clone()).or_default();
+                for other in &group {
+                    if other != term && !others.contains(other) {
+                        others.push(other.clone());
+                    }
+                }
+            }
+        }
+
+        Synonyms(map)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Expand each bare term of an (escaped) FTS5 query into an OR group
+    /// The last term keeps its `*` prefix marker. FTS5 has no implicit AND
+    /// next to a parenthesized group, so those joins are made explicit.
+    pub fn expand(&self, query: &str) -> String {
+        let mut expanded = String::new();
+        let mut prev_joinable = false;
+
+        for token in query.split_whitespace() {
+            let (term, suffix) = match token.strip_suffix('*') {
+                Some(term) => (term, "*"),
+                None => (token, ""),
+            };
+            let is_operator = matches!(token, "AND" | "OR" | "NOT");
+
+            let group = match self.0.get(&term.to_lowercase()) {
+                Some(others) if !is_operator && !term.contains('"') => {
+                    let alternatives: Vec<String> = others
+                        .iter()
+                        .map(|other| format!("\"{}\"", other))
+                        .collect();
+                    Some(format!("({}{} OR {})", term, suffix, alternatives.join(" OR ")))
+                }
+                _ => None,
+            };
+
+            if !expanded.is_empty() {
+                expanded.push_str(if prev_joinable && !is_operator { " AND " } else { " " });
+            }
+            expanded.push_str(group.as_deref().unwrap_or(token));
+            prev_joinable = !is_operator;
+        }
+
+        expanded
+    }
+}
+
+struct Cached {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    synonyms: Arc<Synonyms>,
+}
+
+/// Synonyms of the last loaded vault
+static CACHE: OnceLock<Mutex<Option<Cached>>> = OnceLock::new();
+
+/// Synonyms for the open vault (empty if no vault or no synonyms file)
+pub fn for_vault() -> Arc<Synonyms> {
+    let root = match vault::root() {
+        Some(root) => root,
+        None => return Arc::default(),
+    };
+    let path = Path::new(&root).join(SYNONYMS_FILE);
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+    let mut cache = CACHE.get_or_init(|| Mutex::new(None)).lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(cached) = cache.as_ref() {
+        if cached.path == path && cached.modified == modified {
+            return cached.synonyms.clone();
+        }
+    }
+
+    let synonyms = match modified {
+        Some(_) => match fs::read_to_string(&path) {
+            Ok(text) => {
+                let synonyms = Synonyms::parse(&text);
+                info!("[INFO] [synonyms] Loaded {} terms from {:?}", synonyms.0.len(), path);
+                synonyms
+            }
+            Err(e) => {
+                warn!("[WARN] [synonyms] Failed to read {:?}: {}", path, e);
+                Synonyms::default()
+            }
+        },
+        None => Synonyms::default(),
+    };
+
+    let synonyms = Arc::new(synonyms);
+    *cache = Some(Cached {
+        path,
+        modified,
+        synonyms: synonyms.clone(),
+    });
+    synonyms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_expand() {
+        let synonyms = Synonyms::parse("# langs\njs = JavaScript, ecmascript\n\nk8s = kubernetes\n");
+
+        assert_eq!(synonyms.expand("rust"), "rust");
+        assert_eq!(synonyms.expand("k8s deploy*"), "(k8s OR \"kubernetes\") AND deploy*");
+        assert_eq!(synonyms.expand("k8s OR rust*"), "(k8s OR \"kubernetes\") OR rust*");
+        assert_eq!(
+            synonyms.expand("javascript*"),
+            "(javascript* OR \"js\" OR \"ecmascript\")"
+        );
+    }
+}
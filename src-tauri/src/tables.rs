@@ -0,0 +1,401 @@
+//! Tables - Markdown pipe table parsing, editing, and serialization
+//!
+//! Tables are located line by line, skipping frontmatter and fenced code
+//! blocks: a row containing `|` followed by a delimiter row (`| --- | :-: |`)
+//! starts a table, which runs until the first line without a `|`.
+//!
+//! Edits reserialize only the table's lines, with columns padded so the
+//! pipes line up.
+
+use crate::frontmatter;
+use crate::models::{TableData, TableOp};
+
+/// Column alignment from the delimiter row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    fn parse(cell: &str) -> Option<Self> {
+        let cell = cell.trim();
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+            return None;
+        }
+        Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::None,
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Alignment::None => "none",
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+        }
+    }
+
+    fn from_name(s: &str) -> Self {
+        match s {
+            "left" => Alignment::Left,
+            "center" => Alignment::Center,
+            "right" => Alignment::Right,
+            _ => Alignment::None,
+        }
+    }
+
+    fn delimiter(self, width: usize) -> String {
+        let width = width.max(3);
+        match self {
+            Alignment::None => "-".repeat(width),
+            Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+            Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+        }
+    }
+}
+
+/// Byte ranges of every table in `content`, in document order
+fn table_ranges(content: &str) -> Vec<(usize, usize)> {
+    // Start after frontmatter so its `|` block scalars aren't mistaken for rows
+    let body = frontmatter::body(content);
+    let body_start = content.len() - body.len();
+
+    let mut lines = Vec::new();
+    let mut offset = body_start;
+    for line in body.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    let mut ranges = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let text = lines[i].1.trim();
+
+        if let Some(marker) = fence {
+            if text.starts_with(marker) {
+                fence = None;
+            }
+            i += 1;
+            continue;
+        }
+        if text.starts_with("```") || text.starts_with("~~~") {
+            fence = Some(&text[..3]);
+            i += 1;
+            continue;
+        }
+
+        let is_header = text.contains('|')
+            && lines.get(i + 1).is_some_and(|(_, next)| is_delimiter_row(next));
+        if !is_header {
+            i += 1;
+            continue;
+        }
+
+        let start = lines[i].0;
+        let mut j = i + 2;
+        while j < lines.len() && lines[j].1.contains('|') && !lines[j].1.trim().is_empty() {
+            j += 1;
+        }
+        let (last_offset, last_line) = lines[j - 1];
+        ranges.push((start, last_offset + last_line.trim_end_matches(['\r', '\n']).len()));
+        i = j;
+    }
+
+    ranges
+}
+
+fn is_delimiter_row(line: &str) -> bool {
+    let cells = split_row(line);
+    line.contains('-') && !cells.is_empty() && cells.iter().all(|cell| Alignment::parse(cell).is_some())
+}
+
+/// Cells of a row, split on unescaped pipes (outer pipes optional)
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") { &line[..line.len() - 1] } else { line };
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push_str("\\|");
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            c => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+/// Parse a table's text into a cell matrix (row 0 is the header)
+fn parse_table(text: &str) -> TableData {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut cells: Vec<Vec<String>> = Vec::new();
+    let mut alignments: Vec<Alignment> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if i == 1 {
+            alignments = split_row(line).iter().filter_map(|cell| Alignment::parse(cell)).collect();
+        } else {
+            cells.push(split_row(line).iter().map(|cell| cell.replace("\\|", "|")).collect());
+        }
+    }
+
+    // Pad ragged rows to the widest row
+    let columns = cells.iter().map(Vec::len).chain([alignments.len()]).max().unwrap_or(0);
+    for row in &mut cells {
+        row.resize(columns, String::new());
+    }
+    alignments.resize(columns, Alignment::None);
+
+    TableData {
+        cells,
+        alignments: alignments.iter().map(|a| a.as_str().to_string()).collect(),
+    }
+}
+
+/// Serialize a cell matrix as an aligned markdown table (no trailing newline)
+fn serialize_table(table: &TableData, newline: &str) -> String {
+    let escape = |cell: &str| cell.replace(['\r', '\n'], " ").replace('|', "\\|");
+    let rows: Vec<Vec<String>> = table.cells
+        .iter()
+        .map(|row| row.iter().map(|cell| escape(cell)).collect())
+        .collect();
+    let alignments: Vec<Alignment> = table.alignments.iter().map(|a| Alignment::from_name(a)).collect();
+
+    let columns = alignments.len();
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| rows.iter().map(|row| row[c].chars().count()).max().unwrap_or(0).max(3))
+        .collect();
+
+    let render = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let pad = |text: &str, width: usize, alignment: Alignment| {
+        let fill = width - text.chars().count();
+        match alignment {
+            Alignment::Right => format!("{}{}", " ".repeat(fill), text),
+            Alignment::Center => format!("{}{}{}", " ".repeat(fill / 2), text, " ".repeat(fill - fill / 2)),
+            _ => format!("{}{}", text, " ".repeat(fill)),
+        }
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for (i, row) in rows.iter().enumerate() {
+        lines.push(render((0..columns).map(|c| pad(&row[c], widths[c], alignments[c])).collect()));
+        if i == 0 {
+            lines.push(render((0..columns).map(|c| alignments[c].delimiter(widths[c])).collect()));
+        }
+    }
+    lines.join(newline)
+}
+
+/// Convert CSV text to an aligned markdown table (first record is the header)
+/// Handles quoted fields with embedded commas, quotes, and newlines.
+pub fn from_csv(text: &str) -> Option<String> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| r.iter().any(|cell| !cell.trim().is_empty()));
+
+    let columns = records.iter().map(Vec::len).max()?;
+    for row in &mut records {
+        row.resize(columns, String::new());
+    }
+    let table = TableData {
+        cells: records,
+        alignments: vec![Alignment::None.as_str().to_string(); columns],
+    };
+    Some(serialize_table(&table, "\n"))
+}
+
+/// Parsed table at `index` (zero-based, document order)
+pub fn get_table(content: &str, index: usize) -> Option<TableData> {
+    let (start, end) = *table_ranges(content).get(index)?;
+    Some(parse_table(&content[start..end]))
+}
+
+/// Realign the table at `index`: padded cells, a normalized delimiter row
+/// (alignment colons kept). Returns the new content and the table markdown.
+pub fn format_table(content: &str, index: usize) -> Option<(String, String)> {
+    let (start, end) = *table_ranges(content).get(index)?;
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let formatted = serialize_table(&parse_table(&content[start..end]), newline);
+    let updated = format!("{}{}{}", &content[..start], formatted, &content[end..]);
+    Some((updated, formatted))
+}
+
+/// Realign every table; returns the new content and how many tables changed
+pub fn format_all(content: &str) -> (String, usize) {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut updated = content.to_string();
+    let mut changed = 0;
+    // Back to front so earlier ranges stay valid
+    for (start, end) in table_ranges(content).into_iter().rev() {
+        let formatted = serialize_table(&parse_table(&content[start..end]), newline);
+        if formatted != content[start..end] {
+            updated.replace_range(start..end, &formatted);
+            changed += 1;
+        }
+    }
+    (updated, changed)
+}
+
+/// Apply edits to the table at `index`; returns the new content and table
+pub fn apply_edits(content: &str, index: usize, ops: &[TableOp]) -> Result<(String, TableData), String> {
+    let (start, end) = *table_ranges(content)
+        .get(index)
+        .ok_or_else(|| format!("No table at index {}", index))?;
+    let mut table = parse_table(&content[start..end]);
+
+    for op in ops {
+        apply_op(&mut table, op)?;
+    }
+
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let updated = format!("{}{}{}", &content[..start], serialize_table(&table, newline), &content[end..]);
+    Ok((updated, table))
+}
+
+fn apply_op(table: &mut TableData, op: &TableOp) -> Result<(), String> {
+    let rows = table.cells.len();
+    let columns = table.alignments.len();
+
+    match op {
+        TableOp::SetCell { row, column, value } => {
+            let cell = table.cells
+                .get_mut(*row)
+                .and_then(|r| r.get_mut(*column))
+                .ok_or_else(|| format!("No cell at row {}, column {}", row, column))?;
+            *cell = value.clone();
+        }
+        TableOp::InsertRow { index } => {
+            if *index == 0 || *index > rows {
+                return Err(format!("Cannot insert a row at {}", index));
+            }
+            table.cells.insert(*index, vec![String::new(); columns]);
+        }
+        TableOp::DeleteRow { index } => {
+            if *index == 0 || *index >= rows {
+                return Err(format!("Cannot delete row {}", index));
+            }
+            table.cells.remove(*index);
+        }
+        TableOp::InsertColumn { index } => {
+            if *index > columns {
+                return Err(format!("Cannot insert a column at {}", index));
+            }
+            for row in &mut table.cells {
+                row.insert(*index, String::new());
+            }
+            table.alignments.insert(*index, Alignment::None.as_str().to_string());
+        }
+        TableOp::DeleteColumn { index } => {
+            if *index >= columns || columns == 1 {
+                return Err(format!("Cannot delete column {}", index));
+            }
+            for row in &mut table.cells {
+                row.remove(*index);
+            }
+            table.alignments.remove(*index);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\nnote: |\n  a | b\n---\nIntro\n\n```\n| x |\n|---|\n```\n\n|Name|Qty|\n|:--|--:|\n|apple|3|\n|pear \\| fig|12|\n\nAfter\n";
+
+    #[test]
+    fn test_get_table() {
+        let table = get_table(DOC, 0).unwrap();
+        assert_eq!(table.cells, vec![
+            vec!["Name", "Qty"],
+            vec!["apple", "3"],
+            vec!["pear | fig", "12"],
+        ]);
+        assert_eq!(table.alignments, vec!["left", "right"]);
+        assert!(get_table(DOC, 1).is_none());
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let ops = vec![
+            TableOp::SetCell { row: 1, column: 0, value: "green apple".to_string() },
+            TableOp::InsertColumn { index: 2 },
+            TableOp::SetCell { row: 0, column: 2, value: "Note".to_string() },
+            TableOp::DeleteRow { index: 2 },
+        ];
+        let (updated, _) = apply_edits(DOC, 0, &ops).unwrap();
+        assert!(updated.ends_with(
+            "| Name        | Qty | Note |\n| :---------- | --: | ---- |\n| green apple |   3 |      |\n\nAfter\n"
+        ));
+        assert!(updated.contains("```\n| x |\n|---|\n```"));
+
+        assert!(apply_edits(DOC, 0, &[TableOp::DeleteRow { index: 0 }]).is_err());
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let csv = "name,notes\r\n\"Smith, J\",\"said \"\"hi\"\"\"\r\nLee\n";
+        assert_eq!(
+            from_csv(csv).unwrap(),
+            "| name     | notes     |\n| -------- | --------- |\n| Smith, J | said \"hi\" |\n| Lee      |           |"
+        );
+        assert!(from_csv("").is_none());
+    }
+
+    #[test]
+    fn test_format_tables() {
+        let content = "# T\n\n|a|b|\n|:-|--:|\n|long cell|1|\n\ntext\n\n| xyz |\n| --- |\n| abc |\n";
+        let (updated, formatted) = format_table(content, 0).unwrap();
+        assert_eq!(formatted, "| a         |   b |\n| :-------- | --: |\n| long cell |   1 |");
+        assert!(updated.ends_with("text\n\n| xyz |\n| --- |\n| abc |\n"));
+
+        // The second table is already aligned
+        let (all, changed) = format_all(content);
+        assert_eq!((all, changed), (updated, 1));
+    }
+}
@@ -0,0 +1,113 @@
+//! Text Diff - Structured diffs for version history and conflict views
+//!
+//! Texts are split into lines or words (whitespace runs are their own
+//! tokens, so joining a hunk's changes reproduces the text) and diffed with
+//! Myers or patience. Changes are grouped into hunks with `CONTEXT_TOKENS`
+//! (line diffs: `CONTEXT_LINES`) of unchanged context; consecutive tokens of
+//! the same kind are merged into one change.
+
+use similar::{Algorithm, ChangeTag, TextDiff};
+
+use crate::models::{DiffAlgorithm, DiffChange, DiffGranularity, DiffHunk, DiffResult};
+
+/// Unchanged lines kept around each change in line diffs
+const CONTEXT_LINES: usize = 3;
+
+/// Unchanged tokens (words and whitespace) kept around each change in word diffs
+const CONTEXT_TOKENS: usize = 8;
+
+/// Diff `old` against `new`
+pub fn diff(old: &str, new: &str, granularity: DiffGranularity, algorithm: DiffAlgorithm) -> DiffResult {
+    let mut config = TextDiff::configure();
+    config.algorithm(match algorithm {
+        DiffAlgorithm::Myers => Algorithm::Myers,
+        DiffAlgorithm::Patience => Algorithm::Patience,
+    });
+    let (text_diff, context) = match granularity {
+        DiffGranularity::Line => (config.diff_lines(old, new), CONTEXT_LINES),
+        DiffGranularity::Word => (config.diff_words(old, new), CONTEXT_TOKENS),
+    };
+
+    let old_tokens = text_diff.old_slices();
+    let new_tokens = text_diff.new_slices();
+    let mut result = DiffResult::default();
+
+    for group in text_diff.grouped_ops(context) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+
+        let mut changes: Vec<DiffChange> = Vec::new();
+        for op in &group {
+            for change in text_diff.iter_changes(op) {
+                let kind = match change.tag() {
+                    ChangeTag::Equal => "equal",
+                    ChangeTag::Insert => "insert",
+                    ChangeTag::Delete => "delete",
+                };
+                let is_word = !change.value().trim().is_empty();
+                match change.tag() {
+                    ChangeTag::Insert if is_word || granularity == DiffGranularity::Line => result.insertions += 1,
+                    ChangeTag::Delete if is_word || granularity == DiffGranularity::Line => result.deletions += 1,
+                    _ => {}
+                }
+                match changes.last_mut() {
+                    Some(last) if last.kind == kind => last.text.push_str(change.value()),
+                    _ => changes.push(DiffChange { kind: kind.to_string(), text: change.value().to_string() }),
+                }
+            }
+        }
+
+        result.hunks.push(DiffHunk {
+            old_start: line_of(old_tokens, old_range.start),
+            old_lines: lines_spanned(&old_tokens[old_range]),
+            new_start: line_of(new_tokens, new_range.start),
+            new_lines: lines_spanned(&new_tokens[new_range]),
+            changes,
+        });
+    }
+
+    result
+}
+
+/// 1-based line on which token `index` starts
+fn line_of(tokens: &[&str], index: usize) -> usize {
+    1 + tokens[..index].iter().map(|t| t.matches('\n').count()).sum::<usize>()
+}
+
+/// Lines touched by a run of tokens
+fn lines_spanned(tokens: &[&str]) -> usize {
+    if tokens.is_empty() {
+        return 0;
+    }
+    let text: String = tokens.concat();
+    text.trim_end_matches('\n').matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n";
+        let new = "one\ntwo\nthree\nfour\n5\nsix\nseven\neight\nnine\n";
+        let lines = diff(old, new, DiffGranularity::Line, DiffAlgorithm::Myers);
+        assert_eq!((lines.insertions, lines.deletions), (2, 1));
+        assert_eq!(lines.hunks.len(), 1);
+        let hunk = &lines.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines), (2, 7, 2, 8));
+        let kinds: Vec<&str> = hunk.changes.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["equal", "delete", "insert", "equal", "insert"]);
+        assert_eq!(hunk.changes[1].text, "five\n");
+
+        let words = diff("The quick fox jumps.\n", "The slow fox jumps.\n", DiffGranularity::Word, DiffAlgorithm::Patience);
+        assert_eq!((words.insertions, words.deletions), (1, 1));
+        let changes: Vec<(&str, &str)> = words.hunks[0].changes.iter().map(|c| (c.kind.as_str(), c.text.as_str())).collect();
+        assert_eq!(changes, vec![("equal", "The "), ("delete", "quick"), ("insert", "slow"), ("equal", " fox jumps.\n")]);
+
+        assert!(diff("same", "same", DiffGranularity::Word, DiffAlgorithm::Myers).hunks.is_empty());
+    }
+}
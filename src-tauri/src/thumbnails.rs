@@ -0,0 +1,138 @@
+//! Thumbnails - Downscaled copies of vault images
+//!
+//! Thumbnails are cached on disk under `<app data>/thumbnails`, named by the
+//! source's content hash and the requested size, so a renamed image reuses
+//! its cache entry and an edited image gets a new one. Images with alpha are
+//! written as PNG, everything else as JPEG.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageFormat};
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::models::Thumbnail;
+use crate::utils;
+
+/// Allowed `max_px` range
+pub const MIN_SIZE: u32 = 16;
+pub const MAX_SIZE: u32 = 2048;
+
+const JPEG_QUALITY: u8 = 80;
+
+/// Check if path is an image we can thumbnail
+pub fn is_image_file(path: &str) -> bool {
+    matches!(
+        ImageFormat::from_path(path),
+        Ok(ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP | ImageFormat::Bmp)
+    )
+}
+
+/// Thumbnail of `source` fitting in `max_px` x `max_px`, generated on a miss
+/// Images already within the size are returned as-is.
+pub fn thumbnail(source: &Path, max_px: u32, cache_dir: &Path) -> Result<Thumbnail, AppError> {
+    let max_px = max_px.clamp(MIN_SIZE, MAX_SIZE);
+
+    let (width, height) = image::image_dimensions(source).map_err(image_error)?;
+    if width <= max_px && height <= max_px {
+        return Ok(Thumbnail {
+            path: utils::normalize_path(&source.to_string_lossy()),
+            width,
+            height,
+            cached: false,
+        });
+    }
+
+    let bytes = fs::read(source)?;
+    let hash = utils::content_hash(&bytes);
+
+    for extension in ["jpg", "png"] {
+        let cached = cache_path(cache_dir, &hash, max_px, extension);
+        if let Ok((width, height)) = image::image_dimensions(&cached) {
+            return Ok(Thumbnail {
+                path: utils::normalize_path(&cached.to_string_lossy()),
+                width,
+                height,
+                cached: true,
+            });
+        }
+    }
+
+    let image = image::load_from_memory(&bytes).map_err(image_error)?;
+    let resized = image.thumbnail(max_px, max_px);
+
+    fs::create_dir_all(cache_dir)?;
+    let target = if resized.color().has_alpha() {
+        let target = cache_path(cache_dir, &hash, max_px, "png");
+        write_atomic(&target, |file| resized.write_to(file, ImageFormat::Png))?;
+        target
+    } else {
+        let target = cache_path(cache_dir, &hash, max_px, "jpg");
+        let rgb = DynamicImage::ImageRgb8(resized.to_rgb8());
+        write_atomic(&target, |file| rgb.write_with_encoder(JpegEncoder::new_with_quality(file, JPEG_QUALITY)))?;
+        target
+    };
+    info!("[INFO] [thumbnails] Generated {}px thumbnail for {:?}", max_px, source);
+
+    Ok(Thumbnail {
+        path: utils::normalize_path(&target.to_string_lossy()),
+        width: resized.width(),
+        height: resized.height(),
+        cached: false,
+    })
+}
+
+fn cache_path(cache_dir: &Path, hash: &str, max_px: u32, extension: &str) -> PathBuf {
+    cache_dir.join(format!("{}-{}.{}", hash, max_px, extension))
+}
+
+/// Write through a temp file so a concurrent reader never sees a partial image
+fn write_atomic<F>(target: &Path, write: F) -> Result<(), AppError>
+where
+    F: FnOnce(&mut BufWriter<fs::File>) -> image::ImageResult<()>,
+{
+    let temp = target.with_extension("tmp");
+    let mut file = BufWriter::new(fs::File::create(&temp)?);
+    write(&mut file).map_err(image_error)?;
+    drop(file);
+    fs::rename(&temp, target)?;
+    Ok(())
+}
+
+fn image_error(e: image::ImageError) -> AppError {
+    match e {
+        image::ImageError::IoError(e) => e.into(),
+        other => AppError::InvalidOperation(format!("Failed to process image: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_thumbnail_cache() {
+        let dir = std::env::temp_dir().join(format!("unstablon-thumbs-{}", std::process::id()));
+        let cache_dir = dir.join("cache");
+        fs::create_dir_all(&dir).unwrap();
+
+        let source = dir.join("photo.png");
+        RgbImage::from_pixel(400, 200, Rgb([200, 10, 10])).save(&source).unwrap();
+
+        let first = thumbnail(&source, 100, &cache_dir).unwrap();
+        assert_eq!((first.width, first.height, first.cached), (100, 50, false));
+        assert!(first.path.ends_with("-100.jpg"));
+
+        let second = thumbnail(&source, 100, &cache_dir).unwrap();
+        assert_eq!((second.path, second.cached), (first.path, true));
+
+        // Already small enough: the original is returned
+        let original = thumbnail(&source, 1000, &cache_dir).unwrap();
+        assert!(original.path.ends_with("photo.png"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,143 @@
+//! Operation Undo - Short-lived undo for vault-wide operations
+//!
+//! Commands that change many files at once (bulk edits, deduplication,
+//! renames, deletes) record what each touched file held before, and
+//! `undo_last_operation` puts it back: files that existed get their old
+//! bytes, files the operation created are removed. Each file's hash right
+//! after the operation is kept too, so an undo that would throw away a
+//! later edit is refused. This is separate from
+//! the editor's per-document undo stack. Operations expire after
+//! `UNDO_WINDOW_SECS`, and one whose snapshot would exceed `MAX_UNDO_BYTES`
+//! isn't recorded.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::utils;
+
+/// How long an operation stays undoable (seconds)
+pub const UNDO_WINDOW_SECS: u64 = 10 * 60;
+
+/// Operations kept at most (oldest dropped first)
+const MAX_OPERATIONS: usize = 20;
+
+/// Largest snapshot of file contents one operation may keep
+const MAX_UNDO_BYTES: usize = 64 * 1024 * 1024;
+
+/// A recorded operation and what its files held before it
+pub struct Operation {
+    /// Shown to the user ("Set status on 12 notes")
+    pub label: String,
+    /// Seconds since UNIX epoch
+    pub at: u64,
+    pub files: Vec<UndoFile>,
+}
+
+/// One file an operation touched
+pub struct UndoFile {
+    /// Absolute path
+    pub path: String,
+    /// Bytes before the operation; None if the operation created it
+    pub before: Option<Vec<u8>>,
+    /// Content hash right after the operation; None if it was deleted
+    pub after: Option<String>,
+}
+
+impl UndoFile {
+    /// Whether the file still holds what the operation left there
+    pub fn is_unchanged(&self) -> bool {
+        fs::read(&self.path).ok().map(|bytes| utils::content_hash(&bytes)) == self.after
+    }
+}
+
+static OPERATIONS: Mutex<VecDeque<Operation>> = Mutex::new(VecDeque::new());
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Record an operation once its files are written (no-op if it touched
+/// no files); `files` are (absolute path, previous bytes or None if created)
+pub fn record(label: String, files: Vec<(String, Option<Vec<u8>>)>) {
+    if files.is_empty() {
+        return;
+    }
+    let bytes: usize = files.iter().filter_map(|(_, before)| before.as_ref()).map(Vec::len).sum();
+    if bytes > MAX_UNDO_BYTES {
+        warn!("[WARN] [undo] {} keeps {} bytes, too large to undo", label, bytes);
+        return;
+    }
+
+    let files = files
+        .into_iter()
+        .map(|(path, before)| {
+            let after = fs::read(&path).ok().map(|bytes| utils::content_hash(&bytes));
+            UndoFile { path, before, after }
+        })
+        .collect();
+    let mut operations = OPERATIONS.lock().unwrap_or_else(PoisonError::into_inner);
+    operations.push_back(Operation { label, at: now_secs(), files });
+    while operations.len() > MAX_OPERATIONS {
+        operations.pop_front();
+    }
+}
+
+/// Take the most recent operation still within the undo window
+pub fn take_last() -> Option<Operation> {
+    take_last_at(&mut OPERATIONS.lock().unwrap_or_else(PoisonError::into_inner), now_secs())
+}
+
+/// Drop expired operations and pop the newest remaining one
+fn take_last_at(operations: &mut VecDeque<Operation>, now: u64) -> Option<Operation> {
+    operations.retain(|op| now.saturating_sub(op.at) <= UNDO_WINDOW_SECS);
+    operations.pop_back()
+}
+
+/// Label of the operation `undo_last_operation` would revert
+pub fn peek_label() -> Option<String> {
+    let now = now_secs();
+    let operations = OPERATIONS.lock().unwrap_or_else(PoisonError::into_inner);
+    operations
+        .iter()
+        .rev()
+        .find(|op| now.saturating_sub(op.at) <= UNDO_WINDOW_SECS)
+        .map(|op| op.label.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_last() {
+        let op = |label: &str, at| Operation { label: label.to_string(), at, files: Vec::new() };
+        let mut operations = VecDeque::from([op("old", 100), op("recent", 1000), op("latest", 1100)]);
+
+        let now = 100 + UNDO_WINDOW_SECS + 1;
+        assert_eq!(take_last_at(&mut operations, now).map(|op| op.label).as_deref(), Some("latest"));
+        assert_eq!(take_last_at(&mut operations, now).map(|op| op.label).as_deref(), Some("recent"));
+        assert!(take_last_at(&mut operations, now).is_none());
+    }
+
+    #[test]
+    fn test_is_unchanged() {
+        let path = std::env::temp_dir().join(format!("unstablon-undo-{}.md", std::process::id()));
+        fs::write(&path, "after").unwrap();
+        let file = UndoFile {
+            path: path.to_string_lossy().to_string(),
+            before: Some(b"before".to_vec()),
+            after: Some(utils::content_hash(b"after")),
+        };
+        assert!(file.is_unchanged());
+        fs::write(&path, "edited since").unwrap();
+        assert!(!file.is_unchanged());
+        fs::remove_file(&path).unwrap();
+        assert!(!file.is_unchanged());
+    }
+}
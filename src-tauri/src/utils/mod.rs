@@ -1,6 +1,14 @@
 //! Utility functions for Unstablon PKM
 
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::frontmatter;
+use crate::models::{OutlineHeading, TitleSource};
 
 /// Extract module ID from file path
 /// Returns full filename WITH extension (matches OS behavior, eliminates collisions)
@@ -38,68 +46,502 @@ pub fn is_document_file(path: &str) -> bool {
     path.ends_with(".md") || path.ends_with(".txt")
 }
 
+/// Check if path is a structured data file (JSON, YAML, TOML)
+pub fn is_data_file(path: &str) -> bool {
+    crate::structured::Format::from_path(path).is_some()
+}
+
+/// Check if path is a source file indexed by symbols (.js modules excluded)
+pub fn is_code_file(path: &str) -> bool {
+    !is_module_file(path) && crate::indexer::code::Language::from_path(path).is_some()
+}
+
+/// Check if path is any file type the indexer handles
+pub fn is_content_file(path: &str) -> bool {
+    is_module_file(path)
+        || is_page_file(path)
+        || is_document_file(path)
+        || is_data_file(path)
+        || is_code_file(path)
+        || crate::notebook::is_notebook_file(path)
+        || crate::email::is_email_file(path)
+}
+
+/// `name` made safe to use as a file name
+/// Characters some platform disallows become spaces, and leading/trailing
+/// dots are dropped; "Untitled" if nothing is left.
+pub fn safe_file_name(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { ' ' } else { c })
+        .take(100)
+        .collect();
+    let stem = stem.split_whitespace().collect::<Vec<_>>().join(" ");
+    let stem = stem.trim_matches('.');
+    if stem.is_empty() { "Untitled".to_string() } else { stem.to_string() }
+}
+
 /// Normalize path separators to forward slashes
 pub fn normalize_path(path: &str) -> String {
     path.replace('\\', "/")
 }
 
-/// Extract title from file content
-/// Priority:
-/// 1) YAML frontmatter `title:` field (markdown)
-/// 2) First `#` heading (markdown)
-/// 3) First `<h1>` tag content (HTML/JavaScript)
-/// 4) None (falls back to filename)
-pub fn extract_title_from_content(content: &str) -> Option<String> {
-    let mut in_frontmatter = false;
-    let mut frontmatter_started = false;
-
-    for line in content.lines() {
-        let trimmed = line.trim();
-
-        // Track YAML frontmatter bounds (---)
-        if trimmed == "---" {
-            if !frontmatter_started {
-                frontmatter_started = true;
-                in_frontmatter = true;
-                continue;
-            } else {
-                in_frontmatter = false;
-                continue;
+/// Unicode NFC form of `text`
+/// macOS has historically stored file names decomposed (NFD) while Windows
+/// and Linux keep them as typed (usually NFC), so a synced vault can report
+/// the same name either way.
+pub fn nfc(text: &str) -> String {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => text.to_string(),
+        _ => text.nfc().collect(),
+    }
+}
+
+/// Path for comparing names across platforms: forward slashes, NFC
+/// Only for identity and lookups; files are opened by their on-disk name.
+pub fn path_key(path: &str) -> String {
+    nfc(&normalize_path(path))
+}
+
+/// Resolve a frontend-supplied path inside `vault_root`
+/// Accepts vault-relative paths and absolute paths under the root. Rejects
+/// `..` components, absolute paths elsewhere (including UNC and `\\?\`
+/// device paths), drive-relative/stream names, and symlinks leading out.
+pub fn safe_join(vault_root: &Path, user_path: &str) -> Result<PathBuf, AppError> {
+    if user_path.trim().is_empty() || user_path.contains('\0') {
+        return Err(AppError::Path(format!("Invalid path: {:?}", user_path)));
+    }
+
+    let root = normalize_path(&vault_root.to_string_lossy()).trim_end_matches('/').to_string();
+    let normalized = normalize_path(user_path);
+
+    // Absolute paths must sit under the root (case-insensitively on Windows)
+    let under_root = normalized.get(..root.len()).is_some_and(|prefix| {
+        prefix == root || (cfg!(windows) && prefix.eq_ignore_ascii_case(&root))
+    }) && matches!(normalized.as_bytes().get(root.len()), None | Some(b'/'));
+
+    let relative = if under_root {
+        &normalized[root.len()..]
+    } else if normalized.starts_with('/') || normalized.get(1..2) == Some(":") {
+        return Err(AppError::Path(format!("Path is outside the vault: {}", user_path)));
+    } else {
+        normalized.as_str()
+    };
+
+    let mut joined = vault_root.to_path_buf();
+    for component in relative.split('/') {
+        match component {
+            "" | "." => continue,
+            ".." => return Err(AppError::Path(format!("Path escapes the vault: {}", user_path))),
+            // Alternate data streams on Windows; an ordinary character elsewhere
+            c if cfg!(windows) && c.contains(':') => {
+                return Err(AppError::Path(format!("Invalid path component: {}", c)))
             }
+            c => joined.push(c),
         }
+    }
 
-        // YAML frontmatter title field
-        if in_frontmatter && trimmed.starts_with("title:") {
-            let value = trimmed[6..].trim().trim_matches(['\'', '"']);
-            if !value.is_empty() {
-                return Some(value.to_string());
-            }
+    ensure_within(vault_root, &joined, user_path)?;
+    Ok(joined)
+}
+
+/// Reject paths whose nearest existing ancestor resolves (through symlinks)
+/// outside the vault root
+fn ensure_within(vault_root: &Path, path: &Path, user_path: &str) -> Result<(), AppError> {
+    let canonical_root = vault_root.canonicalize()?;
+    let existing = match path.ancestors().find(|p| p.exists()) {
+        Some(existing) => existing.canonicalize()?,
+        None => return Ok(()),
+    };
+
+    if existing.starts_with(&canonical_root) {
+        Ok(())
+    } else {
+        Err(AppError::Path(format!("Path resolves outside the vault: {}", user_path)))
+    }
+}
+
+/// Path relative to `root` (both normalized); unchanged if outside it
+pub fn strip_root(path: &str, root: &str) -> String {
+    match path.strip_prefix(root) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/').to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// SHA-256 of file content as lowercase hex
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Stable note identity
+/// Uses the frontmatter `id` if present, otherwise a UUID derived from the
+/// vault-relative path (stable across machines, changes on rename)
+pub fn note_uid(relative_path: &str, content: Option<&str>) -> String {
+    if let Some(id) = content.and_then(|c| frontmatter::parse(c).id) {
+        return id;
+    }
+    path_uid(relative_path)
+}
+
+/// UUID v5 derived from a vault-relative path
+pub fn path_uid(relative_path: &str) -> String {
+    let name = format!("unstablon://note/{}", path_key(relative_path));
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes()).to_string()
+}
+
+/// Tags of a markdown note: frontmatter `tags` plus inline `#tags`
+/// (outside fenced code; `#` must start a word and the tag can't be all digits)
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let mut tags = frontmatter::parse(content).tags;
+    let mut in_code = false;
+
+    for line in frontmatter::body(content).lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
         }
 
-        // Markdown h1 heading (only outside frontmatter)
-        if !in_frontmatter && trimmed.starts_with("# ") {
-            let title = trimmed[2..].trim();
-            if !title.is_empty() {
-                return Some(title.to_string());
+        let mut prev = ' ';
+        for (i, c) in line.char_indices() {
+            if c == '#' && prev.is_whitespace() {
+                let tag: String = line[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                let tag = tag.trim_end_matches(['-', '/']);
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_numeric()) {
+                    tags.push(tag.to_string());
+                }
             }
+            prev = c;
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    tags.retain(|t| seen.insert(t.to_lowercase()));
+    tags
+}
+
+/// A `@Name` mention found in a note
+#[derive(Debug, PartialEq)]
+pub struct NoteMention {
+    pub name: String,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// `@Name` mentions, skipping code and email addresses
+/// A mention starts a word (or follows an opening bracket) and runs over
+/// letters, digits, `_`, `-` and `.`, without trailing punctuation.
+pub fn extract_mentions(content: &str) -> Vec<NoteMention> {
+    let body = frontmatter::body(content);
+    let first_line = content[..content.len() - body.len()].matches('\n').count() + 1;
+    let mut mentions = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in body.lines().enumerate() {
+        if is_code_fence(line) {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
         }
 
-        // HTML <h1> tag - extract content between <h1> and </h1>
-        if let Some(start) = trimmed.find("<h1") {
-            // Find the closing > of the opening tag
-            if let Some(tag_end) = trimmed[start..].find('>') {
-                let after_tag = &trimmed[start + tag_end + 1..];
-                // Find the closing </h1>
-                if let Some(end) = after_tag.find("</h1>") {
-                    let title = after_tag[..end].trim();
-                    if !title.is_empty() {
-                        return Some(title.to_string());
-                    }
+        let mut prev = ' ';
+        let mut in_code = false;
+        for (i, c) in line.char_indices() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '@' && !in_code && (prev.is_whitespace() || matches!(prev, '(' | '[')) {
+                let name: String = line[i + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                    .collect();
+                let name = name.trim_end_matches(['.', '-']);
+                if !name.is_empty() && !name.chars().all(|c| c.is_numeric()) {
+                    mentions.push(NoteMention { name: name.to_string(), line: first_line + index });
                 }
             }
+            prev = c;
         }
     }
-    None
+    mentions
+}
+
+/// Link key a mention of `name` is indexed under: the note named after
+/// them in `people_folder`
+pub fn mention_key(people_folder: &str, name: &str) -> String {
+    match people_folder.trim_matches('/') {
+        "" => link_key(name),
+        folder => link_key(&format!("{}/{}", folder, name)),
+    }
+}
+
+/// A link found in a note
+#[derive(Debug, PartialEq)]
+pub struct NoteLink {
+    pub target: String,
+    pub wiki: bool,
+    /// `![[...]]` or `![...](...)`
+    pub embed: bool,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// Wikilink and markdown link targets, skipping fenced code blocks and
+/// external URLs
+pub fn extract_links(content: &str) -> Vec<NoteLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find("[[") {
+            let after = &rest[pos + 2..];
+            let Some(end) = after.find("]]") else { break };
+            let inner = &after[..end];
+            let target = inner[..inner.find(['|', '#']).unwrap_or(inner.len())].trim();
+            if !target.is_empty() {
+                let embed = rest[..pos].ends_with('!');
+                links.push(NoteLink { target: target.to_string(), wiki: true, embed, line: index + 1 });
+            }
+            rest = &after[end + 2..];
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find("](") {
+            let after = &rest[pos + 2..];
+            let Some(end) = after.find(')') else { break };
+            let raw = after[..end].trim();
+            let target = match raw.strip_prefix('<').and_then(|r| r.split_once('>')) {
+                Some((target, _)) => target,
+                None => raw.split(' ').next().unwrap_or_default(),
+            };
+            let target = target[..target.find(['#', '?']).unwrap_or(target.len())].replace("%20", " ");
+            if !target.is_empty() && !target.contains("://") && !target.starts_with("mailto:") {
+                let embed = rest[..pos].rfind('[').is_some_and(|open| rest[..open].ends_with('!'));
+                links.push(NoteLink { target, wiki: false, embed, line: index + 1 });
+            }
+            rest = &after[end + 1..];
+        }
+    }
+
+    links
+}
+
+/// Key a link target is stored under in the index: NFC, lowercase, no `.md`
+/// Markdown link targets are resolved to vault-relative paths first.
+pub fn link_key(target: &str) -> String {
+    let target = nfc(target.trim().trim_start_matches('/')).to_lowercase();
+    target.strip_suffix(".md").map(str::to_string).unwrap_or(target)
+}
+
+/// Link keys that refer to the note at a vault-relative path: its path,
+/// and its file name (how a bare `[[Name]]` refers to it). A folder note
+/// also answers to its folder's path and name (see `is_folder_note`).
+pub fn note_link_keys(relative_path: &str, exists: impl Fn(&str) -> bool) -> Vec<String> {
+    let path_key = link_key(relative_path);
+    let mut keys = vec![path_key.clone()];
+    if let Some((_, name)) = path_key.rsplit_once('/') {
+        keys.push(name.to_string());
+    }
+    if is_folder_note(relative_path, exists) {
+        let folder = path_key.rsplit_once('/').map_or("", |(folder, _)| folder);
+        keys.push(folder.to_string());
+        if let Some((_, name)) = folder.rsplit_once('/') {
+            keys.push(name.to_string());
+        }
+    }
+    keys.dedup();
+    keys
+}
+
+/// Vault-relative paths a folder's note may have, in priority order:
+/// `Folder/Folder.md`, then `Folder/index.md`
+pub fn folder_note_paths(folder: &str) -> Vec<String> {
+    let folder = normalize_path(folder).trim_matches('/').to_string();
+    match folder.rsplit('/').next().filter(|name| !name.is_empty()) {
+        Some(name) => vec![format!("{}/{}.md", folder, name), format!("{}/index.md", folder)],
+        None => Vec::new(),
+    }
+}
+
+/// Whether a vault-relative path is its folder's note: the first of the
+/// folder's `folder_note_paths` that `exists` (so `Folder/index.md` isn't
+/// one next to `Folder/Folder.md`)
+pub fn is_folder_note(relative_path: &str, exists: impl Fn(&str) -> bool) -> bool {
+    let path = normalize_path(relative_path);
+    path.rsplit_once('/').is_some_and(|(folder, _)| {
+        folder_note_paths(folder).into_iter().find(|candidate| *candidate == path || exists(candidate)) == Some(path.clone())
+    })
+}
+
+/// Markdown ATX heading texts (outside fenced code)
+pub fn extract_headings(content: &str) -> Vec<String> {
+    outline(content).into_iter().map(|heading| heading.text).collect()
+}
+
+/// Markdown ATX headings (outside fenced code) with level, anchor and line number
+pub fn outline(content: &str) -> Vec<OutlineHeading> {
+    let body = frontmatter::body(content);
+    let first_line = content[..content.len() - body.len()].matches('\n').count() + 1;
+    let mut headings = Vec::new();
+    let mut slugs = HeadingSlugs::default();
+    let mut in_code = false;
+
+    for (index, line) in body.lines().enumerate() {
+        if is_code_fence(line) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        if let Some((level, text)) = atx_heading(line) {
+            if !text.is_empty() {
+                headings.push(OutlineHeading {
+                    level,
+                    text: text.to_string(),
+                    slug: slugs.next(text),
+                    line: first_line + index,
+                });
+            }
+        }
+    }
+
+    headings
+}
+
+/// GitHub-style heading anchor: NFC, lowercase, spaces to `-`, and
+/// punctuation dropped (letters and digits of any script are kept)
+pub fn slugify_heading(text: &str) -> String {
+    nfc(text.trim())
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Anchors for a note's headings taken in order, repeats suffixed `-1`,
+/// `-2`, ... as GitHub renders them
+#[derive(Default)]
+pub struct HeadingSlugs(HashMap<String, usize>);
+
+impl HeadingSlugs {
+    pub fn next(&mut self, text: &str) -> String {
+        let base = slugify_heading(text);
+        let count = self.0.entry(base.clone()).or_insert(0);
+        let slug = if *count == 0 { base } else { format!("{}-{}", base, count) };
+        *count += 1;
+        slug
+    }
+}
+
+/// Check if a line opens or closes a fenced code block
+pub fn is_code_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// (level, text) of a markdown ATX heading line (`## Text ##` -> (2, "Text"))
+pub fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    let rest = &trimmed[level..];
+    if (1..=6).contains(&level) && (rest.starts_with(' ') || rest.trim().is_empty()) {
+        Some((level, rest.trim().trim_end_matches('#').trim()))
+    } else {
+        None
+    }
+}
+
+/// Extract title from file content
+/// Priority:
+/// 1) YAML frontmatter `title:` field (markdown)
+/// 2) First `#` heading (markdown)
+/// 3) First `<h1>` tag content (HTML/JavaScript)
+/// 4) None (falls back to filename)
+pub fn extract_title_from_content(content: &str) -> Option<String> {
+    // YAML frontmatter title field
+    if let Some(title) = frontmatter::parse(content).title {
+        return Some(title);
+    }
+
+    frontmatter::body(content)
+        .lines()
+        .find_map(|line| markdown_h1(line).or_else(|| html_h1(line)))
+}
+
+/// Title of a document or page from the first of `sources` that yields one,
+/// else its file name
+pub fn note_title(path: &str, content: &str, sources: &[TitleSource]) -> String {
+    sources
+        .iter()
+        .find_map(|source| match source {
+            TitleSource::Frontmatter(field) => frontmatter_title(content, field),
+            TitleSource::Heading => frontmatter::body(content).lines().find_map(markdown_h1),
+            TitleSource::HtmlHeading => frontmatter::body(content).lines().find_map(html_h1),
+            TitleSource::HtmlTitle => html_title(content),
+            TitleSource::FileName => Some(path_to_title(path)),
+        })
+        .unwrap_or_else(|| path_to_title(path))
+}
+
+/// A frontmatter field as a title: its value, or a list's first item
+fn frontmatter_title(content: &str, field: &str) -> Option<String> {
+    let metadata = frontmatter::parse(content);
+    let title = match field {
+        "title" => metadata.title,
+        "alias" | "aliases" => metadata.aliases.into_iter().next(),
+        _ => match metadata.properties.get(field)? {
+            serde_json::Value::Array(items) => items.first()?.as_str().map(str::to_string),
+            value => value.as_str().map(str::to_string),
+        },
+    }?;
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Text of a markdown `# ` heading line
+fn markdown_h1(line: &str) -> Option<String> {
+    let title = line.trim().strip_prefix("# ")?.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Text between `<h1 ...>` and `</h1>` on one line
+fn html_h1(line: &str) -> Option<String> {
+    let start = line.find("<h1")?;
+    // Find the closing > of the opening tag
+    let after_tag = &line[start..][line[start..].find('>')? + 1..];
+    let title = after_tag[..after_tag.find("</h1>")?].trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Text of an HTML `<title>` element
+fn html_title(content: &str) -> Option<String> {
+    let start = content.find("<title>")? + "<title>".len();
+    let title = content[start..][..content[start..].find("</title>")?].trim();
+    (!title.is_empty()).then(|| title.to_string())
 }
 
 #[cfg(test)]
@@ -120,6 +562,136 @@ mod tests {
         assert_eq!(path_to_title("test-markdown.md"), "test-markdown");
     }
 
+    #[test]
+    fn test_extract_mentions() {
+        let content = "---\nattendees: [a]\n---\nWith @Jane_Doe and (@bob).\nMail bob@example.com, `@code`, @2024\n```\n@fenced\n```\n";
+        let names: Vec<(String, usize)> = extract_mentions(content).into_iter().map(|m| (m.name, m.line)).collect();
+        assert_eq!(names, vec![("Jane_Doe".to_string(), 4), ("bob".to_string(), 4)]);
+        assert_eq!(mention_key("/People/", "Jane_Doe"), "people/jane_doe");
+    }
+
+    #[test]
+    fn test_safe_file_name() {
+        assert_eq!(safe_file_name("a/b: c?"), "a b c");
+        assert_eq!(safe_file_name(" ..."), "Untitled");
+    }
+
+    #[test]
+    fn test_extract_title_from_content() {
+        assert_eq!(extract_title_from_content("---\ntitle: 'FM'\n---\n# H1"), Some("FM".to_string()));
+        assert_eq!(extract_title_from_content("---\ntags: [a]\n---\n# H1"), Some("H1".to_string()));
+        // A later horizontal rule is not frontmatter
+        assert_eq!(extract_title_from_content("text\n---\ntitle: no\n---\n"), None);
+        assert_eq!(extract_title_from_content("<h1 class=\"x\">Page</h1>"), Some("Page".to_string()));
+
+        let note = "---\naliases: [Short, Other]\n---\n# Heading\n";
+        let sources = [TitleSource::Frontmatter("alias".to_string()), TitleSource::Heading];
+        assert_eq!(note_title("a/My note.md", note, &sources), "Short");
+        assert_eq!(note_title("a/My note.md", note, &[TitleSource::FileName, TitleSource::Heading]), "My note");
+        assert_eq!(note_title("a/My note.md", "no heading", &sources), "My note");
+        assert_eq!(note_title("p.html", "<title>T</title><h1>H</h1>", &[TitleSource::HtmlTitle]), "T");
+    }
+
+    #[test]
+    fn test_note_uid() {
+        // Same filename in different folders no longer collides
+        assert_ne!(note_uid("A/index.md", None), note_uid("B/index.md", None));
+        assert_eq!(note_uid("A/index.md", None), path_uid("A\\index.md"));
+        // The NFD name a Mac reports is the same note
+        assert_eq!(path_uid("Caf\u{e9}.md"), path_uid("Cafe\u{301}.md"));
+        assert_eq!(path_key("A\\Cafe\u{301}.md"), "A/Caf\u{e9}.md");
+        // Frontmatter id wins over the path
+        assert_eq!(note_uid("A/index.md", Some("---\nid: abc-123\n---\n")), "abc-123");
+    }
+
+    #[test]
+    fn test_safe_join() {
+        let root = std::env::temp_dir().join("unstablon-safe-join");
+        std::fs::create_dir_all(root.join("Notes")).unwrap();
+        let root_str = normalize_path(&root.to_string_lossy());
+
+        assert_eq!(safe_join(&root, "Notes/a.md").unwrap(), root.join("Notes").join("a.md"));
+        assert_eq!(safe_join(&root, &format!("{}/Notes/a.md", root_str)).unwrap(), root.join("Notes").join("a.md"));
+
+        for bad in ["../../../../etc/passwd", "Notes/../../x", "/etc/passwd", "C:\\Windows\\win.ini",
+                    "\\\\server\\share\\x", "\\\\?\\C:\\x", "Notes\\..\\..\\x", ""] {
+            assert!(safe_join(&root, bad).is_err(), "accepted {:?}", bad);
+        }
+        assert_eq!(safe_join(&root, "Notes/a.md:stream").is_err(), cfg!(windows));
+
+        // Sibling directory sharing the root as a name prefix
+        assert!(safe_join(&root, &format!("{}-other/a.md", root_str)).is_err());
+
+        #[cfg(unix)]
+        {
+            let link = root.join("escape");
+            let _ = std::fs::remove_file(&link);
+            std::os::unix::fs::symlink("/etc", &link).unwrap();
+            assert!(safe_join(&root, "escape/passwd").is_err());
+        }
+    }
+
+    #[test]
+    fn test_extract_tags() {
+        let content = "---\ntags: [alpha]\n---\n# Heading\nText #beta and #nested/tag, not#this or #123.\n```\n#code\n```\n#Alpha again";
+        assert_eq!(extract_tags(content), vec!["alpha", "beta", "nested/tag"]);
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let links = extract_links("See [[Plan#Goals|plan]] and ![[Diagram]].\n```\n[[code]]\n```\n![img](a%20b.png) [doc](../Doc.md#x) [web](https://x.y)");
+        let summary: Vec<(&str, bool, bool, usize)> =
+            links.iter().map(|l| (l.target.as_str(), l.wiki, l.embed, l.line)).collect();
+        assert_eq!(
+            summary,
+            vec![("Plan", true, false, 1), ("Diagram", true, true, 1), ("a b.png", false, true, 5), ("../Doc.md", false, false, 5)]
+        );
+    }
+
+    #[test]
+    fn test_link_keys() {
+        assert_eq!(link_key("/Projects/Plan.md"), "projects/plan");
+        let none = |_: &str| false;
+        assert_eq!(note_link_keys("Projects/Plan.md", none), vec!["projects/plan", "plan"]);
+        assert_eq!(note_link_keys("Inbox.md", none), vec!["inbox"]);
+        assert_eq!(note_link_keys("Areas/Work/index.md", none), vec!["areas/work/index", "index", "areas/work", "work"]);
+        assert_eq!(note_link_keys("Work/Work.md", none), vec!["work/work", "work"]);
+        assert!(!is_folder_note("Work/Other.md", none) && !is_folder_note("index.md", none));
+        // Folder/Folder.md takes priority over Folder/index.md
+        assert!(!is_folder_note("Work/index.md", |path| path == "Work/Work.md"));
+        assert!(is_folder_note("Work/Work.md", |path| path == "Work/index.md"));
+    }
+
+    #[test]
+    fn test_extract_headings() {
+        let content = "---\ntitle: T\n---\n# One\ntext\n## Two ##\n```\n# not a heading\n```\n#tag\n####### seven";
+        assert_eq!(extract_headings(content), vec!["One", "Two"]);
+        let lines: Vec<(usize, usize)> = outline(content).iter().map(|h| (h.level, h.line)).collect();
+        assert_eq!(lines, vec![(1, 4), (2, 6)]);
+    }
+
+    #[test]
+    fn test_heading_slugs() {
+        assert_eq!(slugify_heading(" Setup [v2]: Día 1 "), "setup-v2-día-1");
+        assert_eq!(slugify_heading("Cafe\u{301}"), slugify_heading("Caf\u{e9}"));
+        let slugs: Vec<String> = outline("# Notes\n## Notes\n## notes!\n").into_iter().map(|h| h.slug).collect();
+        assert_eq!(slugs, vec!["notes", "notes-1", "notes-2"]);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_strip_root() {
+        assert_eq!(strip_root("/vault/Notes/a.md", "/vault"), "Notes/a.md");
+        assert_eq!(strip_root("/vault2/a.md", "/vault"), "/vault2/a.md");
+    }
+
     #[test]
     fn test_is_module_file() {
         assert!(is_module_file("test.js"));
@@ -0,0 +1,75 @@
+//! Vault Root - The currently opened vault directory
+//!
+//! Set only by `open_vault` from the folder picked in the native dialog (or
+//! restored from the last session at startup). Commands never re-root from
+//! a frontend-supplied path; they resolve against the stored root so
+//! path-derived state like note IDs is relative to it.
+
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::error::AppError;
+use crate::utils;
+
+/// Global vault root (normalized, no trailing slash)
+static VAULT_ROOT: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn get_root() -> &'static Mutex<Option<String>> {
+    VAULT_ROOT.get_or_init(|| Mutex::new(None))
+}
+
+fn normalize_root(path: &str) -> String {
+    utils::normalize_path(path).trim_end_matches('/').to_string()
+}
+
+/// Record the vault root
+pub fn set_root(path: &str) {
+    *get_root().lock().unwrap_or_else(PoisonError::into_inner) = Some(normalize_root(path));
+}
+
+/// Current vault root, if one has been opened
+pub fn root() -> Option<String> {
+    get_root().lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Whether `path` is the open vault's root
+pub fn is_root(path: &str) -> bool {
+    root().is_some_and(|root| root == normalize_root(path))
+}
+
+/// Current vault root, or an error if no vault is open
+pub fn require_root() -> Result<String, AppError> {
+    root().ok_or_else(|| AppError::Path("No vault is open".to_string()))
+}
+
+/// Path relative to the vault root (normalized); unchanged if outside the vault
+pub fn relative_path(path: &str) -> String {
+    let normalized = utils::normalize_path(path);
+    match root() {
+        Some(root) => utils::strip_root(&normalized, &root),
+        None => normalized,
+    }
+}
+
+/// Resolve a frontend-supplied path inside the open vault
+/// See `utils::safe_join` for what is rejected.
+pub fn resolve(path: &str) -> Result<String, AppError> {
+    let root = require_root()?;
+    let resolved = utils::safe_join(Path::new(&root), path)?;
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Whether a vault-relative path exists in the open vault
+pub fn exists(path: &str) -> bool {
+    resolve(path).is_ok_and(|resolved| Path::new(&resolved).exists())
+}
+
+/// Resolve where an export will be written inside the open vault
+/// An existing file is only replaced when `overwrite` is set.
+pub fn resolve_output(path: &str, overwrite: bool) -> Result<String, AppError> {
+    let resolved = resolve(path)?;
+    if !overwrite && Path::new(&resolved).exists() {
+        return Err(AppError::Conflict(format!("File already exists: {}", relative_path(&resolved))));
+    }
+    Ok(resolved)
+}
@@ -0,0 +1,159 @@
+//! Vault Key - Passphrase-protected key for encrypted vault data
+//!
+//! The vault key is random and never changes; what's stored is the key
+//! encrypted (ChaCha20-Poly1305) with a key derived from the passphrase by
+//! Argon2id, so changing the passphrase only re-wraps it and nothing
+//! encrypted with the vault key needs rewriting. A wrong passphrase fails
+//! decryption. While unlocked the key is held in memory (zeroed on lock)
+//! and locks itself `auto_lock_secs` after unlocking.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use zeroize::Zeroizing;
+
+use crate::models::WrappedVaultKey;
+
+/// Vault and derived key length (bytes)
+pub const KEY_LEN: usize = 32;
+
+/// Shortest accepted passphrase (characters)
+pub const MIN_PASSPHRASE_CHARS: usize = 8;
+
+const SALT_LEN: usize = 16;
+
+/// Time after unlocking before the vault locks itself (0 = never)
+static AUTO_LOCK_SECS: AtomicU64 = AtomicU64::new(15 * 60);
+
+pub type VaultKey = Zeroizing<[u8; KEY_LEN]>;
+
+struct Unlocked {
+    // Held only to be zeroed on drop until encrypted data uses it
+    _key: VaultKey,
+    unlocked_at: Instant,
+}
+
+static UNLOCKED: Mutex<Option<Unlocked>> = Mutex::new(None);
+
+/// A new random vault key, wrapped with `passphrase`
+pub fn create(passphrase: &str) -> Result<(WrappedVaultKey, VaultKey), String> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    key.copy_from_slice(&ChaCha20Poly1305::generate_key(&mut OsRng));
+    let wrapped = wrap(&key, passphrase)?;
+    Ok((wrapped, key))
+}
+
+/// Encrypt `key` with a key derived from `passphrase` (fresh salt and nonce)
+pub fn wrap(key: &VaultKey, passphrase: &str) -> Result<WrappedVaultKey, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_CHARS));
+    }
+
+    let mut salt = vec![0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut wrapped = WrappedVaultKey {
+        salt,
+        nonce: Vec::new(),
+        wrapped_key: Vec::new(),
+        m_cost: Params::DEFAULT_M_COST,
+        t_cost: Params::DEFAULT_T_COST,
+        p_cost: Params::DEFAULT_P_COST,
+    };
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(derive(passphrase, &wrapped)?.as_ref()));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    wrapped.wrapped_key = cipher
+        .encrypt(&nonce, key.as_ref())
+        .map_err(|_| "Failed to encrypt vault key".to_string())?;
+    wrapped.nonce = nonce.to_vec();
+    Ok(wrapped)
+}
+
+/// Decrypt the vault key; fails if `passphrase` is wrong
+pub fn unwrap(wrapped: &WrappedVaultKey, passphrase: &str) -> Result<VaultKey, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(derive(passphrase, wrapped)?.as_ref()));
+    if wrapped.nonce.len() != 12 {
+        return Err("Stored vault key is corrupt".to_string());
+    }
+    let plain = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&wrapped.nonce), wrapped.wrapped_key.as_ref())
+            .map_err(|_| "Wrong passphrase".to_string())?,
+    );
+    if plain.len() != KEY_LEN {
+        return Err("Stored vault key is corrupt".to_string());
+    }
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    key.copy_from_slice(&plain);
+    Ok(key)
+}
+
+/// Argon2id key from `passphrase` with the stored salt and cost parameters
+fn derive(passphrase: &str, wrapped: &WrappedVaultKey) -> Result<VaultKey, String> {
+    let params = Params::new(wrapped.m_cost, wrapped.t_cost, wrapped.p_cost, Some(KEY_LEN))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &wrapped.salt, key.as_mut())
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Hold `key` in memory until locked
+pub fn unlock(key: VaultKey) {
+    *UNLOCKED.lock().unwrap_or_else(PoisonError::into_inner) = Some(Unlocked { _key: key, unlocked_at: Instant::now() });
+}
+
+/// Drop the in-memory key (zeroed); returns whether it was unlocked
+pub fn lock() -> bool {
+    UNLOCKED.lock().unwrap_or_else(PoisonError::into_inner).take().is_some()
+}
+
+/// Lock if the auto-lock time has passed; true if this call locked it
+pub fn expire() -> bool {
+    let mut unlocked = UNLOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+    let timeout = auto_lock_secs();
+    if timeout > 0 && unlocked.as_ref().is_some_and(|u| u.unlocked_at.elapsed() >= Duration::from_secs(timeout)) {
+        *unlocked = None;
+        return true;
+    }
+    false
+}
+
+/// Whether the key is in memory (locking first if the auto-lock time passed)
+pub fn is_unlocked() -> bool {
+    expire();
+    UNLOCKED.lock().unwrap_or_else(PoisonError::into_inner).is_some()
+}
+
+pub fn auto_lock_secs() -> u64 {
+    AUTO_LOCK_SECS.load(Ordering::Relaxed)
+}
+
+pub fn set_auto_lock_secs(secs: u64) {
+    AUTO_LOCK_SECS.store(secs, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_unwrap() {
+        assert!(create("short").is_err());
+
+        let (wrapped, key) = create("correct horse").unwrap();
+        assert_eq!(unwrap(&wrapped, "correct horse").unwrap(), key);
+        assert_eq!(unwrap(&wrapped, "wrong horse!").unwrap_err(), "Wrong passphrase");
+
+        let rewrapped = wrap(&key, "battery staple").unwrap();
+        assert_ne!(rewrapped.salt, wrapped.salt);
+        assert_eq!(unwrap(&rewrapped, "battery staple").unwrap(), key);
+        assert!(unwrap(&rewrapped, "correct horse").is_err());
+    }
+}
@@ -9,10 +9,14 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::{mpsc, Mutex};
 use tokio::time::{Instant, sleep_until};
 use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+use crate::db::DbState;
+use crate::models::WatcherStatus;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Public Types
@@ -28,6 +32,8 @@ pub struct FileModifiedPayload {
 pub struct FileDeletedPayload {
     pub path: String,
     pub note_id: String,
+    /// Stable note ID (see `utils::note_uid`)
+    pub uid: String,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -36,6 +42,8 @@ pub struct FileRenamedPayload {
     pub new_path: String,
     pub old_note_id: String,
     pub new_note_id: String,
+    pub old_uid: String,
+    pub new_uid: String,
 }
 
 /// Managed vault watcher with lifecycle control
@@ -115,6 +123,15 @@ impl VaultWatcher {
 
         println!("[INFO] [Watcher] Stopped");
     }
+
+    /// Whether a watcher is running, and on which path
+    pub async fn status(&self) -> WatcherStatus {
+        let state = self.inner.lock().await;
+        WatcherStatus {
+            active: state.watcher.is_some(),
+            path: state.current_path.clone(),
+        }
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -148,8 +165,9 @@ fn create_async_watcher() -> notify::Result<(
 struct EventBatch {
     /// Navigation-relevant events (create/remove/rename)
     nav_changed: bool,
-    /// File modifications: path -> mtime
-    modifications: HashMap<PathBuf, u64>,
+    /// File modifications: path key -> (path, mtime), so a file reported
+    /// under both NFC and NFD names (macOS) is handled once
+    modifications: HashMap<String, (PathBuf, u64)>,
     /// Deleted content files
     deletions: Vec<PathBuf>,
     /// Renamed files: (old_path, new_path)
@@ -229,7 +247,8 @@ impl EventBatch {
                     // Only track if file exists (avoid race with deletes)
                     if path.is_file() {
                         if let Ok(mtime) = get_mtime(path) {
-                            self.modifications.insert(path.clone(), mtime);
+                            let key = crate::utils::path_key(&path.to_string_lossy());
+                            self.modifications.insert(key, (path.clone(), mtime));
                         }
                     }
                 }
@@ -313,7 +332,7 @@ async fn run_event_loop(
                     Err(e) => {
                         // Ignore transient OS errors (common during atomic saves)
                         if !is_transient_error(&e) {
-                            eprintln!("[ERROR] [Watcher] {}", e);
+                            error!("[ERROR] [Watcher] {}", e);
                         }
                     }
                 }
@@ -341,12 +360,14 @@ fn emit_batch(batch: &EventBatch, app: &AppHandle) {
             new_path: new_path.to_string_lossy().to_string(),
             old_note_id: path_to_id(&old_path.to_string_lossy()),
             new_note_id: path_to_id(&new_path.to_string_lossy()),
+            old_uid: indexed_uid(app, old_path),
+            new_uid: current_uid(new_path),
         };
         println!(
             "[INFO] [Watcher] Emitting file:renamed: {} -> {}",
             payload.old_path, payload.new_path
         );
-        app.emit("file:renamed", payload).ok();
+        emit_or_log(app, "file:renamed", payload);
     }
 
     // Emit deletions SECOND (before vault:changed)
@@ -354,17 +375,18 @@ fn emit_batch(batch: &EventBatch, app: &AppHandle) {
         let payload = FileDeletedPayload {
             path: path.to_string_lossy().to_string(),
             note_id: path_to_id(&path.to_string_lossy()),
+            uid: indexed_uid(app, path),
         };
         println!("[INFO] [Watcher] Emitting file:deleted: {}", payload.path);
-        app.emit("file:deleted", payload).ok();
+        emit_or_log(app, "file:deleted", payload);
     }
 
     if batch.nav_changed {
         println!("[INFO] [Watcher] Emitting vault:changed");
-        app.emit("vault:changed", ()).ok();
+        emit_or_log(app, "vault:changed", ());
     }
 
-    for (path, mtime) in &batch.modifications {
+    for (path, mtime) in batch.modifications.values() {
         let path_str = path.to_string_lossy().to_string();
 
         // Skip if this file was recently written by our app
@@ -378,7 +400,14 @@ fn emit_batch(batch: &EventBatch, app: &AppHandle) {
             mtime: *mtime,
         };
         println!("[INFO] [Watcher] Emitting file:modified: {}", payload.path);
-        app.emit("file:modified", payload).ok();
+        emit_or_log(app, "file:modified", payload);
+    }
+}
+
+/// Emit an event, logging (rather than silently dropping) failures
+fn emit_or_log<S: serde::Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        error!("[ERROR] [Watcher] Failed to emit {}: {}", event, e);
     }
 }
 
@@ -404,10 +433,10 @@ fn should_ignore(event: &Event) -> bool {
     })
 }
 
-/// Check if path is a content file (.md or .js module)
+/// Check if path is a content file (.md, .js module, data, source, notebook, or email)
 fn is_content_file(path: &Path) -> bool {
     if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        matches!(ext, "md" | "js")
+        matches!(ext, "md" | "js" | "json" | "yaml" | "yml" | "toml" | "ts" | "tsx" | "py" | "rs" | "ipynb" | "eml")
     } else {
         false
     }
@@ -424,6 +453,22 @@ fn path_to_id(path_str: &str) -> String {
         .to_string()
 }
 
+/// Stable ID of a path that no longer exists: the ID it was indexed under,
+/// falling back to the path-derived ID
+fn indexed_uid(app: &AppHandle, path: &Path) -> String {
+    let normalized = crate::utils::normalize_path(&path.to_string_lossy());
+    app.try_state::<DbState>()
+        .and_then(|db| db.0.id_for_path(&normalized).ok().flatten())
+        .unwrap_or_else(|| crate::utils::path_uid(&crate::vault::relative_path(&normalized)))
+}
+
+/// Stable ID of an existing file (frontmatter `id` or path-derived)
+fn current_uid(path: &Path) -> String {
+    let content = std::fs::read_to_string(path).ok();
+    let relative_path = crate::vault::relative_path(&path.to_string_lossy());
+    crate::utils::note_uid(&relative_path, content.as_deref())
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Global Watcher Instance (for IPC)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -441,16 +486,17 @@ pub fn start_vault_watcher(app: AppHandle, vault_path: String) {
     let watcher = get_watcher();
     tauri::async_runtime::spawn(async move {
         if let Err(e) = watcher.watch(app, vault_path).await {
-            eprintln!("[ERROR] [Watcher] Failed to start: {}", e);
+            error!("[ERROR] [Watcher] Failed to start: {}", e);
         }
     });
 }
 
-/// IPC command: Stop watching vault (optional, for future use)
-#[allow(dead_code)]
-pub fn stop_vault_watcher() {
-    let watcher = get_watcher();
-    tauri::async_runtime::spawn(async move {
-        watcher.stop().await;
-    });
+/// Stop watching the vault (app shutdown)
+pub async fn stop_vault_watcher() {
+    get_watcher().stop().await;
+}
+
+/// Current watcher state (diagnostics)
+pub async fn vault_watcher_status() -> WatcherStatus {
+    get_watcher().status().await
 }
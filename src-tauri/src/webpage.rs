@@ -0,0 +1,309 @@
+//! Web Pages - Titles and previews of external pages
+//!
+//! Pages are fetched with a short timeout and only their first
+//! `MAX_PAGE_BYTES` read (the head is all that's needed). `og:title` wins
+//! over `<title>`. Titles, including "no title", are cached for the session;
+//! failed fetches are not, so a flaky connection can be retried. Previews
+//! are cached in the database (see `get_link_preview`).
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+use tauri::Url;
+
+use crate::models::LinkPreview;
+
+/// Whole-request timeout, including redirects
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bytes of a page read at most
+pub const MAX_PAGE_BYTES: usize = 256 * 1024;
+
+/// Cached titles kept before the cache is cleared
+const MAX_CACHED: usize = 1000;
+
+/// Age after which a cached link preview is refetched (seconds)
+pub const PREVIEW_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// The start of a fetched HTML page
+pub struct Page {
+    /// Final URL, after redirects (base for relative links)
+    pub url: Url,
+    pub html: String,
+}
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static TITLE_CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+fn client() -> Result<&'static reqwest::Client, String> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .user_agent(concat!("Unstablon/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+fn title_cache() -> &'static Mutex<HashMap<String, Option<String>>> {
+    TITLE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Parse and check an http(s) URL
+pub fn parse_url(url: &str) -> Result<Url, String> {
+    let url = Url::parse(url.trim()).map_err(|e| format!("Invalid URL {:?}: {}", url, e))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!("Unsupported URL scheme: {}", scheme)),
+    }
+}
+
+/// Title of the page at `url` (None if it isn't HTML or has no title)
+pub async fn title(url: &Url) -> Result<Option<String>, String> {
+    if let Some(title) = title_cache().lock().unwrap_or_else(PoisonError::into_inner).get(url.as_str()) {
+        return Ok(title.clone());
+    }
+
+    let title = fetch_head(url).await?.and_then(|page| extract_title(&page.html));
+
+    let mut cache = title_cache().lock().unwrap_or_else(PoisonError::into_inner);
+    if cache.len() >= MAX_CACHED {
+        cache.clear();
+    }
+    cache.insert(url.to_string(), title.clone());
+    Ok(title)
+}
+
+/// Fetch `url` and extract its preview metadata
+/// A page that isn't HTML gets a preview with nothing but the URL.
+pub async fn preview(url: &Url, fetched_at: u64) -> Result<LinkPreview, String> {
+    let mut preview = LinkPreview {
+        url: url.to_string(),
+        title: None,
+        description: None,
+        image: None,
+        favicon: None,
+        fetched_at,
+        stale: false,
+    };
+    if let Some(page) = fetch_head(url).await? {
+        preview.title = extract_title(&page.html);
+        preview.description = meta_content(&page.html, &["og:description", "twitter:description", "description"]);
+        preview.image = meta_content(&page.html, &["og:image", "og:image:url", "twitter:image"])
+            .and_then(|href| page.url.join(&href).ok())
+            .map(String::from);
+        preview.favicon = extract_favicon(&page.html, &page.url);
+    }
+    Ok(preview)
+}
+
+/// The start of an HTML page, up to `</head>` or `MAX_PAGE_BYTES`
+/// None if the response isn't HTML.
+pub async fn fetch_head(url: &Url) -> Result<Option<Page>, String> {
+    let mut response = client()?
+        .get(url.clone())
+        .header("Accept", "text/html,application/xhtml+xml")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+    let is_html = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map_or(true, |v| v.contains("html"));
+    if !is_html {
+        return Ok(None);
+    }
+
+    let final_url = response.url().clone();
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read {}: {}", url, e))? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_PAGE_BYTES || contains_ignore_case(&body, b"</head>") {
+            break;
+        }
+    }
+    body.truncate(MAX_PAGE_BYTES);
+    Ok(Some(Page {
+        url: final_url,
+        html: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+/// Body of a text resource (a calendar feed, ...), up to `max_bytes`
+pub async fn fetch_text(url: &Url, max_bytes: usize) -> Result<String, String> {
+    let mut response = client()?
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Failed to read {}: {}", url, e))? {
+        body.extend_from_slice(&chunk);
+        if body.len() > max_bytes {
+            return Err(format!("{} is larger than {} bytes", url, max_bytes));
+        }
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// `og:title` (or `twitter:title`), else `<title>`
+pub fn extract_title(html: &str) -> Option<String> {
+    meta_content(html, &["og:title", "twitter:title"]).or_else(|| {
+        let lower = html.to_ascii_lowercase();
+        let open = lower.find("<title")?;
+        let start = open + lower[open..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+        clean_text(&html[start..end])
+    })
+}
+
+/// Icon from `<link rel="icon">` (or `shortcut icon` / `apple-touch-icon`),
+/// else `/favicon.ico` on the page's host
+pub fn extract_favicon(html: &str, base: &Url) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut best: Option<(usize, String)> = None;
+    let mut from = 0;
+
+    while let Some(pos) = lower[from..].find("<link") {
+        let start = from + pos;
+        let Some(len) = lower[start..].find('>') else { break };
+        let tag = &html[start..start + len];
+        from = start + len;
+
+        let rel = attribute(tag, "rel").unwrap_or_default().to_ascii_lowercase();
+        let rank = rel.split_whitespace().filter_map(|r| ["icon", "apple-touch-icon"].iter().position(|want| r == *want)).min();
+        if let (Some(rank), Some(href)) = (rank, attribute(tag, "href")) {
+            if best.as_ref().map_or(true, |(best_rank, _)| rank < *best_rank) {
+                best = Some((rank, href));
+            }
+        }
+    }
+
+    let href = best.map(|(_, href)| href).unwrap_or_else(|| "/favicon.ico".to_string());
+    base.join(&href).ok().map(String::from)
+}
+
+/// `content` of the first `<meta>` whose property/name is one of `keys`
+pub fn meta_content(html: &str, keys: &[&str]) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut found: Vec<(usize, String)> = Vec::new();
+    let mut from = 0;
+
+    while let Some(pos) = lower[from..].find("<meta") {
+        let start = from + pos;
+        let Some(len) = lower[start..].find('>') else { break };
+        let tag = &html[start..start + len];
+        from = start + len;
+
+        let key = attribute(tag, "property").or_else(|| attribute(tag, "name"));
+        if let Some(rank) = key.and_then(|k| keys.iter().position(|want| k.eq_ignore_ascii_case(want))) {
+            if let Some(content) = attribute(tag, "content").and_then(|c| clean_text(&c)) {
+                found.push((rank, content));
+            }
+        }
+    }
+
+    found.into_iter().min_by_key(|(rank, _)| *rank).map(|(_, content)| content)
+}
+
+/// Value of attribute `name` in a tag (quoted or bare)
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let at = from + pos;
+        from = at + name.len();
+        // Whole attribute names only (`name` must not match `og:name=`)
+        let preceded = lower[..at].chars().last().is_some_and(|c| c.is_whitespace());
+        let rest = lower[from..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default().to_string(),
+            _ => value.split_whitespace().next().unwrap_or_default().to_string(),
+        });
+    }
+    None
+}
+
+/// Decode common entities and collapse whitespace; None if empty
+pub fn clean_text(text: &str) -> Option<String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').filter(|end| *end <= 10).map(|end| (&rest[1..end], end));
+        let replacement = entity.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (replacement, entity) {
+            (Some(c), Some((_, end))) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    let collapsed = decoded.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!collapsed.is_empty()).then_some(collapsed)
+}
+
+fn contains_ignore_case(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_title() {
+        let html = r#"<html><head>
+            <TITLE>
+              Plain &amp; simple
+            </TITLE>
+            <meta name="description" content="About">
+            <meta content='Open &quot;Graph&quot; &#8211; title' property="og:title" />
+        </head>"#;
+        assert_eq!(extract_title(html).as_deref(), Some("Open \"Graph\" \u{2013} title"));
+        assert_eq!(extract_title("<title>Only &lt;this&gt;</title>").as_deref(), Some("Only <this>"));
+        assert_eq!(extract_title("<meta property=og:title content=Bare>").as_deref(), Some("Bare"));
+        assert_eq!(extract_title("<title>  </title>"), None);
+        assert!(parse_url("file:///etc/passwd").is_err());
+
+        let base = Url::parse("https://example.com/blog/post").unwrap();
+        let head = r#"<link rel="apple-touch-icon" href="/touch.png"><link rel="shortcut icon" href="img/fav.png">"#;
+        assert_eq!(extract_favicon(head, &base).as_deref(), Some("https://example.com/blog/img/fav.png"));
+        assert_eq!(extract_favicon("", &base).as_deref(), Some("https://example.com/favicon.ico"));
+    }
+}
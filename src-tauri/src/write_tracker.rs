@@ -30,9 +30,9 @@ fn get_tracker() -> &'static Mutex<WriteTracker> {
     WRITE_TRACKER.get_or_init(|| Mutex::new(WriteTracker::new()))
 }
 
-/// Normalize path for consistent comparison (forward slashes, lowercase)
+/// Normalize path for consistent comparison (forward slashes, NFC, lowercase)
 fn normalize_path(path: &str) -> String {
-    path.replace('\\', "/").to_lowercase()
+    crate::utils::path_key(path).to_lowercase()
 }
 
 /// Record that we just wrote to a file
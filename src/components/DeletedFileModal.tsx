@@ -7,7 +7,7 @@
  */
 
 import { useState } from 'react';
-import { writeFile } from '@core/ipc/commands';
+import { writeFile, errorMessage } from '@core/ipc/commands';
 
 interface DeletedFileModalProps {
   absolutePath: string;
@@ -34,7 +34,7 @@ export const DeletedFileModal: React.FC<DeletedFileModalProps> = ({
       console.log('[INFO] [DeletedFileModal] File restored:', absolutePath);
       onRestore();
     } catch (err) {
-      const msg = err instanceof Error ? err.message : 'Failed to restore file';
+      const msg = errorMessage(err, 'Failed to restore file');
       console.error('[ERROR] [DeletedFileModal] Restore failed:', err);
       setError(msg);
       setRestoring(false);
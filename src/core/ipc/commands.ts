@@ -5,11 +5,38 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import { open } from '@tauri-apps/plugin-dialog';
-import type { FileEntry, SearchResult } from '@/types/ipc';
+import type { FileEntry, IpcError, OpenedVault, SearchResult } from '@/types/ipc';
 import type { FolderNode } from '@/types/navigation';
 import { isTauriContext } from './platform';
 
+/**
+ * Whether a rejected command value is a structured backend error
+ */
+export function isIpcError(err: unknown): err is IpcError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as IpcError).kind === 'string' &&
+    typeof (err as IpcError).message === 'string'
+  );
+}
+
+/**
+ * Human-readable message of a failed command
+ * Commands reject with an IpcError object or a plain string, not an Error.
+ * @param err - Caught value
+ * @param fallback - Message when nothing usable was thrown
+ */
+export function errorMessage(err: unknown, fallback: string): string {
+  if (err instanceof Error || isIpcError(err)) {
+    return err.message;
+  }
+  if (typeof err === 'string' && err) {
+    return err;
+  }
+  return fallback;
+}
+
 /**
  * Read file contents from filesystem
  * @param path - Absolute path to file
@@ -83,16 +110,15 @@ export async function listDirectory(path: string): Promise<FileEntry[]> {
 }
 
 /**
- * Get navigation tree from Home directory
- * @param homePath - Path to Home directory
+ * Get navigation tree of the open vault
  * @returns Navigation tree root node
  */
-export async function getNavigationTree(homePath: string): Promise<FolderNode> {
-  console.log('[INFO] [IPC] getNavigationTree:', homePath);
+export async function getNavigationTree(): Promise<FolderNode> {
+  console.log('[INFO] [IPC] getNavigationTree');
 
   // Tauri mode - generate tree from filesystem
   if (isTauriContext()) {
-    return invoke<FolderNode>('get_navigation_tree', { homePath });
+    return invoke<FolderNode>('get_navigation_tree');
   }
 
   // Browser/PWA mode - fetch pre-generated tree.json
@@ -104,16 +130,17 @@ export async function getNavigationTree(homePath: string): Promise<FolderNode> {
 }
 
 /**
- * Start watching the vault directory for file changes
+ * Start watching the open vault for file changes
  * Emits 'vault:changed' event when files are created/deleted/renamed
- * @param vaultPath - Absolute path to vault directory
  */
-export function startWatchingVault(vaultPath: string): void {
-  console.log('[INFO] [IPC] startWatchingVault:', vaultPath);
+export function startWatchingVault(): void {
+  console.log('[INFO] [IPC] startWatchingVault');
 
   // Only supported in Tauri mode
   if (isTauriContext()) {
-    invoke<void>('start_watching_vault', { vaultPath });
+    invoke<void>('start_watching_vault').catch((error) => {
+      console.error('[ERROR] [IPC] startWatchingVault failed:', error);
+    });
   } else {
     console.warn('[WARN] [IPC] startWatchingVault not supported in PWA mode');
   }
@@ -139,17 +166,17 @@ export async function indexContent(path: string): Promise<void> {
 }
 
 /**
- * Rebuild the entire search index
- * @param homePath - Path to Home directory
+ * Rebuild the search index of the open vault
  * @returns Number of files indexed
  */
-export async function rebuildIndex(homePath: string): Promise<number> {
-  console.log('[INFO] [IPC] rebuildIndex:', homePath);
-  return invoke<number>('rebuild_index', { homePath });
+export async function rebuildIndex(): Promise<number> {
+  console.log('[INFO] [IPC] rebuildIndex');
+  return invoke<number>('rebuild_index');
 }
 
 /**
- * Open folder picker dialog and return selected folder path
+ * Open folder picker dialog and make the selected folder the open vault
+ * The backend runs the picker so the vault root never comes from the webview.
  * @returns Object with path and name (both null if cancelled or in PWA mode)
  */
 export async function selectVaultFolder(): Promise<{ path: string | null; name: string | null }> {
@@ -162,30 +189,34 @@ export async function selectVaultFolder(): Promise<{ path: string | null; name:
   }
 
   try {
-    // Open folder picker dialog
-    const selected = await open({
-      directory: true,
-      multiple: false,
-      title: 'Select Vault Folder',
-    });
+    const opened = await invoke<OpenedVault | null>('open_vault');
 
     // User cancelled
-    if (!selected) {
+    if (!opened) {
       console.log('[INFO] [IPC] Folder selection cancelled');
       return { path: null, name: null };
     }
 
-    // Extract folder name from path
-    const path = selected as string;
-    const name = path.split(/[/\\]/).pop() || 'Vault';
-
-    console.log('[INFO] [IPC] Folder selected:', { path, name });
-    return { path, name };
+    console.log('[INFO] [IPC] Folder selected:', opened);
+    return opened;
   } catch (error) {
     console.error('[ERROR] [IPC] selectVaultFolder failed:', error);
     throw error;
   }
 }
 
+/**
+ * Get the vault the backend has open (restored from the last session)
+ * @returns The open vault, or null if none is open or in PWA mode
+ */
+export async function getOpenVault(): Promise<OpenedVault | null> {
+  console.log('[INFO] [IPC] getOpenVault');
+
+  if (!isTauriContext()) {
+    return null;
+  }
+  return invoke<OpenedVault | null>('get_vault');
+}
+
 // Re-export platform detection utilities
 export { isTauriContext, isPWAInstalled, getPlatformMode, isPWABuild } from './platform';
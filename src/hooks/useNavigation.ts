@@ -1,7 +1,7 @@
 import { useCallback } from 'react';
 import { useNavigationStore } from '@core/state/navigationStore';
 import { useVaultStore } from '@core/state/vaultStore';
-import { isTauriContext, getNavigationTree } from '@core/ipc/commands';
+import { isTauriContext, getNavigationTree, errorMessage } from '@core/ipc/commands';
 import type { FolderNode, ContentNode, NavigationNode } from '@/types/navigation';
 import { isFolderNode, isContentNode } from '@/types/navigation';
 
@@ -82,7 +82,7 @@ export function useNavigation(): {
         if (!currentVault?.path) {
           throw new Error('No vault path configured');
         }
-        tree = await getNavigationTree(currentVault.path);
+        tree = await getNavigationTree();
         console.log('[INFO] [useNavigation] Generated tree from filesystem');
       } else {
         // PWA MODE: Fetch pre-generated tree.json (read-only)
@@ -133,7 +133,7 @@ export function useNavigation(): {
         setRefreshing(false);
       }
     } catch (err) {
-      const message = err instanceof Error ? err.message : 'Failed to load navigation tree';
+      const message = errorMessage(err, 'Failed to load navigation tree');
       console.error('[ERROR] [useNavigation]', message);
       setError(message);
       if (isInitialLoad) {
@@ -6,7 +6,7 @@
 import { useCallback } from 'react';
 import { useVaultStore } from '@core/state/vaultStore';
 import { useNavigationStore } from '@core/state/navigationStore';
-import { selectVaultFolder, getNavigationTree, isTauriContext, startWatchingVault } from '@core/ipc/commands';
+import { selectVaultFolder, getOpenVault, getNavigationTree, isTauriContext, startWatchingVault, errorMessage } from '@core/ipc/commands';
 import type { VaultConfig } from '@/types/vault';
 
 // Module-level flag to track current watched vault
@@ -28,7 +28,7 @@ export interface UseVaultReturn {
   /** Open folder picker and load selected vault */
   selectVault: () => Promise<void>;
 
-  /** Load vault from given path */
+  /** Load the vault the backend has open (path and name are for display) */
   loadVault: (path: string, name: string) => Promise<void>;
 
   /** Initialize from persisted vault (call on app startup) */
@@ -58,8 +58,9 @@ export function useVault(): UseVaultReturn {
   const resetNavigation = useNavigationStore((state) => state.reset);
 
   /**
-   * Load vault from given path
-   * Validates path and loads navigation tree
+   * Load the vault the backend has open
+   * The backend only opens folders picked in its own dialog; `path` and
+   * `name` are what it reported and are stored for display.
    */
   const loadVault = useCallback(
     async (path: string, name: string): Promise<void> => {
@@ -68,8 +69,8 @@ export function useVault(): UseVaultReturn {
       setError(null);
 
       try {
-        // Call backend to generate navigation tree from path
-        const tree = await getNavigationTree(path);
+        // Call backend to generate navigation tree of the open vault
+        const tree = await getNavigationTree();
 
         // Validate tree has content
         if (!tree || !tree.children || tree.children.length === 0) {
@@ -94,7 +95,7 @@ export function useVault(): UseVaultReturn {
         if (isTauriContext()) {
           // Only start watcher if not already watching this vault
           if (currentWatcherVault !== path) {
-            startWatchingVault(path);
+            startWatchingVault();
             currentWatcherVault = path;
             console.log('[INFO] [useVault] Started vault file watcher');
           } else {
@@ -120,7 +121,7 @@ export function useVault(): UseVaultReturn {
         console.log('[INFO] [useVault] Vault loaded successfully');
       } catch (err) {
         console.error('[ERROR] [useVault] Failed to load vault:', err);
-        setError(err instanceof Error ? err.message : 'Failed to load vault');
+        setError(errorMessage(err, 'Failed to load vault'));
         resetNavigation();
       } finally {
         setLoading(false);
@@ -148,7 +149,7 @@ export function useVault(): UseVaultReturn {
       await loadVault(result.path, result.name);
     } catch (err) {
       console.error('[ERROR] [useVault] Failed to select vault:', err);
-      setError(err instanceof Error ? err.message : 'Failed to select vault');
+      setError(errorMessage(err, 'Failed to select vault'));
     }
   }, [loadVault, setError]);
 
@@ -172,17 +173,19 @@ export function useVault(): UseVaultReturn {
       return;
     }
 
-    // No persisted vault
-    if (!currentVault) {
-      console.log('[INFO] [useVault] No persisted vault found');
-      setInitialized(true);
-      return;
-    }
-
-    // Try to load persisted vault
+    // Try to load the vault the backend restored
     try {
-      console.log('[INFO] [useVault] Restoring vault:', currentVault.name);
-      await loadVault(currentVault.path, currentVault.name);
+      const opened = await getOpenVault();
+      if (!opened) {
+        console.log('[INFO] [useVault] No persisted vault found');
+        if (currentVault) {
+          clearVaultState();
+        }
+        return;
+      }
+
+      console.log('[INFO] [useVault] Restoring vault:', opened.name);
+      await loadVault(opened.path, opened.name);
     } catch (err) {
       console.error('[ERROR] [useVault] Failed to restore vault:', err);
       // Clear invalid vault
@@ -4,7 +4,7 @@ import { useMathJax } from '@/loaders';
 import ModuleLoader from './ModuleLoader';
 import { MarkdownEditor } from '@/modules/editor/MarkdownEditor';
 import type { ContentNode } from '@/types/navigation';
-import { isTauriContext, readFile } from '@core/ipc/commands';
+import { isTauriContext, readFile, errorMessage } from '@core/ipc/commands';
 import { useVaultStore } from '@core/state/vaultStore';
 
 /**
@@ -131,7 +131,7 @@ function PageViewer({ node, onError }: ViewerProps): React.JSX.Element {
         }
       } catch (err) {
         if (!cancelled) {
-          onError(err instanceof Error ? err.message : 'Failed to load page');
+          onError(errorMessage(err, 'Failed to load page'));
         }
       } finally {
         if (!cancelled) {
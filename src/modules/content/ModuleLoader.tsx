@@ -1,7 +1,7 @@
 import { useEffect, useRef } from 'react';
 import { useThemeStore, type Theme } from '@core/state/themeStore';
 import { useVaultStore } from '@core/state/vaultStore';
-import { isTauriContext, readFile } from '@core/ipc/commands';
+import { isTauriContext, readFile, errorMessage } from '@core/ipc/commands';
 import { useMathJax } from '@/loaders';
 import type { ModuleNode } from '@/types/navigation';
 
@@ -189,7 +189,7 @@ function ModuleLoader({ node, onError }: ModuleLoaderProps): React.JSX.Element {
         console.log('[INFO] [ModuleLoader] Module loaded successfully:', node.id);
       } catch (err) {
         if (!cancelled) {
-          const message = err instanceof Error ? err.message : 'Failed to load module';
+          const message = errorMessage(err, 'Failed to load module');
           console.error('[ERROR] [ModuleLoader]', message);
           onError(message);
         }
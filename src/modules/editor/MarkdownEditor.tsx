@@ -7,7 +7,7 @@
  */
 
 import { useState, useEffect, useRef, useCallback } from 'react';
-import { readFile, writeFile, isTauriContext } from '@core/ipc/commands';
+import { readFile, writeFile, isTauriContext, errorMessage } from '@core/ipc/commands';
 import { listen } from '@tauri-apps/api/event';
 import { useEditorStore } from '@core/state/editorStore';
 import { useNavigationStore } from '@core/state/navigationStore';
@@ -459,7 +459,7 @@ export const MarkdownEditor: React.FC<MarkdownEditorProps> = ({
           }
           return; // Success, exit retry loop
         } catch (err) {
-          lastError = err instanceof Error ? err : new Error('Failed to load document');
+          lastError = err instanceof Error ? err : new Error(errorMessage(err, 'Failed to load document'));
 
           if (attempt < maxRetries) {
             console.log(`[WARN] [MarkdownEditor] Load attempt ${attempt} failed, retrying...`);
@@ -23,15 +23,28 @@ export interface SearchResult {
   snippet?: string;
 }
 
+/** The vault the backend has open (open_vault / get_vault) */
+export interface OpenedVault {
+  path: string;
+  name: string;
+}
+
 /** Navigation tree response */
 export interface NavigationTreeResponse {
   root: NavigationNode;
 }
 
-/** Generic IPC error response */
+/**
+ * Error a command rejects with when it fails with an AppError
+ * (commands returning plain strings reject with the string instead)
+ */
 export interface IpcError {
+  /** Machine-readable kind, e.g. 'notFound', 'conflict', 'busy', 'tooLarge' */
+  kind: string;
   message: string;
-  code?: string;
+  /** File size and read limit in bytes ('tooLarge' only) */
+  size?: number;
+  limit?: number;
 }
 
 /** Content index entry for SQLite */